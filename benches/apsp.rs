@@ -0,0 +1,48 @@
+//! Times the dense and blocked Floyd-Warshall on plans of growing size, to find where blocking starts to pay off. Run with `cargo bench --bench apsp`
+
+use std::time::{Duration, Instant};
+
+use petgraph::graphmap::DiGraphMap;
+use temporal_networks::algorithms::CancelToken;
+use temporal_networks::dense::{floyd_warshall_blocked, floyd_warshall_dense, BLOCK_SIZE};
+
+/// A chain of Episodes with a shortcut every few events, like a long plan with some parallel branches
+fn plan(events: i32) -> DiGraphMap<i32, f64> {
+    let mut graph = DiGraphMap::new();
+    for i in 0..events - 1 {
+        graph.add_edge(i, i + 1, 10. + (i % 7) as f64);
+        graph.add_edge(i + 1, i, -1.);
+        if i % 5 == 0 && i + 9 < events {
+            graph.add_edge(i, i + 9, 200.);
+        }
+    }
+    graph
+}
+
+/// The fastest of a few runs
+fn time(runs: u32, mut f: impl FnMut()) -> Duration {
+    (0..runs)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn main() {
+    let token = CancelToken::new();
+    println!("{:>6} {:>12} {:>12}", "events", "dense", "blocked");
+    for events in [64, 128, 256, 384, 512, 768, 1024, 1536] {
+        let graph = plan(events);
+        let runs = if events > 512 { 2 } else { 5 };
+        let dense = time(runs, || {
+            floyd_warshall_dense(&graph, &token).unwrap();
+        });
+        let blocked = time(runs, || {
+            floyd_warshall_blocked(&graph, BLOCK_SIZE, &token).unwrap();
+        });
+        println!("{:>6} {:>12?} {:>12?}", events, dense, blocked);
+    }
+}
@@ -0,0 +1,131 @@
+#ifndef TEMPORAL_NETWORKS_H
+#define TEMPORAL_NETWORKS_H
+
+/* Generated with cbindgen from src/ffi.rs. Do not edit by hand */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The outcome of a call through the C API
+ */
+typedef enum TnStatus {
+  TN_STATUS_OK = 0,
+  /**
+   * A handle or out pointer was null
+   */
+  TN_STATUS_NULL_POINTER,
+  /**
+   * An event isn't in the Schedule
+   */
+  TN_STATUS_UNKNOWN_EVENT,
+  /**
+   * The interval's lower bound is greater than its upper bound
+   */
+  TN_STATUS_INVALID_INTERVAL,
+  /**
+   * The constraints contradict each other
+   */
+  TN_STATUS_INCONSISTENT,
+  /**
+   * A commitment conflicts with the Schedule or existing commitments
+   */
+  TN_STATUS_REJECTED,
+} TnStatus;
+
+/**
+ * An opaque handle to a Schedule
+ */
+typedef struct TnSchedule TnSchedule;
+
+/**
+ * An ID representing an event in the Schedule
+ */
+typedef int32_t EventID;
+
+/**
+ * Create an empty Schedule. Free it with `tn_schedule_free`
+ */
+struct TnSchedule *tn_schedule_new(void);
+
+/**
+ * Free a Schedule created with `tn_schedule_new`. Null is ignored
+ *
+ * # Safety
+ *
+ * `schedule` must be null or a handle from `tn_schedule_new` that hasn't been freed
+ */
+void tn_schedule_free(struct TnSchedule *schedule);
+
+/**
+ * Add an Episode that takes between `lower` and `upper` time to complete. Writes its start and end events to `start` and `end`
+ *
+ * # Safety
+ *
+ * `schedule` must be a live handle and `start` and `end` must be valid to write to
+ */
+enum TnStatus tn_schedule_add_episode(struct TnSchedule *schedule,
+                                      double lower,
+                                      double upper,
+                                      EventID *start,
+                                      EventID *end);
+
+/**
+ * Require the target to happen between `lower` and `upper` time after the source
+ *
+ * # Safety
+ *
+ * `schedule` must be a live handle
+ */
+enum TnStatus tn_schedule_add_constraint(struct TnSchedule *schedule,
+                                         EventID source,
+                                         EventID target,
+                                         double lower,
+                                         double upper);
+
+/**
+ * Compile the Schedule. Queries compile automatically, so this is only needed to control when the work happens
+ *
+ * # Safety
+ *
+ * `schedule` must be a live handle
+ */
+enum TnStatus tn_schedule_compile(struct TnSchedule *schedule);
+
+/**
+ * Record the time an event happened
+ *
+ * # Safety
+ *
+ * `schedule` must be a live handle
+ */
+enum TnStatus tn_schedule_commit(struct TnSchedule *schedule, EventID event, double time);
+
+/**
+ * Write the earliest and latest times an event can happen to `earliest` and `latest`
+ *
+ * # Safety
+ *
+ * `schedule` must be a live handle and `earliest` and `latest` must be valid to write to
+ */
+enum TnStatus tn_schedule_window(struct TnSchedule *schedule,
+                                 EventID event,
+                                 double *earliest,
+                                 double *latest);
+
+/**
+ * Write the [lower, upper] interval from the source to the target to `lower` and `upper`
+ *
+ * # Safety
+ *
+ * `schedule` must be a live handle and `lower` and `upper` must be valid to write to
+ */
+enum TnStatus tn_schedule_interval(struct TnSchedule *schedule,
+                                   EventID source,
+                                   EventID target,
+                                   double *lower,
+                                   double *upper);
+
+#endif /* TEMPORAL_NETWORKS_H */
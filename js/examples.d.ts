@@ -0,0 +1,17 @@
+/* tslint:disable */
+/* eslint-disable */
+import { Episode, Schedule } from "./index";
+import { Mission } from "./mission";
+
+/**
+ * The example from the walkthrough in docs/references/STNs_for_EVAs.pdf
+ */
+export function walkthrough(): { schedule: Schedule; X0: number; L: Episode; S: Episode };
+/**
+ * The STS-134 example as a Mission spec, with durations widened by `uncertainty`
+ */
+export function sts134Spec(uncertainty?: number): object;
+/**
+ * Build the STS-134 example as a Mission
+ */
+export function sts134(uncertainty?: number): { mission: Mission; steps: { [name: string]: any } };
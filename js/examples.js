@@ -0,0 +1,57 @@
+/**
+ * Canonical example plans for documentation, demos, and benchmarks. The same plans are available from Rust with the `examples` feature
+ */
+
+const { Schedule } = require("./index");
+const { Mission } = require("./mission");
+
+/**
+ * The example from the walkthrough in docs/references/STNs_for_EVAs.pdf
+ * @returns {{schedule: Schedule, X0: number, L: Episode, S: Episode}}
+ */
+module.exports.walkthrough = function walkthrough() {
+  const schedule = new Schedule();
+  const X0 = schedule.createEvent();
+  const L = schedule.addEpisode([30, 40]);
+  const S = schedule.addEpisode([40, 50]);
+  schedule.addConstraint(X0, L.start, [10, 20]);
+  schedule.addConstraint(X0, S.end, [60, 70]);
+  schedule.addConstraint(S.start, L.end, [10, 20]);
+  return { schedule, X0, L, S };
+};
+
+/**
+ * The first two activities of the STS-134 EVA summary timeline as a Mission spec. See `Mission.fromSpec`
+ * @param {number} uncertainty widens every bounded duration by this fraction on both sides, eg. 0.1 turns [40, 50] into [36, 55]
+ * @returns {object}
+ */
+module.exports.sts134Spec = function sts134Spec(uncertainty = 0) {
+  const interval = ([lower, upper]) => [
+    lower - uncertainty * lower,
+    upper === Number.MAX_VALUE ? upper : upper + uncertainty * upper
+  ];
+
+  return {
+    actors: ["EV1", "EV2"],
+    steps: [
+      { name: "egress", description: "EGRESS/SETUP", duration: interval([40, 50]) },
+      { name: "ev1Egress", description: "EV1 egress", duration: interval([10, 20]), parent: "egress", actor: "EV1" },
+      { name: "ev1Setup", description: "EV1 setup", duration: interval([0, Number.MAX_VALUE]), parent: "egress", actor: "EV1" },
+      { name: "ev2Egress", description: "EV2 egress", duration: interval([40, 50]), parent: "egress", actor: "EV2" },
+      { name: "misse7", description: "MISSE7", duration: interval([55, 65]) },
+      { name: "ev1MISSE7", description: "EV1 MISSE7", duration: interval([55, 65]), parent: "misse7", actor: "EV1" },
+      { name: "ev2MISSE7", description: "EV2 MISSE7", duration: interval([55, 65]), parent: "misse7", actor: "EV2" }
+    ],
+    // an 8 hour limiting consumable
+    deadlines: [{ step: "misse7", deadline: 480 }]
+  };
+};
+
+/**
+ * Build the STS-134 example as a Mission
+ * @param {number} uncertainty see `sts134Spec`
+ * @returns {{mission: Step, steps: Object<string, Step>}}
+ */
+module.exports.sts134 = function sts134(uncertainty = 0) {
+  return Mission.fromSpec(module.exports.sts134Spec(uncertainty));
+};
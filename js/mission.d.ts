@@ -3,4 +3,81 @@
 /**
  * Mission mission mission
  */
-export class Mission {}
+export class Mission {
+  /**
+   * Build a Mission from a declarative timeline spec
+   */
+  static fromSpec(spec: object | string): { mission: Mission; steps: { [name: string]: any } };
+  /**
+   * Regenerate the declarative spec from the current state of the Mission
+   */
+  toSpec(): object;
+  /**
+   * Create an actor with optional display hints
+   */
+  createActor(name?: string, hints?: ActorHints): Actor;
+  /**
+   * Create a sync block that joins the timelines of several actors
+   */
+  createSync(description?: string, duration?: number[], actors?: Actor[], preceding?: Map<Actor, any>): any;
+  /**
+   * Move a step to another actor, rewiring its chain constraints. Undone and thrown if the Mission becomes infeasible
+   */
+  reassign(step: any, actor: Actor, position?: number | null): void;
+  /**
+   * Limit when an actor can work, as [from, until] times after the start of the Mission
+   */
+  addAvailability(actor: Actor, window: number[]): void;
+  /**
+   * Turn automatic chaining of each actor's steps on or off. On by default
+   */
+  setAutoChain(enabled: boolean): void;
+  /**
+   * Set the minimum time to travel between worksites, keyed by origin then destination
+   */
+  setTravelTimes(matrix: { [from: string]: { [to: string]: number } }): void;
+  /**
+   * Give an actor a consumable with a capacity that runs down at a rate from the start of the Mission
+   */
+  addConsumable(actor: Actor, name: string, capacity: number, rate?: number): void;
+  /**
+   * Get the worst-case remaining consumable at the start and end of each of an actor's steps
+   */
+  marginProfile(actor: Actor, name?: string | null): { consumable: string; capacity: number; points: { time: number; margin: number; step: any; event: "start" | "end" | null }[] };
+  /**
+   * Alert when the worst-case margin of a consumable drops below a value
+   */
+  alertIfMarginBelow(consumable: string, value: number, label?: string | null): void;
+  /**
+   * Find the steps during which the worst-case margin of each consumable crosses its thresholds
+   */
+  marginAlerts(): { actor: string; consumable: string; threshold: number; label: string | null; step: any; time: number }[];
+  /**
+   * Order steps by the same actor that could overlap, and report pairs that must overlap
+   */
+  level(): { ordered: { actor: string; first: any; second: any }[]; conflicts: { actor: string; first: any; second: any }[] };
+  /**
+   * Get the display hints for every actor, sorted into rows by order
+   */
+  actorHints(): { name: string; color: string | null; order: number | null }[];
+  /**
+   * Aggregate the planned timeline into buckets with the occupancy of each actor
+   */
+  zoom(resolutions?: number[]): { resolution: number; buckets: { start: number; end: number; occupancy: { [actor: string]: number } }[]; actors: { name: string; color: string | null; order: number | null }[] }[];
+}
+/**
+ * How an actor should be displayed
+ */
+export interface ActorHints {
+  color?: string;
+  order?: number;
+}
+/**
+ * An actor in the timeline
+ */
+export class Actor {
+  constructor(name?: string, hints?: ActorHints);
+  name: string;
+  color: string | null;
+  order: number | null;
+}
@@ -32,6 +32,8 @@ const { Episode, Interval, Schedule } = require("./index");
  * Note that in the first example above, each activity has a start and end node. The same is true for tasks, as shown in the second example. In fact, all Steps have a definite start and end node. The methods in this class automatically handle start and end nodes for you.
  */
 class Step {
+  /** Unique name used to refer to this Step from a declarative spec */
+  name = null;
   /** Human readable description */
   description = "";
   /** duration of the episode represented by this step */
@@ -42,6 +44,12 @@ class Step {
   schedule = new Schedule();
   /** who is performing this step */
   actor = new Actor();
+  /** consumption rates by consumable name while this step runs, eg. `{ O2: 1.5 }` for heavy work. Consumables without a rate here are used at the actor's rate, see `addConsumable` */
+  usage = {};
+  /** whether this step runs alongside the actor's other steps instead of after the one before it, see `createStep` */
+  parallel = false;
+  /** the worksite where this step happens, eg. `"P1 truss"`. Consecutive steps at different worksites are kept apart by the travel time between them, see `setTravelTimes` */
+  location = null;
   /** the parent Step to this */
   _parent = null;
   /** the root of the Mission */
@@ -53,6 +61,30 @@ class Step {
   // TODO: is this necessary? this would only prevent recompiling unnecessarily if the schedule is accidentally marked dirty while updating
   /** track whether or not any substeps have changed */
   _dirty = true;
+  /** constraints between the events of any two steps. only used by the root Mission */
+  _syncs = [];
+  /** latest allowed end times for steps, relative to the start of the Mission. only used by the root Mission */
+  _deadlines = [];
+  /** windows when actors are available, with their anchor events. only used by the root Mission */
+  _availability = [];
+  /** actors created for the Mission. only used by the root Mission */
+  _actors = [];
+  /** consumables carried by actors, with their capacities and nominal rates. only used by the root Mission */
+  _consumables = [];
+  /** margins to alert on, by consumable name. only used by the root Mission */
+  _marginThresholds = [];
+  /** minimum time to get from one worksite to another, keyed by origin then destination. only used by the root Mission */
+  _travelTimes = {};
+  /** whether steps in an actor's branch are chained one after the other, see `setAutoChain`. only used by the root Mission */
+  _autoChain = true;
+  /** the constraints the Mission has written to the schedule, keyed by "source,target", so a frozen plan is only amended where they change. only used by the root Mission */
+  _wired = new Map();
+  /** actors taking part if this step is a sync block, see `createSync` */
+  _syncActors = null;
+  /** as-performed PET of the start of this step, if known */
+  _startedAt = null;
+  /** as-performed PET of the end of this step, if known */
+  _completedAt = null;
 
   constructor(
     description = "",
@@ -151,6 +183,7 @@ class Step {
    */
   startedAt(pet) {
     this.schedule.commitEvent(this.start, pet);
+    this._startedAt = pet;
   }
 
   /**
@@ -159,6 +192,7 @@ class Step {
    */
   completedAt(pet) {
     this.schedule.commitEvent(this.end, pet);
+    this._completedAt = pet;
   }
 
   /**
@@ -183,12 +217,26 @@ class Step {
   /**
    * Create an actor for the EVA
    * @param {string} name
+   * @param {{color: string, order: number}} hints how the actor should be displayed. See `actorHints`
    */
-  createActor(name = "") {
-    const actor = new Actor(name);
+  createActor(name = "", hints = {}) {
+    const actor = new Actor(name, hints);
+    this._root._actors.push(actor);
     return actor;
   };
 
+  /**
+   * Get the display hints for every actor in the Mission, sorted into rows by `order`. Actors without an order come last, in the order they were created. Exports like `zoom` and `toSpec` carry the same hints so every view of the timeline agrees on how actors are presented
+   * @returns {{name: string, color: string|null, order: number|null}[]}
+   */
+  actorHints() {
+    const rank = a => (a.order === null ? Infinity : a.order);
+    return this._root._actors
+      .map((actor, index) => ({ actor, index }))
+      .sort((a, b) => rank(a.actor) - rank(b.actor) || a.index - b.index)
+      .map(({ actor }) => ({ name: actor.name, color: actor.color, order: actor.order }));
+  }
+
   /**
    * Change the actor for this step
    * @param {Actor} actor
@@ -214,6 +262,73 @@ class Step {
     this.setOrCreateBranch(actor, branch);
   }
 
+  /**
+   * Move a substep to another actor while the Mission is running, eg. to rebalance work when one actor falls behind. The step stays under the same parent. Its constraints to its old neighbors and to the old actor's availability are removed, the old neighbors are chained to each other, and the step is chained into the new actor's branch. Once the schedule is frozen or executing, the changes are made as amendments. If the Mission is no longer feasible, every change is undone and the reason is thrown
+   * @param {Step} step
+   * @param {Actor} actor
+   * @param {number|null} position the 0-indexed position of the step in the new actor's branch. Defaults to the end
+   * @throws {Error} if the step is the Mission or a sync, or the Mission would be infeasible with the step reassigned
+   */
+  reassign(step, actor, position = null) {
+    const parent = step._parent;
+    if (!parent || step._syncActors) {
+      throw new Error(`Only a step by a single actor can be reassigned | ${step.description}`);
+    }
+    if (actor === step.actor) {
+      return;
+    }
+
+    const root = this._root;
+    const schedule = root.schedule;
+    const reason = `reassign ${step.description} to ${actor.name}`;
+    // wire up the Mission as it is so the step's constraints can be taken apart
+    root.construct(reason);
+
+    const previous = { actor: step.actor, branches: new Map(parent._branches), wired: new Map(root._wired) };
+    const oldBranch = parent._branches.get(step.actor);
+    const newBranch = (parent._branches.get(actor) || []).slice();
+
+    schedule.beginTransaction();
+    try {
+      // out of the old branch, leaving its neighbors to be chained together
+      const [before, after] = neighbors(parent, oldBranch, step);
+      unwire(root, before, step.start, reason);
+      unwire(root, step.end, after, reason);
+      root._availability
+        .filter(a => a.actor === step.actor)
+        .forEach(({ opens, closes }) => {
+          unwire(root, opens, step.start, reason);
+          unwire(root, step.end, closes, reason);
+        });
+      const remaining = oldBranch.filter(s => s !== step);
+      if (remaining.length > 0) {
+        parent.setOrCreateBranch(step.actor, remaining);
+      } else {
+        parent._branches.delete(step.actor);
+      }
+
+      // into the new one, between two steps that were chained to each other
+      newBranch.splice(position === null ? newBranch.length : position, 0, step);
+      step.actor = actor;
+      parent.setOrCreateBranch(actor, newBranch);
+      const [newBefore, newAfter] = neighbors(parent, newBranch, step);
+      if (chained(root, step) && (newBefore !== parent.start || newAfter !== parent.end)) {
+        unwire(root, newBefore, newAfter, reason);
+      }
+
+      root.construct(reason);
+      schedule.commitTransaction();
+    } catch (e) {
+      if (schedule.inTransaction) {
+        schedule.rollbackTransaction();
+      }
+      step.actor = previous.actor;
+      parent._branches = previous.branches;
+      root._wired = previous.wired;
+      throw new Error(`Cannot reassign step to ${actor.name} | ${step.description}: ${e.message || e}`);
+    }
+  }
+
   /**
    * Move a Step in the timeline to a new position.
    * @param {Step} parent the new parent Step
@@ -263,16 +378,18 @@ class Step {
   }
 
   /**
-   * Create a step beneath this Mission/Step. If no actor is provided, then the substep has the same actor
+   * Create a step beneath this Mission/Step. If no actor is provided, then the substep has the same actor. The step starts as soon as the actor's step before it in this branch ends, unless auto-chaining is turned off with `setAutoChain` or it's created with `{ parallel: true }`, eg. for monitoring that happens while the actor does something else. A step that isn't chained can happen any time during this step
    * @param {string} description
    * @param {number[]} duration [lower, upper] interval duration
    * @param {Actor} actor
    * @param {number[][]} slack [before, after] interval slack
+   * @param {{parallel: boolean}} options
    */
-  createStep(description = "", duration = [], actor = null, slack = [[0, 0], [0, 0]]) {
+  createStep(description = "", duration = [], actor = null, slack = [[0, 0], [0, 0]], options = {}) {
     let a = actor || this.actor;
 
     const step = new Step(description, duration, slack, this, this._root, a);
+    step.parallel = Boolean(options.parallel);
 
     const branch = this.getOrCreateBranch(a);
     branch.push(step);
@@ -281,6 +398,43 @@ class Step {
     return step;
   }
 
+  /**
+   * Create a sync block beneath this Step: a step that several actors perform together, eg. a joint task once both EVs are out of the airlock. The sync joins the actors' timelines, so it can't start until every actor has finished the step before it, and each actor's next step forks from its end. Actors that are ready early wait for the others
+   * @param {string} description
+   * @param {number[]} duration [lower, upper] interval duration
+   * @param {Actor[]} actors actors taking part. Defaults to every actor with substeps under this Step
+   * @param {Map<Actor, Step|null>} preceding the step each actor does right before the sync, or null if the sync comes first. Actors that aren't in the map join after their latest substep
+   * @returns {Step}
+   * @throws {Error} if there are no actors or a preceding step isn't in its actor's branch
+   */
+  createSync(description = "", duration = [0, 0], actors = null, preceding = new Map()) {
+    const participants = actors || Array.from(this._branches.keys());
+    if (participants.length === 0) {
+      throw new Error(`A sync needs at least one actor | ${description}`);
+    }
+
+    const step = new Step(description, duration, [[0, 0], [0, 0]], this, this._root, participants[0]);
+    step._syncActors = participants;
+
+    participants.forEach(actor => {
+      const branch = this.getOrCreateBranch(actor);
+      if (!preceding.has(actor)) {
+        branch.push(step);
+      } else if (preceding.get(actor) === null) {
+        branch.unshift(step);
+      } else {
+        const index = branch.indexOf(preceding.get(actor));
+        if (index === -1) {
+          throw new Error(`The step before sync "${description}" is not one of ${actor.name}'s steps | ${preceding.get(actor).description}`);
+        }
+        branch.splice(index + 1, 0, step);
+      }
+      this.setOrCreateBranch(actor, branch);
+    });
+
+    return step;
+  }
+
   /**
    * Append a substep to the end of the list for an actor
    * @param {Step} substep
@@ -290,6 +444,156 @@ class Step {
     //
   }
 
+  /**
+   * Constrain an event of one step relative to an event of another step, eg. to keep two actors in sync. Events are either `"start"` or `"end"`
+   * @param {Step} source
+   * @param {string} sourceEvent
+   * @param {Step} target
+   * @param {string} targetEvent
+   * @param {number[]} interval [lower, upper] time from the source event to the target event
+   */
+  addSync(source, sourceEvent, target, targetEvent, interval = [0, 0]) {
+    // fail early on a bad event name rather than when the Mission is constructed
+    eventOf(source, sourceEvent);
+    eventOf(target, targetEvent);
+
+    this._root._syncs.push({ source, sourceEvent, target, targetEvent, interval });
+  }
+
+  /**
+   * Require a step to end no later than `deadline` after the start of the Mission
+   * @param {Step} step
+   * @param {number} deadline
+   */
+  addDeadline(step, deadline) {
+    this._root._deadlines.push({ step, deadline });
+  }
+
+  /**
+   * Limit when an actor can work, eg. `[30, Number.MAX_VALUE]` if EV2 can't leave the airlock until 30 minutes in. Every step the actor takes part in, including syncs and nested substeps, must start and end within the window, relative to the start of the Mission. The actor's first step waits for the window to open instead of starting with the Mission. Use `validate` to find steps that can't fit
+   * @param {Actor} actor
+   * @param {number[]} window [from, until] times after the start of the Mission
+   */
+  addAvailability(actor, window) {
+    const root = this._root;
+    // constrain steps through anchor events at the edges of the window so the constraints between the Mission and its substeps aren't overwritten
+    const opens = root.schedule.createEvent();
+    const closes = root.schedule.createEvent();
+    root._availability.push({ actor, window, opens, closes });
+  }
+
+  /**
+   * Give an actor a consumable that runs down from the start of the Mission, eg. `addConsumable(ev1, "O2", 480)` for 480 minutes of oxygen used at 1 per minute. Steps that use it faster or slower set their own rate in `usage`. Adding a consumable with the same name to the same actor replaces it
+   * @param {Actor} actor
+   * @param {string} name
+   * @param {number} capacity how much the actor starts with
+   * @param {number} rate how much is used per unit of time outside steps with their own rate
+   * @throws {Error} if the capacity or rate is negative
+   */
+  addConsumable(actor, name, capacity, rate = 1) {
+    if (!(capacity >= 0) || !(rate >= 0)) {
+      throw new Error(`A consumable needs a non-negative capacity and rate | ${actor.name} ${name}: ${capacity} at ${rate}`);
+    }
+    const root = this._root;
+    root._consumables = root._consumables.filter(c => c.actor !== actor || c.name !== name);
+    root._consumables.push({ actor, name, capacity, rate });
+  }
+
+  /**
+   * Get the worst-case margin of an actor's consumable over the planned timeline, eg. to plot O2 margin against the steps. The profile is piecewise linear between points at the start and end of each of the actor's steps without substeps, in order of their earliest starts, plus the start of the Mission. Each point is at the latest time the event can happen, where the margin is what's left if everything runs as late and as long as the bounds allow. Steps that use the consumable faster than the actor's rate count with their longest planned duration, and slower ones with their shortest
+   * @param {Actor} actor
+   * @param {string} name the consumable. Defaults to the actor's first one
+   * @returns {{consumable: string, capacity: number, points: {time: number, margin: number, step: Step|null, event: string|null}[]}}
+   * @throws {Error} if the actor has no such consumable
+   */
+  marginProfile(actor, name = null) {
+    const root = this._root;
+    const consumable = root._consumables.find(c => c.actor === actor && (name === null || c.name === name));
+    if (!consumable) {
+      throw new Error(name === null ? `${actor.name} has no consumables` : `${actor.name} has no consumable "${name}"`);
+    }
+    // actually create the graph
+    root.construct();
+
+    const latest = event => root.schedule.window(event).toJSON()[1];
+    const earliest = step => root.schedule.window(step.start).toJSON()[0];
+    const steps = stepsOf(root, actor)
+      .filter(step => step._branches.size === 0)
+      .sort((a, b) => earliest(a) - earliest(b));
+
+    const { capacity, rate } = consumable;
+    // use beyond the nominal rate by the steps finished so far
+    let extra = 0;
+    const points = [{ time: 0, margin: capacity, step: null, event: null }];
+    steps.forEach(step => {
+      const used = step.usage[consumable.name];
+      points.push({ time: latest(step.start), margin: capacity - rate * latest(step.start) - extra, step, event: "start" });
+      if (used !== undefined) {
+        const [lower, upper] = root.schedule.interval(step.start, step.end).toJSON();
+        extra += (used - rate) * (used > rate ? upper : lower);
+      }
+      points.push({ time: latest(step.end), margin: capacity - rate * latest(step.end) - extra, step, event: "end" });
+    });
+    return { consumable: consumable.name, capacity, points };
+  }
+
+  /**
+   * Alert when the worst-case margin of a consumable drops below a value, eg. `alertIfMarginBelow("O2", 60, "caution")` and `alertIfMarginBelow("O2", 30, "warning")`. Applies to every actor carrying the consumable. See `marginAlerts`
+   * @param {string} consumable
+   * @param {number} value
+   * @param {string|null} label carried into the alerts, eg. `"caution"` or `"warning"`
+   */
+  alertIfMarginBelow(consumable, value, label = null) {
+    this._root._marginThresholds.push({ consumable, value, label });
+  }
+
+  /**
+   * Find where the worst-case margin of each consumable drops below its thresholds from `alertIfMarginBelow`, following the profiles from `marginProfile`. A margin that crosses a threshold between two steps is reported with the step after the crossing. A consumable that starts below a threshold is reported at the start of the Mission without a step
+   * @returns {{actor: string, consumable: string, threshold: number, label: string|null, step: Step|null, time: number}[]} one alert per actor and threshold crossed, in order of `time`, the latest time the margin can cross it
+   */
+  marginAlerts() {
+    const root = this._root;
+    const alerts = [];
+    root._consumables.forEach(({ actor, name }) => {
+      const thresholds = root._marginThresholds.filter(t => t.consumable === name);
+      if (thresholds.length === 0) {
+        return;
+      }
+      const { points } = this.marginProfile(actor, name);
+      thresholds.forEach(({ value, label }) => {
+        const alert = (step, time) => alerts.push({ actor: actor.name, consumable: name, threshold: value, label, step, time });
+        if (points[0].margin < value) {
+          alert(null, points[0].time);
+          return;
+        }
+        const index = points.findIndex((p, i) => i > 0 && points[i - 1].margin >= value && p.margin < value);
+        if (index > 0) {
+          // margins are linear between points
+          const [from, to] = [points[index - 1], points[index]];
+          const time = from.time + ((from.margin - value) / (from.margin - to.margin)) * (to.time - from.time);
+          alert(to.step, time);
+        }
+      });
+    });
+    return alerts.sort((a, b) => a.time - b.time);
+  }
+
+  /**
+   * Turn automatic chaining of steps on or off for the whole Mission. On by default, so each step in an actor's branch starts when the one before it ends. With it off, steps only follow each other through syncs added with `addSync`. Sync blocks from `createSync` always join their actors' timelines
+   * @param {boolean} enabled
+   */
+  setAutoChain(enabled) {
+    this._root._autoChain = enabled;
+  }
+
+  /**
+   * Set the time it takes to get between worksites, eg. `{ "airlock": { "P1 truss": 15 } }`. Whenever an actor goes straight from a step at one location to a step at another, the second step can't start until the travel time has passed. A missing entry falls back to the trip in the other direction, and worksites without either entry take no time to travel between. Steps without a location are never kept apart
+   * @param {Object<string, Object<string, number>>} matrix travel times keyed by origin then destination
+   */
+  setTravelTimes(matrix) {
+    this._root._travelTimes = matrix;
+  }
+
   /**
    * Get the Step as-planned duration as a [lower, upper] range
    */
@@ -311,6 +615,47 @@ class Step {
     return this.schedule.window(this.start).toJSON();
   }
 
+  /**
+   * Aggregate the planned timeline into buckets at one or more resolutions, eg. `[1, 15]` for 1-minute and 15-minute buckets. Each bucket reports the fraction of its time that each actor is busy. Only steps without substeps count towards occupancy, so an actor isn't busy just because a parent step is open
+   * @param {number[]} resolutions bucket widths
+   * @returns {object[]} `{ resolution, buckets, actors }` for each resolution, where each bucket is `{ start, end, occupancy }`, occupancy maps actor names to a fraction between 0 and 1, and actors are the display hints from `actorHints`
+   */
+  zoom(resolutions = [1]) {
+    // actually create the graph
+    this._root.construct();
+
+    const actors = this.actorHints();
+    return this.schedule.zoom(resolutions, leafGroups(this)).map(zoom => Object.assign(zoom, { actors }));
+  }
+
+  /**
+   * Stop any actor from being scheduled in two places at once. Steps without substeps by the same actor that could overlap, eg. in different branches or syncs, are put in order, and pairs that must overlap are reported. The ordering constraints stay in the Mission's schedule
+   * @returns {{ordered: object[], conflicts: object[]}} pairs of steps by actor name as `{ actor, first, second }`
+   */
+  level() {
+    // actually create the graph
+    this._root.construct();
+
+    const byEvents = new Map();
+    const walk = step => {
+      for (const substeps of step._branches.values()) {
+        substeps.forEach(substep => {
+          byEvents.set(`${substep.start},${substep.end}`, substep);
+          walk(substep);
+        });
+      }
+    };
+    walk(this);
+
+    const { ordered, conflicts } = this.schedule.level(leafGroups(this));
+    const toSteps = ({ group, first, second }) => ({
+      actor: group,
+      first: byEvents.get(first.join(",")),
+      second: byEvents.get(second.join(",")),
+    });
+    return { ordered: ordered.map(toSteps), conflicts: conflicts.map(toSteps) };
+  }
+
   /**
    * Build the substeps into a branch that looks like so
    *
@@ -318,45 +663,220 @@ class Step {
    *        [0, 0] \____/ [0, ∞]
    *
    * Note that there is [0, ∞] "slack" between the last substep and the end of the step. Slack between substeps is unioned.
+   * Once the schedule is frozen or executing, constraints that are already in place are left alone and any that changed are made as amendments with the reason
+   * @param {string|null} reason why the plan is changing, if it isn't a draft. Without one, changing a frozen plan throws
    * @throws {Error} if the duration of the substeps > duration of the parent. This is not impossible from a temporal networks perspective, but it is impossible in an EVA timeline
    */
-  construct() {
+  construct(reason = null) {
     // TODO: use some kind of this.dirty to determine if this is necessary to run?
     for (const [a, substeps] of this._branches.entries()) {
-      const minDuration = substeps.reduce((prev, curr) => {
-        return prev + curr.duration[0];
-      }, 0);
+      const [minDuration] = branchDuration(this._root, substeps);
 
       if (minDuration > this.duration[1]) {
         throw new Error(`The minimum duration of substeps cannot exceed the max duration of this step | ${this.actor.name} ${this.description}: ${this.duration[1]} vs. substeps: ${minDuration}`);
       }
 
-      // handle slack time between substeps
-      if (substeps.length > 1) {
-        substeps.forEach((substep, index) => {
+      // substeps that aren't chained can happen any time during this step
+      substeps
+        .filter(substep => !chained(this._root, substep))
+        .forEach(substep => {
+          constrain(this._root, this.start, substep.start, [0, Number.MAX_VALUE], reason);
+          constrain(this._root, substep.end, this.end, [0, Number.MAX_VALUE], reason);
+        });
+
+      const chain = substeps.filter(substep => chained(this._root, substep));
+      if (chain.length > 0) {
+        // handle slack time between substeps
+        chain.forEach((substep, index) => {
           // we're creating constraints looking back to the previous substep, so there's nothing to do on the first substep
           if (index === 0) {
             return;
           }
 
           // create a constraint between two substeps. the constraint will follow the slack set on each substep
-          const prevStep = substeps[index - 1];
+          const prevStep = chain[index - 1];
           // TODO: add back
           // const slack = (new Interval(...prevStep.slack[1])).union(new Interval(...substep.slack[0])).toJSON();
-          this.schedule.addConstraint(prevStep.end, substep.start);
+          // moving between worksites takes time
+          const [lower, upper] = joinInterval(substep);
+          const travel = travelTime(this._root._travelTimes, prevStep.location, substep.location);
+          constrain(this._root, prevStep.end, substep.start, [Math.max(lower, travel), Math.max(upper, travel)], reason);
         });
-      }
 
-      // create a constraint between start of this step and the first substep
-      this.schedule.addConstraint(this.start, substeps[0].start);
-      // constraint between end of the last substep and this step. allow for any amount of time between the last substep and the end of this step
-      this.schedule.addConstraint(substeps[substeps.length - 1].end, this.end, [0, Number.MAX_VALUE]);
+        // create a constraint between start of this step and the first substep. an actor with limited availability may need to wait for it to open
+        const waits = a !== this.actor && this._root._availability.some(({ actor }) => actor === a);
+        constrain(this._root, this.start, chain[0].start, waits ? [0, Number.MAX_VALUE] : joinInterval(chain[0]), reason);
+        // constraint between end of the last substep and this step. allow for any amount of time between the last substep and the end of this step
+        constrain(this._root, chain[chain.length - 1].end, this.end, [0, Number.MAX_VALUE], reason);
+      }
 
       // recurse through substeps
-      substeps.forEach(s => s.construct());
+      substeps.forEach(s => s.construct(reason));
+    }
+
+    // syncs and deadlines are applied last so that they aren't overwritten by the constraints between substeps
+    if (this._root === this) {
+      this._syncs.forEach(({ source, sourceEvent, target, targetEvent, interval }) => {
+        constrain(this._root, eventOf(source, sourceEvent), eventOf(target, targetEvent), interval, reason);
+      });
+      this._deadlines.forEach(({ step, deadline }) => {
+        constrain(this._root, this.start, step.end, [0, deadline], reason);
+      });
+      this._availability.forEach(({ actor, window, opens, closes }) => {
+        const [from, until] = window;
+        constrain(this._root, this.start, opens, [from, from], reason);
+        if (until < Number.MAX_VALUE) {
+          constrain(this._root, this.start, closes, [until, until], reason);
+        }
+        stepsOf(this, actor).forEach(step => {
+          constrain(this._root, opens, step.start, [0, Number.MAX_VALUE], reason);
+          if (until < Number.MAX_VALUE) {
+            constrain(this._root, step.end, closes, [0, Number.MAX_VALUE], reason);
+          }
+        });
+      });
     }
   };
 
+  /**
+   * Regenerate the declarative spec describing this Mission in its current state, including any as-performed times. The spec can be passed back into `Mission.fromSpec`. Steps are written in the order they were created, except that a step always comes after its parent and the steps before it in its actors' branches, eg. after it's been reassigned. Steps without a name are named after their start event
+   * @returns {object}
+   */
+  toSpec() {
+    const root = this._root;
+    const nameOf = step => step.name || `step-${step.start}`;
+    const ref = (step, event) => `${nameOf(step)}.${event}`;
+    const isDefaultSlack = slack => slack.every(([lower, upper]) => lower === 0 && upper === 0);
+
+    // actors with display hints are written as objects so the hints survive the round trip
+    const actorSpec = a => {
+      if (a.color === null && a.order === null) {
+        return a.name;
+      }
+      const spec = { name: a.name };
+      if (a.color !== null) {
+        spec.color = a.color;
+      }
+      if (a.order !== null) {
+        spec.order = a.order;
+      }
+      return spec;
+    };
+    const actorName = a => (typeof a === "string" ? a : a.name);
+    const actors = root._actors.map(actorSpec);
+    const steps = [];
+    const written = new Set();
+
+    const walk = parent => {
+      for (const [actor, substeps] of parent._branches.entries()) {
+        if (actor.name && !actors.some(a => actorName(a) === actor.name)) {
+          actors.push(actorSpec(actor));
+        }
+
+        substeps.forEach(substep => {
+          // a sync is in the branch of every actor taking part, but is only written once
+          if (written.has(substep)) {
+            return;
+          }
+          written.add(substep);
+
+          const s = {
+            name: nameOf(substep),
+            description: substep.description,
+            duration: substep.duration,
+          };
+          if (substep._syncActors) {
+            s.actors = substep._syncActors.map(a => a.name);
+            s.after = {};
+            substep._syncActors.forEach(a => {
+              const branch = parent._branches.get(a);
+              const index = branch.indexOf(substep);
+              s.after[a.name] = index === 0 ? null : nameOf(branch[index - 1]);
+            });
+          } else if (actor !== parent.actor) {
+            // substeps without an actor inherit the actor of their parent
+            s.actor = actor.name;
+          }
+          if (parent !== root) {
+            s.parent = nameOf(parent);
+          }
+          if (substep.location !== null) {
+            s.location = substep.location;
+          }
+          if (substep.parallel) {
+            s.parallel = true;
+          }
+          if (Object.keys(substep.usage).length > 0) {
+            s.usage = substep.usage;
+          }
+          if (!isDefaultSlack(substep.slack)) {
+            s.slack = substep.slack;
+          }
+          if (substep._startedAt !== null) {
+            s.started = substep._startedAt;
+          }
+          if (substep._completedAt !== null) {
+            s.completed = substep._completedAt;
+          }
+          steps.push({ step: substep, spec: s });
+
+          walk(substep);
+        });
+      }
+    };
+    walk(root);
+
+    // fromSpec appends each step to its branches in the order they're listed, so a step has to come after its parent and the steps before it in its branches. Otherwise steps are written in the order they were created, which a step's events give away
+    const prerequisites = new Map(
+      steps.map(({ step }) => {
+        const before = step._parent === root ? [] : [step._parent];
+        for (const branch of step._parent._branches.values()) {
+          const index = branch.indexOf(step);
+          if (index > 0) {
+            before.push(branch[index - 1]);
+          }
+        }
+        return [step, before];
+      })
+    );
+    const ordered = [];
+    const done = new Set();
+    while (ordered.length < steps.length) {
+      const next = steps
+        .filter(({ step }) => !done.has(step) && prerequisites.get(step).every(s => done.has(s)))
+        .reduce((a, b) => (b.step.start < a.step.start ? b : a));
+      done.add(next.step);
+      ordered.push(next);
+    }
+
+    const spec = {
+      actors,
+      steps: ordered.map(({ spec }) => spec),
+      syncs: root._syncs.map(({ source, sourceEvent, target, targetEvent, interval }) => ({
+        source: ref(source, sourceEvent),
+        target: ref(target, targetEvent),
+        interval,
+      })),
+      deadlines: root._deadlines.map(({ step, deadline }) => ({ step: nameOf(step), deadline })),
+    };
+    if (root._availability.length > 0) {
+      spec.availability = root._availability.map(({ actor, window }) => ({ actor: actor.name, window }));
+    }
+    if (Object.keys(root._travelTimes).length > 0) {
+      spec.travelTimes = root._travelTimes;
+    }
+    if (!root._autoChain) {
+      spec.autoChain = false;
+    }
+    if (root._consumables.length > 0) {
+      spec.consumables = root._consumables.map(({ actor, name, capacity, rate }) => ({ actor: actor.name, name, capacity, rate }));
+    }
+    if (root._marginThresholds.length > 0) {
+      spec.marginAlerts = root._marginThresholds.map(({ consumable, value, label }) => ({ consumable, value, label }));
+    }
+    return spec;
+  };
+
   /**
    * Check the timeline for internal consistency with respect to parent<->child relationships. Returns issues found.
    * @returns {object[string[]]}
@@ -368,12 +888,7 @@ class Step {
     };
 
     for (const [a, substeps] of this._branches.entries()) {
-      const minDuration = substeps.reduce((prev, curr) => {
-        return prev + curr.duration[0];
-      }, 0);
-      const maxDuration = substeps.reduce((prev, curr) => {
-        return prev + curr.duration[1];
-      }, 0);
+      const [minDuration, maxDuration] = branchDuration(this._root, substeps);
 
       if (minDuration > this.duration[1]) {
         ret.errors.push(`The minimum duration of substeps cannot exceed the max duration of this step | ${this.actor.name} ${this.description}: ${this.duration[1]} vs. substeps: ${minDuration}`);
@@ -389,12 +904,216 @@ class Step {
       ret.warnings = ret.warnings.concat(subrets.map(s => s.warnings)).flat();
     }
 
+    // steps that can't fit within their actor's availability leave the schedule inconsistent
+    if (this._root === this && this._availability.length > 0) {
+      try {
+        this.construct();
+      } catch (e) {
+        // already reported above
+        return ret;
+      }
+
+      const contradictions = this.schedule.diagnoseCycles().filter(d => d.kind === "contradictory");
+      this._availability.forEach(({ actor, window, opens, closes }) => {
+        const involved = contradictions.filter(d => d.events.includes(opens) || d.events.includes(closes));
+        if (involved.length === 0) {
+          return;
+        }
+        const steps = stepsOf(this, actor)
+          .filter(step => involved.some(d => d.events.includes(step.start) || d.events.includes(step.end)))
+          .map(step => step.description);
+        ret.errors.push(`Steps cannot fit within the availability of ${actor.name} | [${window}]: ${steps.join(", ")}`);
+      });
+    }
+
     return ret;
   };
 }
 
 module.exports.Step = Step;
 
+/**
+ * Get the `[start, end]` events of every step without substeps beneath a step, grouped by actor name. Syncs count for every actor taking part
+ * @param {Step} step
+ * @returns {Object<string, number[][]>}
+ */
+function leafGroups(step) {
+  const groups = {};
+  const walk = parent => {
+    for (const [actor, substeps] of parent._branches.entries()) {
+      substeps.forEach(substep => {
+        if (substep._branches.size === 0) {
+          groups[actor.name] = groups[actor.name] || [];
+          groups[actor.name].push([substep.start, substep.end]);
+        }
+        walk(substep);
+      });
+    }
+  };
+  walk(step);
+  return groups;
+}
+
+/**
+ * Get every step beneath a step that an actor takes part in, including syncs and nested substeps
+ * @param {Step} step
+ * @param {Actor} actor
+ * @returns {Step[]}
+ */
+function stepsOf(step, actor) {
+  const steps = new Set();
+  const walk = parent => {
+    for (const substeps of parent._branches.values()) {
+      substeps.forEach(substep => {
+        const participants = substep._syncActors || [substep.actor];
+        if (participants.includes(actor)) {
+          steps.add(substep);
+        }
+        walk(substep);
+      });
+    }
+  };
+  walk(step);
+  return Array.from(steps);
+}
+
+/**
+ * The events a substep is chained between in a branch: the end of the chained step before it or the start of the parent, and the start of the chained step after it or the end of the parent. A substep that isn't chained is only tied to its parent
+ * @param {Step} parent
+ * @param {Step[]} branch
+ * @param {Step} substep
+ * @returns {number[]}
+ */
+function neighbors(parent, branch, substep) {
+  if (!chained(parent._root, substep)) {
+    return [parent.start, parent.end];
+  }
+  const chain = branch.filter(s => chained(parent._root, s));
+  const index = chain.indexOf(substep);
+  return [
+    index > 0 ? chain[index - 1].end : parent.start,
+    index < chain.length - 1 ? chain[index + 1].start : parent.end,
+  ];
+}
+
+/**
+ * Constrain two events of a Mission. In a draft the constraint is added like any other. Once the plan is frozen, a constraint the Mission already wrote is left alone and a new or changed one is added as an amendment with the reason
+ * @param {Step} root
+ * @param {number} source
+ * @param {number} target
+ * @param {number[]} interval
+ * @param {string|null} reason
+ * @throws {Error} if the plan isn't a draft and there's no reason
+ */
+function constrain(root, source, target, interval, reason = null) {
+  const key = `${source},${target}`;
+  const schedule = root.schedule;
+  if (schedule.phase === "draft") {
+    schedule.addConstraint(source, target, interval);
+  } else {
+    const wired = root._wired.get(key);
+    if (wired && wired[0] === interval[0] && wired[1] === interval[1]) {
+      return;
+    }
+    if (reason) {
+      schedule.amend(reason, { op: "addConstraint", source, target, interval });
+    } else {
+      // let the schedule explain why the plan can't change
+      schedule.addConstraint(source, target, interval);
+    }
+  }
+  root._wired.set(key, interval);
+}
+
+/**
+ * Remove the constraint between two events in both directions. Once the plan is frozen, the removals are amendments with the reason
+ * @param {Step} root
+ * @param {number} a
+ * @param {number} b
+ * @param {string} reason
+ */
+function unwire(root, a, b, reason) {
+  const schedule = root.schedule;
+  [[a, b], [b, a]].forEach(([source, target]) => {
+    if (schedule.phase === "draft") {
+      schedule.removeConstraint(source, target);
+    } else {
+      schedule.amend(reason, { op: "removeConstraint", source, target });
+    }
+    root._wired.delete(`${source},${target}`);
+  });
+}
+
+/**
+ * Whether a substep follows the step before it in its branch. Syncs always do, and other steps do unless they're parallel or the Mission doesn't chain steps
+ * @param {Step} root
+ * @param {Step} substep
+ * @returns {boolean}
+ */
+function chained(root, substep) {
+  return Boolean(substep._syncActors) || (root._autoChain && !substep.parallel);
+}
+
+/**
+ * The [lower, upper] time a branch of substeps takes: the chained substeps back to back, or the longest substep that isn't chained if that takes more
+ * @param {Step} root
+ * @param {Step[]} substeps
+ * @returns {number[]}
+ */
+function branchDuration(root, substeps) {
+  return substeps.reduce(
+    ([lower, upper], substep) => {
+      const [l, u] = substep.duration;
+      return chained(root, substep) ? [lower + l, upper + u] : [Math.max(lower, l), Math.max(upper, u)];
+    },
+    [0, 0]
+  );
+}
+
+/**
+ * The interval into a substep from whatever comes before it in a branch. Actors can wait at a sync for the others to catch up, while ordinary substeps follow on immediately
+ * @param {Step} substep
+ * @returns {number[]}
+ */
+function joinInterval(substep) {
+  return substep._syncActors ? [0, Number.MAX_VALUE] : [0, 0];
+}
+
+/**
+ * The minimum time to travel between two worksites, falling back to the trip in the other direction. Steps without a location, or at the same location, don't need to travel
+ * @param {Object<string, Object<string, number>>} matrix
+ * @param {string|null} from
+ * @param {string|null} to
+ * @returns {number}
+ */
+function travelTime(matrix, from, to) {
+  if (from === null || to === null || from === to) {
+    return 0;
+  }
+  const there = (matrix[from] || {})[to];
+  if (there !== undefined) {
+    return there;
+  }
+  const back = (matrix[to] || {})[from];
+  return back === undefined ? 0 : back;
+}
+
+/**
+ * Get the event ID for the `"start"` or `"end"` of a step
+ * @param {Step} step
+ * @param {string} event
+ * @returns {number}
+ */
+function eventOf(step, event) {
+  if (event === "start") {
+    return step.start;
+  }
+  if (event === "end") {
+    return step.end;
+  }
+  throw new Error(`Unknown event "${event}" on step ${step.description}. Expected "start" or "end"`);
+}
+
 /**
  * Create a new Mission (which is just a special kind of Step)
  * @returns {Step}
@@ -406,13 +1125,168 @@ module.exports.Mission = function Mission() {
   return mission;
 };
 
+/**
+ * Build a Mission from a declarative timeline spec. YAML documents should be parsed into an object first
+ *
+ * ```
+ * {
+ *   "actors": ["EV1", "EV2"],
+ *   "steps": [
+ *     { "name": "egress", "description": "EGRESS/SETUP", "duration": [40, 50], "actor": "EV1" },
+ *     { "name": "uia", "description": "work UIA", "duration": [4, 6], "parent": "egress" }
+ *   ],
+ *   "syncs": [{ "source": "egress.end", "target": "uia.start", "interval": [0, 10] }],
+ *   "deadlines": [{ "step": "egress", "deadline": 60 }],
+ *   "availability": [{ "actor": "EV1", "window": [0, 400] }],
+ *   "travelTimes": { "airlock": { "P1 truss": 15 } },
+ *   "autoChain": true,
+ *   "consumables": [{ "actor": "EV1", "name": "O2", "capacity": 480, "rate": 1 }],
+ *   "marginAlerts": [{ "consumable": "O2", "value": 60, "label": "caution" }]
+ * }
+ * ```
+ *
+ * Actors are names or `{ name, color, order }` objects with display hints, see `Step.actorHints`. A step with a list of `actors` is a sync block, see `Step.createSync`, and may name the step each actor does right `after` it, eg. `{ "name": "meet", "duration": [5, 5], "actors": ["EV1", "EV2"], "after": { "EV1": "egress", "EV2": null } }`. Steps are created in the order they are listed. A step without a `parent` falls directly under the Mission, and a step without an `actor` inherits the actor of its parent. Steps may also include a `location`, `parallel: true` to opt out of chaining, consumable `usage` rates, `slack`, and as-performed `started` and `completed` times. See `Step.toSpec` for the inverse
+ * @param {object|string} spec the spec or its JSON representation
+ * @returns {{mission: Step, steps: Object<string, Step>}} the Mission and its Steps keyed by name
+ * @throws {Error} if the spec refers to a step or actor that hasn't been defined
+ */
+module.exports.Mission.fromSpec = function fromSpec(spec) {
+  const s = typeof spec === "string" ? JSON.parse(spec) : spec;
+  const mission = module.exports.Mission();
+  if (s.autoChain === false) {
+    mission.setAutoChain(false);
+  }
+
+  const actors = new Map();
+  (s.actors || []).forEach(a => {
+    const { name, color, order } = typeof a === "string" ? { name: a } : a;
+    actors.set(name, mission.createActor(name, { color, order }));
+  });
+
+  const steps = {};
+  (s.steps || []).forEach(({ name, description, duration, actor, actors: participants, after, parent, location, parallel, usage, slack, started, completed }) => {
+    if (!name) {
+      throw new Error(`Every step needs a name | ${JSON.stringify(description)}`);
+    }
+    if (steps[name]) {
+      throw new Error(`Step names must be unique | ${name}`);
+    }
+    if (parent && !steps[parent]) {
+      throw new Error(`Parent "${parent}" of step "${name}" must be defined before the step`);
+    }
+    if (!Array.isArray(duration) || duration.length !== 2) {
+      throw new Error(`Step "${name}" needs a [lower, upper] duration`);
+    }
+    if (actor && !actors.has(actor)) {
+      throw new Error(`Unknown actor "${actor}" for step "${name}"`);
+    }
+
+    const p = parent ? steps[parent] : mission;
+    let step;
+    if (participants) {
+      const preceding = new Map();
+      const syncActors = participants.map(a => {
+        if (!actors.has(a)) {
+          throw new Error(`Unknown actor "${a}" for sync "${name}"`);
+        }
+        const ref = (after || {})[a];
+        if (ref !== undefined) {
+          if (ref !== null && !steps[ref]) {
+            throw new Error(`Step "${ref}" before sync "${name}" must be defined before the sync`);
+          }
+          preceding.set(actors.get(a), ref === null ? null : steps[ref]);
+        }
+        return actors.get(a);
+      });
+      step = p.createSync(description === undefined ? name : description, duration, syncActors, preceding);
+    } else {
+      step = p.createStep(
+        description === undefined ? name : description,
+        duration,
+        actor ? actors.get(actor) : null,
+        slack,
+        { parallel }
+      );
+    }
+    step.name = name;
+    if (location !== undefined) {
+      step.location = location;
+    }
+    if (usage !== undefined) {
+      step.usage = usage;
+    }
+    steps[name] = step;
+
+    if (started !== undefined) {
+      step.startedAt(started);
+    }
+    if (completed !== undefined) {
+      step.completedAt(completed);
+    }
+  });
+
+  // resolve "name.start" or "name.end" to a step and an event
+  const resolve = ref => {
+    const [name, event] = ref.split(".");
+    if (!steps[name]) {
+      throw new Error(`Unknown step "${name}" in "${ref}"`);
+    }
+    return [steps[name], event];
+  };
+
+  (s.syncs || []).forEach(({ source, target, interval }) => {
+    mission.addSync(...resolve(source), ...resolve(target), interval);
+  });
+
+  (s.deadlines || []).forEach(({ step, deadline }) => {
+    if (!steps[step]) {
+      throw new Error(`Unknown step "${step}" for deadline`);
+    }
+    mission.addDeadline(steps[step], deadline);
+  });
+
+  (s.availability || []).forEach(({ actor, window }) => {
+    if (!actors.has(actor)) {
+      throw new Error(`Unknown actor "${actor}" for availability`);
+    }
+    mission.addAvailability(actors.get(actor), window);
+  });
+
+  if (s.travelTimes) {
+    mission.setTravelTimes(s.travelTimes);
+  }
+
+  (s.consumables || []).forEach(({ actor, name, capacity, rate }) => {
+    if (!actors.has(actor)) {
+      throw new Error(`Unknown actor "${actor}" for consumable "${name}"`);
+    }
+    mission.addConsumable(actors.get(actor), name, capacity, rate);
+  });
+
+  (s.marginAlerts || []).forEach(({ consumable, value, label }) => {
+    mission.alertIfMarginBelow(consumable, value, label === undefined ? null : label);
+  });
+
+  return { mission, steps };
+};
+
 /**
  * An actor in the timeline.
  */
 class Actor {
   name = null;
-  constructor(name = "") {
+  /** display color, eg. `"#1f77b4"` */
+  color = null;
+  /** display row, lowest first */
+  order = null;
+  constructor(name = "", hints = {}) {
     this.name = name;
+    if (hints.color !== undefined) {
+      this.color = hints.color;
+    }
+    if (hints.order !== undefined) {
+      this.order = hints.order;
+    }
   }
 }
 module.exports.Actor = Actor;
@@ -0,0 +1,16 @@
+/* tslint:disable */
+/* eslint-disable */
+import { Schedule, StnOptions } from "./index";
+
+/**
+ * Build a Schedule from a plan file path or its contents
+ */
+export function loadSchedule(file: string | Uint8Array, options?: StnOptions): Schedule;
+/**
+ * Read a plan file into a plain `{ edges }` object
+ */
+export function readPlan(file: string | Uint8Array): { edges: { source: number; target: number; interval: number[] }[] };
+/**
+ * Write the authored constraints of a Schedule in the binary plan format
+ */
+export function saveSchedule(schedule: Schedule, path?: string): Buffer;
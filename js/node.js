@@ -0,0 +1,54 @@
+/**
+ * Helpers for using temporal networks from Node. The package is built for Node, so the wasm is ready as soon as it's required - there's no async init step. Plan files can be JSON or the compact binary plan format and are read and written as Buffers
+ */
+
+const fs = require("fs");
+const { readPlan, registerScheduleFromBytes, writePlan } = require("./index");
+
+/**
+ * Turn a path, Buffer, or Uint8Array into bytes
+ * @param {string|Buffer|Uint8Array} file
+ * @returns {Uint8Array}
+ */
+function toBytes(file) {
+  if (typeof file === "string") {
+    return fs.readFileSync(file);
+  }
+  if (file instanceof Uint8Array) {
+    return file;
+  }
+  throw new Error(`Expected a path, Buffer, or Uint8Array but got ${typeof file}`);
+}
+
+/**
+ * Build a Schedule from a plan file. Events keep the IDs used in the file
+ * @param {string|Buffer|Uint8Array} file a path or the contents of the file
+ * @param {StnOptions} options how to read the edges. Defaults to explicit intervals
+ * @returns {Schedule}
+ */
+module.exports.loadSchedule = function loadSchedule(file, options) {
+  return registerScheduleFromBytes(toBytes(file), options);
+};
+
+/**
+ * Read a plan file into a plain `{ edges }` object without building a Schedule
+ * @param {string|Buffer|Uint8Array} file a path or the contents of the file
+ * @returns {object}
+ */
+module.exports.readPlan = function (file) {
+  return readPlan(toBytes(file));
+};
+
+/**
+ * Write the authored constraints of a Schedule in the binary plan format. Returns the bytes as a Buffer, and also writes them to `path` if given
+ * @param {Schedule} schedule
+ * @param {string} path
+ * @returns {Buffer}
+ */
+module.exports.saveSchedule = function saveSchedule(schedule, path = null) {
+  const bytes = Buffer.from(writePlan(schedule));
+  if (path) {
+    fs.writeFileSync(path, bytes);
+  }
+  return bytes;
+};
@@ -1,12 +1,123 @@
 use itertools::Itertools;
 use petgraph::graphmap::DiGraphMap;
-use std::collections::BTreeMap;
+use std::cmp::Ordering as Order;
+use std::collections::{BTreeMap, BinaryHeap};
+use std::fmt;
 use std::string::String;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
-/// Similar to [Python's networkx Floyd Warshall implementation](https://networkx.github.io/documentation/stable/reference/algorithms/generated/networkx.algorithms.shortest_paths.dense.floyd_warshall.html#networkx.algorithms.shortest_paths.dense.floyd_warshall). Performs all-pairs shortest paths against a graph and returns a mapping of the shortest paths
-pub fn floyd_warshall(graph: &DiGraphMap<i32, f64>) -> Result<BTreeMap<(i32, i32), f64>, String> {
+/// A flag for abandoning a long-running computation from another thread, eg. a compile that went stale because the user kept editing. Clones share the same flag
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    /// Ask every computation holding this token to stop at its next checkpoint
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// How far all-pairs shortest paths got, in iterations of its outer loop. There is one iteration per node
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Progress {
+    pub completed: usize,
+    pub total: usize,
+}
+
+/// Why a cancellable computation stopped without a result
+#[derive(Clone, Debug, PartialEq)]
+pub enum Abort {
+    /// The token was cancelled. Nothing was changed
+    Cancelled(Progress),
+    /// The computation failed, eg. on a negative cycle
+    Failed(String),
+}
+
+impl fmt::Display for Abort {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Abort::Cancelled(p) => write!(
+                f,
+                "cancelled after {} of {} iterations",
+                p.completed, p.total
+            ),
+            Abort::Failed(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// All-pairs shortest paths, with the predecessors needed to rebuild each path. Created by `floyd_warshall`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DistanceOracle {
+    distances: BTreeMap<(i32, i32), f64>,
+    /// The node just before the target on the shortest path from the source to the target
+    predecessors: BTreeMap<(i32, i32), i32>,
+}
+
+impl DistanceOracle {
+    /// The shortest distance from `a` to `b`. `f64::MAX` if `b` can't be reached from `a`. `None` if either node isn't in the graph
+    pub fn distance(&self, a: i32, b: i32) -> Option<f64> {
+        self.distances.get(&(a, b)).copied()
+    }
+
+    /// The nodes along the shortest path from `a` to `b`, including both ends. `None` if there is no path
+    pub fn path(&self, a: i32, b: i32) -> Option<Vec<i32>> {
+        if a == b {
+            return self.distances.get(&(a, b)).map(|_| vec![a]);
+        }
+
+        let mut path = vec![b];
+        let mut current = b;
+        while current != a {
+            current = *self.predecessors.get(&(a, current))?;
+            // a negative cycle can make predecessors loop forever
+            if path.len() > self.distances.len() {
+                return None;
+            }
+            path.push(current);
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Whether or not the graph is free of negative cycles
+    pub fn consistent(&self) -> bool {
+        self.distances
+            .iter()
+            .all(|((a, b), d)| match self.distances.get(&(*b, *a)) {
+                Some(back) => d + back >= 0.,
+                None => true,
+            })
+    }
+
+    /// Every shortest distance, keyed by (source, target)
+    pub fn distances(&self) -> &BTreeMap<(i32, i32), f64> {
+        &self.distances
+    }
+}
+
+/// Similar to [Python's networkx Floyd Warshall implementation](https://networkx.github.io/documentation/stable/reference/algorithms/generated/networkx.algorithms.shortest_paths.dense.floyd_warshall.html#networkx.algorithms.shortest_paths.dense.floyd_warshall). Performs all-pairs shortest paths against a graph and returns the shortest distances along with the predecessors needed to rebuild the paths
+pub fn floyd_warshall(graph: &DiGraphMap<i32, f64>) -> Result<DistanceOracle, String> {
+    floyd_warshall_cancellable(graph, &CancelToken::new()).map_err(|e| e.to_string())
+}
+
+/// `floyd_warshall` that checks a token before each iteration of its outer loop and stops early if it was cancelled
+pub fn floyd_warshall_cancellable(
+    graph: &DiGraphMap<i32, f64>,
+    token: &CancelToken,
+) -> Result<DistanceOracle, Abort> {
     // TODO: would be neat to use generics instead
     let mut mappings = BTreeMap::new();
+    let mut predecessors = BTreeMap::new();
 
     // initialize distances to self to 0
     for node in graph.nodes() {
@@ -16,10 +127,16 @@ pub fn floyd_warshall(graph: &DiGraphMap<i32, f64>) -> Result<BTreeMap<(i32, i32
     // add existing edges
     for (source, target, weight) in graph.all_edges() {
         mappings.insert((source, target), *weight);
+        predecessors.insert((source, target), source);
     }
 
     // get the smallest distances seen so far
     let triangles = graph.nodes().permutations(3);
+    let mut progress = Progress {
+        completed: 0,
+        total: graph.node_count(),
+    };
+    let mut current_k = None;
 
     for triangle in triangles {
         let k = triangle[0];
@@ -27,6 +144,17 @@ pub fn floyd_warshall(graph: &DiGraphMap<i32, f64>) -> Result<BTreeMap<(i32, i32
         let j = triangle[2];
         let position = (i, j);
 
+        // permutations are in order, so k only changes between iterations of the outer loop
+        if current_k != Some(k) {
+            if current_k.is_some() {
+                progress.completed += 1;
+            }
+            if token.is_cancelled() {
+                return Err(Abort::Cancelled(progress));
+            }
+            current_k = Some(k);
+        }
+
         let d_ik = match mappings.get(&(i, k)) {
             Some(d) => d,
             None => &std::f64::MAX,
@@ -50,11 +178,415 @@ pub fn floyd_warshall(graph: &DiGraphMap<i32, f64>) -> Result<BTreeMap<(i32, i32
                 "negative cycle found on node ID {}: {} + {} = {}",
                 i, d_ik, d_kj, d_new
             );
-            return Err(error_message);
+            return Err(Abort::Failed(error_message));
         }
 
+        if d_new < *d_current {
+            if let Some(p) = predecessors.get(&(k, j)).copied() {
+                predecessors.insert(position, p);
+            }
+        }
         mappings.insert(position, d_new);
     }
 
-    Ok(mappings)
+    Ok(DistanceOracle {
+        distances: mappings,
+        predecessors,
+    })
+}
+
+/// Johnson's algorithm. Same results as `floyd_warshall`, but faster on sparse graphs: the graph is reweighted with Bellman-Ford potentials so Dijkstra can run from every node. Every pair of nodes gets a distance, `f64::MAX` if the target can't be reached. Errs on a negative cycle
+pub fn johnson(graph: &DiGraphMap<i32, f64>) -> Result<DistanceOracle, String> {
+    johnson_cancellable(graph, &CancelToken::new()).map_err(|e| e.to_string())
+}
+
+/// `johnson` that checks a token before each Dijkstra run and stops early if it was cancelled
+pub fn johnson_cancellable(
+    graph: &DiGraphMap<i32, f64>,
+    token: &CancelToken,
+) -> Result<DistanceOracle, Abort> {
+    let potentials = potentials(graph).map_err(Abort::Failed)?;
+    let edges = bounded_edges(graph);
+
+    let mut neighbors: BTreeMap<i32, Vec<(i32, f64)>> = BTreeMap::new();
+    for (s, t, w) in edges.iter() {
+        // reweighted edges are never negative
+        let reweighted = (w + potentials[s] - potentials[t]).max(0.);
+        neighbors.entry(*s).or_default().push((*t, reweighted));
+    }
+
+    let mut distances = BTreeMap::new();
+    let mut predecessors = BTreeMap::new();
+    let nodes: Vec<i32> = graph.nodes().collect();
+    let mut progress = Progress {
+        completed: 0,
+        total: nodes.len(),
+    };
+    for source in nodes.iter() {
+        if token.is_cancelled() {
+            return Err(Abort::Cancelled(progress));
+        }
+
+        // Dijkstra over the reweighted edges
+        let mut tentative: BTreeMap<i32, f64> = BTreeMap::new();
+        let mut reached: BTreeMap<i32, f64> = BTreeMap::new();
+        let mut queue = BinaryHeap::new();
+        tentative.insert(*source, 0.);
+        queue.push(Visit(0., *source));
+        while let Some(Visit(d, node)) = queue.pop() {
+            if reached.contains_key(&node) {
+                continue;
+            }
+            reached.insert(node, d);
+            for (next, w) in neighbors.get(&node).into_iter().flatten() {
+                let d_next = d + w;
+                if !reached.contains_key(next) && tentative.get(next).is_none_or(|t| d_next < *t) {
+                    tentative.insert(*next, d_next);
+                    predecessors.insert((*source, *next), node);
+                    queue.push(Visit(d_next, *next));
+                }
+            }
+        }
+
+        for target in nodes.iter() {
+            let distance = match reached.get(target) {
+                Some(d) => d - potentials[source] + potentials[target],
+                None => f64::MAX,
+            };
+            distances.insert((*source, *target), distance);
+        }
+        progress.completed += 1;
+    }
+
+    Ok(DistanceOracle {
+        distances,
+        predecessors,
+    })
+}
+/// Johnson's potentials: the shortest distance to every node from a virtual source with a 0 edge to each of them. Adding `potentials[s] - potentials[t]` to every edge makes it non-negative without changing which paths are shortest. Errs on a negative cycle
+pub fn potentials(graph: &DiGraphMap<i32, f64>) -> Result<BTreeMap<i32, f64>, String> {
+    let edges = bounded_edges(graph);
+    let mut potentials: BTreeMap<i32, f64> = graph.nodes().map(|n| (n, 0.)).collect();
+    for pass in 0..=graph.node_count() {
+        let mut changed = false;
+        for (s, t, w) in edges.iter() {
+            let d_new = potentials[s] + w;
+            if d_new < potentials[t] {
+                potentials.insert(*t, d_new);
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+        if pass == graph.node_count() {
+            let cycle = negative_cycle(graph).unwrap_or_default();
+            return Err(format!("negative cycle found through node IDs {:?}", cycle));
+        }
+    }
+    Ok(potentials)
+}
+
+/// Every edge with a finite weight. Unbounded edges don't constrain anything
+fn bounded_edges(graph: &DiGraphMap<i32, f64>) -> Vec<(i32, i32, f64)> {
+    graph
+        .all_edges()
+        .filter(|(_, _, w)| **w < f64::MAX)
+        .map(|(s, t, w)| (s, t, *w))
+        .collect()
+}
+
+/// A node waiting to be visited by Dijkstra, ordered so the closest comes out of a max-heap first
+#[derive(PartialEq)]
+pub(crate) struct Visit(pub f64, pub i32);
+
+impl Eq for Visit {}
+
+impl PartialOrd for Visit {
+    fn partial_cmp(&self, other: &Visit) -> Option<Order> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Visit {
+    fn cmp(&self, other: &Visit) -> Order {
+        other.0.total_cmp(&self.0).then(other.1.cmp(&self.1))
+    }
+}
+
+/// Update all-pairs shortest distances after an edge from `source` to `target` was added or shortened to `weight`, instead of recomputing every distance. With `bounded`, only pairs the edge can shorten are relaxed: sources that reach `target` more cheaply through the edge, and targets reached more cheaply from `source` through it. Otherwise every pair is. Missing distances are unbounded. Returns how many distances changed. Errs if the edge closes a negative cycle
+pub fn tighten_edge(
+    distances: &mut BTreeMap<(i32, i32), f64>,
+    nodes: &[i32],
+    (source, target, weight): (i32, i32, f64),
+    bounded: bool,
+) -> Result<usize, String> {
+    let d = |distances: &BTreeMap<(i32, i32), f64>, a: i32, b: i32| {
+        distances.get(&(a, b)).copied().unwrap_or(f64::MAX)
+    };
+    if weight >= f64::MAX {
+        return Ok(0);
+    }
+    if weight + d(distances, target, source) < 0. {
+        return Err(format!(
+            "negative cycle found through node IDs [{}, {}]",
+            source, target
+        ));
+    }
+
+    // distances to `source` and from `target` can't change, so they're safe to read while relaxing
+    let (sources, targets): (Vec<i32>, Vec<i32>) = if bounded {
+        (
+            nodes
+                .iter()
+                .copied()
+                .filter(|i| {
+                    let to_source = d(distances, *i, source);
+                    to_source < f64::MAX && to_source + weight < d(distances, *i, target)
+                })
+                .collect(),
+            nodes
+                .iter()
+                .copied()
+                .filter(|j| {
+                    let from_target = d(distances, target, *j);
+                    from_target < f64::MAX && weight + from_target < d(distances, source, *j)
+                })
+                .collect(),
+        )
+    } else {
+        (nodes.to_vec(), nodes.to_vec())
+    };
+
+    let mut changed = 0;
+    for i in sources.iter() {
+        let to_source = d(distances, *i, source);
+        if to_source >= f64::MAX {
+            continue;
+        }
+        for j in targets.iter() {
+            let from_target = d(distances, target, *j);
+            if from_target >= f64::MAX {
+                continue;
+            }
+            let d_new = to_source + weight + from_target;
+            if d_new < d(distances, *i, *j) {
+                distances.insert((*i, *j), d_new);
+                changed += 1;
+            }
+        }
+    }
+    Ok(changed)
+}
+
+/// Single-source shortest paths that tolerates negative edge weights. Returns the distance to every node reachable from `source`. `skip` leaves one edge out of the search, which is useful for checking whether or not an edge is dominated by other paths. Errs if a negative cycle is reachable from `source`
+pub fn bellman_ford(
+    graph: &DiGraphMap<i32, f64>,
+    source: i32,
+    skip: Option<(i32, i32)>,
+) -> Result<BTreeMap<i32, f64>, String> {
+    let mut distances = BTreeMap::new();
+    distances.insert(source, 0.);
+
+    let edges: Vec<(i32, i32, f64)> = graph
+        .all_edges()
+        .filter(|(s, t, _)| Some((*s, *t)) != skip)
+        .map(|(s, t, w)| (s, t, *w))
+        .collect();
+
+    // relax every edge up to |V| - 1 times, stopping early if nothing changes
+    for _ in 1..graph.node_count() {
+        let mut changed = false;
+        for (s, t, w) in edges.iter() {
+            let d_s = match distances.get(s) {
+                Some(d) => *d,
+                None => continue,
+            };
+            let d_new = d_s + w;
+            let d_t = distances.get(t).copied().unwrap_or(f64::MAX);
+            if d_new < d_t {
+                distances.insert(*t, d_new);
+                changed = true;
+            }
+        }
+
+        if !changed {
+            return Ok(distances);
+        }
+    }
+
+    // one more pass. anything that still relaxes is on a negative cycle
+    for (s, t, w) in edges.iter() {
+        if let (Some(d_s), Some(d_t)) = (distances.get(s), distances.get(t)) {
+            if d_s + w < *d_t {
+                return Err(format!("negative cycle found through node ID {}", t));
+            }
+        }
+    }
+
+    Ok(distances)
+}
+
+/// Find a negative cycle anywhere in the graph. Returns the nodes of the cycle in order, starting from its lowest node ID, or `None` if there isn't one
+pub fn negative_cycle(graph: &DiGraphMap<i32, f64>) -> Option<Vec<i32>> {
+    // start every node at 0, as if there were a virtual source connected to every node
+    let mut distances: BTreeMap<i32, f64> = graph.nodes().map(|n| (n, 0.)).collect();
+    let mut predecessors: BTreeMap<i32, i32> = BTreeMap::new();
+
+    let mut relaxed = None;
+    for _ in 0..graph.node_count() {
+        relaxed = None;
+        for (s, t, w) in graph.all_edges() {
+            let d_new = distances[&s] + w;
+            if d_new < distances[&t] {
+                distances.insert(t, d_new);
+                predecessors.insert(t, s);
+                relaxed = Some(t);
+            }
+        }
+        if relaxed.is_none() {
+            break;
+        }
+    }
+
+    // still relaxing after |V| passes. walk back far enough to be sure we're on the cycle
+    let mut node = relaxed?;
+    for _ in 0..graph.node_count() {
+        node = predecessors[&node];
+    }
+
+    let mut cycle = vec![node];
+    let mut current = predecessors[&node];
+    while current != node {
+        cycle.push(current);
+        current = predecessors[&current];
+    }
+    cycle.reverse();
+
+    let lowest = cycle
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, n)| **n)
+        .map_or(0, |(i, _)| i);
+    cycle.rotate_left(lowest);
+    Some(cycle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bellman_ford() {
+        // walkthrough example, [10, 20] then [30, 40]
+        let mut graph = DiGraphMap::new();
+        graph.add_edge(0, 1, 20.);
+        graph.add_edge(1, 0, -10.);
+        graph.add_edge(1, 2, 40.);
+        graph.add_edge(2, 1, -30.);
+
+        let distances = bellman_ford(&graph, 0, None).unwrap();
+        assert_eq!(distances[&2], 60.);
+        assert_eq!(bellman_ford(&graph, 2, None).unwrap()[&0], -40.);
+
+        // without the edge, 2 can't be reached
+        let distances = bellman_ford(&graph, 0, Some((1, 2))).unwrap();
+        assert_eq!(distances.get(&2), None);
+    }
+
+    #[test]
+    fn test_distance_oracle() {
+        // [10, 20] then [30, 40], and no more than 55 from start to end
+        let mut graph = DiGraphMap::new();
+        graph.add_edge(0, 1, 20.);
+        graph.add_edge(1, 0, -10.);
+        graph.add_edge(1, 2, 40.);
+        graph.add_edge(2, 1, -30.);
+        graph.add_edge(0, 2, 55.);
+
+        let oracle = floyd_warshall(&graph).unwrap();
+        assert!(oracle.consistent());
+        assert_eq!(oracle.distance(0, 2), Some(55.));
+        assert_eq!(oracle.distance(2, 0), Some(-40.));
+        assert_eq!(oracle.distance(0, 3), None);
+
+        assert_eq!(oracle.path(0, 2), Some(vec![0, 2]));
+        assert_eq!(oracle.path(2, 0), Some(vec![2, 1, 0]));
+        // the direct edge from 1 to 2 is tighter than going back through 0: -10 + 55 = 45
+        assert_eq!(oracle.path(1, 2), Some(vec![1, 2]));
+        assert_eq!(oracle.path(0, 1), Some(vec![0, 1]));
+
+        graph.add_edge(0, 2, 35.);
+        assert!(!floyd_warshall(&graph).unwrap().consistent());
+    }
+
+    #[test]
+    fn test_johnson() {
+        // [10, 20] then [30, 40], no more than 55 from start to end, and an unconnected node
+        let mut graph = DiGraphMap::new();
+        graph.add_edge(0, 1, 20.);
+        graph.add_edge(1, 0, -10.);
+        graph.add_edge(1, 2, 40.);
+        graph.add_edge(2, 1, -30.);
+        graph.add_edge(0, 2, 55.);
+        graph.add_node(3);
+
+        let oracle = johnson(&graph).unwrap();
+        assert_eq!(
+            oracle.distances(),
+            floyd_warshall(&graph).unwrap().distances()
+        );
+        assert_eq!(oracle.path(2, 0), Some(vec![2, 1, 0]));
+
+        graph.add_edge(0, 2, 35.);
+        assert_eq!(
+            johnson(&graph),
+            Err("negative cycle found through node IDs [0, 2, 1]".to_string())
+        );
+    }
+
+    #[test]
+    fn test_tighten_edge() {
+        let mut graph = DiGraphMap::new();
+        graph.add_edge(0, 1, 20.);
+        graph.add_edge(1, 0, -10.);
+        graph.add_edge(1, 2, 40.);
+        graph.add_edge(2, 1, -30.);
+        graph.add_node(3);
+        let nodes: Vec<i32> = graph.nodes().collect();
+
+        for bounded in [false, true] {
+            let mut distances = floyd_warshall(&graph).unwrap().distances().clone();
+            assert!(tighten_edge(&mut distances, &nodes, (0, 2, 55.), bounded).unwrap() > 0);
+
+            let mut tightened = graph.clone();
+            tightened.add_edge(0, 2, 55.);
+            assert_eq!(&distances, floyd_warshall(&tightened).unwrap().distances());
+
+            assert!(tighten_edge(&mut distances, &nodes, (0, 2, 35.), bounded).is_err());
+        }
+    }
+
+    #[test]
+    fn test_negative_cycle() {
+        // [10, 20] then [30, 40], but no more than 35 from start to end
+        let mut graph = DiGraphMap::new();
+        graph.add_edge(0, 1, 20.);
+        graph.add_edge(1, 0, -10.);
+        graph.add_edge(1, 2, 40.);
+        graph.add_edge(2, 1, -30.);
+        assert_eq!(negative_cycle(&graph), None);
+
+        graph.add_edge(0, 2, 35.);
+        assert_eq!(negative_cycle(&graph), Some(vec![0, 2, 1]));
+    }
+
+    #[test]
+    fn test_bellman_ford_negative_cycle() {
+        let mut graph = DiGraphMap::new();
+        graph.add_edge(0, 1, 1.);
+        graph.add_edge(1, 0, -2.);
+
+        assert!(bellman_ford(&graph, 0, None).is_err());
+    }
 }
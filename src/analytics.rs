@@ -0,0 +1,232 @@
+//! # Analytics
+//! Export execution traces as Apache Arrow record batches or Parquet files, eg. for pipelines that aggregate many missions. Enable with the `analytics` feature. Not built for wasm.
+//!
+//! A trace is three tables, each with a `mission` column so traces from different missions can be concatenated:
+//! - commitments: every event committed, in order
+//! - windows: the execution window of every event after each commitment
+//! - constraints: the authored constraints
+//!
+//! Unbounded window and constraint bounds (±f64::MAX) are written as nulls.
+//!
+//! ```no_run
+//! use std::path::Path;
+//! use temporal_networks::analytics::export;
+//! use temporal_networks::schedule::Schedule;
+//!
+//! let mut schedule = Schedule::new();
+//! let egress = schedule.add_episode(Some(vec![40., 50.]));
+//! schedule.commit_event(egress.start(), 0.).unwrap();
+//! export(&schedule, "sts-134", Path::new("traces")).unwrap();
+//! ```
+
+use std::fs::{create_dir_all, File};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow_array::{ArrayRef, Float64Array, Int32Array, RecordBatch, StringArray, UInt32Array};
+use arrow_schema::{DataType, Field, Schema};
+use parquet::arrow::ArrowWriter;
+
+use super::journal::Mutation;
+use super::schedule::Schedule;
+
+/// Every commitment in the order it was made. Columns are `mission`, `sequence`, `event`, and `time`
+pub fn commitments_batch(schedule: &Schedule, mission: &str) -> Result<RecordBatch, String> {
+    let commits: Vec<_> = schedule
+        .journal()
+        .iter()
+        .filter_map(|m| match m {
+            Mutation::CommitEvent { event, time } => Some((*event, *time)),
+            _ => None,
+        })
+        .collect();
+
+    let schema = Schema::new(vec![
+        Field::new("mission", DataType::Utf8, false),
+        Field::new("sequence", DataType::UInt32, false),
+        Field::new("event", DataType::Int32, false),
+        Field::new("time", DataType::Float64, false),
+    ]);
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from(vec![mission; commits.len()])),
+        Arc::new(UInt32Array::from_iter_values(0..commits.len() as u32)),
+        Arc::new(Int32Array::from_iter_values(commits.iter().map(|c| c.0))),
+        Arc::new(Float64Array::from_iter_values(commits.iter().map(|c| c.1))),
+    ];
+    RecordBatch::try_new(Arc::new(schema), columns).map_err(|e| e.to_string())
+}
+
+/// The execution window of every event after each commitment, found by replaying the Schedule's journal. Columns are `mission`, `sequence` of the commitment, `event`, `earliest`, and `latest`. Errs if the Schedule is inconsistent when a commitment is made
+pub fn windows_batch(schedule: &Schedule, mission: &str) -> Result<RecordBatch, String> {
+    let mut replay = Schedule::new();
+    let mut sequences = vec![];
+    let mut events = vec![];
+    let mut earliest = vec![];
+    let mut latest = vec![];
+
+    let mut sequence = 0;
+    for mutation in schedule.journal().iter() {
+        let commit = matches!(mutation, Mutation::CommitEvent { .. });
+        // committing compiles, which can only fail on contradictions. check first since the errors are JS values
+        if commit && replay.negative_cycle().is_some() {
+            return Err(format!(
+                "the Schedule is inconsistent at commitment {}",
+                sequence
+            ));
+        }
+        replay
+            .apply_journal(std::slice::from_ref(mutation))
+            .map_err(|_| format!("could not replay commitment {}", sequence))?;
+        if !commit {
+            continue;
+        }
+
+        let windows = replay
+            .time_windows()
+            .map_err(|_| format!("could not compile at commitment {}", sequence))?;
+        for w in windows.iter() {
+            sequences.push(sequence);
+            events.push(w.event);
+            earliest.push(bound(w.earliest));
+            latest.push(bound(w.latest));
+        }
+        sequence += 1;
+    }
+
+    let schema = Schema::new(vec![
+        Field::new("mission", DataType::Utf8, false),
+        Field::new("sequence", DataType::UInt32, false),
+        Field::new("event", DataType::Int32, false),
+        Field::new("earliest", DataType::Float64, true),
+        Field::new("latest", DataType::Float64, true),
+    ]);
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from(vec![mission; events.len()])),
+        Arc::new(UInt32Array::from(sequences)),
+        Arc::new(Int32Array::from(events)),
+        Arc::new(Float64Array::from(earliest)),
+        Arc::new(Float64Array::from(latest)),
+    ];
+    RecordBatch::try_new(Arc::new(schema), columns).map_err(|e| e.to_string())
+}
+
+/// The authored constraints. Columns are `mission`, `source`, `target`, `lower`, and `upper`
+pub fn constraints_batch(schedule: &Schedule, mission: &str) -> Result<RecordBatch, String> {
+    let constraints = schedule.constraints();
+
+    let schema = Schema::new(vec![
+        Field::new("mission", DataType::Utf8, false),
+        Field::new("source", DataType::Int32, false),
+        Field::new("target", DataType::Int32, false),
+        Field::new("lower", DataType::Float64, true),
+        Field::new("upper", DataType::Float64, true),
+    ]);
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from(vec![mission; constraints.len()])),
+        Arc::new(Int32Array::from_iter_values(
+            constraints.iter().map(|c| c.source),
+        )),
+        Arc::new(Int32Array::from_iter_values(
+            constraints.iter().map(|c| c.target),
+        )),
+        Arc::new(
+            constraints
+                .iter()
+                .map(|c| bound(c.interval.lower()))
+                .collect::<Float64Array>(),
+        ),
+        Arc::new(
+            constraints
+                .iter()
+                .map(|c| bound(c.interval.upper()))
+                .collect::<Float64Array>(),
+        ),
+    ];
+    RecordBatch::try_new(Arc::new(schema), columns).map_err(|e| e.to_string())
+}
+
+/// Write a record batch as a Parquet file
+pub fn write_parquet<W: Write + Send>(batch: &RecordBatch, writer: W) -> Result<(), String> {
+    let mut writer =
+        ArrowWriter::try_new(writer, batch.schema(), None).map_err(|e| e.to_string())?;
+    writer.write(batch).map_err(|e| e.to_string())?;
+    writer.close().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Write the trace of a Schedule to `commitments.parquet`, `windows.parquet`, and `constraints.parquet` in a directory, creating it if needed
+pub fn export(schedule: &Schedule, mission: &str, dir: &Path) -> Result<(), String> {
+    create_dir_all(dir).map_err(|e| e.to_string())?;
+
+    let tables = [
+        ("commitments", commitments_batch(schedule, mission)?),
+        ("windows", windows_batch(schedule, mission)?),
+        ("constraints", constraints_batch(schedule, mission)?),
+    ];
+    for (name, batch) in tables.iter() {
+        let file =
+            File::create(dir.join(format!("{}.parquet", name))).map_err(|e| e.to_string())?;
+        write_parquet(batch, file)?;
+    }
+    Ok(())
+}
+
+/// Unbounded values are written as nulls
+fn bound(value: f64) -> Option<f64> {
+    if value.abs() >= f64::MAX {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::examples::{sts134, walkthrough};
+    use arrow_array::Array;
+
+    #[test]
+    fn test_trace() {
+        let mut plan = walkthrough();
+        plan.schedule.commit_event(plan.x0, 0.).unwrap();
+        plan.schedule.commit_event(plan.l.start(), 15.).unwrap();
+
+        let commitments = commitments_batch(&plan.schedule, "walkthrough").unwrap();
+        assert_eq!(commitments.num_rows(), 2);
+
+        // every event after each of the two commitments
+        let windows = windows_batch(&plan.schedule, "walkthrough").unwrap();
+        assert_eq!(windows.num_rows(), 10);
+        let earliest = windows
+            .column_by_name("earliest")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap();
+        let latest = windows
+            .column_by_name("latest")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap();
+        let events = windows
+            .column_by_name("event")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<Int32Array>()
+            .unwrap();
+        let row = (5..10).find(|i| events.value(*i) == plan.l.end()).unwrap();
+        assert_eq!((earliest.value(row), latest.value(row)), (45., 50.));
+
+        let constraints = constraints_batch(&plan.schedule, "walkthrough").unwrap();
+        assert_eq!(constraints.num_rows(), plan.schedule.constraints().len());
+        let open_ended = constraints_batch(&sts134(0.).schedule, "sts-134").unwrap();
+        assert!(open_ended.column_by_name("upper").unwrap().null_count() > 0);
+
+        let mut bytes = vec![];
+        write_parquet(&windows, &mut bytes).unwrap();
+        assert_eq!(&bytes[..4], b"PAR1");
+    }
+}
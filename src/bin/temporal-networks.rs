@@ -0,0 +1,244 @@
+//! # temporal-networks
+//! Compile and validate a plan from the command line, eg. in CI. Plans are a list of edges in JSON, YAML, or the binary plan format:
+//!
+//! ```yaml
+//! edges:
+//!   - { source: 0, target: 1, interval: [40, 50] }
+//!   - { source: 1, target: 2, interval: [10, 15] }
+//! ```
+//!
+//! Prints the execution window of every event relative to the first event, the critical path, and any lint findings. Exits with 1 if there are lint errors and 2 if the plan can't be read.
+//!
+//! With `--watch`, keeps running and prints a line of JSON (NDJSON) every time the plan changes, eg. for an editor integration. Each line is either `{ "status": "compiled", "bounds", "criticalPath", "findings" }` or `{ "status": "unreadable", "error" }` if the plan can't be read or parsed mid-edit.
+
+use std::env;
+use std::fs;
+use std::process;
+use std::thread;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use temporal_networks::interval::{set_display_format, Interval, IntervalFormat};
+use temporal_networks::registration::{register, RegistrationPayload, StnOptions};
+use temporal_networks::schedule::{EventID, Finding, Schedule, TimeWindow};
+
+/// How often to check the plan for changes in watch mode
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+const USAGE: &str = "Usage: temporal-networks [--json | --dot | --watch] [--implicit] [--uncertainty <fraction>] <plan>
+
+Reads a plan of edges (.json, .yaml, .yml, or the binary plan format) and prints
+event bounds, the critical path, and lint findings.
+
+Options:
+  --json                   print the report as JSON
+  --dot                    print the constraints as Graphviz DOT instead of a report
+  --watch                  keep running and print a JSON report on one line whenever the plan changes
+  --implicit               edges use `minutes` instead of `interval`
+  --uncertainty <fraction> how much implicit intervals can vary by (default 0.1)
+  --decimals <n>           print times with n digits after the decimal point
+  --units <suffix>         print times with a suffix, eg. \" min\"
+  --unbounded <text>       print unbounded times as text instead of ∞";
+
+enum Output {
+    Text,
+    Json,
+    Dot,
+    Watch,
+}
+
+struct Args {
+    plan: String,
+    output: Output,
+    options: StnOptions,
+    format: IntervalFormat,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut plan = None;
+    let mut output = Output::Text;
+    let mut options = StnOptions::new();
+    let mut format = IntervalFormat::default();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => output = Output::Json,
+            "--dot" => output = Output::Dot,
+            "--watch" => output = Output::Watch,
+            "--implicit" => options = options.implicit_intervals(true),
+            "--uncertainty" => {
+                let value = args.next().ok_or("--uncertainty needs a value")?;
+                let uncertainty = value
+                    .parse()
+                    .map_err(|_| format!("{} is not a number", value))?;
+                options = options.uncertainty(uncertainty);
+            }
+            "--decimals" => {
+                let value = args.next().ok_or("--decimals needs a value")?;
+                let decimals = value
+                    .parse()
+                    .map_err(|_| format!("{} is not a number of digits", value))?;
+                format.decimals = Some(decimals);
+            }
+            "--units" => format.units = args.next().ok_or("--units needs a value")?,
+            "--unbounded" => {
+                let text = args.next().ok_or("--unbounded needs a value")?;
+                format.negative_infinity = format!("-{}", text);
+                format.infinity = text;
+            }
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            a if a.starts_with('-') => return Err(format!("unknown option {}", a)),
+            a => plan = Some(a.to_string()),
+        }
+    }
+
+    Ok(Args {
+        plan: plan.ok_or("missing a plan file")?,
+        output,
+        options,
+        format,
+    })
+}
+
+fn parse_plan(path: &str, bytes: &[u8]) -> Result<RegistrationPayload, String> {
+    if path.ends_with(".yaml") || path.ends_with(".yml") {
+        serde_yaml::from_slice(bytes).map_err(|e| format!("could not parse {}: {}", path, e))
+    } else {
+        RegistrationPayload::from_bytes(bytes)
+            .map_err(|e| format!("could not parse {}: {}", path, e))
+    }
+}
+
+fn read_plan(path: &str) -> Result<RegistrationPayload, String> {
+    let bytes = fs::read(path).map_err(|e| format!("could not read {}: {}", path, e))?;
+    parse_plan(path, &bytes)
+}
+
+/// Everything printed about a plan
+struct Report {
+    windows: Vec<TimeWindow>,
+    critical_path: Vec<EventID>,
+    findings: Vec<Finding>,
+}
+
+impl Report {
+    fn new(schedule: &mut Schedule) -> Report {
+        let findings = schedule.lint();
+        let errors = findings.iter().any(|f| f.severity == "error");
+
+        // bounds and the critical path only make sense for a plan that can be compiled
+        let (windows, critical_path) = match (errors, schedule.root()) {
+            (false, Some(root)) => {
+                let critical_path = schedule.critical_path().unwrap_or_default();
+                let _ = schedule.commit_events(vec![(root, 0.)]);
+                (schedule.time_windows().unwrap_or_default(), critical_path)
+            }
+            _ => (vec![], vec![]),
+        };
+
+        Report {
+            windows,
+            critical_path,
+            findings,
+        }
+    }
+
+    fn has_errors(&self) -> bool {
+        self.findings.iter().any(|f| f.severity == "error")
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "bounds": self.windows,
+            "criticalPath": self.critical_path,
+            "findings": self.findings,
+        })
+    }
+
+    fn print(&self) {
+        println!("bounds");
+        for w in self.windows.iter() {
+            println!("  {}: {}", w.event, Interval::new(w.earliest, w.latest));
+        }
+        let path: Vec<String> = self.critical_path.iter().map(|e| e.to_string()).collect();
+        println!("critical path: {}", path.join(" -> "));
+        println!("findings");
+        for f in self.findings.iter() {
+            println!("  {}: {} {:?}", f.severity, f.message, f.events);
+        }
+    }
+}
+
+/// Print a line of JSON for every change to the plan until killed. Plans that don't parse are reported instead of ending the process, since they're often mid-edit
+fn watch(path: &str, options: &StnOptions) -> ! {
+    let mut last: Option<Vec<u8>> = None;
+    let mut missing = false;
+    loop {
+        let line = match fs::read(path) {
+            Ok(bytes) if last.as_ref() == Some(&bytes) => None,
+            Ok(bytes) => {
+                let line = parse_plan(path, &bytes)
+                    .and_then(|payload| register(&payload, options))
+                    .map(|mut schedule| {
+                        let mut report = Report::new(&mut schedule).to_json();
+                        report["status"] = json!("compiled");
+                        report
+                    })
+                    .unwrap_or_else(|e| json!({ "status": "unreadable", "error": e }));
+                last = Some(bytes);
+                missing = false;
+                Some(line)
+            }
+            // report a missing file once, then again when it comes back
+            Err(_) if missing => None,
+            Err(e) => {
+                last = None;
+                missing = true;
+                Some(json!({
+                    "status": "unreadable",
+                    "error": format!("could not read {}: {}", path, e),
+                }))
+            }
+        };
+
+        if let Some(line) = line {
+            println!("{}", line);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(2);
+}
+
+fn main() {
+    let args = parse_args().unwrap_or_else(|e| fail(&format!("{}\n\n{}", e, USAGE)));
+    set_display_format(args.format.clone());
+    if let Output::Watch = args.output {
+        watch(&args.plan, &args.options);
+    }
+
+    let payload = read_plan(&args.plan).unwrap_or_else(|e| fail(&e));
+    let mut schedule: Schedule = register(&payload, &args.options).unwrap_or_else(|e| fail(&e));
+
+    if let Output::Dot = args.output {
+        print!("{}", schedule.to_dot());
+        return;
+    }
+
+    let report = Report::new(&mut schedule);
+    match args.output {
+        Output::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&report.to_json()).unwrap_or_default()
+        ),
+        _ => report.print(),
+    }
+
+    if report.has_errors() {
+        process::exit(1);
+    }
+}
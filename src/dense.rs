@@ -0,0 +1,615 @@
+//! # Dense
+//! All-pairs shortest paths over a dense n×n matrix of distances instead of a map keyed by pairs of events. Big plans compile much faster this way because every row is a contiguous slice. The min-plus inner loop of Floyd-Warshall, `row[j] = min(row[j], d_ik + row_k[j])`, runs two lanes at a time with wasm SIMD when the module is built with `-C target-feature=+simd128`, and four at a time with AVX on x86_64 CPUs that have it.
+//!
+//! Plans too big for an n×n matrix at all can use `DistanceRows` instead, which computes rows and columns of the matrix one at a time.
+//!
+//! `floyd_warshall_traced` records every relaxation that shortens a distance, eg. for training material that shows how implied constraints arise.
+
+use petgraph::graphmap::DiGraphMap;
+use std::collections::{BTreeMap, BinaryHeap};
+
+use super::algorithms::{potentials, Abort, CancelToken, Progress, Visit};
+
+/// Shortest distances between every pair of nodes, stored row by row. `f64::MAX` where the target can't be reached
+#[derive(Clone, Debug, PartialEq)]
+pub struct DenseMatrix {
+    nodes: Vec<i32>,
+    index: BTreeMap<i32, usize>,
+    distances: Vec<f64>,
+}
+
+impl DenseMatrix {
+    /// Lay out the edges of a graph as a matrix, with 0 from every node to itself
+    pub fn from_graph(graph: &DiGraphMap<i32, f64>) -> DenseMatrix {
+        let mut nodes: Vec<i32> = graph.nodes().collect();
+        nodes.sort_unstable();
+        let index: BTreeMap<i32, usize> = nodes.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+
+        let n = nodes.len();
+        let mut distances = vec![f64::MAX; n * n];
+        for i in 0..n {
+            distances[i * n + i] = 0.;
+        }
+        for (source, target, weight) in graph.all_edges() {
+            let cell = index[&source] * n + index[&target];
+            distances[cell] = distances[cell].min(*weight);
+        }
+
+        DenseMatrix {
+            nodes,
+            index,
+            distances,
+        }
+    }
+
+    /// Every node, in the order rows and columns are laid out
+    pub fn nodes(&self) -> &[i32] {
+        &self.nodes
+    }
+
+    /// Number of nodes
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// The shortest distance from `a` to `b`. `None` if either node isn't in the matrix
+    pub fn distance(&self, a: i32, b: i32) -> Option<f64> {
+        let (i, j) = (self.index.get(&a)?, self.index.get(&b)?);
+        Some(self.distances[i * self.len() + j])
+    }
+
+    /// Whether or not the graph is free of negative cycles, ie. no node is a negative distance from itself
+    pub fn consistent(&self) -> bool {
+        let n = self.len();
+        (0..n).all(|i| self.distances[i * n + i] >= 0.)
+    }
+
+    /// Every distance keyed by (source, target), like `DistanceOracle::distances`
+    pub fn to_map(&self) -> BTreeMap<(i32, i32), f64> {
+        let n = self.len();
+        let mut map = BTreeMap::new();
+        for (i, source) in self.nodes.iter().enumerate() {
+            for (j, target) in self.nodes.iter().enumerate() {
+                map.insert((*source, *target), self.distances[i * n + j]);
+            }
+        }
+        map
+    }
+}
+
+/// One relaxation of Floyd-Warshall that shortened a distance: going from `i` to `j` through `k` beat the `old` distance with the `new` one. Nodes are the graph's, not matrix indices
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Relaxation {
+    pub k: i32,
+    pub i: i32,
+    pub j: i32,
+    pub old: f64,
+    pub new: f64,
+}
+
+/// The relaxations recorded by `floyd_warshall_traced`, in the order they happened. Only the first `limit` are kept so a big plan can't fill memory, and the rest are counted in `dropped`
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Trace {
+    pub limit: usize,
+    pub relaxations: Vec<Relaxation>,
+    pub dropped: usize,
+}
+
+impl Trace {
+    pub fn new(limit: usize) -> Trace {
+        Trace {
+            limit,
+            ..Default::default()
+        }
+    }
+
+    fn record(&mut self, relaxation: Relaxation) {
+        if self.relaxations.len() < self.limit {
+            self.relaxations.push(relaxation);
+        } else {
+            self.dropped += 1;
+        }
+    }
+}
+
+/// Floyd-Warshall over a dense matrix. Checks a token before each iteration of its outer loop and stops early if it was cancelled. Doesn't track predecessors, so use `floyd_warshall` when paths are needed. Check `DenseMatrix::consistent` for negative cycles
+pub fn floyd_warshall_dense(
+    graph: &DiGraphMap<i32, f64>,
+    token: &CancelToken,
+) -> Result<DenseMatrix, Abort> {
+    relax_dense(graph, token, None)
+}
+
+/// `floyd_warshall_dense` that records each relaxation that shortens a distance in a trace. Slower, since rows are relaxed one entry at a time instead of with vector instructions
+pub fn floyd_warshall_traced(
+    graph: &DiGraphMap<i32, f64>,
+    trace: &mut Trace,
+    token: &CancelToken,
+) -> Result<DenseMatrix, Abort> {
+    relax_dense(graph, token, Some(trace))
+}
+
+fn relax_dense(
+    graph: &DiGraphMap<i32, f64>,
+    token: &CancelToken,
+    mut trace: Option<&mut Trace>,
+) -> Result<DenseMatrix, Abort> {
+    let mut matrix = DenseMatrix::from_graph(graph);
+    let n = matrix.len();
+    let nodes = &matrix.nodes;
+    let mut row_k = vec![0.; n];
+
+    for k in 0..n {
+        if token.is_cancelled() {
+            return Err(Abort::Cancelled(Progress {
+                completed: k,
+                total: n,
+            }));
+        }
+
+        // copy row k so every other row can be updated against it in place
+        row_k.copy_from_slice(&matrix.distances[k * n..(k + 1) * n]);
+        for (i, row) in matrix.distances.chunks_exact_mut(n).enumerate() {
+            let d_ik = row[k];
+            // nothing goes through k from an event that can't reach it
+            if d_ik >= f64::MAX {
+                continue;
+            }
+            match trace.as_deref_mut() {
+                None => min_plus_row(row, d_ik, &row_k),
+                Some(trace) => {
+                    for (j, (d_ij, d_kj)) in row.iter_mut().zip(row_k.iter()).enumerate() {
+                        let d_new = d_ik + d_kj;
+                        if d_new < *d_ij {
+                            trace.record(Relaxation {
+                                k: nodes[k],
+                                i: nodes[i],
+                                j: nodes[j],
+                                old: *d_ij,
+                                new: d_new,
+                            });
+                            *d_ij = d_new;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(matrix)
+}
+
+/// A tile size for `floyd_warshall_blocked` that keeps a tile of pivot rows in L2 on common hardware. Measured with `cargo bench --bench apsp`
+pub const BLOCK_SIZE: usize = 128;
+
+/// Floyd-Warshall in square tiles of `block` rows and columns, so the rows being relaxed against stay in cache on big matrices. Same results as `floyd_warshall_dense`. For each diagonal tile, its rows are relaxed first, then the columns of the other rows through the tile, and finally everything else, one column tile at a time. Checks a token before each diagonal tile
+pub fn floyd_warshall_blocked(
+    graph: &DiGraphMap<i32, f64>,
+    block: usize,
+    token: &CancelToken,
+) -> Result<DenseMatrix, Abort> {
+    let mut matrix = DenseMatrix::from_graph(graph);
+    let n = matrix.len();
+    let block = block.max(1);
+    let mut pivot_rows = vec![0.; block * n];
+
+    for start in (0..n).step_by(block) {
+        if token.is_cancelled() {
+            return Err(Abort::Cancelled(Progress {
+                completed: start,
+                total: n,
+            }));
+        }
+        let end = (start + block).min(n);
+        let d = &mut matrix.distances;
+
+        // the tile's own rows, in full, in the usual order
+        for k in start..end {
+            let (before, rest) = d.split_at_mut(k * n);
+            let (row_k, after) = rest.split_at_mut(n);
+            for row in before[start * n..]
+                .chunks_exact_mut(n)
+                .chain(after[..(end - k - 1) * n].chunks_exact_mut(n))
+            {
+                let d_ik = row[k];
+                if d_ik < f64::MAX {
+                    min_plus_row(row, d_ik, row_k);
+                }
+            }
+        }
+        pivot_rows[..(end - start) * n].copy_from_slice(&d[start * n..end * n]);
+        let pivot = |k: usize, from: usize, to: usize| {
+            &pivot_rows[(k - start) * n + from..(k - start) * n + to]
+        };
+
+        // the tile's columns in every other row
+        for (i, row) in d.chunks_exact_mut(n).enumerate() {
+            if i >= start && i < end {
+                continue;
+            }
+            for k in start..end {
+                let d_ik = row[k];
+                if d_ik < f64::MAX {
+                    min_plus_row(&mut row[start..end], d_ik, pivot(k, start, end));
+                }
+            }
+        }
+
+        // everything else, a column tile at a time so the pivot rows stay in cache
+        for from in (0..n).step_by(block).filter(|from| *from != start) {
+            let to = (from + block).min(n);
+            for (i, row) in d.chunks_exact_mut(n).enumerate() {
+                if i >= start && i < end {
+                    continue;
+                }
+                for k in start..end {
+                    let d_ik = row[k];
+                    if d_ik < f64::MAX {
+                        min_plus_row(&mut row[from..to], d_ik, pivot(k, from, to));
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(matrix)
+}
+
+/// Rows and columns of the distance matrix computed one at a time, for plans too big to hold every distance. Each one is a Dijkstra run over edges reweighted with Johnson's potentials, so only the graph and O(n) distances per row are ever in memory
+#[derive(Clone, Debug)]
+pub struct DistanceRows {
+    nodes: Vec<i32>,
+    index: BTreeMap<i32, usize>,
+    potentials: Vec<f64>,
+    /// Reweighted edges out of each node, by index
+    outgoing: Vec<Vec<(usize, f64)>>,
+    /// Reweighted edges into each node, by index
+    incoming: Vec<Vec<(usize, f64)>>,
+}
+
+impl DistanceRows {
+    /// Reweight the edges of a graph. Errs on a negative cycle
+    pub fn new(graph: &DiGraphMap<i32, f64>) -> Result<DistanceRows, String> {
+        let potentials = potentials(graph)?;
+        let nodes: Vec<i32> = potentials.keys().copied().collect();
+        let index: BTreeMap<i32, usize> = nodes.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+
+        let mut outgoing = vec![vec![]; nodes.len()];
+        let mut incoming = vec![vec![]; nodes.len()];
+        for (source, target, weight) in graph.all_edges() {
+            if *weight >= f64::MAX {
+                continue;
+            }
+            // reweighted edges are never negative
+            let reweighted = (weight + potentials[&source] - potentials[&target]).max(0.);
+            let (s, t) = (index[&source], index[&target]);
+            outgoing[s].push((t, reweighted));
+            incoming[t].push((s, reweighted));
+        }
+
+        Ok(DistanceRows {
+            potentials: potentials.values().copied().collect(),
+            nodes,
+            index,
+            outgoing,
+            incoming,
+        })
+    }
+
+    /// Every node, in the order rows and columns are laid out
+    pub fn nodes(&self) -> &[i32] {
+        &self.nodes
+    }
+
+    /// The shortest distance from `source` to every node. `None` if `source` isn't in the graph
+    pub fn row(&self, source: i32) -> Option<Vec<f64>> {
+        let s = *self.index.get(&source)?;
+        let reached = dijkstra(&self.outgoing, s);
+        Some(
+            reached
+                .iter()
+                .enumerate()
+                .map(|(t, d)| self.restore(*d, s, t))
+                .collect(),
+        )
+    }
+
+    /// The shortest distance from every node to `target`. `None` if `target` isn't in the graph
+    pub fn column(&self, target: i32) -> Option<Vec<f64>> {
+        let t = *self.index.get(&target)?;
+        let reached = dijkstra(&self.incoming, t);
+        Some(
+            reached
+                .iter()
+                .enumerate()
+                .map(|(s, d)| self.restore(*d, s, t))
+                .collect(),
+        )
+    }
+
+    /// Compute the rows from `sources` `tile` at a time, handing each tile to `f` as the sources and their rows back to back, so at most `tile` rows are in memory. Sources that aren't in the graph are skipped. Checks a token before each tile
+    pub fn for_each_tile(
+        &self,
+        sources: &[i32],
+        tile: usize,
+        token: &CancelToken,
+        mut f: impl FnMut(&[i32], &[f64]),
+    ) -> Result<(), Abort> {
+        let sources: Vec<i32> = sources
+            .iter()
+            .copied()
+            .filter(|s| self.index.contains_key(s))
+            .collect();
+        let mut rows = Vec::with_capacity(tile.max(1) * self.nodes.len());
+        for (i, chunk) in sources.chunks(tile.max(1)).enumerate() {
+            if token.is_cancelled() {
+                return Err(Abort::Cancelled(Progress {
+                    completed: i * tile.max(1),
+                    total: sources.len(),
+                }));
+            }
+            rows.clear();
+            for source in chunk {
+                rows.extend(self.row(*source).unwrap_or_default());
+            }
+            f(chunk, &rows);
+        }
+        Ok(())
+    }
+
+    /// Undo the reweighting of a distance from index `s` to index `t`
+    fn restore(&self, reweighted: f64, s: usize, t: usize) -> f64 {
+        if reweighted >= f64::MAX {
+            f64::MAX
+        } else {
+            reweighted - self.potentials[s] + self.potentials[t]
+        }
+    }
+}
+
+/// Dijkstra from one node over non-negative edges listed by node index. `f64::MAX` for nodes that can't be reached
+fn dijkstra(edges: &[Vec<(usize, f64)>], source: usize) -> Vec<f64> {
+    let mut distances = vec![f64::MAX; edges.len()];
+    let mut done = vec![false; edges.len()];
+    let mut queue = BinaryHeap::new();
+    distances[source] = 0.;
+    queue.push(Visit(0., source as i32));
+    while let Some(Visit(d, node)) = queue.pop() {
+        let node = node as usize;
+        if done[node] {
+            continue;
+        }
+        done[node] = true;
+        for (next, w) in edges[node].iter() {
+            let d_next = d + w;
+            if !done[*next] && d_next < distances[*next] {
+                distances[*next] = d_next;
+                queue.push(Visit(d_next, *next as i32));
+            }
+        }
+    }
+    distances
+}
+
+/// `row[j] = min(row[j], d_ik + row_k[j])` for every `j`, with the fastest instructions available
+pub fn min_plus_row(row: &mut [f64], d_ik: f64, row_k: &[f64]) {
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    {
+        simd128::min_plus_row(row, d_ik, row_k)
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    {
+        if std::is_x86_feature_detected!("avx") {
+            // safe because the CPU was just checked for AVX
+            unsafe { avx::min_plus_row(row, d_ik, row_k) }
+        } else {
+            min_plus_scalar(row, d_ik, row_k)
+        }
+    }
+
+    #[cfg(not(any(
+        all(target_arch = "wasm32", target_feature = "simd128"),
+        target_arch = "x86_64"
+    )))]
+    {
+        min_plus_scalar(row, d_ik, row_k)
+    }
+}
+
+/// `min_plus_row` one lane at a time
+pub fn min_plus_scalar(row: &mut [f64], d_ik: f64, row_k: &[f64]) {
+    for (d_ij, d_kj) in row.iter_mut().zip(row_k.iter()) {
+        let d_new = d_ik + d_kj;
+        if d_new < *d_ij {
+            *d_ij = d_new;
+        }
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+mod simd128 {
+    use core::arch::wasm32::*;
+
+    pub fn min_plus_row(row: &mut [f64], d_ik: f64, row_k: &[f64]) {
+        let n = row.len().min(row_k.len());
+        let d = f64x2_splat(d_ik);
+        let mut j = 0;
+        while j + 2 <= n {
+            // safe because j + 2 is within both slices, and wasm loads don't need to be aligned
+            unsafe {
+                let d_ij = v128_load(row.as_ptr().add(j) as *const v128);
+                let d_kj = v128_load(row_k.as_ptr().add(j) as *const v128);
+                // pmin picks the second lane only when it's strictly smaller, like the scalar loop
+                let d_new = f64x2_pmin(d_ij, f64x2_add(d, d_kj));
+                v128_store(row.as_mut_ptr().add(j) as *mut v128, d_new);
+            }
+            j += 2;
+        }
+        super::min_plus_scalar(&mut row[j..n], d_ik, &row_k[j..n]);
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod avx {
+    use std::arch::x86_64::*;
+
+    /// Callers must check that the CPU supports AVX
+    #[target_feature(enable = "avx")]
+    pub unsafe fn min_plus_row(row: &mut [f64], d_ik: f64, row_k: &[f64]) {
+        let n = row.len().min(row_k.len());
+        let d = _mm256_set1_pd(d_ik);
+        let mut j = 0;
+        while j + 4 <= n {
+            let d_ij = _mm256_loadu_pd(row.as_ptr().add(j));
+            let d_kj = _mm256_loadu_pd(row_k.as_ptr().add(j));
+            // min_pd returns the second operand unless the first is strictly smaller
+            let d_new = _mm256_min_pd(_mm256_add_pd(d, d_kj), d_ij);
+            _mm256_storeu_pd(row.as_mut_ptr().add(j), d_new);
+            j += 4;
+        }
+        super::min_plus_scalar(&mut row[j..n], d_ik, &row_k[j..n]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::floyd_warshall;
+
+    #[test]
+    fn test_floyd_warshall_dense() {
+        // [10, 20] then [30, 40], no more than 55 from start to end, and an unconnected node
+        let mut graph = DiGraphMap::new();
+        graph.add_edge(0, 1, 20.);
+        graph.add_edge(1, 0, -10.);
+        graph.add_edge(1, 2, 40.);
+        graph.add_edge(2, 1, -30.);
+        graph.add_edge(0, 2, 55.);
+        graph.add_node(3);
+
+        let matrix = floyd_warshall_dense(&graph, &CancelToken::new()).unwrap();
+        assert!(matrix.consistent());
+        assert_eq!(matrix.distance(2, 0), Some(-40.));
+        assert_eq!(matrix.distance(0, 3), Some(f64::MAX));
+        assert_eq!(
+            &matrix.to_map(),
+            floyd_warshall(&graph).unwrap().distances()
+        );
+
+        graph.add_edge(0, 2, 35.);
+        let matrix = floyd_warshall_dense(&graph, &CancelToken::new()).unwrap();
+        assert!(!matrix.consistent());
+    }
+
+    #[test]
+    fn test_floyd_warshall_traced() {
+        let mut graph = DiGraphMap::new();
+        graph.add_edge(0, 1, 20.);
+        graph.add_edge(1, 0, -10.);
+        graph.add_edge(1, 2, 40.);
+        graph.add_edge(2, 1, -30.);
+        graph.add_edge(0, 2, 55.);
+
+        let mut trace = Trace::new(10);
+        let matrix = floyd_warshall_traced(&graph, &mut trace, &CancelToken::new()).unwrap();
+        assert_eq!(
+            matrix,
+            floyd_warshall_dense(&graph, &CancelToken::new()).unwrap()
+        );
+        // the only implied constraint: 2 happens at least 40 after 0, through 1
+        assert_eq!(
+            trace.relaxations,
+            vec![Relaxation {
+                k: 1,
+                i: 2,
+                j: 0,
+                old: f64::MAX,
+                new: -40.
+            }]
+        );
+        assert_eq!(trace.dropped, 0);
+
+        let mut trace = Trace::new(0);
+        floyd_warshall_traced(&graph, &mut trace, &CancelToken::new()).unwrap();
+        assert_eq!((trace.relaxations.len(), trace.dropped), (0, 1));
+    }
+
+    #[test]
+    fn test_floyd_warshall_blocked() {
+        // a chain with a few shortcuts and a node that's only reached one way
+        let mut graph = DiGraphMap::new();
+        for i in 0..23 {
+            graph.add_edge(i, i + 1, 10. + (i % 4) as f64);
+            graph.add_edge(i + 1, i, -1.);
+        }
+        graph.add_edge(3, 17, 60.);
+        graph.add_edge(20, 6, -80.);
+        graph.add_edge(24, 0, 1.);
+
+        let dense = floyd_warshall_dense(&graph, &CancelToken::new()).unwrap();
+        for block in [1, 5, 8, 24, 100] {
+            let blocked = floyd_warshall_blocked(&graph, block, &CancelToken::new()).unwrap();
+            assert_eq!(blocked, dense, "block {}", block);
+        }
+
+        graph.add_edge(17, 3, -200.);
+        let blocked = floyd_warshall_blocked(&graph, 8, &CancelToken::new()).unwrap();
+        assert!(!blocked.consistent());
+    }
+
+    #[test]
+    fn test_distance_rows() {
+        let mut graph = DiGraphMap::new();
+        for i in 0..9 {
+            graph.add_edge(i, i + 1, 10. + i as f64);
+            graph.add_edge(i + 1, i, -2.);
+        }
+        graph.add_edge(2, 7, 30.);
+        graph.add_edge(9, 0, f64::MAX);
+        graph.add_node(10);
+        let matrix = floyd_warshall_dense(&graph, &CancelToken::new()).unwrap();
+
+        let rows = DistanceRows::new(&graph).unwrap();
+        for a in 0..=10 {
+            let row = rows.row(a).unwrap();
+            let column = rows.column(a).unwrap();
+            for b in 0..=10 {
+                let i = b as usize;
+                assert_eq!(Some(row[i]), matrix.distance(a, b), "{} to {}", a, b);
+                assert_eq!(Some(column[i]), matrix.distance(b, a), "{} to {}", b, a);
+            }
+        }
+        assert_eq!(rows.row(11), None);
+
+        let mut tiles = vec![];
+        rows.for_each_tile(&[4, 11, 1, 8], 2, &CancelToken::new(), |sources, tile| {
+            assert_eq!(tile.len(), sources.len() * rows.nodes().len());
+            tiles.push(sources.to_vec());
+        })
+        .unwrap();
+        assert_eq!(tiles, vec![vec![4, 1], vec![8]]);
+
+        graph.add_edge(7, 2, -100.);
+        assert!(DistanceRows::new(&graph).is_err());
+    }
+
+    #[test]
+    fn test_min_plus_row() {
+        // odd lengths exercise the scalar tail after the vector lanes
+        let row_k: Vec<f64> = (0..11).map(|j| (j as f64 - 5.) * 1.5).collect();
+        let mut row: Vec<f64> = (0..11)
+            .map(|j| if j % 3 == 0 { f64::MAX } else { 2. })
+            .collect();
+        let mut expected = row.clone();
+
+        min_plus_row(&mut row, 1., &row_k);
+        min_plus_scalar(&mut expected, 1., &row_k);
+        assert_eq!(row, expected);
+        assert_eq!(row[0], -6.5);
+    }
+}
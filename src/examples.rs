@@ -0,0 +1,117 @@
+//! # Examples
+//! Canonical plans for documentation, demos, and benchmarks, so they don't each need their own copy. Enable with the `examples` feature. The same plans are available from JS in `examples.js`.
+
+use super::schedule::{Episode, EventID, Schedule};
+
+/// The example from the walkthrough in docs/references/STNs_for_EVAs.pdf
+pub struct Walkthrough {
+    pub schedule: Schedule,
+    /// The start of the EVA
+    pub x0: EventID,
+    /// Takes [30, 40], starting [10, 20] after X0
+    pub l: Episode,
+    /// Takes [40, 50] and ends [60, 70] after X0. L ends [10, 20] after S starts
+    pub s: Episode,
+}
+
+/// Build the walkthrough example
+pub fn walkthrough() -> Walkthrough {
+    let mut schedule = Schedule::new();
+    let x0 = schedule.create_event();
+    let l = schedule.add_episode(Some(vec![30., 40.]));
+    let s = schedule.add_episode(Some(vec![40., 50.]));
+    schedule.update_interval(x0, l.start(), vec![10., 20.]);
+    schedule.update_interval(x0, s.end(), vec![60., 70.]);
+    schedule.update_interval(s.start(), l.end(), vec![10., 20.]);
+
+    Walkthrough { schedule, x0, l, s }
+}
+
+/// The first two activities of the STS-134 EVA summary timeline, performed by two crew members within an 8 hour limiting consumable
+pub struct Sts134 {
+    pub schedule: Schedule,
+    /// The limiting consumable, [0, 480] minutes
+    pub lim_cons: Episode,
+    /// [40, 50]
+    pub egress: Episode,
+    /// [55, 65], right after egress
+    pub misse7: Episode,
+    pub ev1_egress: Episode,
+    pub ev1_setup: Episode,
+    pub ev2_egress: Episode,
+    pub ev1_misse7: Episode,
+    pub ev2_misse7: Episode,
+}
+
+/// Build the STS-134 example. `uncertainty` widens every bounded duration by that fraction on both sides, eg. 0.1 turns [40, 50] into [36, 55]
+pub fn sts134(uncertainty: f64) -> Sts134 {
+    let interval = |lower: f64, upper: f64| {
+        let upper = if upper < f64::MAX {
+            upper + uncertainty * upper
+        } else {
+            upper
+        };
+        vec![lower - uncertainty * lower, upper]
+    };
+
+    let mut schedule = Schedule::new();
+    let lim_cons = schedule.add_episode(Some(interval(0., 480.)));
+
+    // high level activities in series
+    let egress = schedule.add_episode(Some(interval(40., 50.)));
+    let misse7 = schedule.add_episode(Some(interval(55., 65.)));
+    schedule.update_interval(lim_cons.start(), egress.start(), vec![0., 0.]);
+    schedule.update_interval(egress.end(), misse7.start(), vec![0., 0.]);
+    // the last activity needs to end before the limiting consumable is gone
+    schedule.update_interval(misse7.end(), lim_cons.end(), interval(0., f64::MAX));
+
+    // EV1 and EV2 egress in parallel but may not start at the same time
+    let ev1_egress = schedule.add_episode(Some(interval(10., 20.)));
+    let ev1_setup = schedule.add_episode(Some(interval(0., f64::MAX)));
+    let ev2_egress = schedule.add_episode(Some(interval(40., 50.)));
+    schedule.update_interval(egress.start(), ev1_egress.start(), vec![0., 0.]);
+    schedule.update_interval(ev1_egress.end(), ev1_setup.start(), vec![0., 0.]);
+    schedule.update_interval(egress.start(), ev2_egress.start(), interval(0., f64::MAX));
+    // the activity ends when both EVs finish
+    schedule.update_interval(egress.end(), ev1_setup.end(), vec![0., 0.]);
+    schedule.update_interval(egress.end(), ev2_egress.end(), vec![0., 0.]);
+
+    // EV1 and EV2 perform MISSE7 in parallel
+    let ev1_misse7 = schedule.add_episode(Some(interval(55., 65.)));
+    let ev2_misse7 = schedule.add_episode(Some(interval(55., 65.)));
+    schedule.update_interval(misse7.start(), ev1_misse7.start(), vec![0., 0.]);
+    schedule.update_interval(misse7.start(), ev2_misse7.start(), vec![0., 0.]);
+    schedule.update_interval(ev1_misse7.end(), misse7.end(), vec![0., 0.]);
+    schedule.update_interval(ev2_misse7.end(), misse7.end(), vec![0., 0.]);
+
+    Sts134 {
+        schedule,
+        lim_cons,
+        egress,
+        misse7,
+        ev1_egress,
+        ev1_setup,
+        ev2_egress,
+        ev1_misse7,
+        ev2_misse7,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interval::Interval;
+
+    #[test]
+    fn test_sts134_uncertainty() {
+        let plan = sts134(0.1);
+        assert_eq!(
+            plan.schedule.get_duration(&plan.egress),
+            Interval::new(36., 55.)
+        );
+        assert_eq!(
+            plan.schedule.get_duration(&plan.ev1_setup).upper(),
+            f64::MAX
+        );
+    }
+}
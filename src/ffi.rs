@@ -0,0 +1,291 @@
+//! # FFI
+//! A C API for embedding the scheduler in C/C++ programs, eg. flight software simulators. Schedules are passed around as opaque handles that must be freed with `tn_schedule_free`. Every fallible function returns a `TnStatus` and writes its results through out pointers. Not built for wasm.
+//!
+//! The header in `include/temporal_networks.h` is generated with [cbindgen](https://github.com/eqrion/cbindgen), see `make header`.
+//!
+//! ```c
+//! TnSchedule *schedule = tn_schedule_new();
+//! int32_t egress_start, egress_end;
+//! tn_schedule_add_episode(schedule, 40., 50., &egress_start, &egress_end);
+//! tn_schedule_commit(schedule, egress_start, 0.);
+//!
+//! double earliest, latest;
+//! if (tn_schedule_window(schedule, egress_end, &earliest, &latest) == TN_STATUS_OK) {
+//!     printf("egress ends between %f and %f\n", earliest, latest);
+//! }
+//! tn_schedule_free(schedule);
+//! ```
+
+use super::interval::Interval;
+use super::schedule::{EventID, Schedule};
+
+/// An opaque handle to a Schedule
+pub struct TnSchedule(Schedule);
+
+/// The outcome of a call through the C API
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TnStatus {
+    Ok = 0,
+    /// A handle or out pointer was null
+    NullPointer,
+    /// An event isn't in the Schedule
+    UnknownEvent,
+    /// The interval's lower bound is greater than its upper bound
+    InvalidInterval,
+    /// The constraints contradict each other
+    Inconsistent,
+    /// A commitment conflicts with the Schedule or existing commitments
+    Rejected,
+}
+
+/// Create an empty Schedule. Free it with `tn_schedule_free`
+#[no_mangle]
+pub extern "C" fn tn_schedule_new() -> *mut TnSchedule {
+    Box::into_raw(Box::new(TnSchedule(Schedule::new())))
+}
+
+/// Free a Schedule created with `tn_schedule_new`. Null is ignored
+///
+/// # Safety
+///
+/// `schedule` must be null or a handle from `tn_schedule_new` that hasn't been freed
+#[no_mangle]
+pub unsafe extern "C" fn tn_schedule_free(schedule: *mut TnSchedule) {
+    if !schedule.is_null() {
+        drop(Box::from_raw(schedule));
+    }
+}
+
+/// Add an Episode that takes between `lower` and `upper` time to complete. Writes its start and end events to `start` and `end`
+///
+/// # Safety
+///
+/// `schedule` must be a live handle and `start` and `end` must be valid to write to
+#[no_mangle]
+pub unsafe extern "C" fn tn_schedule_add_episode(
+    schedule: *mut TnSchedule,
+    lower: f64,
+    upper: f64,
+    start: *mut EventID,
+    end: *mut EventID,
+) -> TnStatus {
+    let schedule = match schedule.as_mut() {
+        Some(s) => &mut s.0,
+        None => return TnStatus::NullPointer,
+    };
+    if start.is_null() || end.is_null() {
+        return TnStatus::NullPointer;
+    }
+    if !Interval::new(lower, upper).is_valid() {
+        return TnStatus::InvalidInterval;
+    }
+
+    let episode = schedule.add_episode(Some(vec![lower, upper]));
+    *start = episode.start();
+    *end = episode.end();
+    TnStatus::Ok
+}
+
+/// Require the target to happen between `lower` and `upper` time after the source
+///
+/// # Safety
+///
+/// `schedule` must be a live handle
+#[no_mangle]
+pub unsafe extern "C" fn tn_schedule_add_constraint(
+    schedule: *mut TnSchedule,
+    source: EventID,
+    target: EventID,
+    lower: f64,
+    upper: f64,
+) -> TnStatus {
+    let schedule = match schedule.as_mut() {
+        Some(s) => &mut s.0,
+        None => return TnStatus::NullPointer,
+    };
+    if !schedule.contains_event(source) || !schedule.contains_event(target) {
+        return TnStatus::UnknownEvent;
+    }
+    if !Interval::new(lower, upper).is_valid() {
+        return TnStatus::InvalidInterval;
+    }
+
+    schedule.update_interval(source, target, vec![lower, upper]);
+    TnStatus::Ok
+}
+
+/// Compile the Schedule. Queries compile automatically, so this is only needed to control when the work happens
+///
+/// # Safety
+///
+/// `schedule` must be a live handle
+#[no_mangle]
+pub unsafe extern "C" fn tn_schedule_compile(schedule: *mut TnSchedule) -> TnStatus {
+    match schedule.as_mut() {
+        Some(s) => compile(&mut s.0),
+        None => TnStatus::NullPointer,
+    }
+}
+
+/// Record the time an event happened
+///
+/// # Safety
+///
+/// `schedule` must be a live handle
+#[no_mangle]
+pub unsafe extern "C" fn tn_schedule_commit(
+    schedule: *mut TnSchedule,
+    event: EventID,
+    time: f64,
+) -> TnStatus {
+    let schedule = match schedule.as_mut() {
+        Some(s) => &mut s.0,
+        None => return TnStatus::NullPointer,
+    };
+    if !schedule.contains_event(event) {
+        return TnStatus::UnknownEvent;
+    }
+    let status = compile(schedule);
+    if status != TnStatus::Ok {
+        return status;
+    }
+
+    match schedule.commit_events(vec![(event, time)]) {
+        Ok(report) if report.rejected.is_empty() => TnStatus::Ok,
+        _ => TnStatus::Rejected,
+    }
+}
+
+/// Write the earliest and latest times an event can happen to `earliest` and `latest`
+///
+/// # Safety
+///
+/// `schedule` must be a live handle and `earliest` and `latest` must be valid to write to
+#[no_mangle]
+pub unsafe extern "C" fn tn_schedule_window(
+    schedule: *mut TnSchedule,
+    event: EventID,
+    earliest: *mut f64,
+    latest: *mut f64,
+) -> TnStatus {
+    let schedule = match schedule.as_mut() {
+        Some(s) => &mut s.0,
+        None => return TnStatus::NullPointer,
+    };
+    if earliest.is_null() || latest.is_null() {
+        return TnStatus::NullPointer;
+    }
+    if !schedule.contains_event(event) {
+        return TnStatus::UnknownEvent;
+    }
+    let status = compile(schedule);
+    if status != TnStatus::Ok {
+        return status;
+    }
+
+    match schedule.window(event) {
+        Ok(window) => {
+            *earliest = window.lower();
+            *latest = window.upper();
+            TnStatus::Ok
+        }
+        Err(_) => TnStatus::UnknownEvent,
+    }
+}
+
+/// Write the [lower, upper] interval from the source to the target to `lower` and `upper`
+///
+/// # Safety
+///
+/// `schedule` must be a live handle and `lower` and `upper` must be valid to write to
+#[no_mangle]
+pub unsafe extern "C" fn tn_schedule_interval(
+    schedule: *mut TnSchedule,
+    source: EventID,
+    target: EventID,
+    lower: *mut f64,
+    upper: *mut f64,
+) -> TnStatus {
+    let schedule = match schedule.as_mut() {
+        Some(s) => &mut s.0,
+        None => return TnStatus::NullPointer,
+    };
+    if lower.is_null() || upper.is_null() {
+        return TnStatus::NullPointer;
+    }
+    if !schedule.contains_event(source) || !schedule.contains_event(target) {
+        return TnStatus::UnknownEvent;
+    }
+    let status = compile(schedule);
+    if status != TnStatus::Ok {
+        return status;
+    }
+
+    match schedule.interval(source, target) {
+        Ok(interval) => {
+            *lower = interval.lower();
+            *upper = interval.upper();
+            TnStatus::Ok
+        }
+        Err(_) => TnStatus::UnknownEvent,
+    }
+}
+
+/// Check for contradictions before compiling. The Schedule's own errors are JS values, which can't be created outside of wasm
+fn compile(schedule: &mut Schedule) -> TnStatus {
+    if schedule.negative_cycle().is_some() {
+        return TnStatus::Inconsistent;
+    }
+    match schedule.compile() {
+        Ok(()) => TnStatus::Ok,
+        Err(_) => TnStatus::Inconsistent,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_c_api() {
+        unsafe {
+            let schedule = tn_schedule_new();
+            let (mut start, mut end) = (0, 0);
+            assert_eq!(
+                tn_schedule_add_episode(schedule, 40., 50., &mut start, &mut end),
+                TnStatus::Ok
+            );
+            assert_eq!(tn_schedule_commit(schedule, start, 0.), TnStatus::Ok);
+
+            let (mut earliest, mut latest) = (0., 0.);
+            assert_eq!(
+                tn_schedule_window(schedule, end, &mut earliest, &mut latest),
+                TnStatus::Ok
+            );
+            assert_eq!((earliest, latest), (40., 50.));
+
+            assert_eq!(tn_schedule_commit(schedule, end, 60.), TnStatus::Rejected);
+            assert_eq!(
+                tn_schedule_add_constraint(schedule, start, 99, 0., 1.),
+                TnStatus::UnknownEvent
+            );
+
+            // a [10, 10] Episode right after the first can't also end within 20
+            let (mut next_start, mut next_end) = (0, 0);
+            tn_schedule_add_episode(schedule, 10., 10., &mut next_start, &mut next_end);
+            tn_schedule_add_constraint(schedule, end, next_start, 0., 0.);
+            assert_eq!(
+                tn_schedule_add_constraint(schedule, start, next_end, 0., 20.),
+                TnStatus::Ok
+            );
+            assert_eq!(tn_schedule_compile(schedule), TnStatus::Inconsistent);
+            assert_eq!(
+                tn_schedule_window(schedule, end, std::ptr::null_mut(), &mut latest),
+                TnStatus::NullPointer
+            );
+
+            tn_schedule_free(schedule);
+        }
+    }
+}
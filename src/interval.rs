@@ -1,9 +1,13 @@
-use serde_json::json;
+use serde::de::{self, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cell::RefCell;
 use std::default::Default;
 use std::fmt::{self, Display, Formatter};
 use std::ops::{Add, AddAssign, BitAnd, BitAndAssign, Neg, Sub, SubAssign};
 use wasm_bindgen::prelude::*;
 
+use super::{from_js, to_js};
+
 /// An interval represents a context-agnostic inclusive [lower, upper] time range. While Interval may be accessible from JS, the Rust implementation includes additional operator overloads for simplified arithmetic.
 ///
 /// # JS-specific
@@ -11,6 +15,14 @@ use wasm_bindgen::prelude::*;
 /// * Use `.toJSON()` if you want to convert an `Interval` to an array of numbers
 /// * Using indexing to get the lower and upper bounds is also an option, eg. `interval[0] === lower && interval[1] === upper`
 /// * use `Number.MAX_VALUE` and `-Number.MAX_VALUE` to represent infinity and -infinity respectively
+/// * Intervals are read from `[lower, upper]`, `{ lower, upper }`, or `"lower..upper"`, and written in the shape chosen with `setIntervalShape`
+///
+/// # Ownership in JS
+///
+/// Every `Interval` handed to JS, eg. by `Schedule.window` or `Schedule.interval`, is a fresh copy in wasm memory that JS owns. Changing it doesn't change the Schedule, and it's only reclaimed when `.free()` is called on it, so a display that queries every frame leaks unless it frees what it gets. Methods that take an `Interval` only borrow it, so passing one in never frees it. To avoid the churn:
+///
+/// * Get plain numbers instead with `Schedule.windowLowerUpper`, `Schedule.intervalLowerUpper`, or `Schedule.windowsLowerUpper`
+/// * Or create one `Interval` up front and have `Schedule.windowInto` or `Schedule.intervalInto` overwrite it on every query
 ///
 /// # Examples
 ///
@@ -32,7 +44,7 @@ use wasm_bindgen::prelude::*;
 /// assert_eq!(interval1 & interval2, unioned_interval);
 /// ```
 #[wasm_bindgen]
-#[derive(Deserialize, Serialize, Copy, Clone, Debug, PartialEq, Default)]
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
 pub struct Interval(pub f64, pub f64);
 
 #[wasm_bindgen]
@@ -43,16 +55,21 @@ impl Interval {
         Interval(lower, upper)
     }
 
-    /// Get an interval from a vector
-    pub fn from_vec(other: Vec<f64>) -> Interval {
-        Interval::new(other[0], other[1])
+    /// Get an interval from a vector. Errs unless it holds exactly two numbers
+    pub fn from_vec(other: Vec<f64>) -> Result<Interval, JsValue> {
+        Interval::try_from_slice(&other).map_err(|e| JsValue::from_str(&e))
     }
 
-    /// Convert the interval to JSON `[lower, upper]`
+    /// Convert the interval to JSON, `[lower, upper]` unless another shape was chosen with `setIntervalShape`
     #[wasm_bindgen(js_name = toJSON)]
-    pub fn to_json(&self) -> JsValue {
-        let value = json!([self.0, self.1]);
-        JsValue::from_serde(&value).unwrap()
+    pub fn to_json(&self) -> Result<JsValue, JsValue> {
+        to_js(self)
+    }
+
+    /// Read an interval from `[lower, upper]`, `{ lower, upper }`, or `"lower..upper"`, where an empty side is unbounded
+    #[wasm_bindgen(js_name = fromJSON)]
+    pub fn from_json(value: JsValue) -> Result<Interval, JsValue> {
+        from_js(&value)
     }
 
     /// The lower bound of the range
@@ -67,6 +84,13 @@ impl Interval {
         self.1
     }
 
+    /// Overwrite both bounds, eg. to reuse one Interval for many values
+    #[wasm_bindgen]
+    pub fn set(&mut self, lower: f64, upper: f64) {
+        self.0 = lower;
+        self.1 = upper;
+    }
+
     /// Whether or not a point in time falls within a range
     #[wasm_bindgen]
     pub fn contains(&self, v: f64) -> bool {
@@ -90,14 +114,293 @@ impl Interval {
     pub fn union(&self, other: &Interval) -> Interval {
         *self & *other
     }
+
+    /// Write the interval as `[lower, upper]` in the format from `setIntervalFormat`
+    #[wasm_bindgen(js_name = toString)]
+    pub fn to_string_js(&self) -> String {
+        self.to_string()
+    }
+
+    /// Multiply both bounds by a factor, eg. 1.5 to stretch a duration by half. Unbounded sides (±Number.MAX_VALUE) stay unbounded
+    #[wasm_bindgen]
+    pub fn scale(&self, factor: f64) -> Interval {
+        Interval(scale_bound(self.0, factor), scale_bound(self.1, factor))
+    }
+}
+
+impl Interval {
+    /// Read an interval from `[lower, upper]`, eg. one passed in from JS. Errs unless there are exactly two numbers
+    pub fn try_from_slice(values: &[f64]) -> Result<Interval, String> {
+        match values {
+            [lower, upper] if lower.is_nan() || upper.is_nan() => {
+                Err("an interval can't have NaN bounds".to_string())
+            }
+            [lower, upper] => Ok(Interval::new(*lower, *upper)),
+            _ => Err(format!(
+                "an interval needs two numbers, [lower, upper], but got {}",
+                values.len()
+            )),
+        }
+    }
+}
+
+/// Multiply a bound by a factor unless it's unbounded
+pub(crate) fn scale_bound(bound: f64, factor: f64) -> f64 {
+    if bound.abs() >= f64::MAX {
+        bound
+    } else {
+        bound * factor
+    }
+}
+
+/// Generate recurring windows, eg. orbital night every 92 minutes for 35 minutes, to pass to `Schedule::apply_blackouts`. A window starts at `phase` plus every multiple of `period` and lasts `duration`. Every window that overlaps `span` is returned in order, including ones that start before it or end after it. Errs if the period isn't positive, the duration is negative or longer than the period, or the span isn't finite
+pub fn periodic_windows(
+    period: f64,
+    duration: f64,
+    phase: f64,
+    span: Interval,
+) -> Result<Vec<Interval>, String> {
+    if !period.is_finite() || period <= 0. {
+        return Err(format!(
+            "the period must be a positive number, got {}",
+            period
+        ));
+    }
+    if !(0. ..=period).contains(&duration) {
+        return Err(format!(
+            "the duration must be between 0 and the period {}, got {}",
+            period, duration
+        ));
+    }
+    if !phase.is_finite() || span.lower().abs() >= f64::MAX || span.upper().abs() >= f64::MAX {
+        return Err(format!("cannot generate windows over {}", span));
+    }
+
+    // the last window that ends at or before the span starts
+    let mut k = ((span.lower() - phase - duration) / period).floor();
+    let mut windows = vec![];
+    loop {
+        let start = phase + k * period;
+        if start >= span.upper() {
+            break;
+        }
+        if start + duration > span.lower() {
+            windows.push(Interval::new(start, start + duration));
+        }
+        k += 1.;
+    }
+    Ok(windows)
+}
+
+/// Generate recurring windows, eg. orbital night every 92 minutes for 35 minutes, to pass to `Schedule.applyBlackouts`. A window starts at `phase` plus every multiple of `period` and lasts `duration`. Returns every `[start, end]` window that overlaps `from` to `to`, in order. Errs if the period isn't positive, the duration is negative or longer than the period, or the span isn't finite
+#[wasm_bindgen(js_name = periodicWindows)]
+pub fn periodic_windows_js(
+    period: f64,
+    duration: f64,
+    phase: f64,
+    from: f64,
+    to: f64,
+) -> Result<JsValue, JsValue> {
+    let windows = periodic_windows(period, duration, phase, Interval::new(from, to))
+        .map_err(|e| JsValue::from_str(&e))?;
+    to_js(&windows)
+}
+
+/// How to write intervals and times as text, eg. to match the conventions of an operations display. Used by `Display`, reports, and constraint-table dumps
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct IntervalFormat {
+    /// Digits after the decimal point. `None` writes as many as the number needs
+    pub decimals: Option<usize>,
+    /// Written for an unbounded upper side, eg. `"∞"` or `"unbounded"`
+    pub infinity: String,
+    /// Written for an unbounded lower side
+    pub negative_infinity: String,
+    /// Written after every bounded time, eg. `" min"`
+    pub units: String,
+}
+
+impl Default for IntervalFormat {
+    fn default() -> IntervalFormat {
+        IntervalFormat {
+            decimals: None,
+            infinity: "∞".to_string(),
+            negative_infinity: "-∞".to_string(),
+            units: String::new(),
+        }
+    }
+}
+
+impl IntervalFormat {
+    /// Write a time, or an unbounded bound (±f64::MAX)
+    pub fn time(&self, value: f64) -> String {
+        if value >= f64::MAX {
+            self.infinity.clone()
+        } else if value <= -f64::MAX {
+            self.negative_infinity.clone()
+        } else {
+            match self.decimals {
+                Some(d) => format!("{:.*}{}", d, value, self.units),
+                None => format!("{}{}", value, self.units),
+            }
+        }
+    }
+
+    /// Write an interval as `[lower, upper]`
+    pub fn interval(&self, interval: &Interval) -> String {
+        format!("[{}, {}]", self.time(interval.0), self.time(interval.1))
+    }
+}
+
+thread_local! {
+    static DISPLAY_FORMAT: RefCell<IntervalFormat> = RefCell::new(IntervalFormat::default());
+}
+
+/// Change how `Display` writes intervals and times from now on
+pub fn set_display_format(format: IntervalFormat) {
+    DISPLAY_FORMAT.with(|f| *f.borrow_mut() = format);
+}
+
+/// The format `Display` uses
+pub fn display_format() -> IntervalFormat {
+    DISPLAY_FORMAT.with(|f| f.borrow().clone())
+}
+
+/// Change how intervals and times are written as text, eg. in error messages, `Interval.toString`, and `Schedule.dumpConstraintTable`. Takes `{ decimals, infinity, negativeInfinity, units }`, each optional: digits after the decimal point (as many as needed by default), what to write for unbounded sides (`"∞"` and `"-∞"` by default), and a suffix for every time, eg. `" min"`
+#[wasm_bindgen(js_name = setIntervalFormat)]
+pub fn set_display_format_js(format: JsValue) -> Result<(), JsValue> {
+    set_display_format(from_js(&format)?);
+    Ok(())
+}
+
+/// How intervals are serialized, eg. to match what an upstream tool produces. Every shape is accepted when deserializing
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IntervalShape {
+    /// `[lower, upper]`. The default
+    #[default]
+    Tuple,
+    /// `{ "lower": lower, "upper": upper }`
+    Object,
+    /// `"lower..upper"`, leaving an unbounded side empty, eg. `"10.."`
+    String,
+}
+
+thread_local! {
+    static SHAPE: RefCell<IntervalShape> = const { RefCell::new(IntervalShape::Tuple) };
+}
+
+/// Change how intervals are serialized from now on
+pub fn set_interval_shape(shape: IntervalShape) {
+    SHAPE.with(|s| *s.borrow_mut() = shape);
+}
+
+/// The shape intervals are serialized in
+pub fn interval_shape() -> IntervalShape {
+    SHAPE.with(|s| *s.borrow())
+}
+
+/// Change how intervals are written in JSON outputs: `"tuple"` for `[lower, upper]` (the default), `"object"` for `{ lower, upper }`, or `"string"` for `"lower..upper"` with unbounded sides left empty. Every shape is always accepted as input
+#[wasm_bindgen(js_name = setIntervalShape)]
+pub fn set_interval_shape_js(shape: JsValue) -> Result<(), JsValue> {
+    set_interval_shape(from_js(&shape)?);
+    Ok(())
+}
+
+impl Interval {
+    /// Write the interval as `"lower..upper"`, leaving unbounded sides empty
+    fn to_range(self) -> String {
+        let side = |v: f64| {
+            if v.abs() >= f64::MAX {
+                String::new()
+            } else {
+                v.to_string()
+            }
+        };
+        format!("{}..{}", side(self.0), side(self.1))
+    }
+
+    /// Read an interval from `"lower..upper"`. An empty side is unbounded
+    fn from_range(range: &str) -> Result<Interval, String> {
+        let (lower, upper) = range
+            .split_once("..")
+            .ok_or_else(|| format!("expected \"lower..upper\" but got \"{}\"", range))?;
+        let side = |text: &str, unbounded: f64| match text.trim() {
+            "" => Ok(unbounded),
+            t => t
+                .parse::<f64>()
+                .map(|v| v.clamp(-f64::MAX, f64::MAX))
+                .map_err(|_| format!("\"{}\" is not a number in \"{}\"", t, range)),
+        };
+        Interval::try_from_slice(&[side(lower, -f64::MAX)?, side(upper, f64::MAX)?])
+    }
+}
+
+impl Serialize for Interval {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match interval_shape() {
+            IntervalShape::Tuple => (self.0, self.1).serialize(serializer),
+            IntervalShape::Object => {
+                use serde::ser::SerializeStruct;
+                let mut object = serializer.serialize_struct("Interval", 2)?;
+                object.serialize_field("lower", &self.0)?;
+                object.serialize_field("upper", &self.1)?;
+                object.end()
+            }
+            IntervalShape::String => serializer.serialize_str(&self.to_range()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Interval {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Interval, D::Error> {
+        deserializer.deserialize_any(IntervalVisitor)
+    }
+}
+
+/// Reads an interval from any of its shapes
+struct IntervalVisitor;
+
+impl<'de> Visitor<'de> for IntervalVisitor {
+    type Value = Interval;
+
+    fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str("[lower, upper], { lower, upper }, or \"lower..upper\"")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Interval, A::Error> {
+        let mut bounds = vec![];
+        while let Some(bound) = seq.next_element::<f64>()? {
+            bounds.push(bound);
+        }
+        Interval::try_from_slice(&bounds).map_err(de::Error::custom)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Interval, A::Error> {
+        let (mut lower, mut upper) = (None, None);
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "lower" => lower = Some(map.next_value::<f64>()?),
+                "upper" => upper = Some(map.next_value::<f64>()?),
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+        let lower = lower.ok_or_else(|| de::Error::missing_field("lower"))?;
+        let upper = upper.ok_or_else(|| de::Error::missing_field("upper"))?;
+        Interval::try_from_slice(&[lower, upper]).map_err(de::Error::custom)
+    }
+
+    fn visit_str<E: de::Error>(self, range: &str) -> Result<Interval, E> {
+        Interval::from_range(range).map_err(E::custom)
+    }
 }
 
 impl Display for Interval {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        // `f` is a buffer, and this method must write the formatted string into it
-        // `write!` is like `format!`, but it will write the formatted string
-        // into a buffer (the first argument)
-        write!(f, "[{}, {}]", self.0, self.1)
+        let text = DISPLAY_FORMAT.with(|format| format.borrow().interval(self));
+        f.write_str(&text)
     }
 }
 
@@ -375,4 +678,90 @@ mod tests {
             "interval math from scheduling walkthrough"
         );
     }
+
+    #[test]
+    fn test_periodic_windows() {
+        // the night in progress at t=0 is included
+        assert_eq!(
+            periodic_windows(92., 35., 60., Interval::new(0., 200.)),
+            Ok(vec![
+                Interval::new(-32., 3.),
+                Interval::new(60., 95.),
+                Interval::new(152., 187.),
+            ])
+        );
+        // windows that only touch the span are left out
+        assert_eq!(
+            periodic_windows(92., 35., 60., Interval::new(3., 60.)),
+            Ok(vec![])
+        );
+        assert!(periodic_windows(92., 100., 0., Interval::new(0., 200.)).is_err());
+        assert!(periodic_windows(92., 35., 0., Interval::new(0., f64::MAX)).is_err());
+    }
+
+    #[test]
+    fn test_try_from_slice() {
+        assert_eq!(
+            Interval::try_from_slice(&[1., 2.]),
+            Ok(Interval::new(1., 2.))
+        );
+        for values in [vec![], vec![1.], vec![1., 2., 3.], vec![f64::NAN, 2.]].iter() {
+            assert!(Interval::try_from_slice(values).is_err());
+        }
+    }
+
+    #[test]
+    fn test_display_format() {
+        let interval = Interval::new(10., f64::MAX);
+        assert_eq!(interval.to_string(), "[10, ∞]");
+
+        let format = IntervalFormat {
+            decimals: Some(1),
+            infinity: "unbounded".to_string(),
+            units: " min".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(format.interval(&interval), "[10.0 min, unbounded]");
+        assert_eq!(format.time(-f64::MAX), "-∞");
+
+        set_display_format(format);
+        assert_eq!(Interval::new(1.25, 2.).to_string(), "[1.2 min, 2.0 min]");
+        set_display_format(IntervalFormat::default());
+    }
+
+    #[test]
+    fn test_interval_shapes() {
+        let interval = Interval::new(1.5, f64::MAX);
+        for json in [
+            "[1.5, 1.7976931348623157e308]",
+            r#"{"lower": 1.5, "upper": 1.7976931348623157e308}"#,
+            r#""1.5..""#,
+        ] {
+            assert_eq!(
+                serde_json::from_str::<Interval>(json).unwrap(),
+                interval,
+                "{}",
+                json
+            );
+        }
+        assert_eq!(
+            serde_json::from_str::<Interval>(r#""..-2""#).unwrap(),
+            Interval::new(-f64::MAX, -2.)
+        );
+        assert!(serde_json::from_str::<Interval>(r#""1-2""#).is_err());
+        assert!(serde_json::from_str::<Interval>("[1]").is_err());
+
+        assert_eq!(
+            serde_json::to_string(&interval).unwrap(),
+            "[1.5,1.7976931348623157e+308]"
+        );
+        set_interval_shape(IntervalShape::Object);
+        assert_eq!(
+            serde_json::to_string(&interval).unwrap(),
+            r#"{"lower":1.5,"upper":1.7976931348623157e+308}"#
+        );
+        set_interval_shape(IntervalShape::String);
+        assert_eq!(serde_json::to_string(&interval).unwrap(), r#""1.5..""#);
+        set_interval_shape(IntervalShape::Tuple);
+    }
 }
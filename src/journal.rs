@@ -0,0 +1,662 @@
+//! # Journal
+//! An append-only record of the mutations made to a `Schedule`. Hosts can persist the journal incrementally and replay it with `Schedule::apply_journal` to reconstruct the exact same Schedule, eg. after a crash or on another client.
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use super::interval::Interval;
+use super::probability::Distribution;
+use super::schedule::{
+    Defaults, EventID, Limits, OutputBounds, Phase, Preference, Propagation, Provenance,
+};
+
+/// A single change to a Schedule, with everything needed to replay it. Serialized as an object with an `op` field naming the change, eg. `{ "op": "commitEvent", "event": 3, "time": 12.5 }`
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum Mutation {
+    /// An event was created with this ID
+    CreateEvent { event: EventID },
+    /// An Episode was created from a pair of new events
+    AddEpisode {
+        start: EventID,
+        end: EventID,
+        duration: Interval,
+    },
+    /// A constraint was added between two existing events
+    AddConstraint {
+        source: EventID,
+        target: EventID,
+        interval: Interval,
+    },
+    /// The interval between two events was replaced
+    UpdateInterval {
+        source: EventID,
+        target: EventID,
+        interval: Interval,
+    },
+    /// The edge from source to target was removed
+    RemoveConstraint { source: EventID, target: EventID },
+    /// Every constraint between two Episodes was removed
+    RemoveConstraints {
+        source: (EventID, EventID),
+        target: (EventID, EventID),
+    },
+    /// Constraints around an Episode were removed
+    FreeEpisode { start: EventID, end: EventID },
+    /// An event was committed to a time
+    CommitEvent { event: EventID, time: f64 },
+    /// An Episode's duration was marked as out of the executor's control
+    MarkContingent { start: EventID, end: EventID },
+    /// The actual duration of a contingent Episode was observed
+    ObserveContingent {
+        start: EventID,
+        end: EventID,
+        duration: f64,
+    },
+    /// An Episode was interrupted after some time had elapsed. The part that ran ends at a new `pause` event, committed when the split was made, and the rest became an Episode from a new `resume` event to the original end
+    SplitEpisode {
+        start: EventID,
+        end: EventID,
+        elapsed: f64,
+        pause: EventID,
+        resume: EventID,
+    },
+    /// Two Episodes were made mutually exclusive
+    AddMutex {
+        first: (EventID, EventID),
+        second: (EventID, EventID),
+    },
+    /// An Episode's duration was tied to a named parameter
+    SetDurationParameter {
+        start: EventID,
+        end: EventID,
+        parameter: String,
+    },
+    /// A parameter was bound to an interval
+    BindParameter { name: String, interval: Interval },
+    /// An external ID was attached to an event
+    SetEventAlias { event: EventID, alias: String },
+    /// An external ID was attached to an Episode
+    SetEpisodeAlias {
+        start: EventID,
+        end: EventID,
+        alias: String,
+    },
+    /// An external ID was removed from whichever event or Episode it referred to
+    RemoveAlias { alias: String },
+    /// Data was attached to an Episode
+    SetEpisodeMetadata {
+        start: EventID,
+        end: EventID,
+        metadata: Value,
+    },
+    /// Data was attached to the constraint between two events
+    SetConstraintMetadata {
+        source: EventID,
+        target: EventID,
+        metadata: Value,
+    },
+    /// The source document of the constraint between two events was recorded
+    SetConstraintProvenance {
+        source: EventID,
+        target: EventID,
+        provenance: Provenance,
+    },
+    /// When an Episode would prefer to occur was set
+    SetPreference {
+        start: EventID,
+        end: EventID,
+        preference: Preference,
+    },
+    /// The uncertain duration of an Episode was described
+    SetDistribution {
+        start: EventID,
+        end: EventID,
+        distribution: Distribution,
+    },
+    /// An event was made the virtual origin by `Schedule::ensure_root`
+    SetVirtualRoot { event: EventID },
+    /// The intervals used when none are given were changed
+    SetDefaults { defaults: Defaults },
+    /// The largest plan a compile takes on was changed
+    SetLimits { limits: Limits },
+    /// The compile budget in milliseconds was changed
+    SetCompileBudget { budget: Option<f64> },
+    /// Lazy dispatchable graphs were turned on or off
+    SetLazyDispatchable { lazy: bool },
+    /// How many relaxations compiles trace was changed
+    SetTrace { limit: Option<usize> },
+    /// How many window snapshots are kept was changed
+    SetHistoryLimit { limit: Option<usize> },
+    /// Strict commitments were turned on or off
+    SetStrictCommits { strict: bool },
+    /// Adding a virtual root on compile was turned on or off
+    SetAutoRoot { enabled: bool },
+    /// How far commitments propagate was changed
+    SetPropagation { propagation: Propagation },
+    /// How infinite bounds are written in outputs was changed
+    SetOutputBounds { bounds: OutputBounds },
+    /// How many decimal places outputs are rounded to was changed
+    SetOutputDecimals { decimals: Option<u32> },
+    /// The Schedule moved to another phase of its lifecycle
+    SetPhase { phase: Phase },
+    /// A plan-time change made after the plan was frozen, and why
+    Amend {
+        reason: String,
+        mutation: Box<Mutation>,
+    },
+}
+
+/// The result of merging two journals with `merge`
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Merge {
+    /// Mutations to apply to the base Schedule: every mutation from the first journal followed by the non-conflicting mutations from the second, with new events renumbered so they don't collide
+    pub mutations: Vec<Mutation>,
+    /// Pairs of mutations that contradict each other. The first journal's mutation is kept in `mutations` until the conflict is resolved
+    pub conflicts: Vec<Conflict>,
+}
+
+/// Two mutations that change the same thing in different ways
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Conflict {
+    /// The mutation from the first journal
+    pub ours: Mutation,
+    /// The mutation from the second journal, after renumbering its new events
+    pub theirs: Mutation,
+    pub reason: String,
+}
+
+/// What a mutation does to one directed edge of the distance graph
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum EdgeEdit {
+    /// The edge is set to this weight
+    Set(f64),
+    Remove,
+}
+
+impl Mutation {
+    /// Events created by this mutation
+    fn created(&self) -> Vec<EventID> {
+        match self {
+            Mutation::CreateEvent { event } => vec![*event],
+            Mutation::AddEpisode { start, end, .. } => vec![*start, *end],
+            Mutation::SplitEpisode { pause, resume, .. } => vec![*pause, *resume],
+            Mutation::Amend { mutation, .. } => mutation.created(),
+            _ => vec![],
+        }
+    }
+
+    /// The same mutation with its intervals multiplied by a factor. Commitments and observations are times that already happened, so they're dropped
+    pub(crate) fn scaled(&self, factor: f64) -> Option<Mutation> {
+        match self {
+            Mutation::AddEpisode {
+                start,
+                end,
+                duration,
+            } => Some(Mutation::AddEpisode {
+                start: *start,
+                end: *end,
+                duration: duration.scale(factor),
+            }),
+            Mutation::AddConstraint {
+                source,
+                target,
+                interval,
+            } => Some(Mutation::AddConstraint {
+                source: *source,
+                target: *target,
+                interval: interval.scale(factor),
+            }),
+            Mutation::UpdateInterval {
+                source,
+                target,
+                interval,
+            } => Some(Mutation::UpdateInterval {
+                source: *source,
+                target: *target,
+                interval: interval.scale(factor),
+            }),
+            Mutation::BindParameter { name, interval } => Some(Mutation::BindParameter {
+                name: name.clone(),
+                interval: interval.scale(factor),
+            }),
+            Mutation::SetPreference {
+                start,
+                end,
+                preference: Preference::Target(t),
+            } => Some(Mutation::SetPreference {
+                start: *start,
+                end: *end,
+                preference: Preference::Target(t * factor),
+            }),
+            Mutation::SetDistribution {
+                start,
+                end,
+                distribution,
+            } => Some(Mutation::SetDistribution {
+                start: *start,
+                end: *end,
+                distribution: distribution.scale(factor),
+            }),
+            Mutation::SetPropagation {
+                propagation: Propagation::Horizon(h),
+            } => Some(Mutation::SetPropagation {
+                propagation: Propagation::Horizon(h * factor),
+            }),
+            Mutation::Amend { reason, mutation } => {
+                mutation.scaled(factor).map(|m| Mutation::Amend {
+                    reason: reason.clone(),
+                    mutation: Box::new(m),
+                })
+            }
+            // a scaled copy starts over as a draft
+            Mutation::CommitEvent { .. }
+            | Mutation::ObserveContingent { .. }
+            | Mutation::SplitEpisode { .. }
+            | Mutation::SetPhase { .. } => None,
+            m => Some(m.clone()),
+        }
+    }
+
+    /// Rewrite every event ID in this mutation
+    fn map_events(&self, f: &dyn Fn(EventID) -> EventID) -> Mutation {
+        match self {
+            Mutation::CreateEvent { event } => Mutation::CreateEvent { event: f(*event) },
+            Mutation::AddEpisode {
+                start,
+                end,
+                duration,
+            } => Mutation::AddEpisode {
+                start: f(*start),
+                end: f(*end),
+                duration: *duration,
+            },
+            Mutation::AddConstraint {
+                source,
+                target,
+                interval,
+            } => Mutation::AddConstraint {
+                source: f(*source),
+                target: f(*target),
+                interval: *interval,
+            },
+            Mutation::UpdateInterval {
+                source,
+                target,
+                interval,
+            } => Mutation::UpdateInterval {
+                source: f(*source),
+                target: f(*target),
+                interval: *interval,
+            },
+            Mutation::RemoveConstraint { source, target } => Mutation::RemoveConstraint {
+                source: f(*source),
+                target: f(*target),
+            },
+            Mutation::RemoveConstraints { source, target } => Mutation::RemoveConstraints {
+                source: (f(source.0), f(source.1)),
+                target: (f(target.0), f(target.1)),
+            },
+            Mutation::FreeEpisode { start, end } => Mutation::FreeEpisode {
+                start: f(*start),
+                end: f(*end),
+            },
+            Mutation::CommitEvent { event, time } => Mutation::CommitEvent {
+                event: f(*event),
+                time: *time,
+            },
+            Mutation::MarkContingent { start, end } => Mutation::MarkContingent {
+                start: f(*start),
+                end: f(*end),
+            },
+            Mutation::ObserveContingent {
+                start,
+                end,
+                duration,
+            } => Mutation::ObserveContingent {
+                start: f(*start),
+                end: f(*end),
+                duration: *duration,
+            },
+            Mutation::SplitEpisode {
+                start,
+                end,
+                elapsed,
+                pause,
+                resume,
+            } => Mutation::SplitEpisode {
+                start: f(*start),
+                end: f(*end),
+                elapsed: *elapsed,
+                pause: f(*pause),
+                resume: f(*resume),
+            },
+            Mutation::AddMutex { first, second } => Mutation::AddMutex {
+                first: (f(first.0), f(first.1)),
+                second: (f(second.0), f(second.1)),
+            },
+            Mutation::SetDurationParameter {
+                start,
+                end,
+                parameter,
+            } => Mutation::SetDurationParameter {
+                start: f(*start),
+                end: f(*end),
+                parameter: parameter.clone(),
+            },
+            Mutation::SetEventAlias { event, alias } => Mutation::SetEventAlias {
+                event: f(*event),
+                alias: alias.clone(),
+            },
+            Mutation::SetEpisodeAlias { start, end, alias } => Mutation::SetEpisodeAlias {
+                start: f(*start),
+                end: f(*end),
+                alias: alias.clone(),
+            },
+            Mutation::SetEpisodeMetadata {
+                start,
+                end,
+                metadata,
+            } => Mutation::SetEpisodeMetadata {
+                start: f(*start),
+                end: f(*end),
+                metadata: metadata.clone(),
+            },
+            Mutation::SetConstraintMetadata {
+                source,
+                target,
+                metadata,
+            } => Mutation::SetConstraintMetadata {
+                source: f(*source),
+                target: f(*target),
+                metadata: metadata.clone(),
+            },
+            Mutation::SetConstraintProvenance {
+                source,
+                target,
+                provenance,
+            } => Mutation::SetConstraintProvenance {
+                source: f(*source),
+                target: f(*target),
+                provenance: provenance.clone(),
+            },
+            Mutation::SetPreference {
+                start,
+                end,
+                preference,
+            } => Mutation::SetPreference {
+                start: f(*start),
+                end: f(*end),
+                preference: *preference,
+            },
+            Mutation::SetDistribution {
+                start,
+                end,
+                distribution,
+            } => Mutation::SetDistribution {
+                start: f(*start),
+                end: f(*end),
+                distribution: *distribution,
+            },
+            Mutation::SetVirtualRoot { event } => Mutation::SetVirtualRoot { event: f(*event) },
+            Mutation::BindParameter { .. }
+            | Mutation::RemoveAlias { .. }
+            | Mutation::SetDefaults { .. }
+            | Mutation::SetLimits { .. }
+            | Mutation::SetCompileBudget { .. }
+            | Mutation::SetLazyDispatchable { .. }
+            | Mutation::SetTrace { .. }
+            | Mutation::SetHistoryLimit { .. }
+            | Mutation::SetStrictCommits { .. }
+            | Mutation::SetAutoRoot { .. }
+            | Mutation::SetPropagation { .. }
+            | Mutation::SetOutputBounds { .. }
+            | Mutation::SetOutputDecimals { .. }
+            | Mutation::SetPhase { .. } => self.clone(),
+            Mutation::Amend { reason, mutation } => Mutation::Amend {
+                reason: reason.clone(),
+                mutation: Box::new(mutation.map_events(f)),
+            },
+        }
+    }
+
+    /// The directed edges of the distance graph this mutation changes, and how. A constraint sets the edge each way, while removing a constraint only removes the edge from source to target
+    fn edge_edits(&self) -> Vec<((EventID, EventID), EdgeEdit)> {
+        match self {
+            Mutation::AddConstraint {
+                source,
+                target,
+                interval,
+            }
+            | Mutation::UpdateInterval {
+                source,
+                target,
+                interval,
+            } => vec![
+                ((*source, *target), EdgeEdit::Set(interval.upper())),
+                ((*target, *source), EdgeEdit::Set(-interval.lower())),
+            ],
+            Mutation::RemoveConstraint { source, target } => {
+                vec![((*source, *target), EdgeEdit::Remove)]
+            }
+            Mutation::Amend { mutation, .. } => mutation.edge_edits(),
+            _ => vec![],
+        }
+    }
+}
+
+/// Merge two journals recorded against the same base Schedule. `next_event` is the ID the base Schedule would give its next new event. Events created by the second journal are renumbered to follow the events created by the first. Changes to the same directed edge of the distance graph and commitments of the same event are conflicts unless both journals agree, so removing a constraint in one direction doesn't collide with removing it in the other. Changes that affect many constraints at once, like freeing an Episode, are merged without conflict checks
+pub fn merge(next_event: EventID, ours: &[Mutation], theirs: &[Mutation]) -> Merge {
+    let mut next = ours
+        .iter()
+        .flat_map(|m| m.created())
+        .fold(next_event, |n, e| n.max(e + 1));
+
+    // renumber events created by the second journal
+    let mut renumbered = BTreeMap::new();
+    for event in theirs.iter().flat_map(|m| m.created()) {
+        renumbered.entry(event).or_insert_with(|| {
+            next += 1;
+            next - 1
+        });
+    }
+    let theirs: Vec<Mutation> = theirs
+        .iter()
+        .map(|m| m.map_events(&|e| *renumbered.get(&e).unwrap_or(&e)))
+        .collect();
+
+    // the last change our mutations made to each edge or commitment
+    let mut our_edges = BTreeMap::new();
+    let mut our_commits = BTreeMap::new();
+    for m in ours.iter() {
+        for (edge, edit) in m.edge_edits() {
+            our_edges.insert(edge, (edit, m));
+        }
+        if let Mutation::CommitEvent { event, time } = m {
+            our_commits.insert(*event, (*time, m));
+        }
+    }
+
+    let mut merge = Merge {
+        mutations: ours.to_vec(),
+        conflicts: vec![],
+    };
+
+    for m in theirs.into_iter() {
+        let edits = m.edge_edits();
+        let conflict = if !edits.is_empty() {
+            let clash = edits
+                .iter()
+                .find_map(|(edge, edit)| match our_edges.get(edge) {
+                    Some((e, ours)) if e != edit => Some((edge, ours)),
+                    _ => None,
+                });
+            match clash {
+                Some((edge, ours)) => Some((
+                    (*ours).clone(),
+                    format!(
+                        "both journals change the constraint from event {} to {}",
+                        edge.0, edge.1
+                    ),
+                )),
+                // every edge is already changed the same way
+                None if edits
+                    .iter()
+                    .all(|(edge, edit)| our_edges.get(edge).map(|(e, _)| e) == Some(edit)) =>
+                {
+                    continue
+                }
+                None => None,
+            }
+        } else if let Mutation::CommitEvent { event, time } = m {
+            match our_commits.get(&event) {
+                Some((t, _)) if *t == time => continue,
+                Some((t, ours)) => Some((
+                    (*ours).clone(),
+                    format!(
+                        "event {} is committed at {} in one journal and {} in the other",
+                        event, t, time
+                    ),
+                )),
+                None => None,
+            }
+        } else {
+            None
+        };
+
+        match conflict {
+            Some((ours, reason)) => merge.conflicts.push(Conflict {
+                ours,
+                theirs: m,
+                reason,
+            }),
+            None => merge.mutations.push(m),
+        }
+    }
+
+    merge
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_renumbers_new_events() {
+        let ours = vec![Mutation::AddEpisode {
+            start: 4,
+            end: 5,
+            duration: Interval::new(1., 2.),
+        }];
+        let theirs = vec![
+            Mutation::AddEpisode {
+                start: 4,
+                end: 5,
+                duration: Interval::new(3., 4.),
+            },
+            Mutation::AddConstraint {
+                source: 1,
+                target: 4,
+                interval: Interval::new(0., 0.),
+            },
+        ];
+
+        let merged = merge(4, &ours, &theirs);
+        assert!(merged.conflicts.is_empty());
+        assert_eq!(
+            merged.mutations[1..].to_vec(),
+            vec![
+                Mutation::AddEpisode {
+                    start: 6,
+                    end: 7,
+                    duration: Interval::new(3., 4.),
+                },
+                Mutation::AddConstraint {
+                    source: 1,
+                    target: 6,
+                    interval: Interval::new(0., 0.),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_flags_conflicts() {
+        let ours = vec![
+            Mutation::UpdateInterval {
+                source: 0,
+                target: 1,
+                interval: Interval::new(5., 10.),
+            },
+            Mutation::CommitEvent { event: 0, time: 0. },
+        ];
+        let theirs = vec![
+            // the same change written in the other direction
+            Mutation::UpdateInterval {
+                source: 1,
+                target: 0,
+                interval: Interval::new(-10., -5.),
+            },
+            Mutation::CommitEvent { event: 0, time: 2. },
+            Mutation::RemoveConstraint {
+                source: 2,
+                target: 3,
+            },
+        ];
+
+        let merged = merge(4, &ours, &theirs);
+        assert_eq!(merged.mutations.len(), 3);
+        assert_eq!(merged.conflicts.len(), 1);
+        assert_eq!(
+            merged.conflicts[0].theirs,
+            Mutation::CommitEvent { event: 0, time: 2. }
+        );
+    }
+
+    #[test]
+    fn test_merge_keys_removals_by_direction() {
+        let ours = vec![
+            Mutation::RemoveConstraint {
+                source: 2,
+                target: 3,
+            },
+            Mutation::UpdateInterval {
+                source: 0,
+                target: 1,
+                interval: Interval::new(5., 10.),
+            },
+        ];
+        let theirs = vec![
+            Mutation::RemoveConstraint {
+                source: 3,
+                target: 2,
+            },
+            Mutation::RemoveConstraint {
+                source: 2,
+                target: 3,
+            },
+            Mutation::RemoveConstraint {
+                source: 1,
+                target: 0,
+            },
+        ];
+
+        let merged = merge(4, &ours, &theirs);
+        // the other direction is kept and the same removal isn't repeated
+        assert_eq!(merged.mutations.len(), 3);
+        assert_eq!(
+            merged.mutations[2],
+            Mutation::RemoveConstraint {
+                source: 3,
+                target: 2,
+            }
+        );
+        // but removing an edge our interval sets is a conflict
+        assert_eq!(merged.conflicts.len(), 1);
+        assert_eq!(
+            merged.conflicts[0].reason,
+            "both journals change the constraint from event 1 to 0"
+        );
+    }
+}
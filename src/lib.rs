@@ -7,12 +7,33 @@ extern crate wasm_bindgen;
 #[macro_use]
 extern crate serde_derive;
 
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsValue;
 
 pub mod algorithms;
+#[cfg(all(feature = "analytics", not(target_arch = "wasm32")))]
+pub mod analytics;
+pub mod dense;
+#[cfg(any(test, feature = "examples"))]
+pub mod examples;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod ffi;
 pub mod interval;
+pub mod journal;
+pub mod logging;
+pub mod node;
+pub mod probability;
+#[cfg(all(feature = "python", not(target_arch = "wasm32")))]
+pub mod python;
+pub mod registration;
+pub mod registry;
 pub mod schedule;
+pub mod service;
+pub mod template;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod testing;
 
 #[cfg(feature = "wee_alloc")]
 #[global_allocator]
@@ -25,3 +46,18 @@ pub fn install() -> Result<(), JsValue> {
     console_error_panic_hook::set_once();
     Ok(())
 }
+
+/// Convert anything serializable into a plain JS value (objects, arrays, numbers, etc.)
+pub(crate) fn to_js<T: Serialize>(value: &T) -> Result<JsValue, JsValue> {
+    let json = serde_json::to_string(value).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    js_sys::JSON::parse(&json)
+}
+
+/// Convert a plain JS value into anything deserializable
+pub(crate) fn from_js<T: DeserializeOwned>(value: &JsValue) -> Result<T, JsValue> {
+    if value.is_undefined() {
+        return Err(JsValue::from_str("expected a value but got undefined"));
+    }
+    let json: String = js_sys::JSON::stringify(value)?.into();
+    serde_json::from_str(&json).map_err(|e| JsValue::from_str(&e.to_string()))
+}
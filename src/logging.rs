@@ -0,0 +1,178 @@
+//! # Logging
+//! The solver emits `tracing` events when it compiles, propagates commitments, and finds conflicts, so a misbehaving plan can be debugged in the field without a custom build. Natively, install any standard subscriber, eg. `tracing_subscriber::fmt`, and the events show up with everything else. In JS, call `setLogSink` to forward them to `console.debug` or to a function of your own.
+//!
+//! ```js
+//! import { setLogSink } from "temporal-networks";
+//!
+//! setLogSink("debug"); // console.debug
+//! setLogSink("warn", (level, message) => telemetry.push({ level, message }));
+//! ```
+//!
+//! Events use these messages, with the numbers as fields:
+//! - `compile started` / `compile finished` / `compile cancelled` / `compile failed`
+//! - `propagated commitment` / `propagated commitments`
+//! - `commitment rejected` / `commitment left events infeasible`
+
+use std::cell::RefCell;
+use std::fmt::{self, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsValue;
+
+/// A subscriber that formats each event at or above a level as `message key=value ...` and hands it to a function. Spans are ignored
+pub struct Sink<F> {
+    level: Level,
+    write: F,
+    next_span: AtomicU64,
+}
+
+impl<F: Fn(&Level, &str) + Send + Sync + 'static> Sink<F> {
+    pub fn new(level: Level, write: F) -> Sink<F> {
+        Sink {
+            level,
+            write,
+            next_span: AtomicU64::new(1),
+        }
+    }
+}
+
+impl<F: Fn(&Level, &str) + Send + Sync + 'static> Subscriber for Sink<F> {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        // more verbose levels compare greater
+        metadata.level() <= &self.level
+    }
+
+    fn new_span(&self, _: &Attributes<'_>) -> Id {
+        Id::from_u64(self.next_span.fetch_add(1, Ordering::Relaxed))
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut line = Line::default();
+        event.record(&mut line);
+        (self.write)(event.metadata().level(), &line.finish());
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+/// An event's message followed by its other fields
+#[derive(Default)]
+struct Line {
+    message: String,
+    fields: String,
+}
+
+impl Line {
+    fn finish(self) -> String {
+        format!("{}{}", self.message, self.fields)
+    }
+}
+
+impl Visit for Line {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.fields, " {}={}", field.name(), value);
+        }
+    }
+}
+
+/// Parse a level name, eg. `"debug"`
+pub fn parse_level(level: &str) -> Result<Level, String> {
+    level
+        .parse()
+        .map_err(|_| format!("unknown log level \"{}\"", level))
+}
+
+thread_local! {
+    /// Where JS wants events to go: the most verbose level and an optional function. `None` until `setLogSink` is called
+    static JS_SINK: RefCell<Option<(Level, Option<js_sys::Function>)>> = const { RefCell::new(None) };
+}
+
+/// Forward the solver's log events at or above a level (`"error"`, `"warn"`, `"info"`, `"debug"`, or `"trace"`) to `sink(level, message)`, or to `console.debug` without one. Can be called again to change the level or the sink. Errs if the level is unknown or another subscriber was already installed
+#[wasm_bindgen(js_name = setLogSink)]
+pub fn set_log_sink(level: &str, sink: Option<js_sys::Function>) -> Result<(), JsValue> {
+    let level = parse_level(level).map_err(|e| JsValue::from_str(&e))?;
+    let installed = JS_SINK.with(|s| s.replace(Some((level, sink))).is_some());
+    if installed {
+        return Ok(());
+    }
+
+    // the level is checked when forwarding so it can change after the subscriber is installed
+    tracing::subscriber::set_global_default(Sink::new(Level::TRACE, forward_to_js)).map_err(|_| {
+        JS_SINK.with(|s| s.replace(None));
+        JsValue::from_str("a log subscriber is already installed")
+    })
+}
+
+fn forward_to_js(level: &Level, message: &str) {
+    JS_SINK.with(|s| match &*s.borrow() {
+        Some((max, _)) if level > max => {}
+        Some((_, Some(sink))) => {
+            let _ = sink.call2(
+                &JsValue::NULL,
+                &JsValue::from_str(&level.to_string().to_lowercase()),
+                &JsValue::from_str(message),
+            );
+        }
+        Some((_, None)) => web_sys::console::debug_1(&JsValue::from_str(message)),
+        None => {}
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::examples::walkthrough;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_sink() {
+        let lines = Arc::new(Mutex::new(vec![]));
+        let written = lines.clone();
+        let sink = Sink::new(Level::DEBUG, move |level: &Level, message: &str| {
+            written
+                .lock()
+                .unwrap()
+                .push(format!("{} {}", level, message))
+        });
+
+        let mut plan = walkthrough();
+        tracing::subscriber::with_default(sink, || {
+            plan.schedule
+                .compile_with_cancel(&crate::algorithms::CancelToken::new())
+                .unwrap();
+            plan.schedule.commit_events(vec![(plan.x0, 0.)]).unwrap();
+        });
+
+        let lines = lines.lock().unwrap();
+        assert!(lines[0].starts_with("DEBUG compile started events=5"));
+        assert!(lines
+            .iter()
+            .any(|l| l.starts_with("DEBUG compile finished")));
+        assert!(lines
+            .iter()
+            .any(|l| l == "DEBUG propagated commitments commitments=1 windows=5"));
+
+        assert!(parse_level("verbose").is_err());
+    }
+}
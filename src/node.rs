@@ -0,0 +1,62 @@
+//! # Node
+//! Entry points for server-side JS, eg. build scripts and services running in Node. Plan files are passed in as Buffers or Uint8Arrays (either JSON or the binary plan format, see `RegistrationPayload::to_bytes`) and results come back as plain objects or bytes, so callers don't need to build intermediate JS objects.
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsValue;
+
+use super::registration::{register, Edge, RegistrationPayload, StnOptions};
+use super::schedule::Schedule;
+use super::to_js;
+
+/// Decode a plan file into a plain `{ edges }` object
+#[wasm_bindgen(js_name = readPlan)]
+pub fn read_plan(bytes: &[u8]) -> Result<JsValue, JsValue> {
+    let payload = RegistrationPayload::from_bytes(bytes).map_err(|e| JsValue::from_str(&e))?;
+    to_js(&payload)
+}
+
+/// Build a Schedule from a plan file. Events keep the IDs used in the file. Options default to explicit intervals
+#[wasm_bindgen(js_name = registerScheduleFromBytes)]
+pub fn register_from_bytes(bytes: &[u8], options: Option<StnOptions>) -> Result<Schedule, JsValue> {
+    let payload = RegistrationPayload::from_bytes(bytes).map_err(|e| JsValue::from_str(&e))?;
+    register(&payload, &options.unwrap_or_default()).map_err(|e| JsValue::from_str(&e))
+}
+
+/// Encode every authored constraint in a Schedule in the binary plan format. Metadata, aliases, and commitments are not included
+#[wasm_bindgen(js_name = writePlan)]
+pub fn write_plan(schedule: &Schedule) -> Vec<u8> {
+    payload(schedule).to_bytes()
+}
+
+/// The authored constraints of a Schedule as a registration payload
+pub fn payload(schedule: &Schedule) -> RegistrationPayload {
+    let edges = schedule
+        .constraints()
+        .into_iter()
+        .map(|c| Edge::interval(c.source, c.target, c.interval))
+        .collect();
+    RegistrationPayload { edges }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interval::Interval;
+
+    #[test]
+    fn test_round_trip_schedule() {
+        let mut schedule = Schedule::new();
+        let episode1 = schedule.add_episode(Some(vec![10., 20.]));
+        let episode2 = schedule.add_episode(Some(vec![30., 40.]));
+        schedule
+            .add_constraint(episode1.end(), episode2.start(), None)
+            .unwrap();
+
+        let bytes = write_plan(&schedule);
+        let mut copy = register_from_bytes(&bytes, None).unwrap();
+        assert_eq!(
+            copy.interval(episode1.start(), episode2.end()).unwrap(),
+            Interval::new(40., 60.)
+        );
+    }
+}
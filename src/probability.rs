@@ -0,0 +1,534 @@
+//! # Probability
+//! Duration distributions for Episodes whose durations are uncertain. A distribution turns a risk, ie. the probability of a duration falling outside of a range, into deterministic [lower, upper] bounds that a `Schedule` can reason about.
+
+use std::collections::BTreeMap;
+use std::f64::consts::SQRT_2;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsValue;
+
+use super::interval::Interval;
+use super::{from_js, to_js};
+
+/// A probability distribution over the duration of an Episode. Serialized with a `type` field, eg. `{ "type": "normal", "mean": 10, "stdDev": 2 }`
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Distribution {
+    /// Every duration between lower and upper is equally likely
+    Uniform { lower: f64, upper: f64 },
+    /// A bell curve around a mean
+    #[serde(rename_all = "camelCase")]
+    Normal { mean: f64, std_dev: f64 },
+    /// Rises linearly from min to the most likely duration, then falls to max. A quick way to describe skewed durations from three-point estimates
+    Triangular { min: f64, mode: f64, max: f64 },
+    /// The sum of `shape` exponential delays with `rate` events per unit time, shifted by a minimum duration. Right-skewed, like tasks that usually finish quickly but occasionally run long
+    Erlang { shift: f64, shape: u32, rate: f64 },
+}
+
+impl Distribution {
+    /// Check that the parameters describe a real distribution: finite, with lower ≤ upper, std_dev ≥ 0, min ≤ mode ≤ max, shape ≥ 1 and rate > 0
+    pub fn validate(&self) -> Result<(), String> {
+        let (params, valid): (Vec<f64>, bool) = match *self {
+            Distribution::Uniform { lower, upper } => (vec![lower, upper], lower <= upper),
+            Distribution::Normal { mean, std_dev } => (vec![mean, std_dev], std_dev >= 0.),
+            Distribution::Triangular { min, mode, max } => {
+                (vec![min, mode, max], min <= mode && mode <= max)
+            }
+            Distribution::Erlang { shift, shape, rate } => {
+                (vec![shift, rate], shape >= 1 && rate > 0.)
+            }
+        };
+
+        if params.iter().all(|p| p.is_finite()) && valid {
+            Ok(())
+        } else {
+            Err(format!("Invalid distribution {:?}", self))
+        }
+    }
+
+    /// The same distribution over durations multiplied by a factor
+    pub fn scale(&self, factor: f64) -> Distribution {
+        match *self {
+            Distribution::Uniform { lower, upper } => Distribution::Uniform {
+                lower: lower * factor,
+                upper: upper * factor,
+            },
+            Distribution::Normal { mean, std_dev } => Distribution::Normal {
+                mean: mean * factor,
+                std_dev: std_dev * factor,
+            },
+            Distribution::Triangular { min, mode, max } => Distribution::Triangular {
+                min: min * factor,
+                mode: mode * factor,
+                max: max * factor,
+            },
+            Distribution::Erlang { shift, shape, rate } => Distribution::Erlang {
+                shift: shift * factor,
+                shape,
+                rate: rate / factor,
+            },
+        }
+    }
+
+    /// Probability that a duration is less than or equal to `x`
+    pub fn cdf(&self, x: f64) -> f64 {
+        match *self {
+            Distribution::Uniform { lower, upper } => {
+                if x <= lower {
+                    0.
+                } else if x >= upper {
+                    1.
+                } else {
+                    (x - lower) / (upper - lower)
+                }
+            }
+            Distribution::Normal { mean, std_dev } => {
+                0.5 * (1. + erf((x - mean) / (std_dev * SQRT_2)))
+            }
+            Distribution::Triangular { min, mode, max } => {
+                if x <= min {
+                    0.
+                } else if x >= max {
+                    1.
+                } else if x <= mode {
+                    (x - min).powi(2) / ((max - min) * (mode - min))
+                } else {
+                    1. - (max - x).powi(2) / ((max - min) * (max - mode))
+                }
+            }
+            Distribution::Erlang { shift, shape, rate } => {
+                if x <= shift {
+                    return 0.;
+                }
+                // 1 - sum_{n=0}^{k-1} e^(-λx) (λx)^n / n!
+                let lx = rate * (x - shift);
+                let mut term = (-lx).exp();
+                let mut sum = term;
+                for n in 1..shape {
+                    term *= lx / n as f64;
+                    sum += term;
+                }
+                1. - sum
+            }
+        }
+    }
+
+    /// The duration below which a fraction `p` of outcomes fall. `p` is clamped to [0, 1]. Unbounded tails come back as ±f64::MAX
+    pub fn quantile(&self, p: f64) -> f64 {
+        let p = p.clamp(0., 1.);
+        match *self {
+            Distribution::Uniform { lower, upper } => lower + p * (upper - lower),
+            Distribution::Normal { mean, std_dev } => {
+                if p <= 0. {
+                    -f64::MAX
+                } else if p >= 1. {
+                    f64::MAX
+                } else {
+                    mean + std_dev * inverse_normal_cdf(p)
+                }
+            }
+            Distribution::Triangular { min, mode, max } => {
+                let split = (mode - min) / (max - min);
+                if p <= split {
+                    min + (p * (max - min) * (mode - min)).sqrt()
+                } else {
+                    max - ((1. - p) * (max - min) * (max - mode)).sqrt()
+                }
+            }
+            Distribution::Erlang { shift, .. } => {
+                if p <= 0. {
+                    return shift;
+                } else if p >= 1. {
+                    return f64::MAX;
+                }
+
+                // no closed form. bracket the quantile, then bisect
+                let mut low = shift;
+                let mut high = shift + self.mean().max(1.);
+                for _ in 0..64 {
+                    if self.cdf(high) >= p {
+                        break;
+                    }
+                    high = shift + 2. * (high - shift);
+                }
+                if !high.is_finite() || self.cdf(high) < p {
+                    return f64::MAX;
+                }
+                for _ in 0..100 {
+                    let mid = (low + high) / 2.;
+                    if self.cdf(mid) < p {
+                        low = mid;
+                    } else {
+                        high = mid;
+                    }
+                }
+                (low + high) / 2.
+            }
+        }
+    }
+
+    /// Draw a random duration
+    pub fn sample(&self, rng: &mut Rng) -> f64 {
+        match *self {
+            Distribution::Erlang { shift, shape, rate } => {
+                // sum of exponentials
+                shift - (0..shape).map(|_| rng.next_open().ln()).sum::<f64>() / rate
+            }
+            _ => self.quantile(rng.next_open()),
+        }
+    }
+
+    /// The average duration
+    pub fn mean(&self) -> f64 {
+        match *self {
+            Distribution::Uniform { lower, upper } => (lower + upper) / 2.,
+            Distribution::Normal { mean, .. } => mean,
+            Distribution::Triangular { min, mode, max } => (min + mode + max) / 3.,
+            Distribution::Erlang { shift, shape, rate } => shift + shape as f64 / rate,
+        }
+    }
+
+    /// How spread out durations are around the mean
+    pub fn variance(&self) -> f64 {
+        match *self {
+            Distribution::Uniform { lower, upper } => (upper - lower).powi(2) / 12.,
+            Distribution::Normal { std_dev, .. } => std_dev * std_dev,
+            Distribution::Triangular { min, mode, max } => {
+                (min * min + mode * mode + max * max - min * mode - min * max - mode * max) / 18.
+            }
+            Distribution::Erlang { shape, rate, .. } => shape as f64 / (rate * rate),
+        }
+    }
+
+    /// Bounds that contain the duration with probability `1 - risk`. The risk is split evenly between the two tails
+    pub fn bounds(&self, risk: f64) -> Interval {
+        Interval::new(self.quantile(risk / 2.), self.quantile(1. - risk / 2.))
+    }
+
+    /// Probability that a duration falls outside of an interval
+    pub fn risk(&self, bounds: Interval) -> f64 {
+        self.cdf(bounds.lower()) + (1. - self.cdf(bounds.upper()))
+    }
+}
+
+/// A recommended duration for a task, fit from how long it took in past executions
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Calibration {
+    /// [lower, upper] duration between the requested percentiles. Ready to use with `Schedule::add_episode`
+    pub interval: Interval,
+    pub median: f64,
+    /// Number of as-run durations the interval is based on
+    pub samples: usize,
+}
+
+/// The value below which a fraction `p` of sorted samples fall, linearly interpolating between samples
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let position = p * (sorted.len() - 1) as f64;
+    let below = position.floor() as usize;
+    let above = position.ceil() as usize;
+    sorted[below] + (position - below as f64) * (sorted[above] - sorted[below])
+}
+
+/// Fit a duration from as-run samples, eg. `calibrate(&durations, 0.05, 0.95)` for the 5th to 95th percentile. Samples that aren't finite are ignored. `None` if there are no usable samples
+pub fn calibrate(samples: &[f64], lower: f64, upper: f64) -> Option<Calibration> {
+    let mut sorted: Vec<f64> = samples.iter().cloned().filter(|s| s.is_finite()).collect();
+    if sorted.is_empty() {
+        return None;
+    }
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+    Some(Calibration {
+        interval: Interval::new(percentile(&sorted, lower), percentile(&sorted, upper)),
+        median: percentile(&sorted, 0.5),
+        samples: sorted.len(),
+    })
+}
+
+/// Fit durations for many named tasks at once. Tasks without usable samples are left out. Errs if the percentiles aren't in order between 0 and 1
+pub fn calibrate_tasks(
+    history: &BTreeMap<String, Vec<f64>>,
+    lower: f64,
+    upper: f64,
+) -> Result<BTreeMap<String, Calibration>, String> {
+    if !(0. ..=1.).contains(&lower) || !(0. ..=1.).contains(&upper) || lower > upper {
+        return Err(format!(
+            "percentiles must be between 0 and 1 with lower <= upper, got {} and {}",
+            lower, upper
+        ));
+    }
+
+    Ok(history
+        .iter()
+        .filter_map(|(task, samples)| calibrate(samples, lower, upper).map(|c| (task.clone(), c)))
+        .collect())
+}
+
+/// Recommend durations from historical as-run data. `history` maps task names to arrays of past durations, eg. `{ "EGRESS": [22, 25, 31] }`. `lower` and `upper` are percentiles between 0 and 1. Returns an object mapping task names to `{ interval, median, samples }`
+#[wasm_bindgen(js_name = calibrateDurations)]
+pub fn calibrate_durations_js(
+    history: JsValue,
+    lower: f64,
+    upper: f64,
+) -> Result<JsValue, JsValue> {
+    let history: BTreeMap<String, Vec<f64>> = from_js(&history)?;
+    let calibrations =
+        calibrate_tasks(&history, lower, upper).map_err(|e| JsValue::from_str(&e))?;
+    to_js(&calibrations)
+}
+
+/// A small, seedable pseudo-random number generator (SplitMix64) so simulations are reproducible and don't depend on the host's randomness
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A uniform number in the open interval (0, 1)
+    pub fn next_open(&mut self) -> f64 {
+        // 53 random bits, offset by half a step so 0 and 1 never come up
+        ((self.next_u64() >> 11) as f64 + 0.5) / (1u64 << 53) as f64
+    }
+}
+
+/// Error function. Abramowitz & Stegun 7.1.26, accurate to about 1.5e-7
+fn erf(x: f64) -> f64 {
+    let t = 1. / (1. + 0.327_591_1 * x.abs());
+    let poly = t
+        * (0.254_829_592
+            + t * (-0.284_496_736
+                + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    let y = 1. - poly * (-x * x).exp();
+    if x >= 0. {
+        y
+    } else {
+        -y
+    }
+}
+
+/// Inverse of the standard normal CDF for 0 < p < 1. Acklam's rational approximation, accurate to about 1.15e-9
+fn inverse_normal_cdf(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [f64; 5] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [f64; 4] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+    ];
+    const P_LOW: f64 = 0.024_25;
+
+    if p < P_LOW {
+        let q = (-2. * p.ln()).sqrt();
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.)
+    } else if p <= 1. - P_LOW {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.)
+    } else {
+        let q = (-2. * (1. - p).ln()).sqrt();
+        -(((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn test_normal_quantile_round_trip() {
+        let normal = Distribution::Normal {
+            mean: 10.,
+            std_dev: 2.,
+        };
+
+        assert!(close(normal.quantile(0.5), 10.));
+        assert!(close(normal.quantile(0.975), 10. + 2. * 1.959_964));
+        for p in [0.001, 0.01, 0.2, 0.5, 0.8, 0.99, 0.999].iter() {
+            assert!(close(normal.cdf(normal.quantile(*p)), *p), "p = {}", p);
+        }
+    }
+
+    #[test]
+    fn test_skewed_distributions() {
+        let triangular = Distribution::Triangular {
+            min: 10.,
+            mode: 15.,
+            max: 40.,
+        };
+        let erlang = Distribution::Erlang {
+            shift: 10.,
+            shape: 3,
+            rate: 0.5,
+        };
+
+        for d in [triangular, erlang].iter() {
+            for p in [0.05, 0.5, 0.95].iter() {
+                assert!(close(d.cdf(d.quantile(*p)), *p), "{:?} p = {}", d, p);
+            }
+
+            // sample moments are close to the analytic ones
+            let mut rng = Rng::new(7);
+            let samples: Vec<f64> = (0..20_000).map(|_| d.sample(&mut rng)).collect();
+            let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+            let variance =
+                samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / samples.len() as f64;
+            assert!((mean - d.mean()).abs() / d.mean() < 0.02, "{:?}", d);
+            assert!(
+                (variance - d.variance()).abs() / d.variance() < 0.05,
+                "{:?}",
+                d
+            );
+        }
+
+        assert!(close(triangular.quantile(1. / 6.), 15.));
+        assert_eq!(erlang.mean(), 16.);
+        assert_eq!(erlang.variance(), 12.);
+    }
+
+    #[test]
+    fn test_calibrate() {
+        let mut history = BTreeMap::new();
+        history.insert(
+            "EGRESS".to_string(),
+            vec![30., 22., 25., 27., 24., 26., 45., 23., 28., 25., 29.],
+        );
+        history.insert("SAFER CHECKOUT".to_string(), vec![f64::NAN]);
+
+        let calibrations = calibrate_tasks(&history, 0.1, 0.9).unwrap();
+        assert_eq!(calibrations.len(), 1);
+
+        let egress = calibrations["EGRESS"];
+        assert_eq!(egress.samples, 11);
+        assert_eq!(egress.median, 26.);
+        assert_eq!(egress.interval, Interval::new(23., 30.));
+
+        assert!(calibrate_tasks(&history, 0.9, 0.1).is_err());
+    }
+
+    #[test]
+    fn test_bounds_and_risk() {
+        let uniform = Distribution::Uniform {
+            lower: 0.,
+            upper: 10.,
+        };
+        assert_eq!(uniform.bounds(0.2), Interval::new(1., 9.));
+        assert!(close(uniform.risk(Interval::new(1., 9.)), 0.2));
+
+        let normal = Distribution::Normal {
+            mean: 0.,
+            std_dev: 1.,
+        };
+        assert!(close(normal.risk(normal.bounds(0.05)), 0.05));
+    }
+
+    #[test]
+    fn test_validate() {
+        let valid = [
+            Distribution::Uniform {
+                lower: 5.,
+                upper: 5.,
+            },
+            Distribution::Normal {
+                mean: 10.,
+                std_dev: 0.,
+            },
+            Distribution::Triangular {
+                min: 1.,
+                mode: 1.,
+                max: 4.,
+            },
+            Distribution::Erlang {
+                shift: 0.,
+                shape: 1,
+                rate: 0.5,
+            },
+        ];
+        for d in valid.iter() {
+            assert!(d.validate().is_ok(), "{:?}", d);
+        }
+
+        let invalid = [
+            Distribution::Uniform {
+                lower: 10.,
+                upper: 5.,
+            },
+            Distribution::Normal {
+                mean: 10.,
+                std_dev: -1.,
+            },
+            Distribution::Normal {
+                mean: f64::NAN,
+                std_dev: 1.,
+            },
+            Distribution::Triangular {
+                min: 1.,
+                mode: 5.,
+                max: 4.,
+            },
+            Distribution::Erlang {
+                shift: 0.,
+                shape: 0,
+                rate: 0.5,
+            },
+            Distribution::Erlang {
+                shift: 0.,
+                shape: 2,
+                rate: 0.,
+            },
+        ];
+        for d in invalid.iter() {
+            assert!(d.validate().is_err(), "{:?}", d);
+        }
+
+        // bracketing gives up instead of doubling forever
+        let slow = Distribution::Erlang {
+            shift: 0.,
+            shape: 1,
+            rate: 1e-300,
+        };
+        assert!(close(slow.quantile(0.5) * 1e-300, 2f64.ln()));
+        let slower = Distribution::Erlang {
+            shift: 0.,
+            shape: 1000,
+            rate: 1e-306,
+        };
+        assert_eq!(slower.quantile(0.5), f64::MAX);
+    }
+}
@@ -0,0 +1,217 @@
+//! # Python
+//! Bindings for running the same engine from Python, eg. in a Jupyter notebook. Only built with the `python` feature and never for wasm. Build a wheel with [maturin](https://github.com/PyO3/maturin), eg. `maturin develop --features python`.
+//!
+//! ```python
+//! from temporal_networks import Schedule
+//!
+//! schedule = Schedule()
+//! egress = schedule.add_episode((40, 50))
+//! traverse = schedule.add_episode((10, 15))
+//! schedule.add_constraint(egress[1], traverse[0])
+//! schedule.commit(egress[0], 0)
+//! schedule.window(traverse[1])  # (50.0, 65.0)
+//! ```
+//!
+//! The Mission/Step API is implemented in JS (see `js/mission.js`) and isn't available here. Missions can be brought over by registering their constraints with `Schedule.from_edges`.
+//!
+//! Inputs are checked before they reach the `Schedule` because its errors are JS values, which can't be created outside of wasm.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::collections::BTreeMap;
+
+use super::interval::Interval;
+use super::registration::{register, Edge, RegistrationPayload, StnOptions};
+use super::schedule::{EventID, Schedule};
+
+/// A [lower, upper] span of time
+#[pyclass(name = "Interval")]
+#[derive(Clone, Copy)]
+pub struct PyInterval(Interval);
+
+#[pymethods]
+impl PyInterval {
+    #[new]
+    fn new(lower: f64, upper: f64) -> PyInterval {
+        PyInterval(Interval::new(lower, upper))
+    }
+
+    #[getter]
+    fn lower(&self) -> f64 {
+        self.0.lower()
+    }
+
+    #[getter]
+    fn upper(&self) -> f64 {
+        self.0.upper()
+    }
+
+    fn contains(&self, value: f64) -> bool {
+        self.0.contains(value)
+    }
+
+    fn is_valid(&self) -> bool {
+        self.0.is_valid()
+    }
+
+    fn converged(&self) -> bool {
+        self.0.converged()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Interval({}, {})", self.0.lower(), self.0.upper())
+    }
+}
+
+/// A Schedule of events and the timing constraints between them
+#[pyclass(name = "Schedule")]
+#[derive(Clone, Default)]
+pub struct PySchedule(Schedule);
+
+impl PySchedule {
+    /// Errs if the event isn't in the Schedule
+    fn require(&self, event: EventID) -> PyResult<()> {
+        if self.0.contains_event(event) {
+            Ok(())
+        } else {
+            Err(PyValueError::new_err(format!(
+                "event {} is not in the Schedule",
+                event
+            )))
+        }
+    }
+
+    /// Errs if the Schedule has no consistent set of times
+    fn require_consistent(&mut self) -> PyResult<()> {
+        match self.0.negative_cycle() {
+            None => Ok(()),
+            Some(cycle) => Err(PyValueError::new_err(format!(
+                "the Schedule is inconsistent around events {:?}",
+                cycle
+            ))),
+        }
+    }
+}
+
+#[pymethods]
+impl PySchedule {
+    #[new]
+    fn new() -> PySchedule {
+        PySchedule(Schedule::new())
+    }
+
+    /// Build a Schedule from `(source, target, (lower, upper))` edges. Events keep their IDs
+    #[staticmethod]
+    fn from_edges(edges: Vec<(EventID, EventID, (f64, f64))>) -> PyResult<PySchedule> {
+        let payload = RegistrationPayload {
+            edges: edges
+                .into_iter()
+                .map(|(s, t, (l, u))| Edge::interval(s, t, Interval::new(l, u)))
+                .collect(),
+        };
+        let schedule = register(&payload, &StnOptions::new()).map_err(PyValueError::new_err)?;
+        Ok(PySchedule(schedule))
+    }
+
+    /// Add an Episode with a (lower, upper) duration. Returns its (start, end) events. Raises a ValueError if a bound is NaN
+    #[pyo3(signature = (duration=None))]
+    fn add_episode(&mut self, duration: Option<(f64, f64)>) -> PyResult<(EventID, EventID)> {
+        let duration = duration
+            .map(|(l, u)| Interval::try_from_slice(&[l, u]).map(|i| vec![i.lower(), i.upper()]))
+            .transpose()
+            .map_err(PyValueError::new_err)?;
+        let episode = self.0.add_episode(duration);
+        Ok((episode.start(), episode.end()))
+    }
+
+    /// Constrain the time from the source to the target. Defaults to (0, ∞)
+    #[pyo3(signature = (source, target, interval=None))]
+    fn add_constraint(
+        &mut self,
+        source: EventID,
+        target: EventID,
+        interval: Option<(f64, f64)>,
+    ) -> PyResult<()> {
+        self.require(source)?;
+        self.require(target)?;
+        self.0
+            .add_constraint(source, target, interval.map(|(l, u)| vec![l, u]))
+            .map_err(|_| PyValueError::new_err("could not add the constraint"))
+    }
+
+    /// Run all-pairs shortest paths. Queries compile automatically, so this is only needed to control when the work happens
+    fn compile(&mut self) -> PyResult<()> {
+        self.require_consistent()?;
+        self.0
+            .compile()
+            .map_err(|_| PyValueError::new_err("could not compile the Schedule"))
+    }
+
+    /// Record the time an event happened
+    fn commit(&mut self, event: EventID, time: f64) -> PyResult<()> {
+        self.require(event)?;
+        self.require_consistent()?;
+        let report = self
+            .0
+            .commit_events(vec![(event, time)])
+            .map_err(|_| PyValueError::new_err("could not compile the Schedule"))?;
+        match report.rejected.first() {
+            Some(r) => Err(PyValueError::new_err(r.reason.clone())),
+            None => Ok(()),
+        }
+    }
+
+    /// The (earliest, latest) time an event can happen
+    fn window(&mut self, event: EventID) -> PyResult<(f64, f64)> {
+        self.require(event)?;
+        self.require_consistent()?;
+        let windows = self
+            .0
+            .time_windows()
+            .map_err(|_| PyValueError::new_err("could not compile the Schedule"))?;
+        windows
+            .iter()
+            .find(|w| w.event == event)
+            .map(|w| (w.earliest, w.latest))
+            .ok_or_else(|| PyValueError::new_err(format!("no window for event {}", event)))
+    }
+
+    /// The interval between two events
+    fn interval(&mut self, source: EventID, target: EventID) -> PyResult<PyInterval> {
+        self.require(source)?;
+        self.require(target)?;
+        self.require_consistent()?;
+        self.0
+            .interval(source, target)
+            .map(PyInterval)
+            .map_err(|_| PyValueError::new_err("could not find the interval"))
+    }
+
+    fn is_consistent(&mut self) -> bool {
+        self.require_consistent().is_ok() && self.0.is_consistent()
+    }
+
+    /// A concrete time for every event that satisfies every constraint
+    fn extract_schedule(&mut self) -> PyResult<BTreeMap<EventID, f64>> {
+        self.require_consistent()?;
+        self.0
+            .compile()
+            .map_err(|_| PyValueError::new_err("could not compile the Schedule"))?;
+        if self.0.root().is_none() {
+            return Err(PyValueError::new_err(
+                "cannot find the first event in the Schedule",
+            ));
+        }
+        self.0
+            .extract_schedule()
+            .map_err(|_| PyValueError::new_err("could not extract a schedule"))
+    }
+}
+
+/// The `temporal_networks` Python module
+#[pymodule]
+fn temporal_networks(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyInterval>()?;
+    m.add_class::<PySchedule>()?;
+    Ok(())
+}
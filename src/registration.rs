@@ -0,0 +1,474 @@
+//! # Registration
+//! Build a `Schedule` in one shot from a list of edges, eg. a plan exported by another tool. Edges either carry a [lower, upper] `interval` or a nominal number of `minutes` that is widened into an interval by an uncertainty factor.
+
+use petgraph::graphmap::DiGraphMap;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsValue;
+
+use super::algorithms::{floyd_warshall, negative_cycle};
+use super::interval::Interval;
+use super::schedule::{EventID, Schedule};
+use super::{from_js, to_js};
+
+/// Default uncertainty for the edge between two events if the interval is not given
+fn default_execution_uncertainty() -> f64 {
+    0.1
+}
+
+/// Options for registering a Schedule from a payload of edges.
+///
+/// # Example
+///
+/// ```
+/// use temporal_networks::registration::{register, Edge, RegistrationPayload, StnOptions};
+///
+/// let options = StnOptions::new().implicit_intervals(true).uncertainty(0.05);
+/// let payload = RegistrationPayload {
+///     edges: vec![Edge::minutes(0, 1, 20.)],
+/// };
+///
+/// let mut schedule = register(&payload, &options).unwrap();
+/// assert_eq!(schedule.interval(0, 1).unwrap().upper(), 21.);
+/// ```
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StnOptions {
+    /// Are distances in the form of [x, x] (keyed by edges[].minutes) instead of [lower, upper]? eg. set to true if edges are in the form of `{ "source": 1, "target": 2, "minutes": 5}`. Set to false if edges are in the form of `{ "source": 1, "target": 2, "interval": [4, 6] }`. Default false
+    #[serde(default, alias = "implicit_intervals")]
+    implicit_intervals: bool,
+    /// The amount of uncertainty that should be applied if interval definitions are implicit (see above). Value must be between 0 and 1 inclusive. Defaults to 0.1 (10%)
+    #[serde(
+        default = "default_execution_uncertainty",
+        alias = "execution_uncertainty",
+        alias = "uncertainty"
+    )]
+    execution_uncertainty: f64,
+}
+
+/// The name used by the original STN registration API
+pub type RegistrationOptions = StnOptions;
+
+impl Default for StnOptions {
+    fn default() -> StnOptions {
+        StnOptions {
+            implicit_intervals: false,
+            execution_uncertainty: default_execution_uncertainty(),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl StnOptions {
+    /// Create options with explicit intervals and 10% uncertainty
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> StnOptions {
+        StnOptions::default()
+    }
+
+    /// Parse options from a plain JS object, eg. `{ implicitIntervals: true, executionUncertainty: 0.05 }`. Errs instead of panicking if the object is malformed
+    #[wasm_bindgen(js_name = fromObject)]
+    pub fn from_object(options: JsValue) -> Result<StnOptions, JsValue> {
+        let options: StnOptions = from_js(&options)?;
+        options.validate().map_err(|e| JsValue::from_str(&e))?;
+        Ok(options)
+    }
+
+    /// Whether or not edges are registered with `minutes` instead of `interval`
+    #[wasm_bindgen(getter, js_name = implicitIntervals)]
+    pub fn get_implicit_intervals(&self) -> bool {
+        self.implicit_intervals
+    }
+
+    #[wasm_bindgen(setter, js_name = implicitIntervals)]
+    pub fn set_implicit_intervals(&mut self, implicit_intervals: bool) {
+        self.implicit_intervals = implicit_intervals;
+    }
+
+    /// The fraction of `minutes` that an implicit interval can vary by
+    #[wasm_bindgen(getter, js_name = executionUncertainty)]
+    pub fn get_execution_uncertainty(&self) -> f64 {
+        self.execution_uncertainty
+    }
+
+    #[wasm_bindgen(setter, js_name = executionUncertainty)]
+    pub fn set_execution_uncertainty(&mut self, uncertainty: f64) {
+        self.execution_uncertainty = uncertainty;
+    }
+}
+
+impl StnOptions {
+    /// Builder-style setter for whether or not edges are registered with `minutes` instead of `interval`
+    pub fn implicit_intervals(mut self, implicit_intervals: bool) -> StnOptions {
+        self.implicit_intervals = implicit_intervals;
+        self
+    }
+
+    /// Builder-style setter for the fraction of `minutes` that an implicit interval can vary by. Checked when the options are used
+    pub fn uncertainty(mut self, uncertainty: f64) -> StnOptions {
+        self.execution_uncertainty = uncertainty;
+        self
+    }
+
+    /// Errs if the uncertainty is not between 0 and 1 inclusive
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0. ..=1.).contains(&self.execution_uncertainty) {
+            return Err(format!(
+                "execution uncertainty must be between 0 and 1 inclusive, got {}",
+                self.execution_uncertainty
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Edges to register in a Schedule
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct RegistrationPayload {
+    pub edges: Vec<Edge>,
+}
+
+/// Magic bytes at the start of a binary plan file, followed by a format version
+const PLAN_MAGIC: &[u8; 4] = b"STN\x01";
+
+/// Bytes in each edge of a binary plan file: source and target as little-endian i32s, then lower and upper as little-endian f64s
+const EDGE_BYTES: usize = 24;
+
+impl RegistrationPayload {
+    /// Encode the edges in the compact binary plan format. Edges are written with their explicit intervals
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(8 + EDGE_BYTES * self.edges.len());
+        bytes.extend_from_slice(PLAN_MAGIC);
+        bytes.extend_from_slice(&(self.edges.len() as u32).to_le_bytes());
+        for edge in self.edges.iter() {
+            bytes.extend_from_slice(&edge.source.to_le_bytes());
+            bytes.extend_from_slice(&edge.target.to_le_bytes());
+            bytes.extend_from_slice(&edge.interval.lower().to_le_bytes());
+            bytes.extend_from_slice(&edge.interval.upper().to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Decode a plan file. Accepts either the binary plan format or UTF-8 JSON, eg. the contents of a `.json` file read into a Buffer
+    pub fn from_bytes(bytes: &[u8]) -> Result<RegistrationPayload, String> {
+        if !bytes.starts_with(PLAN_MAGIC) {
+            return serde_json::from_slice(bytes).map_err(|e| e.to_string());
+        }
+
+        let mut count = [0; 4];
+        count.copy_from_slice(
+            bytes
+                .get(4..8)
+                .ok_or("plan file is missing its edge count")?,
+        );
+        let count = u32::from_le_bytes(count) as usize;
+
+        let records = &bytes[8..];
+        // a corrupt count can overflow on 32-bit targets like wasm
+        let expected = count.checked_mul(EDGE_BYTES);
+        if expected != Some(records.len()) {
+            return Err(format!(
+                "plan file should have {} edges ({} bytes) but has {} bytes",
+                count,
+                count as u64 * EDGE_BYTES as u64,
+                records.len()
+            ));
+        }
+
+        let edges = records
+            .chunks_exact(EDGE_BYTES)
+            .map(|r| {
+                let mut i = [0; 4];
+                let mut f = [0; 8];
+                i.copy_from_slice(&r[0..4]);
+                let source = i32::from_le_bytes(i);
+                i.copy_from_slice(&r[4..8]);
+                let target = i32::from_le_bytes(i);
+                f.copy_from_slice(&r[8..16]);
+                let lower = f64::from_le_bytes(f);
+                f.copy_from_slice(&r[16..24]);
+                let upper = f64::from_le_bytes(f);
+                Edge::interval(source, target, Interval::new(lower, upper))
+            })
+            .collect();
+
+        Ok(RegistrationPayload { edges })
+    }
+}
+
+/// A constraint between two events as it appears in a registration payload
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Edge {
+    pub source: EventID,
+    pub target: EventID,
+    /// [lower, upper] time from the source to the target. Used when intervals are explicit
+    #[serde(default)]
+    pub interval: Interval,
+    /// Nominal time from the source to the target. Used when intervals are implicit
+    #[serde(default)]
+    pub minutes: f64,
+}
+
+impl Edge {
+    /// An edge with an explicit [lower, upper] interval
+    pub fn interval(source: EventID, target: EventID, interval: Interval) -> Edge {
+        Edge {
+            source,
+            target,
+            interval,
+            minutes: 0.,
+        }
+    }
+
+    /// An edge with a nominal time that is widened by the execution uncertainty
+    pub fn minutes(source: EventID, target: EventID, minutes: f64) -> Edge {
+        Edge {
+            source,
+            target,
+            interval: Interval::default(),
+            minutes,
+        }
+    }
+
+    /// The [lower, upper] interval this edge represents under the given options
+    fn resolve(&self, options: &StnOptions) -> Interval {
+        if options.implicit_intervals {
+            // apply the uncertainty
+            let error_estimate = self.minutes * options.execution_uncertainty;
+            Interval::new(self.minutes - error_estimate, self.minutes + error_estimate)
+        } else {
+            self.interval
+        }
+    }
+}
+
+/// Build a Schedule from a payload of edges. Events keep the IDs used in the payload
+pub fn register(payload: &RegistrationPayload, options: &StnOptions) -> Result<Schedule, String> {
+    options.validate()?;
+
+    let mut events: Vec<EventID> = payload
+        .edges
+        .iter()
+        .flat_map(|e| vec![e.source, e.target])
+        .collect();
+    events.sort_unstable();
+    events.dedup();
+
+    let mut schedule = Schedule::new();
+    for event in events.iter() {
+        schedule.insert_event(*event);
+    }
+
+    for edge in payload.edges.iter() {
+        let interval = edge.resolve(options);
+        if !interval.is_valid() {
+            return Err(format!(
+                "edge from {} to {} has an invalid interval {}",
+                edge.source, edge.target, interval
+            ));
+        }
+        schedule.update_interval(
+            edge.source,
+            edge.target,
+            vec![interval.lower(), interval.upper()],
+        );
+    }
+
+    Ok(schedule)
+}
+
+/// Build a Schedule from a payload of edges, eg. `{ edges: [{ source: 1, target: 2, interval: [4, 6] }] }`. Events keep the IDs used in the payload
+#[wasm_bindgen(js_name = registerSchedule)]
+pub fn register_js(payload: JsValue, options: &StnOptions) -> Result<Schedule, JsValue> {
+    let payload: RegistrationPayload = from_js(&payload)?;
+    register(&payload, options).map_err(|e| JsValue::from_str(&e))
+}
+
+/// The outcome of a one-shot consistency check
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum Consistency {
+    /// Every edge can be satisfied. Bounds are the tightest interval implied for each edge in the payload
+    Consistent { bounds: Vec<Bound> },
+    /// The edges contradict each other. The cycle lists events whose constraints can't all be met, in order
+    Inconsistent { cycle: Vec<EventID> },
+}
+
+/// The tightest [lower, upper] interval between two events implied by every edge together
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Bound {
+    pub source: EventID,
+    pub target: EventID,
+    pub interval: Interval,
+}
+
+/// Check a payload of edges for consistency without building a Schedule. Errs if an edge has an invalid interval
+pub fn check(payload: &RegistrationPayload, options: &StnOptions) -> Result<Consistency, String> {
+    options.validate()?;
+
+    let mut graph: DiGraphMap<EventID, f64> = DiGraphMap::new();
+    let mut add = |source: EventID, target: EventID, weight: f64| {
+        let weight = match graph.edge_weight(source, target) {
+            Some(w) => w.min(weight),
+            None => weight,
+        };
+        graph.add_edge(source, target, weight);
+    };
+
+    let mut pairs = vec![];
+    for edge in payload.edges.iter() {
+        let interval = edge.resolve(options);
+        if !interval.is_valid() {
+            return Err(format!(
+                "edge from {} to {} has an invalid interval {}",
+                edge.source, edge.target, interval
+            ));
+        }
+        add(edge.source, edge.target, interval.upper());
+        add(edge.target, edge.source, -interval.lower());
+        if !pairs.contains(&(edge.source, edge.target)) {
+            pairs.push((edge.source, edge.target));
+        }
+    }
+
+    if let Some(cycle) = negative_cycle(&graph) {
+        return Ok(Consistency::Inconsistent { cycle });
+    }
+
+    let distances = floyd_warshall(&graph)?;
+    let bounds = pairs
+        .into_iter()
+        .map(|(source, target)| {
+            let upper = distances.distance(source, target).unwrap_or(f64::MAX);
+            let lower = distances.distance(target, source).map_or(-f64::MAX, |d| -d);
+            Bound {
+                source,
+                target,
+                interval: Interval::new(lower, upper),
+            }
+        })
+        .collect();
+
+    Ok(Consistency::Consistent { bounds })
+}
+
+/// Check a JSON payload of edges, eg. `{ "edges": [{ "source": 1, "target": 2, "interval": [4, 6] }] }`, for consistency without creating a Schedule. Returns `{ status: "consistent", bounds }` with the tightest `{ source, target, interval }` for each edge, or `{ status: "inconsistent", cycle }` with the events on a negative cycle. Options default to explicit intervals
+#[wasm_bindgen(js_name = checkConsistency)]
+pub fn check_consistency(
+    edges_json: &str,
+    options: Option<StnOptions>,
+) -> Result<JsValue, JsValue> {
+    let payload: RegistrationPayload =
+        serde_json::from_str(edges_json).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let consistency =
+        check(&payload, &options.unwrap_or_default()).map_err(|e| JsValue::from_str(&e))?;
+    to_js(&consistency)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_walkthrough() -> Result<(), String> {
+        let payload = RegistrationPayload {
+            edges: vec![
+                Edge::interval(1, 2, Interval::new(10., 20.)),
+                Edge::interval(2, 3, Interval::new(30., 40.)),
+                Edge::interval(4, 3, Interval::new(10., 20.)),
+                Edge::interval(4, 5, Interval::new(40., 50.)),
+                Edge::interval(1, 5, Interval::new(60., 70.)),
+            ],
+        };
+
+        let mut schedule = register(&payload, &StnOptions::new())?;
+
+        schedule.compile().unwrap();
+        assert_eq!(schedule.root(), Some(1));
+        assert_eq!(schedule.interval(1, 3).unwrap(), Interval::new(40., 50.));
+        assert_eq!(schedule.interval(1, 4).unwrap(), Interval::new(20., 30.));
+
+        // new events don't collide with registered IDs
+        assert_eq!(schedule.create_event(), 6);
+        Ok(())
+    }
+
+    #[test]
+    fn test_register_implicit_intervals() -> Result<(), String> {
+        let options = StnOptions::new().implicit_intervals(true).uncertainty(0.05);
+        let payload = RegistrationPayload {
+            edges: vec![Edge::minutes(0, 1, 20.), Edge::minutes(1, 2, 40.)],
+        };
+
+        let mut schedule = register(&payload, &options)?;
+        assert_eq!(schedule.interval(0, 2).unwrap(), Interval::new(57., 63.));
+        Ok(())
+    }
+
+    #[test]
+    fn test_options_from_json() {
+        let options: StnOptions =
+            serde_json::from_str(r#"{ "implicitIntervals": true, "uncertainty": 0.2 }"#).unwrap();
+        assert_eq!(
+            options,
+            StnOptions::new().implicit_intervals(true).uncertainty(0.2)
+        );
+
+        let defaults: StnOptions = serde_json::from_str("{}").unwrap();
+        assert_eq!(defaults, StnOptions::new());
+
+        assert!(StnOptions::new().uncertainty(1.5).validate().is_err());
+    }
+
+    #[test]
+    fn test_plan_bytes() {
+        let payload = RegistrationPayload {
+            edges: vec![
+                Edge::interval(1, 2, Interval::new(10., 20.)),
+                Edge::interval(2, 3, Interval::new(0., f64::MAX)),
+            ],
+        };
+
+        let bytes = payload.to_bytes();
+        assert_eq!(bytes.len(), 8 + 2 * EDGE_BYTES);
+        assert_eq!(RegistrationPayload::from_bytes(&bytes), Ok(payload.clone()));
+
+        // JSON files work too
+        let json = serde_json::to_vec(&payload).unwrap();
+        assert_eq!(RegistrationPayload::from_bytes(&json), Ok(payload));
+
+        assert!(RegistrationPayload::from_bytes(&bytes[..20]).is_err());
+    }
+
+    #[test]
+    fn test_check_consistency() -> Result<(), String> {
+        let mut payload: RegistrationPayload = serde_json::from_str(
+            r#"{ "edges": [
+                { "source": 1, "target": 2, "interval": [10, 20] },
+                { "source": 2, "target": 3, "interval": [30, 40] },
+                { "source": 1, "target": 3, "interval": [0, 55] }
+            ] }"#,
+        )
+        .unwrap();
+
+        match check(&payload, &StnOptions::new())? {
+            Consistency::Consistent { bounds } => {
+                assert_eq!(bounds.len(), 3);
+                assert_eq!(bounds[0].interval, Interval::new(10., 20.));
+                assert_eq!(bounds[2].interval, Interval::new(40., 55.));
+            }
+            c => panic!("expected a consistent payload, got {:?}", c),
+        }
+
+        payload
+            .edges
+            .push(Edge::interval(1, 3, Interval::new(0., 35.)));
+        assert_eq!(
+            check(&payload, &StnOptions::new())?,
+            Consistency::Inconsistent {
+                cycle: vec![1, 3, 2]
+            }
+        );
+        Ok(())
+    }
+}
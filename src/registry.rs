@@ -0,0 +1,494 @@
+//! # Registry
+//! Several named Schedules, eg. the EVAs an operations center is tracking, owned by one wasm instance so every mission doesn't pay for its own. Each mission has an epoch, the absolute time its root happens at, which lines the missions up for queries across them, like two EVAs that need the same airlock.
+//!
+//! Missions can depend on each other through links, constraints between events of two missions, eg. EVA 2 prep can't begin until 12 hours after EVA 1 ingress. Once there are links, the missions are compiled together as one network, and each mission's windows reflect the others.
+//!
+//! ```
+//! use std::collections::BTreeMap;
+//! use temporal_networks::registry::Registry;
+//! use temporal_networks::schedule::Schedule;
+//!
+//! let mut eva1 = Schedule::new();
+//! let egress1 = eva1.add_episode(Some(vec![40., 50.]));
+//! let mut eva2 = Schedule::new();
+//! let egress2 = eva2.add_episode(Some(vec![40., 50.]));
+//!
+//! let mut registry = Registry::new();
+//! registry.insert("eva-1", eva1, 0.);
+//! registry.insert("eva-2", eva2, 45.);
+//!
+//! let mut airlock = BTreeMap::new();
+//! airlock.insert("eva-1".to_string(), vec![egress1]);
+//! airlock.insert("eva-2".to_string(), vec![egress2]);
+//! assert_eq!(registry.resource_conflicts(&airlock).unwrap().len(), 1);
+//! ```
+
+use std::collections::BTreeMap;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsValue;
+
+use super::interval::Interval;
+use super::schedule::{Episode, EventID, Schedule};
+use super::{from_js, to_js};
+
+/// An Episode of a mission that uses a shared resource
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Usage {
+    pub mission: String,
+    /// `(start, end)` events
+    pub episode: (EventID, EventID),
+}
+
+/// Two Episodes in different missions that could use a shared resource at the same time
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ResourceConflict {
+    pub first: Usage,
+    pub second: Usage,
+    /// When, in absolute time, both could be using the resource
+    pub overlap: Interval,
+    /// Whether or not they overlap however the missions are executed
+    pub certain: bool,
+}
+
+/// An event in one of a Registry's missions
+#[derive(Clone, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct MissionEvent {
+    pub mission: String,
+    pub event: EventID,
+}
+
+impl MissionEvent {
+    pub fn new(mission: &str, event: EventID) -> MissionEvent {
+        MissionEvent {
+            mission: mission.to_string(),
+            event,
+        }
+    }
+}
+
+/// A constraint between events of two missions
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Link {
+    pub source: MissionEvent,
+    pub target: MissionEvent,
+    /// [lower, upper] time from the source to the target
+    pub interval: Interval,
+}
+
+/// Named Schedules with the absolute time each one starts
+#[wasm_bindgen]
+#[derive(Clone, Debug, Default)]
+pub struct Registry {
+    missions: BTreeMap<String, Schedule>,
+    epochs: BTreeMap<String, f64>,
+    links: Vec<Link>,
+}
+
+#[wasm_bindgen]
+impl Registry {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Registry {
+        Registry::default()
+    }
+
+    /// Add a mission, replacing any mission with the same name. The Schedule is moved into the registry, so JS can't use it afterwards. `epoch` is the absolute time of its root and defaults to 0
+    #[wasm_bindgen(js_name = insert)]
+    pub fn insert_js(&mut self, name: &str, schedule: Schedule, epoch: Option<f64>) {
+        self.insert(name, schedule, epoch.unwrap_or(0.));
+    }
+
+    /// Take a mission out of the registry, along with its links to other missions
+    pub fn remove(&mut self, name: &str) -> Option<Schedule> {
+        self.epochs.remove(name);
+        self.links
+            .retain(|l| l.source.mission != name && l.target.mission != name);
+        self.missions.remove(name)
+    }
+
+    /// Constrain an event in one mission relative to an event in another, eg. `link("eva-1", ingress, "eva-2", prep, [720, Infinity])`. Linking the same events again replaces the interval. Errs if a mission or event doesn't exist or the interval isn't `[lower, upper]`
+    #[wasm_bindgen(js_name = link)]
+    pub fn link_js(
+        &mut self,
+        source_mission: &str,
+        source_event: EventID,
+        target_mission: &str,
+        target_event: EventID,
+        interval: Vec<f64>,
+    ) -> Result<(), JsValue> {
+        let interval = Interval::try_from_slice(&interval).map_err(|e| JsValue::from_str(&e))?;
+        self.link(
+            MissionEvent::new(source_mission, source_event),
+            MissionEvent::new(target_mission, target_event),
+            interval,
+        )
+        .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Get every link as an array of `{ source, target, interval }`, where `source` and `target` are `{ mission, event }`
+    #[wasm_bindgen(js_name = links)]
+    pub fn links_js(&self) -> Result<JsValue, JsValue> {
+        to_js(&self.links)
+    }
+
+    /// Get the execution windows of one mission in absolute time as an object mapping events to `[earliest, latest]`, taking links to other missions into account. Errs if there's no such mission or the missions are inconsistent together
+    #[wasm_bindgen(js_name = windows)]
+    pub fn windows_js(&self, name: &str) -> Result<JsValue, JsValue> {
+        let windows = self
+            .mission_windows(name)
+            .map_err(|e| JsValue::from_str(&e))?;
+        to_js(&windows)
+    }
+
+    /// A copy of a mission's Schedule. Changes to the copy don't affect the registry
+    #[wasm_bindgen(js_name = get)]
+    pub fn get_js(&self, name: &str) -> Option<Schedule> {
+        self.missions.get(name).cloned()
+    }
+
+    /// Get the name of every mission in alphabetical order
+    #[wasm_bindgen(js_name = names)]
+    pub fn names_js(&self) -> Result<JsValue, JsValue> {
+        to_js(&self.names())
+    }
+
+    /// Move a mission to start at another absolute time. Errs if there's no such mission
+    #[wasm_bindgen(js_name = setEpoch)]
+    pub fn set_epoch(&mut self, name: &str, epoch: f64) -> Result<(), JsValue> {
+        match self.epochs.get_mut(name) {
+            Some(e) => {
+                *e = epoch;
+                Ok(())
+            }
+            None => Err(JsValue::from_str(&format!("no mission named \"{}\"", name))),
+        }
+    }
+
+    /// Commit an event in one mission, like `Schedule.commitEvent`. The time is relative to the mission's root. Errs if there's no such mission
+    #[wasm_bindgen(js_name = commitEvent)]
+    pub fn commit_event(&mut self, name: &str, event: EventID, time: f64) -> Result<(), JsValue> {
+        self.mission_mut(name)
+            .map_err(|e| JsValue::from_str(&e))?
+            .commit_event(event, time)
+    }
+
+    /// Get the execution windows of one mission, like `Schedule.timeWindows`. Errs if there's no such mission
+    #[wasm_bindgen(js_name = timeWindows)]
+    pub fn time_windows_js(&mut self, name: &str) -> Result<JsValue, JsValue> {
+        let windows = self
+            .mission_mut(name)
+            .map_err(|e| JsValue::from_str(&e))?
+            .time_windows()?;
+        to_js(&windows)
+    }
+
+    /// Find Episodes in different missions that could use a shared resource at the same time. `uses` maps mission names to the `[start, end]` events of their Episodes that use the resource, eg. `{ "eva-1": [[1, 2]], "eva-2": [[3, 4]] }`. Returns an array of `{ first, second, overlap, certain }`, where `first` and `second` are `{ mission, episode }`, `overlap` is when in absolute time both could be using it, and `certain` is whether they overlap no matter how the missions are executed. Errs if a mission or event doesn't exist or a mission doesn't compile
+    #[wasm_bindgen(js_name = resourceConflicts)]
+    pub fn resource_conflicts_js(&self, uses: JsValue) -> Result<JsValue, JsValue> {
+        let uses: BTreeMap<String, Vec<(EventID, EventID)>> = from_js(&uses)?;
+        let uses = uses
+            .into_iter()
+            .map(|(mission, episodes)| {
+                let episodes = episodes.into_iter().map(|(s, e)| Episode(s, e)).collect();
+                (mission, episodes)
+            })
+            .collect();
+        let conflicts = self
+            .resource_conflicts(&uses)
+            .map_err(|e| JsValue::from_str(&e))?;
+        to_js(&conflicts)
+    }
+}
+
+impl Registry {
+    /// Add a mission whose root happens at `epoch`, replacing any mission with the same name
+    pub fn insert(&mut self, name: &str, schedule: Schedule, epoch: f64) {
+        self.missions.insert(name.to_string(), schedule);
+        self.epochs.insert(name.to_string(), epoch);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Schedule> {
+        self.missions.get(name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Schedule> {
+        self.missions.get_mut(name)
+    }
+
+    /// Every mission name in alphabetical order
+    pub fn names(&self) -> Vec<String> {
+        self.missions.keys().cloned().collect()
+    }
+
+    /// The absolute time a mission's root happens at
+    pub fn epoch(&self, name: &str) -> Option<f64> {
+        self.epochs.get(name).copied()
+    }
+
+    /// Constrain an event in one mission relative to an event in another. Linking the same events again replaces the interval. Errs if a mission or event doesn't exist
+    pub fn link(
+        &mut self,
+        source: MissionEvent,
+        target: MissionEvent,
+        interval: Interval,
+    ) -> Result<(), String> {
+        for end in [&source, &target].iter() {
+            let schedule = self
+                .missions
+                .get(&end.mission)
+                .ok_or_else(|| format!("no mission named \"{}\"", end.mission))?;
+            if !schedule.contains_event(end.event) {
+                return Err(format!(
+                    "event {} is not in mission \"{}\"",
+                    end.event, end.mission
+                ));
+            }
+        }
+
+        self.links
+            .retain(|l| (&l.source, &l.target) != (&source, &target));
+        self.links.push(Link {
+            source,
+            target,
+            interval,
+        });
+        Ok(())
+    }
+
+    /// Every link between missions
+    pub fn links(&self) -> &[Link] {
+        &self.links
+    }
+
+    /// The execution windows of one mission in absolute time, taking links into account. Errs if there's no such mission or the missions are inconsistent together
+    pub fn mission_windows(&self, name: &str) -> Result<BTreeMap<EventID, Interval>, String> {
+        if !self.missions.contains_key(name) {
+            return Err(format!("no mission named \"{}\"", name));
+        }
+        Ok(self.windows()?.remove(name).unwrap_or_default())
+    }
+
+    /// The execution windows of every mission in absolute time. Without links, each mission's windows are relative to its root, at t=0 unless it's been committed, shifted by its epoch. With links, every mission is compiled together. Errs if a mission doesn't compile or the missions are inconsistent together
+    pub fn windows(&self) -> Result<BTreeMap<String, BTreeMap<EventID, Interval>>, String> {
+        if self.links.is_empty() {
+            self.unlinked_windows()
+        } else {
+            self.linked_windows()
+        }
+    }
+
+    fn unlinked_windows(&self) -> Result<BTreeMap<String, BTreeMap<EventID, Interval>>, String> {
+        let mut windows = BTreeMap::new();
+        for (name, schedule) in self.missions.iter() {
+            let epoch = Interval::new(self.epochs[name], self.epochs[name]);
+            let (_, _, mission) = schedule
+                .windows_from_root()
+                .map_err(|e| format!("mission \"{}\": {}", name, e))?;
+            let mission = mission.into_iter().map(|(e, w)| (e, w + epoch)).collect();
+            windows.insert(name.clone(), mission);
+        }
+        Ok(windows)
+    }
+
+    /// Compile every mission and link as one network. Each mission is pinned to its own windows from an event at absolute t=0, which carries over its epoch and commitments
+    fn linked_windows(&self) -> Result<BTreeMap<String, BTreeMap<EventID, Interval>>, String> {
+        let mut union = Schedule::new();
+        let zero = union.create_event();
+        let mut ids: BTreeMap<MissionEvent, EventID> = BTreeMap::new();
+
+        for (name, mission) in self.unlinked_windows()? {
+            let schedule = &self.missions[&name];
+            for (event, window) in mission.iter() {
+                let id = union.create_event();
+                ids.insert(MissionEvent::new(&name, *event), id);
+                if window.lower() > -f64::MAX || window.upper() < f64::MAX {
+                    union.update_interval(zero, id, vec![window.lower(), window.upper()]);
+                }
+            }
+            for c in schedule.constraints() {
+                union.update_interval(
+                    ids[&MissionEvent::new(&name, c.source)],
+                    ids[&MissionEvent::new(&name, c.target)],
+                    vec![c.interval.lower(), c.interval.upper()],
+                );
+            }
+        }
+        for l in self.links.iter() {
+            union.update_interval(
+                ids[&l.source],
+                ids[&l.target],
+                vec![l.interval.lower(), l.interval.upper()],
+            );
+        }
+
+        if let Some(cycle) = union.negative_cycle() {
+            let events: Vec<String> = cycle
+                .iter()
+                .filter_map(|id| ids.iter().find(|(_, i)| *i == id))
+                .map(|(e, _)| format!("{} {}", e.mission, e.event))
+                .collect();
+            return Err(format!(
+                "the missions are inconsistent together. No times satisfy the constraints between {}",
+                events.join(", ")
+            ));
+        }
+
+        let absolute = union.windows_from(zero)?;
+        let mut windows: BTreeMap<String, BTreeMap<EventID, Interval>> = BTreeMap::new();
+        for (e, id) in ids.into_iter() {
+            windows
+                .entry(e.mission)
+                .or_default()
+                .insert(e.event, absolute[&id]);
+        }
+        Ok(windows)
+    }
+
+    fn mission_mut(&mut self, name: &str) -> Result<&mut Schedule, String> {
+        self.missions
+            .get_mut(name)
+            .ok_or_else(|| format!("no mission named \"{}\"", name))
+    }
+
+    /// Find Episodes in different missions that could use a shared resource at the same time, using the absolute windows from `windows`. Errs if a mission or event doesn't exist or the missions don't compile
+    pub fn resource_conflicts(
+        &self,
+        uses: &BTreeMap<String, Vec<Episode>>,
+    ) -> Result<Vec<ResourceConflict>, String> {
+        let all = self.windows()?;
+        // when each Episode could start and end, in absolute time
+        let mut spans = vec![];
+        for (mission, episodes) in uses.iter() {
+            let windows = all
+                .get(mission)
+                .ok_or_else(|| format!("no mission named \"{}\"", mission))?;
+            let window = |event: EventID| {
+                windows
+                    .get(&event)
+                    .copied()
+                    .ok_or_else(|| format!("event {} is not in mission \"{}\"", event, mission))
+            };
+            for episode in episodes.iter() {
+                spans.push((
+                    mission,
+                    episode,
+                    window(episode.start())?,
+                    window(episode.end())?,
+                ));
+            }
+        }
+
+        let mut conflicts = vec![];
+        for (i, (mission_a, a, a_start, a_end)) in spans.iter().enumerate() {
+            for (mission_b, b, b_start, b_end) in spans.iter().skip(i + 1) {
+                if mission_a == mission_b {
+                    continue;
+                }
+
+                // both could be in progress from the later earliest start to the earlier latest end
+                let overlap = Interval::new(
+                    a_start.lower().max(b_start.lower()),
+                    a_end.upper().min(b_end.upper()),
+                );
+                if overlap.lower() >= overlap.upper() {
+                    continue;
+                }
+                // and both are definitely in progress from the later latest start to the earlier earliest end
+                let certain =
+                    a_start.upper().max(b_start.upper()) < a_end.lower().min(b_end.lower());
+                conflicts.push(ResourceConflict {
+                    first: Usage {
+                        mission: mission_a.to_string(),
+                        episode: (a.start(), a.end()),
+                    },
+                    second: Usage {
+                        mission: mission_b.to_string(),
+                        episode: (b.start(), b.end()),
+                    },
+                    overlap,
+                    certain,
+                });
+            }
+        }
+        Ok(conflicts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resource_conflicts() {
+        let mut eva1 = Schedule::new();
+        let egress1 = eva1.add_episode(Some(vec![40., 50.]));
+        let mut eva2 = Schedule::new();
+        let egress2 = eva2.add_episode(Some(vec![40., 50.]));
+
+        let mut registry = Registry::new();
+        registry.insert("eva-1", eva1, 0.);
+        registry.insert("eva-2", eva2, 30.);
+        let mut airlock = BTreeMap::new();
+        airlock.insert("eva-1".to_string(), vec![egress1]);
+        airlock.insert("eva-2".to_string(), vec![egress2]);
+
+        let conflicts = registry.resource_conflicts(&airlock).unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].overlap, Interval::new(30., 50.));
+        assert!(conflicts[0].certain);
+
+        // eva-2 starts after eva-1 is done at the latest
+        registry.set_epoch("eva-2", 50.).unwrap();
+        assert!(registry.resource_conflicts(&airlock).unwrap().is_empty());
+
+        airlock.insert("eva-3".to_string(), vec![]);
+        assert!(registry.resource_conflicts(&airlock).is_err());
+        assert_eq!(registry.names(), vec!["eva-1", "eva-2"]);
+    }
+
+    #[test]
+    fn test_links() {
+        let mut eva1 = Schedule::new();
+        let ingress = eva1.add_episode(Some(vec![40., 50.]));
+        let mut eva2 = Schedule::new();
+        let prep = eva2.add_episode(Some(vec![10., 20.]));
+
+        let mut registry = Registry::new();
+        registry.insert("eva-1", eva1, 0.);
+        registry.insert("eva-2", eva2, 100.);
+        assert_eq!(
+            registry.mission_windows("eva-1").unwrap()[&ingress.end()],
+            Interval::new(40., 50.)
+        );
+
+        // eva-2 prep can't start until 60 after eva-1 ingress
+        let from = MissionEvent::new("eva-1", ingress.end());
+        let to = MissionEvent::new("eva-2", prep.start());
+        registry
+            .link(from.clone(), to.clone(), Interval::new(60., f64::MAX))
+            .unwrap();
+        assert_eq!(
+            registry.mission_windows("eva-1").unwrap()[&ingress.end()],
+            Interval::new(40., 40.)
+        );
+        assert_eq!(
+            registry.mission_windows("eva-2").unwrap()[&prep.end()],
+            Interval::new(110., 120.)
+        );
+
+        registry
+            .link(from.clone(), to, Interval::new(70., f64::MAX))
+            .unwrap();
+        assert_eq!(registry.links().len(), 1);
+        assert!(registry
+            .windows()
+            .unwrap_err()
+            .starts_with("the missions are inconsistent together"));
+
+        assert!(registry
+            .link(from, MissionEvent::new("eva-2", 99), Interval::new(0., 0.))
+            .is_err());
+        registry.remove("eva-2");
+        assert!(registry.links().is_empty());
+    }
+}
@@ -13,14 +13,30 @@
 //!
 //! [1] Ono, M., Williams, B. C., & Blackmore, L. (2013). Probabilistic planning for continuous dynamic systems under bounded risk. Journal of Artificial Intelligence Research, 46, 511–577. https://doi.org/10.1613/jair.3893
 
+use petgraph::algo::tarjan_scc;
 use petgraph::graphmap::DiGraphMap;
 use petgraph::Direction::{Incoming, Outgoing};
-use std::collections::BTreeMap;
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::ops::Deref;
+use std::sync::OnceLock;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsValue;
 
-use super::algorithms::floyd_warshall;
-use super::interval::Interval;
+use super::algorithms::{
+    bellman_ford, floyd_warshall, floyd_warshall_cancellable, johnson_cancellable, negative_cycle,
+    tighten_edge, Abort, CancelToken, Progress,
+};
+use super::dense::{
+    floyd_warshall_blocked, floyd_warshall_dense, floyd_warshall_traced, DenseMatrix, DistanceRows,
+    Trace, BLOCK_SIZE,
+};
+use super::interval::{display_format, scale_bound, Interval};
+use super::journal::{self, Merge, Mutation};
+use super::probability::{Distribution, Rng};
+use super::{from_js, to_js};
 
 /// An ID representing an event in the Schedule
 pub type EventID = i32;
@@ -66,6 +82,7 @@ impl Episode {
 /// schedule.add_constraint(Episode1.end(), Episode2.start(), None);
 ///
 /// // find the [lower, upper] interval between the start of the Schedule and the start of the second Episode
+/// schedule.compile().unwrap();
 /// let root = schedule.root().unwrap();
 /// let result = schedule.interval(root, Episode2.start()).unwrap();
 ///
@@ -73,18 +90,914 @@ impl Episode {
 /// assert_eq!(result, Interval::new(6., 17.));
 /// ```
 #[wasm_bindgen]
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct Schedule {
     /// the STN as Schedulened by the user
     stn: DiGraphMap<EventID, f64>,
     // STN in dispatchable form after APSP
-    dispatchable: DiGraphMap<EventID, f64>,
+    dispatchable: Dispatchable,
+    /// The dispatchable graph from the compile before the last one, see `compile_diff`
+    previous_dispatchable: Dispatchable,
+    /// Whether or not compiles keep the distance matrix and only build the dispatchable graph when something needs it
+    lazy_dispatchable: bool,
+    /// How many relaxations compiles record, see `set_trace`. `None` doesn't trace
+    trace_limit: Option<usize>,
+    /// The relaxations recorded by the last traced compile
+    trace: Option<Trace>,
+    /// The distance graph the dispatchable graph was compiled from, to find what changed before the next compile
+    compiled_graph: DiGraphMap<EventID, f64>,
+    /// Target time for a compile in milliseconds. `None` always runs Floyd-Warshall
+    compile_budget: Option<f64>,
+    /// How the last compile went
+    stats: CompileStats,
+    /// Intervals already looked up in the dispatchable graph. Cleared on every compile
+    intervals: BTreeMap<(EventID, EventID), Interval>,
     /// Execution windows when each event can be scheduled. Referenced to a timeframe where the Schedule.root() is t=0
     execution_windows: BTreeMap<EventID, Interval>,
     /// User-provided inputs about event completion. Also referenced to a timeframe where Schedule.root() is t=0
     committments: BTreeMap<EventID, f64>,
     /// Whether or not changes have been made since the last compile
     dirty: bool,
+    /// The event this Schedule was branched at, if it was created with `branch_at`
+    branch_point: Option<EventID>,
+    /// When events would prefer to occur within their execution windows
+    preferences: BTreeMap<EventID, Preference>,
+    /// External IDs for events, eg. from a procedure database
+    event_aliases: BTreeMap<String, EventID>,
+    /// External IDs for Episodes
+    episode_aliases: BTreeMap<String, Episode>,
+    /// Arbitrary data carried with Episodes, eg. procedure references or colors
+    episode_metadata: BTreeMap<Episode, Value>,
+    /// Arbitrary data carried with constraints, keyed by (source, target) as authored
+    constraint_metadata: BTreeMap<(EventID, EventID), Value>,
+    /// Where constraints came from, keyed by (source, target) as authored
+    constraint_provenance: BTreeMap<(EventID, EventID), Provenance>,
+    /// Events that could be the root as of the last compile
+    roots: Vec<EventID>,
+    /// The origin event created by `ensure_root`, if any
+    virtual_root: Option<EventID>,
+    /// Whether or not compiling a draft calls `ensure_root` when there isn't exactly one root
+    auto_root: bool,
+    /// How infinite bounds are written in serialized outputs
+    output_bounds: OutputBounds,
+    /// Decimal places serialized outputs are rounded to. Never applied to internal math
+    output_decimals: Option<u32>,
+    /// How far commitments are propagated by `commit_event`
+    propagation: Propagation,
+    /// Whether or not commitments earlier than a committed event that must precede them are rejected
+    strict_commits: bool,
+    /// Every mutation made through the public API, in order
+    journal: Vec<Mutation>,
+    /// Episodes whose durations are chosen by nature instead of the executor
+    contingent: BTreeSet<Episode>,
+    /// Actual durations of contingent Episodes that have finished
+    observations: BTreeMap<Episode, f64>,
+    /// Probability distributions over uncertain Episode durations
+    distributions: BTreeMap<Episode, Distribution>,
+    /// Every Episode added to the Schedule
+    episodes: BTreeSet<Episode>,
+    /// Pairs of Episodes that can't overlap, in the order they were added
+    mutexes: Vec<(Episode, Episode)>,
+    /// Execution windows after each commit, oldest first
+    window_history: VecDeque<WindowSnapshot>,
+    /// How many snapshots `window_history` keeps. Unlimited if `None`
+    history_limit: Option<usize>,
+    /// The largest plan `compile` will take on
+    limits: Limits,
+    /// Intervals for Episodes and constraints added without one
+    defaults: Defaults,
+    /// Episodes whose durations come from a named parameter when compiling
+    duration_parameters: BTreeMap<Episode, String>,
+    /// Intervals bound to parameter names
+    parameters: BTreeMap<String, Interval>,
+    /// Why the last compile failed, if it did and nothing has changed since. Queries answer from the last good compile in the meantime
+    compile_error: Option<String>,
+    /// Where the Schedule is in its lifecycle
+    phase: Phase,
+    /// The Schedule as it was when the open transaction began, to roll back to
+    transaction: Option<Box<Schedule>>,
+}
+
+/// The dispatchable graph from a compile. A lazy one only keeps the distance matrix, and the graph is built from it the first time it's dereferenced, eg. when it's exported. Reading distances with `distance` never builds the graph
+#[derive(Clone, Debug, Default)]
+struct Dispatchable {
+    matrix: Option<DenseMatrix>,
+    graph: OnceLock<DiGraphMap<EventID, f64>>,
+}
+
+impl Dispatchable {
+    fn from_graph(graph: DiGraphMap<EventID, f64>) -> Dispatchable {
+        Dispatchable {
+            matrix: None,
+            graph: OnceLock::from(graph),
+        }
+    }
+
+    fn lazy(matrix: DenseMatrix) -> Dispatchable {
+        Dispatchable {
+            matrix: Some(matrix),
+            graph: OnceLock::new(),
+        }
+    }
+
+    /// The shortest distance from one event to another. `None` if there's no edge
+    fn distance(&self, source: EventID, target: EventID) -> Option<f64> {
+        match (&self.matrix, self.graph.get()) {
+            (Some(matrix), None) => matrix.distance(source, target),
+            _ => self.edge_weight(source, target).copied(),
+        }
+    }
+
+    /// Every event, in order of event ID
+    fn events(&self) -> Vec<EventID> {
+        match (&self.matrix, self.graph.get()) {
+            (Some(matrix), None) => matrix.nodes().to_vec(),
+            _ => {
+                let mut events: Vec<EventID> = self.nodes().collect();
+                events.sort_unstable();
+                events
+            }
+        }
+    }
+
+    fn node_count(&self) -> usize {
+        match (&self.matrix, self.graph.get()) {
+            (Some(matrix), None) => matrix.len(),
+            _ => self.deref().node_count(),
+        }
+    }
+
+    fn edge_count(&self) -> usize {
+        match (&self.matrix, self.graph.get()) {
+            (Some(matrix), None) => matrix.len() * matrix.len(),
+            _ => self.deref().edge_count(),
+        }
+    }
+
+    /// Every distance keyed by (source, target)
+    fn to_map(&self) -> BTreeMap<(EventID, EventID), f64> {
+        match (&self.matrix, self.graph.get()) {
+            (Some(matrix), None) => matrix.to_map(),
+            _ => self.all_edges().map(|(s, t, w)| ((s, t), *w)).collect(),
+        }
+    }
+}
+
+impl Deref for Dispatchable {
+    type Target = DiGraphMap<EventID, f64>;
+
+    fn deref(&self) -> &DiGraphMap<EventID, f64> {
+        self.graph.get_or_init(|| {
+            let distances = self.matrix.as_ref().map(DenseMatrix::to_map);
+            dispatchable_graph(&distances.unwrap_or_default())
+        })
+    }
+}
+
+/// How infinite bounds (±f64::MAX) are written when a Schedule serializes its outputs, eg. `timeWindows`. Some JSON parsers lose precision on f64::MAX or turn it into null
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OutputBounds {
+    /// Write ±f64::MAX as-is. The default
+    #[default]
+    Raw,
+    /// Write `"unbounded"` and `"-unbounded"` instead of numbers
+    Unbounded,
+    /// Write ±horizon instead. Finite numbers, including times past the horizon, are written as-is
+    Horizon(f64),
+}
+
+impl OutputBounds {
+    /// Rewrite every infinite number in a JSON value. Finite numbers, like metadata and statistics, are left alone
+    pub fn apply(&self, value: &mut Value) {
+        match value {
+            Value::Number(n) => {
+                let v = match n.as_f64() {
+                    Some(v) if v.abs() >= f64::MAX => v,
+                    _ => return,
+                };
+                match self {
+                    OutputBounds::Raw => (),
+                    OutputBounds::Unbounded => {
+                        let marker = if v > 0. { "unbounded" } else { "-unbounded" };
+                        *value = Value::from(marker);
+                    }
+                    OutputBounds::Horizon(h) => *value = Value::from(v.signum() * h.abs()),
+                }
+            }
+            Value::Array(values) => values.iter_mut().for_each(|v| self.apply(v)),
+            Value::Object(map) => map.values_mut().for_each(|v| self.apply(v)),
+            _ => (),
+        }
+    }
+}
+
+/// Round every finite, fractional number in a JSON value to some decimal places, eg. so 12.300000000000001 is written as 12.3. Integers like event IDs and infinite bounds are left alone
+pub fn round_output(value: &mut Value, decimals: u32) {
+    match value {
+        Value::Number(n) if n.is_f64() => {
+            if let Some(v) = n.as_f64() {
+                *value = Value::from(round_to(v, decimals));
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(|v| round_output(v, decimals)),
+        Value::Object(map) => map.values_mut().for_each(|v| round_output(v, decimals)),
+        _ => (),
+    }
+}
+
+/// Round a number to some decimal places unless it's unbounded
+fn round_to(value: f64, decimals: u32) -> f64 {
+    if value.abs() >= f64::MAX {
+        return value;
+    }
+    let scale = 10f64.powi(decimals as i32);
+    let rounded = (value * scale).round() / scale;
+    // scaling can overflow for huge numbers
+    if rounded.is_finite() {
+        rounded
+    } else {
+        value
+    }
+}
+
+/// Where a Schedule is in its lifecycle. Plan-time changes like adding constraints are only accepted in a Draft. Once the plan is frozen they have to go through `Schedule::amend`, which records why in the journal, so a plan can't be edited by accident during a run
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Phase {
+    /// Being authored. Accepts any change. The default, so Schedules that are never frozen behave as they always have
+    #[default]
+    Draft,
+    /// Reviewed and ready to run. Plan-time changes must be amendments
+    Frozen,
+    /// Events are being committed. Entered on the first commitment after freezing
+    Executing,
+    /// Finished. Accepts no more changes or commitments
+    Complete,
+}
+
+impl std::fmt::Display for Phase {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            Phase::Draft => "draft",
+            Phase::Frozen => "frozen",
+            Phase::Executing => "executing",
+            Phase::Complete => "complete",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The result of looking for the first event in a Schedule
+#[derive(Clone, Debug, PartialEq)]
+pub enum Root {
+    /// No event happens before every other event, eg. the Schedule doesn't compile
+    Missing,
+    /// Exactly one event happens before every other event
+    Unique(EventID),
+    /// Multiple events could be first. They are all simultaneous with each other. Sorted by event ID
+    Ambiguous(Vec<EventID>),
+}
+
+/// How far a commitment is propagated to the execution windows of other events. Limiting propagation keeps commits fast on enormous plans where only the near future matters. `Schedule::reconcile` catches every window up on demand
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Propagation {
+    /// Update every event. The default
+    #[default]
+    Full,
+    /// Only update events that can happen no later than this much time after the committed event
+    Horizon(f64),
+    /// Only update events within this many authored constraints of the committed event
+    Hops(usize),
+}
+
+/// A hint for choosing a concrete time for an event within its execution window
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Preference {
+    /// As early as possible
+    Earliest,
+    /// As late as possible
+    Latest,
+    /// Middle of the window. The default
+    #[default]
+    Midpoint,
+    /// As close as possible to a specific time
+    Target(f64),
+}
+
+impl Preference {
+    /// Pick a time within a window. Falls back to the finite side of a half-bounded window. `None` if the window is unbounded on both sides
+    pub fn choose(&self, window: Interval) -> Option<f64> {
+        let lower = window.lower();
+        let upper = window.upper();
+
+        match (lower > -f64::MAX, upper < f64::MAX) {
+            (false, false) => None,
+            (true, false) => match self {
+                Preference::Target(t) => Some(t.max(lower)),
+                _ => Some(lower),
+            },
+            (false, true) => match self {
+                Preference::Target(t) => Some(t.min(upper)),
+                _ => Some(upper),
+            },
+            (true, true) => match self {
+                Preference::Earliest => Some(lower),
+                Preference::Latest => Some(upper),
+                Preference::Midpoint => Some((lower + upper) / 2.),
+                Preference::Target(t) => Some(t.max(lower).min(upper)),
+            },
+        }
+    }
+}
+
+/// Side-by-side summary of two Schedules, eg. a Schedule and a branch created with `Schedule::branch_at`
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BranchComparison {
+    /// Whether or not the first Schedule is consistent
+    pub feasible: bool,
+    /// Whether or not the other Schedule is consistent
+    pub other_feasible: bool,
+    /// [earliest, latest] end of the first Schedule
+    pub makespan: Interval,
+    /// [earliest, latest] end of the other Schedule
+    pub other_makespan: Interval,
+}
+
+/// A constraint authored between two events, including the duration of an Episode
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Constraint {
+    pub source: EventID,
+    pub target: EventID,
+    /// [lower, upper] time from the source to the target
+    pub interval: Interval,
+    /// Data attached with `Schedule::set_constraint_metadata`, or with `Schedule::set_episode_metadata` for an Episode's duration
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Value>,
+    /// Where the constraint came from, see `Schedule::set_constraint_provenance`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+}
+
+/// The authored and implied timing between two events, so editors can tell a bound that was written down from one that follows from other constraints
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ConstraintBetween {
+    pub source: EventID,
+    pub target: EventID,
+    /// The authored constraint, from source to target even if it was authored the other way. `None` if the events aren't constrained directly
+    pub authored: Option<Constraint>,
+    /// How many authored bounds there are between the events: 0, 1 if the constraint is only bounded on one side, or 2
+    pub bounds: usize,
+    /// Whether or not the authored constraint is the duration of an Episode
+    pub episode: bool,
+    /// The interval from source to target implied by every constraint in the compiled Schedule. `None` if the events aren't connected
+    pub implied: Option<Interval>,
+}
+
+/// Proof that a plan was compiled and found consistent, so downstream systems can check which engine version checked which inputs. Hashes are hex SHA-256
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct FeasibilityCertificate {
+    pub engine: String,
+    pub version: String,
+    /// Hash of the plan, see `Schedule::hash`, and the commitments
+    pub inputs: String,
+    /// Hash of the compiled distance matrix
+    pub distances: String,
+    pub events: usize,
+    pub edges: usize,
+    /// Hash of every other field, so an edited certificate doesn't verify
+    pub stamp: String,
+}
+
+impl FeasibilityCertificate {
+    fn digest(&self) -> String {
+        let mut hasher = Sha256::new();
+        for field in [&self.engine, &self.version, &self.inputs, &self.distances].iter() {
+            hasher.update(field.as_bytes());
+            hasher.update([0]);
+        }
+        hasher.update((self.events as u64).to_le_bytes());
+        hasher.update((self.edges as u64).to_le_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Whether or not the stamp matches the rest of the certificate
+    pub fn stamp_matches(&self) -> bool {
+        self.digest() == self.stamp
+    }
+}
+
+/// A concrete schedule on the tradeoff between finishing early and leaving room for things to slip
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ParetoPoint {
+    /// Time from the root to the last event
+    pub makespan: f64,
+    /// The least room any constraint has before one of its bounds is broken. Rigid constraints and contingent Episodes don't count
+    pub slack: f64,
+    /// The time of every event connected to the root
+    pub times: BTreeMap<EventID, f64>,
+}
+
+/// The source document a constraint was written from, eg. a line of an EVA procedure, so reviewers can be pointed back at it when the constraint causes trouble
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Provenance {
+    /// Document ID, eg. `"EVA-2B rev C"`
+    pub document: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+}
+
+impl std::fmt::Display for Provenance {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.document)?;
+        if let Some(line) = self.line {
+            write!(f, " line {}", line)?;
+        }
+        if let Some(author) = &self.author {
+            write!(f, " by {}", author)?;
+        }
+        Ok(())
+    }
+}
+
+/// Structural statistics about a Schedule, used to flag convoluted timelines
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct Complexity {
+    /// Number of events in the longest chain of events that must happen in order
+    pub depth: usize,
+    /// Largest number of events that can happen in parallel, ie. in the same layer of the chains
+    pub width: usize,
+    /// Number of constraints that fork into or join parallel chains of events
+    pub syncs: usize,
+    /// Number of constraints that are implied by other constraints and could be removed
+    pub redundant: usize,
+}
+
+/// An uncommitted event that must happen before another event can be dispatched, see `Schedule::blocking`
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Blocker {
+    pub event: EventID,
+    /// What's left of the blocker's execution window
+    pub window: Interval,
+    /// [lower, upper] time from the blocker to the blocked event
+    pub interval: Interval,
+}
+
+/// Everything needed to draw the execution window of an event. Times are referenced to a timeframe where the Schedule root is t=0
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct TimeWindow {
+    pub event: EventID,
+    /// Earliest time the event can occur
+    pub earliest: f64,
+    /// Latest time the event can occur
+    pub latest: f64,
+    /// The time the event would nominally occur according to its `Preference`. `None` if the window is unbounded on both sides
+    pub nominal: Option<f64>,
+    /// Whether or not the window has converged to a single time
+    pub converged: bool,
+}
+
+/// The outcome of replaying a log of commitments with `Schedule::commit_events`
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct CommitReport {
+    /// Events that were committed, in log order
+    pub applied: Vec<EventID>,
+    /// Entries that were skipped and why
+    pub rejected: Vec<RejectedCommit>,
+    /// Events whose execution windows are empty after the commitments, see `Schedule::infeasible_events`
+    pub infeasible: Vec<EventID>,
+}
+
+/// The error `Schedule::commit_event` raises in JS when a commitment leaves events with nowhere to go
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct InfeasibleCommit {
+    pub message: String,
+    pub event: EventID,
+    pub time: f64,
+    /// Events whose execution windows are now empty
+    pub infeasible: Vec<EventID>,
+}
+
+/// An entry in a log of commitments that could not be applied
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RejectedCommit {
+    pub event: EventID,
+    pub time: f64,
+    pub reason: String,
+}
+
+/// What `Schedule::repair` does about a contingent Episode that ran outside its declared duration
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RepairPolicy {
+    /// Extend the contingent Episode's duration to include the observed duration, then observe it
+    Extend,
+    /// Report what `Extend` would do without changing the Schedule
+    DryRun,
+}
+
+/// How an event's execution window changed during a repair
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct WindowChange {
+    pub event: EventID,
+    pub before: Interval,
+    pub after: Interval,
+}
+
+/// How the implied interval between two events changed from one compile to the next, see `Schedule::compile_diff`
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct BoundChange {
+    pub source: EventID,
+    pub target: EventID,
+    pub before: Interval,
+    pub after: Interval,
+    /// How far the lower bound rose. Negative if it fell
+    pub lower: f64,
+    /// How far the upper bound fell. Negative if it rose
+    pub upper: f64,
+}
+
+/// The consequences of repairing a contingent overrun
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RepairReport {
+    /// The declared duration before the repair
+    pub declared: Interval,
+    /// The duration of the contingent Episode after the repair
+    pub extended: Interval,
+    /// Whether or not the repaired Schedule is still consistent
+    pub consistent: bool,
+    /// Whether or not the repaired Schedule is still strongly controllable
+    pub strongly_controllable: bool,
+    /// Uncommitted events whose execution windows changed
+    pub changes: Vec<WindowChange>,
+    /// Events left without any valid time
+    pub infeasible: Vec<EventID>,
+}
+
+/// The consequences of keeping events out of blackout windows with `Schedule::apply_blackouts`
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct BlackoutReport {
+    /// Events whose execution windows were clipped to avoid a blackout, directly or through their constraints
+    pub clipped: Vec<WindowChange>,
+    /// Filtered events with a blackout strictly inside their window. A temporal network can't forbid the middle of a window, so these have to be kept out of the blackout when they're scheduled
+    pub split: Vec<EventID>,
+    /// Events left without any valid time
+    pub infeasible: Vec<EventID>,
+}
+
+/// The share of the total acceptable risk given to one uncertain Episode
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RiskAssignment {
+    pub start: EventID,
+    pub end: EventID,
+    /// Probability that the duration falls outside of `bounds`
+    pub risk: f64,
+    /// Deterministic [lower, upper] duration to use in the Schedule
+    pub bounds: Interval,
+}
+
+/// The result of `Schedule::allocate_risk`
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RiskAllocation {
+    /// The acceptable probability that any duration falls outside of its bounds
+    pub total_risk: f64,
+    pub assignments: Vec<RiskAssignment>,
+    /// Whether or not the Schedule is strongly controllable with these bounds
+    pub feasible: bool,
+}
+
+/// The expected time of an event and how uncertain it is. Times are referenced to a timeframe where the Schedule root is t=0
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct ExpectedTime {
+    pub event: EventID,
+    pub mean: f64,
+    pub variance: f64,
+}
+
+/// Summary of a Monte Carlo simulation of a Schedule
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Simulation {
+    pub runs: usize,
+    /// Fraction of runs where every authored constraint was met
+    pub success_rate: f64,
+    pub mean_makespan: f64,
+    pub p05_makespan: f64,
+    pub p50_makespan: f64,
+    pub p95_makespan: f64,
+}
+
+/// An authored constraint along a shortest path through the Schedule
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Binding {
+    #[serde(flatten)]
+    pub constraint: Constraint,
+    /// `"upper"` if the path follows the constraint from its source to its target, or `"lower"` if it goes backwards
+    pub bound: &'static str,
+}
+
+/// Why the interval between two events is what it is. See `Schedule::explain_interval`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Explanation {
+    pub source: EventID,
+    pub target: EventID,
+    /// The interval implied by the authored constraints
+    pub interval: Interval,
+    /// Constraints along the shortest path from the source to the target, which set the upper bound
+    pub upper: Vec<Binding>,
+    /// Constraints along the shortest path from the target back to the source, which set the lower bound
+    pub lower: Vec<Binding>,
+}
+
+/// A problem found by `Schedule::lint`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Finding {
+    /// `"error"` if the Schedule can't be executed as written, otherwise `"warning"`
+    pub severity: &'static str,
+    pub message: String,
+    /// Events involved in the problem
+    pub events: Vec<EventID>,
+}
+
+/// A loop in the authored constraints found by `Schedule::diagnose_cycles`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CycleDiagnostic {
+    /// `"rigid"` if the loop only forces its events to happen at the same time, or `"contradictory"` if no times satisfy it
+    pub kind: &'static str,
+    /// Events around the loop in order, starting with the lowest ID
+    pub events: Vec<EventID>,
+    /// The authored constraints that form the loop, in the same order as the events
+    pub constraints: Vec<Binding>,
+    /// How much time the loop is short by. Always 0 for rigid loops
+    pub excess: f64,
+    /// Edits to the input that would break the loop
+    pub suggestions: Vec<String>,
+}
+
+/// One bucket of a zoomed-out timeline. Occupancy is the fraction of the bucket, from 0 to 1, that each group spends inside its Episodes
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Bucket {
+    pub start: f64,
+    pub end: f64,
+    pub occupancy: BTreeMap<String, f64>,
+}
+
+/// A timeline aggregated into fixed-width buckets, eg. 15-minute buckets for a mission overview
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Zoom {
+    pub resolution: f64,
+    pub buckets: Vec<Bucket>,
+}
+
+/// Two Episodes in the same group, eg. steps by one actor, that could overlap. Episodes are `(start, end)` events
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Overlap {
+    pub group: String,
+    pub first: (EventID, EventID),
+    pub second: (EventID, EventID),
+}
+
+/// Bounds computed without compiling by `Schedule::streamed_bounds`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct StreamedBounds {
+    pub root: EventID,
+    /// The execution window of every event, in order of event ID
+    pub windows: Vec<TimeWindow>,
+    /// [lower, upper] time between each requested pair of events, in the order they were requested
+    pub intervals: Vec<Interval>,
+}
+
+/// The largest plan a Schedule will compile, to fail fast on a runaway plan generator instead of freezing in cubic all-pairs shortest paths. No limits by default
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Limits {
+    pub max_events: Option<usize>,
+    /// Edges in the distance graph, two for each constraint
+    pub max_edges: Option<usize>,
+    /// Bytes for the distance between every pair of events, 8 per pair. Also how many rows `Schedule::streamed_bounds` holds at a time
+    pub max_matrix_bytes: Option<usize>,
+}
+
+/// Plans with at least this many events are compiled over a dense matrix, which is faster for big plans
+const DENSE_MIN_EVENTS: usize = 64;
+
+/// Plans with at least this many events are compiled in tiles, which is where the dense matrix stops fitting in cache. `cargo bench --bench apsp` shows the crossover
+const BLOCKED_MIN_EVENTS: usize = 512;
+
+/// Roughly how many steps of shortest path work a compile gets through per millisecond, to turn a compile budget into steps
+const STEPS_PER_MS: f64 = 100_000.;
+
+/// What an edge of the distance graph encodes, see `LabeledEdge`
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EdgeMeaning {
+    /// The longest an Episode can take, from its start to its end
+    EpisodeUpper,
+    /// The shortest an Episode can take, negated, from its end to its start
+    EpisodeLower,
+    /// The most time a constraint allows, from its source to its target
+    ConstraintUpper,
+    /// The least time a constraint allows, negated, from its target to its source
+    ConstraintLower,
+}
+
+/// An edge of the authored distance graph with what it came from, from `Schedule::labeled_edges`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LabeledEdge {
+    pub source: EventID,
+    pub target: EventID,
+    pub weight: f64,
+    pub meaning: EdgeMeaning,
+    /// The meaning in words, eg. "negative lower bound of Episode (0, 1)"
+    pub label: String,
+}
+
+/// How a compile found the shortest distance between every pair of events, see `Schedule::set_compile_budget`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Strategy {
+    /// Floyd-Warshall over every event. The default
+    #[default]
+    FloydWarshall,
+    /// Johnson's algorithm, which is faster on sparse plans
+    Johnson,
+    /// Only the edges that tightened since the last compile, relaxed through every pair of events
+    Incremental,
+    /// Like `Incremental`, but only relaxing the pairs each tightened edge can affect
+    Bounded,
+}
+
+/// How the last compile went, see `Schedule::stats`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompileStats {
+    pub strategy: Strategy,
+    pub events: usize,
+    /// Edges in the distance graph, two for each constraint
+    pub edges: usize,
+    /// Edges that tightened since the compile before, if the compile was incremental
+    pub changed: usize,
+    /// Estimated steps of shortest path work
+    pub steps: f64,
+    /// Whether or not the estimate fit the compile budget. Always true without a budget
+    pub within_budget: bool,
+}
+
+/// Intervals used when an Episode or constraint is added without one. `None` makes the interval required, so a forgotten argument errs instead of silently making the plan rigid. Both are [0, 0] by default
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Defaults {
+    pub duration: Option<Interval>,
+    pub constraint: Option<Interval>,
+}
+
+impl Default for Defaults {
+    fn default() -> Defaults {
+        Defaults {
+            duration: Some(Interval::new(0., 0.)),
+            constraint: Some(Interval::new(0., 0.)),
+        }
+    }
+}
+
+impl Defaults {
+    /// Require every duration and constraint interval to be given explicitly
+    pub fn strict() -> Defaults {
+        Defaults {
+            duration: None,
+            constraint: None,
+        }
+    }
+}
+
+/// Use an interval if it was given or its default otherwise. Errs if it's required
+fn or_default(
+    interval: Option<Vec<f64>>,
+    default: Option<Interval>,
+    what: &str,
+) -> Result<Vec<f64>, String> {
+    match (interval, default) {
+        (Some(i), _) => Ok(i),
+        (None, Some(d)) => Ok(vec![d.lower(), d.upper()]),
+        (None, None) => Err(format!(
+            "{} is required. Pass a [lower, upper] interval",
+            what
+        )),
+    }
+}
+
+/// Every execution window right after one or more events were committed, see `Schedule::window_history`
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct WindowSnapshot {
+    /// Position in the history. Keeps counting when old snapshots are dropped, so gaps show where the history was cut
+    pub sequence: usize,
+    /// The `[event, time]` commitments that led to this snapshot
+    pub commits: Vec<(EventID, f64)>,
+    pub windows: BTreeMap<EventID, Interval>,
+}
+
+/// The execution windows that changed since a snapshot in the window history, see `Schedule::delta_since`. Windows are sent whole rather than as differences
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct WindowDelta {
+    /// The snapshot the delta starts from. `None` for a full sync, which replaces every window
+    pub since: Option<usize>,
+    /// The latest snapshot, to ask for the next delta from. `None` if nothing has been committed yet
+    pub version: Option<usize>,
+    pub changed: Vec<TimeWindow>,
+    /// Events that were removed from the Schedule
+    pub removed: Vec<EventID>,
+}
+
+/// A copy of a Schedule's execution windows on the other end of a connection, kept up to date with `WindowDelta`s, eg. for a display mirroring the server
+#[wasm_bindgen]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WindowMirror {
+    version: Option<usize>,
+    windows: BTreeMap<EventID, TimeWindow>,
+}
+
+#[wasm_bindgen]
+impl WindowMirror {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WindowMirror {
+        WindowMirror::default()
+    }
+
+    /// The snapshot the mirror is up to date with. Pass it to `deltaSince` on the Schedule
+    #[wasm_bindgen(getter)]
+    pub fn version(&self) -> Option<usize> {
+        self.version
+    }
+
+    /// Apply a delta from `deltaSince`. Errs if the delta doesn't start from the mirror's version, in which case ask for a full sync
+    #[wasm_bindgen(js_name = apply)]
+    pub fn apply_js(&mut self, delta: JsValue) -> Result<(), JsValue> {
+        let delta: WindowDelta = from_js(&delta)?;
+        self.apply(&delta).map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Get every mirrored window as an array of `{ event, earliest, latest, nominal, converged }` in order of event ID, like `timeWindows`
+    #[wasm_bindgen(js_name = timeWindows)]
+    pub fn time_windows_js(&self) -> Result<JsValue, JsValue> {
+        to_js(&self.time_windows())
+    }
+}
+
+impl WindowMirror {
+    /// Apply a delta from `Schedule::delta_since`. Errs if the delta doesn't start from the mirror's version
+    pub fn apply(&mut self, delta: &WindowDelta) -> Result<(), String> {
+        match delta.since {
+            None => self.windows.clear(),
+            Some(since) if Some(since) == self.version => {}
+            Some(since) => {
+                return Err(format!(
+                "the delta starts from snapshot {} but the mirror is at {:?}. Ask for a full sync",
+                since, self.version
+            ))
+            }
+        }
+
+        for event in delta.removed.iter() {
+            self.windows.remove(event);
+        }
+        for window in delta.changed.iter() {
+            self.windows.insert(window.event, *window);
+        }
+        self.version = delta.version;
+        Ok(())
+    }
+
+    /// Every mirrored window in order of event ID
+    pub fn time_windows(&self) -> Vec<TimeWindow> {
+        self.windows.values().copied().collect()
+    }
+}
+
+/// The outcome of `Schedule::level`
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Leveling {
+    /// Pairs that were put in order by constraining the first to end before the second starts
+    pub ordered: Vec<Overlap>,
+    /// Pairs that overlap in every feasible schedule, whichever goes first. The Schedule can't be leveled until they're fixed
+    pub conflicts: Vec<Overlap>,
+}
+
+/// A gap a new Episode could be inserted into, from `Schedule::suggest_insertion`
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Insertion {
+    /// The event the new Episode would start after
+    pub anchor: EventID,
+    /// The event it would have to end before. `None` if nothing bounds the gap
+    pub before: Option<EventID>,
+    /// When it could start, relative to the anchor
+    pub offsets: Interval,
+    /// How much room would be left with the shortest duration
+    pub slack: f64,
 }
 
 #[wasm_bindgen]
@@ -97,334 +1010,7938 @@ impl Schedule {
         }
     }
 
-    /// Get the first event in the Schedule. Found implicitly based on the current constraints
-    #[wasm_bindgen(getter)]
-    pub fn root(&mut self) -> Option<EventID> {
-        match self.compile() {
-            Ok(_) => (),
-            Err(_e) => return None,
-        };
+    /// Get the first event in the Schedule. Found implicitly based on the constraints as of the last compile, so call `compile` after changing the Schedule. If multiple events could be first, the one with the lowest ID is returned. See `findRoot` to get every candidate and whether they're stale
+    #[wasm_bindgen(getter)]
+    pub fn root(&self) -> Option<EventID> {
+        match self.find_root() {
+            Root::Missing => None,
+            Root::Unique(r) => Some(r),
+            Root::Ambiguous(candidates) => candidates.first().copied(),
+        }
+    }
+
+    /// Compile and compare the implied interval between every pair of events against the compile before, eg. to see the impact of a single edit. Returns an array of `{ source, target, before, after, lower, upper }` objects, where `lower` is how far the lower bound rose and `upper` is how far the upper bound fell, so positive numbers mean the interval tightened. Empty if the Schedule has only been compiled once. Errs if the Schedule doesn't compile
+    #[wasm_bindgen(js_name = compileDiff)]
+    pub fn compile_diff_js(&mut self) -> Result<JsValue, JsValue> {
+        let changes = self.compile_diff()?;
+        self.output(&changes)
+    }
+
+    /// Make sure the Schedule has exactly one root. If it doesn't, a virtual origin event is created and constrained `[0, ∞]` before the first events of every group of connected events. Returns the root. Errs if the Schedule doesn't compile or isn't a draft when a root has to be added
+    #[wasm_bindgen(js_name = ensureRoot)]
+    pub fn ensure_root_js(&mut self) -> Result<EventID, JsValue> {
+        self.ensure_root().map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Add a virtual origin with `ensureRoot` every time a draft compiles without exactly one root. Off by default
+    #[wasm_bindgen(js_name = setAutoRoot)]
+    pub fn set_auto_root_js(&mut self, auto_root: bool) {
+        self.set_auto_root(auto_root);
+    }
+
+    /// Look for the first event in the Schedule as of the last compile. Returns `{ kind, candidates, stale }` where `kind` is `"missing"`, `"unique"`, or `"ambiguous"`, `candidates` lists every event that could be first, and `stale` is true if the Schedule has changed since it was compiled
+    #[wasm_bindgen(js_name = findRoot)]
+    pub fn find_root_js(&self) -> Result<JsValue, JsValue> {
+        let (kind, candidates) = match self.find_root() {
+            Root::Missing => ("missing", vec![]),
+            Root::Unique(r) => ("unique", vec![r]),
+            Root::Ambiguous(candidates) => ("ambiguous", candidates),
+        };
+        to_js(&serde_json::json!({ "kind": kind, "candidates": candidates, "stale": self.dirty }))
+    }
+
+    /// List event IDs in chronological order, by the earliest time each event can happen relative to the root. Ties are broken by precedence layer (see `layers`) and then by ID. Events that aren't connected to the root come last
+    pub fn order(&mut self) -> Result<Vec<EventID>, JsValue> {
+        let layers = self.layers()?;
+        let layer_of: BTreeMap<EventID, usize> = layers
+            .iter()
+            .enumerate()
+            .flat_map(|(i, layer)| layer.iter().map(move |e| (*e, i)))
+            .collect();
+        let earliest = match self.root() {
+            Some(root) => self.earliest_times(root),
+            None => BTreeMap::new(),
+        };
+
+        let mut events: Vec<EventID> = layer_of.keys().copied().collect();
+        events.sort_by(|a, b| {
+            let time = |e: &EventID| earliest.get(e).copied().unwrap_or(f64::MAX);
+            time(a)
+                .partial_cmp(&time(b))
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(layer_of[a].cmp(&layer_of[b]))
+                .then(a.cmp(b))
+        });
+        Ok(events)
+    }
+
+    /// Group events into precedence layers, eg. for laying out a timeline. Every event is in a later layer than the events it must happen strictly after, so no two events in a layer are required to happen in a particular order. Returns an array of arrays of event IDs. Errs if the Schedule is inconsistent
+    #[wasm_bindgen(js_name = layers)]
+    pub fn layers_js(&mut self) -> Result<JsValue, JsValue> {
+        let layers = self.layers()?;
+        to_js(&layers)
+    }
+
+    /// Low-level API for creating nodes in the graph. Advanced use only. If you can't explain why you should use this over `addEpisode`, use `addEpisode` instead. Errs if the Schedule isn't a draft
+    #[wasm_bindgen(js_name = createEvent)]
+    pub fn create_event_js(&mut self) -> Result<EventID, JsValue> {
+        self.require_draft("create an event")
+            .map_err(|e| JsValue::from_str(&e))?;
+        Ok(self.create_event())
+    }
+
+    /// Build an Episode but don't add it to the graph
+    fn new_episode(&mut self) -> Episode {
+        let start_id = self.add_event(self.next_event_id());
+        let end_id = self.add_event(self.next_event_id());
+        Episode(start_id, end_id)
+    }
+
+    /// Create a new Episode and add it to this Schedule. Defaults to a [0, 0] duration unless changed with `setDefaults`. Errs if the duration isn't `[lower, upper]` or is required, or the Schedule isn't a draft
+    #[wasm_bindgen(js_name = addEpisode)]
+    pub fn add_episode_js(&mut self, duration: Option<Vec<f64>>) -> Result<Episode, JsValue> {
+        let duration = or_default(duration, self.defaults.duration, "a duration")
+            .map_err(|e| JsValue::from_str(&e))?;
+        let duration = self.plan_time_interval("add an Episode", duration)?;
+        Ok(self.add_episode(Some(duration)))
+    }
+
+    /// Add edges for the duration of an Episode
+    fn link_episode(&mut self, episode: Episode, duration: Interval) {
+        // create the Episode and add edges for its interval
+        // make it a distance graph so the lower bound is negative
+        self.stn.add_edge(episode.0, episode.1, duration.upper());
+        self.stn.add_edge(episode.1, episode.0, -duration.lower());
+        self.episodes.insert(episode);
+
+        self.mark_dirty();
+    }
+
+    /// Create a new Episode whose duration is out of the executor's control, eg. a hardware warm-up. Its end happens somewhere within `duration` after its start and can only be observed, not scheduled. Errs if the duration isn't `[lower, upper]` or is required, or the Schedule isn't a draft
+    #[wasm_bindgen(js_name = addContingentEpisode)]
+    pub fn add_contingent_episode_js(
+        &mut self,
+        duration: Option<Vec<f64>>,
+    ) -> Result<Episode, JsValue> {
+        let duration = or_default(duration, self.defaults.duration, "a duration")
+            .map_err(|e| JsValue::from_str(&e))?;
+        let duration = self.plan_time_interval("add an Episode", duration)?;
+        Ok(self.add_contingent_episode(Some(duration)))
+    }
+
+    /// Keep two Episodes from overlapping, eg. when they both need the only pistol grip tool. Either can go first: compiling picks an order that's consistent with everything else, trying the first Episode first, and errs if there isn't one. Errs if either Episode isn't in the Schedule or they're the same Episode
+    #[wasm_bindgen(js_name = addMutex)]
+    pub fn add_mutex(&mut self, first: &Episode, second: &Episode) -> Result<(), JsValue> {
+        self.require_draft("add a mutex")
+            .map_err(|e| JsValue::from_str(&e))?;
+        for episode in [first, second].iter() {
+            if !self.stn.contains_edge(episode.start(), episode.end()) {
+                return Err(JsValue::from_str(&format!(
+                    "Episode ({}, {}) is not in the Schedule",
+                    episode.start(),
+                    episode.end()
+                )));
+            }
+        }
+        if first == second {
+            return Err(JsValue::from_str(
+                "an Episode can't be mutually exclusive with itself",
+            ));
+        }
+
+        self.mutexes.push((*first, *second));
+        self.journal.push(Mutation::AddMutex {
+            first: (first.start(), first.end()),
+            second: (second.start(), second.end()),
+        });
+        self.mark_dirty();
+        Ok(())
+    }
+
+    /// Take an Episode's duration from a named parameter, eg. `"translation_time"`, instead of its own interval. Parameters are bound with `bindParameter` and looked up every time the Schedule compiles, so one plan can be recompiled against different assumptions. Replaces the Episode's authored duration when compiling. Errs if the Episode isn't in the Schedule
+    #[wasm_bindgen(js_name = setDurationParameter)]
+    pub fn set_duration_parameter(
+        &mut self,
+        episode: &Episode,
+        parameter: &str,
+    ) -> Result<(), JsValue> {
+        self.require_draft("set a duration parameter")
+            .map_err(|e| JsValue::from_str(&e))?;
+        if !self.stn.contains_edge(episode.start(), episode.end()) {
+            return Err(JsValue::from_str(&format!(
+                "Episode ({}, {}) is not in the Schedule",
+                episode.start(),
+                episode.end()
+            )));
+        }
+
+        self.duration_parameters
+            .insert(*episode, parameter.to_string());
+        self.journal.push(Mutation::SetDurationParameter {
+            start: episode.start(),
+            end: episode.end(),
+            parameter: parameter.to_string(),
+        });
+        self.mark_dirty();
+        Ok(())
+    }
+
+    /// Bind a parameter to a [lower, upper] interval. Rebinding replaces the interval. Takes effect the next time the Schedule compiles. Errs if the interval isn't `[lower, upper]` or the Schedule isn't a draft
+    #[wasm_bindgen(js_name = bindParameter)]
+    pub fn bind_parameter_js(&mut self, name: &str, interval: Vec<f64>) -> Result<(), JsValue> {
+        let interval = self.plan_time_interval("bind a parameter", interval)?;
+        self.bind_parameter(name, interval);
+        Ok(())
+    }
+
+    /// Bind every parameter in a set of assumptions at once, eg. `{ translation_time: [10, 15], egress: [40, 50] }`
+    #[wasm_bindgen(js_name = bindParameters)]
+    pub fn bind_parameters_js(&mut self, parameters: JsValue) -> Result<(), JsValue> {
+        let parameters: BTreeMap<String, Interval> = from_js(&parameters)?;
+        self.require_draft("bind a parameter")
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.bind_parameters(&parameters);
+        Ok(())
+    }
+
+    /// Mark an existing Episode's duration as out of the executor's control. Errs if the Episode isn't in the Schedule
+    #[wasm_bindgen(js_name = setContingent)]
+    pub fn set_contingent(&mut self, episode: &Episode) -> Result<(), JsValue> {
+        self.require_draft("mark an Episode contingent")
+            .map_err(|e| JsValue::from_str(&e))?;
+        if !self.stn.contains_edge(episode.start(), episode.end()) {
+            return Err(JsValue::from_str(&format!(
+                "Episode ({}, {}) is not in the Schedule",
+                episode.start(),
+                episode.end()
+            )));
+        }
+
+        self.contingent.insert(*episode);
+        self.journal.push(Mutation::MarkContingent {
+            start: episode.start(),
+            end: episode.end(),
+        });
+        Ok(())
+    }
+
+    /// Record how long a contingent Episode actually took once it finishes and update the schedule for the remaining events. Its start must already be committed. Errs if the Episode is not contingent or the duration is outside its declared bounds
+    #[wasm_bindgen(js_name = observeContingent)]
+    pub fn observe_contingent(&mut self, episode: &Episode, duration: f64) -> Result<(), JsValue> {
+        self.require_executable()
+            .map_err(|e| JsValue::from_str(&e))?;
+        if !self.contingent.contains(episode) {
+            return Err(JsValue::from_str(&format!(
+                "Episode ({}, {}) is not contingent. Commit its end instead",
+                episode.start(),
+                episode.end()
+            )));
+        }
+
+        let start = match self.committments.get(&episode.start()) {
+            Some(t) => *t,
+            None => {
+                return Err(JsValue::from_str(&format!(
+                    "the start of contingent Episode ({}, {}) has not been committed",
+                    episode.start(),
+                    episode.end()
+                )))
+            }
+        };
+
+        let bounds = self.get_duration(episode);
+        if !bounds.contains(duration) {
+            return Err(JsValue::from_str(&format!(
+                "observed duration {} is outside the declared duration {} of contingent Episode ({}, {})",
+                duration,
+                bounds,
+                episode.start(),
+                episode.end()
+            )));
+        }
+
+        self.observations.insert(*episode, duration);
+        self.journal.push(Mutation::ObserveContingent {
+            start: episode.start(),
+            end: episode.end(),
+            duration,
+        });
+        self.commit(episode.end(), start + duration)
+    }
+
+    /// Break an Episode that was interrupted into the part that ran and the part that's left, eg. when a task is paused and resumed later in the timeline. The first part becomes an Episode from the original start to a new event, committed `elapsed` after the start. The rest becomes a new Episode from another new event to the original end, with `elapsed` taken off its duration, and it can start any time after the pause. Constraints on the original end are kept. A contingent Episode stays contingent. Returns the Episode for the rest. Errs if the Episode isn't in the Schedule, hasn't started, has already ended, or `elapsed` is negative or longer than the Episode can last
+    #[wasm_bindgen(js_name = splitEpisode)]
+    pub fn split_episode(&mut self, episode: &Episode, elapsed: f64) -> Result<Episode, JsValue> {
+        let pause = self.next_event_id();
+        self.split(episode, elapsed, pause, pause + 1)
+    }
+
+    /// Record a contingent Episode that ran outside its declared duration. `policy` is `"extend"` to widen the duration to include the observation, or `"dryRun"` to only report the consequences. Returns `{ declared, extended, consistent, stronglyControllable, changes, infeasible }` where `changes` lists `{ event, before, after }` windows
+    #[wasm_bindgen(js_name = repair)]
+    pub fn repair_js(
+        &mut self,
+        episode: &Episode,
+        duration: f64,
+        policy: &str,
+    ) -> Result<JsValue, JsValue> {
+        let policy = match policy {
+            "extend" => RepairPolicy::Extend,
+            "dryRun" => RepairPolicy::DryRun,
+            p => {
+                return Err(JsValue::from_str(&format!(
+                    "unknown repair policy \"{}\". Expected extend or dryRun",
+                    p
+                )))
+            }
+        };
+        let report = self.repair(episode, duration, policy)?;
+        self.output(&report)
+    }
+
+    /// Keep events out of absolute blackout windows, eg. loss of signal, by clipping their execution windows. `events` lists the events to keep out, or every event if it's omitted. `blackouts` is an array of `[start, end]` times relative to the root at t=0. Events may still happen exactly at the start or end of a blackout. Returns `{ clipped, split, infeasible }`, where `clipped` lists `{ event, before, after }` windows and `split` lists events with a blackout in the middle of their window, which can't be clipped. Errs if an event isn't in the Schedule, a blackout ends before it starts, or the Schedule has no root
+    #[wasm_bindgen(js_name = applyBlackouts)]
+    pub fn apply_blackouts_js(
+        &mut self,
+        events: Option<Vec<EventID>>,
+        blackouts: JsValue,
+    ) -> Result<JsValue, JsValue> {
+        let blackouts: Vec<Interval> = from_js(&blackouts)?;
+        let report = self.apply_blackouts(events.as_deref(), &blackouts)?;
+        self.output(&report)
+    }
+
+    /// Describe the uncertain duration of an Episode, eg. `{ type: "normal", mean: 10, stdDev: 2 }` or `{ type: "uniform", lower: 5, upper: 15 }`. Errs if the parameters don't describe a distribution, eg. a negative `stdDev`
+    #[wasm_bindgen(js_name = setDistribution)]
+    pub fn set_distribution_js(
+        &mut self,
+        episode: &Episode,
+        distribution: JsValue,
+    ) -> Result<(), JsValue> {
+        let distribution: Distribution = from_js(&distribution)?;
+        self.set_distribution(episode, distribution)
+    }
+
+    /// Split a total acceptable risk, eg. 0.05 for 5%, across Episodes with distributions. Returns `{ totalRisk, assignments, feasible }` where each assignment is a `{ start, end, risk, bounds }` object. Pass the result to `applyRiskAllocation` to use the bounds as Episode durations
+    #[wasm_bindgen(js_name = allocateRisk)]
+    pub fn allocate_risk_js(&self, total_risk: f64) -> Result<JsValue, JsValue> {
+        let allocation = self.allocate_risk(total_risk)?;
+        self.output(&allocation)
+    }
+
+    /// Estimate the most likely time of every event and its variance. Returns an array of `{ event, mean, variance }` objects
+    #[wasm_bindgen(js_name = expectedTimes)]
+    pub fn expected_times_js(&self) -> Result<JsValue, JsValue> {
+        self.output(&self.expected_times())
+    }
+
+    /// Run a Monte Carlo simulation by sampling Episode durations. Returns `{ runs, successRate, meanMakespan, p05Makespan, p50Makespan, p95Makespan }`. The same seed always gives the same result
+    #[wasm_bindgen(js_name = simulate)]
+    pub fn simulate_js(&self, runs: usize, seed: u32) -> Result<JsValue, JsValue> {
+        self.output(&self.simulate(runs, seed as u64))
+    }
+
+    /// Keep the Episodes in each group, eg. the steps of one actor, from overlapping. Pairs that could overlap are put in order with a constraint from the end of one to the start of the other, and pairs that must overlap are reported as conflicts. Groups are passed like `zoom`, eg. `{ EV1: [[1, 2], [5, 6]] }`. Returns `{ ordered, conflicts }` where each entry is `{ group, first, second }`
+    #[wasm_bindgen(js_name = level)]
+    pub fn level_js(&mut self, groups: JsValue) -> Result<JsValue, JsValue> {
+        let groups: BTreeMap<String, Vec<(EventID, EventID)>> = from_js(&groups)?;
+        let groups = groups
+            .into_iter()
+            .map(|(group, episodes)| {
+                let episodes = episodes.into_iter().map(|(s, e)| Episode(s, e)).collect();
+                (group, episodes)
+            })
+            .collect();
+        let leveling = self.level(&groups)?;
+        self.output(&leveling)
+    }
+
+    /// Find gaps where a new Episode with a `[lower, upper]` duration could be inserted without pushing the horizon event, eg. to place a new task. With an actor, eg. `[[1, 2], [5, 6]]`, only the gaps between the actor's Episodes are considered. Returns an array of `{ anchor, before, offsets, slack }` objects, most slack first, where the new Episode could start `offsets` after `anchor` and still end before `before`. Errs if the duration isn't `[lower, upper]` or the Schedule is inconsistent
+    #[wasm_bindgen(js_name = suggestInsertion)]
+    pub fn suggest_insertion_js(
+        &mut self,
+        duration: Vec<f64>,
+        actor: JsValue,
+    ) -> Result<JsValue, JsValue> {
+        let duration = Interval::try_from_slice(&duration).map_err(|e| JsValue::from_str(&e))?;
+        let actor: Option<Vec<Episode>> = if actor.is_undefined() || actor.is_null() {
+            None
+        } else {
+            let episodes: Vec<(EventID, EventID)> = from_js(&actor)?;
+            Some(episodes.into_iter().map(|(s, e)| Episode(s, e)).collect())
+        };
+        let insertions = self.suggest_insertion(duration, actor.as_deref())?;
+        self.output(&insertions)
+    }
+
+    /// Aggregate the extracted schedule into buckets at one or more resolutions, eg. `zoom([1, 15], { EV1: [[1, 2], [5, 6]], EV2: [[3, 4]] })` where each group (eg. an actor) lists the `[start, end]` events of its Episodes. Returns an array of `{ resolution, buckets }` objects where each bucket is `{ start, end, occupancy }` and occupancy maps each group to the fraction of the bucket it spends busy
+    #[wasm_bindgen(js_name = zoom)]
+    pub fn zoom_js(&mut self, resolutions: Vec<f64>, groups: JsValue) -> Result<JsValue, JsValue> {
+        let groups: BTreeMap<String, Vec<(EventID, EventID)>> = from_js(&groups)?;
+        let groups = groups
+            .into_iter()
+            .map(|(group, episodes)| {
+                let episodes = episodes.into_iter().map(|(s, e)| Episode(s, e)).collect();
+                (group, episodes)
+            })
+            .collect();
+        let zooms = self.zoom(&resolutions, &groups)?;
+        self.output(&zooms)
+    }
+
+    /// Set the duration of every Episode in a risk allocation to its bounds. Errs if a bound is NaN or the Schedule isn't a draft
+    #[wasm_bindgen(js_name = applyRiskAllocation)]
+    pub fn apply_risk_allocation_js(&mut self, allocation: JsValue) -> Result<(), JsValue> {
+        let allocation: RiskAllocation = from_js(&allocation)?;
+        for a in allocation.assignments.iter() {
+            self.plan_time_interval(
+                "apply a risk allocation",
+                vec![a.bounds.lower(), a.bounds.upper()],
+            )?;
+        }
+        self.apply_risk_allocation(&allocation);
+        Ok(())
+    }
+
+    /// The observed duration of a contingent Episode, if it has finished
+    #[wasm_bindgen(js_name = observedDuration)]
+    pub fn observed_duration(&self, episode: &Episode) -> Option<f64> {
+        self.observations.get(episode).copied()
+    }
+
+    /// Whether or not an Episode's duration is out of the executor's control
+    #[wasm_bindgen(js_name = isContingent)]
+    pub fn is_contingent(&self, episode: &Episode) -> bool {
+        self.contingent.contains(episode)
+    }
+
+    /// Whether or not there is a fixed time for every controllable event that satisfies every constraint no matter how long contingent Episodes take. Useful when contingent durations can't be observed during execution
+    #[wasm_bindgen(js_name = isStronglyControllable)]
+    pub fn is_strongly_controllable(&self) -> bool {
+        match self.strong_reduction() {
+            Ok(mut reduced) => reduced.is_consistent(),
+            Err(_) => false,
+        }
+    }
+
+    /// Choose a fixed time for every controllable event that works for any contingent durations. Returns an object mapping event IDs to times. Contingent Episode ends are left out. Errs if the Schedule is not strongly controllable
+    #[wasm_bindgen(js_name = conformantSchedule)]
+    pub fn conformant_schedule_js(&self) -> Result<JsValue, JsValue> {
+        self.output(&self.conformant_schedule()?)
+    }
+
+    /// Get the controllable duration of an Episode
+    #[wasm_bindgen(js_name = getDuration)]
+    pub fn get_duration(&self, s: &Episode) -> Interval {
+        let lower = self.stn.edge_weight(s.1, s.0).unwrap_or(&0.);
+        let upper = self.stn.edge_weight(s.0, s.1).unwrap_or(&0.);
+        Interval::new(-*lower, *upper)
+    }
+
+    /// Compile the Schedule into a dispatchable form. A dispatchable form is required to query the Schedule for almost any scheduling information. This method is called implicitly when you attempt to query the Schedule when the dispatchable graph is not up-to-date. However, you can proactively call `compile` at a time that is computationally convenient for your application to avoid paying the performance penalty when querying the Schedule
+    #[wasm_bindgen(catch)]
+    pub fn compile(&mut self) -> Result<(), JsValue> {
+        self.compile_with_cancel(&CancelToken::new())
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Greedily update execution windows
+    fn update_schedule(&mut self, event: EventID) -> Result<(), JsValue> {
+        self.compile()?;
+
+        // collect the neighbors up front so the windows can be updated while iterating, without copying the whole graph
+        let neighbors = self.propagation_targets(event);
+        tracing::debug!(event, updated = neighbors.len(), "propagated commitment");
+        for neighbor in neighbors {
+            if self.committments.contains_key(&neighbor) {
+                // neighbor has already been scheduled
+                continue;
+            }
+
+            let time_to_neighbor = self.interval(event, neighbor)?;
+            let neighbor_window = match self.execution_windows.get(&neighbor) {
+                Some(i) => i,
+                None => return Err(JsValue::from_str(&format!("no such event {}", neighbor))),
+            };
+            let event_window = match self.execution_windows.get(&event) {
+                Some(i) => i,
+                None => return Err(JsValue::from_str(&format!("no such event {}", event))),
+            };
+
+            // update neighbor execution windows
+            // bounds_i = bounds_i ^ (v + time_event_to_neighbor)
+            let new_neighbor_window = *neighbor_window & (*event_window + time_to_neighbor);
+            self.execution_windows.insert(neighbor, new_neighbor_window);
+        }
+
+        Ok(())
+    }
+
+    /// Low-level API for marking an event complete. Advanced use only. If you can't explain why you should use this over `completeEpisode`, use `completeEpisode` instead. Commits an event to a time within its interval and greedily updates the schedule for remaining events. Time is in elapsed time since the Schedule started
+    ///
+    /// If the commitment leaves any event with an empty execution window, eg. because it was made outside the event's window, it throws `{ message, event, time, infeasible }` where `infeasible` lists those events. The commitment is still recorded, since it already happened
+    #[wasm_bindgen(catch, js_name = commitEvent)]
+    pub fn commit_event(&mut self, event: EventID, time: f64) -> Result<(), JsValue> {
+        self.record_commit(event, time)?;
+
+        let infeasible = self.infeasible_events();
+        if infeasible.is_empty() {
+            return Ok(());
+        }
+        tracing::warn!(event, time, infeasible = ?infeasible, "commitment left events infeasible");
+        Err(self.output(&InfeasibleCommit {
+            message: format!(
+                "committing event {} at {} leaves no time for events {:?}",
+                event, time, infeasible
+            ),
+            event,
+            time,
+            infeasible,
+        })?)
+    }
+
+    /// Commit several events that happen together at the same time, eg. both EVs finishing a sync, as one step. The whole set is checked against the existing commitments and against each other before any of it is applied, so it's committed entirely or not at all, and there's never a moment where only some of the events are committed. Events that are already committed at that time are left alone. Errs if the Schedule isn't executing or any event can't be committed at that time, including events already committed at another time
+    #[wasm_bindgen(js_name = commitSimultaneous)]
+    pub fn commit_simultaneous(&mut self, events: Vec<EventID>, time: f64) -> Result<(), JsValue> {
+        self.require_executable()
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.compile()?;
+        self.check_simultaneous(&events, time)
+            .map_err(|e| JsValue::from_str(&e))?;
+
+        let mut commits = vec![];
+        for event in events {
+            if self.committments.insert(event, time).is_none() {
+                self.journal.push(Mutation::CommitEvent { event, time });
+                commits.push((event, time));
+            }
+        }
+        self.propagate_commitments();
+        self.record_windows(commits);
+        Ok(())
+    }
+
+    /// Events whose execution windows are empty, ie. their earliest time is after their latest time. Nothing can be done at those events without breaking a constraint, which usually means a commitment was made outside of an event's window. Windows aren't recompiled, so this reflects the last propagation
+    #[wasm_bindgen(js_name = infeasibleEvents)]
+    pub fn infeasible_events(&self) -> Vec<EventID> {
+        self.execution_windows
+            .iter()
+            .filter(|(_, w)| !w.is_valid())
+            .map(|(e, _)| *e)
+            .collect()
+    }
+
+    /// Record a commitment in the journal and apply it
+    fn record_commit(&mut self, event: EventID, time: f64) -> Result<(), JsValue> {
+        self.require_executable()
+            .map_err(|e| JsValue::from_str(&e))?;
+        if let Some(episode) = self.contingent_episode_ending_at(event) {
+            return Err(JsValue::from_str(&format!(
+                "event {} ends contingent Episode ({}, {}) and can't be scheduled. Use `observeContingent` instead",
+                event,
+                episode.start(),
+                episode.end()
+            )));
+        }
+
+        if self.strict_commits {
+            self.compile()?;
+            self.check_order(event, time)
+                .map_err(|e| JsValue::from_str(&e))?;
+        }
+
+        self.journal.push(Mutation::CommitEvent { event, time });
+        self.commit(event, time)
+    }
+
+    /// Commit an event without recording it in the journal
+    fn commit(&mut self, event: EventID, time: f64) -> Result<(), JsValue> {
+        self.committments.insert(event, time);
+        self.execution_windows
+            .insert(event, Interval::new(time, time));
+        self.update_schedule(event)?;
+        self.record_windows(vec![(event, time)]);
+
+        Ok(())
+    }
+
+    /// Add a snapshot of the execution windows to the history, dropping the oldest if the history is full
+    fn record_windows(&mut self, commits: Vec<(EventID, f64)>) {
+        let sequence = self.window_history.back().map_or(0, |s| s.sequence + 1);
+        self.window_history.push_back(WindowSnapshot {
+            sequence,
+            commits,
+            windows: self.execution_windows.clone(),
+        });
+        if let Some(limit) = self.history_limit {
+            while self.window_history.len() > limit {
+                self.window_history.pop_front();
+            }
+        }
+    }
+
+    /// Mark an Episode complete to update the schedule to following Episodes. The time should be the elapsed time since the Schedule started (in the same units as well)
+    #[wasm_bindgen(catch, js_name = completeEpisode)]
+    pub fn complete_episode(&mut self, episode: &Episode, time: f64) -> Result<(), JsValue> {
+        self.commit_event(episode.end(), time)?;
+        Ok(())
+    }
+
+    /// Replay a log of `[event, time]` commitments, eg. an as-run log from a reconnecting client. Entries that conflict with earlier commitments are skipped instead of aborting the replay. Returns `{ applied, rejected, infeasible }` where `applied` lists committed events, `rejected` lists `{ event, time, reason }` objects, and `infeasible` lists events left with empty windows
+    #[wasm_bindgen(js_name = commitEvents)]
+    pub fn commit_events_js(&mut self, log: JsValue) -> Result<JsValue, JsValue> {
+        let log: Vec<(EventID, f64)> = from_js(&log)?;
+        let report = self.commit_events(log)?;
+        self.output(&report)
+    }
+
+    /// Get the execution window of an Event
+    #[wasm_bindgen(catch)]
+    pub fn window(&mut self, event: EventID) -> Result<Interval, JsValue> {
+        self.compile_or_keep()?;
+
+        match self.execution_windows.get(&event) {
+            Some(i) => Ok(*i),
+            None => Err(JsValue::from(&format!("could not find event {}", event))),
+        }
+    }
+
+    /// Get the interval between two events
+    #[wasm_bindgen(catch)]
+    pub fn interval(&mut self, source: EventID, target: EventID) -> Result<Interval, JsValue> {
+        self.compile_or_keep()?;
+
+        if let Some(interval) = self.intervals.get(&(source, target)) {
+            return Ok(*interval);
+        }
+
+        let l = match self.dispatchable.distance(target, source) {
+            Some(l) => l,
+            None => {
+                return Err(JsValue::from_str(&format!(
+                    "missing lower edge: {} to {}",
+                    target, source
+                )))
+            }
+        };
+
+        let upper = match self.dispatchable.distance(source, target) {
+            Some(u) => u,
+            None => {
+                return Err(JsValue::from_str(&format!(
+                    "missing upper edge: {} to {}",
+                    source, target
+                )))
+            }
+        };
+
+        // avoid returning -0
+        let lower = if l == 0. { -0. } else { l };
+
+        let interval = Interval::new(-lower, upper);
+        self.intervals.insert((source, target), interval);
+        Ok(interval)
+    }
+
+    /// Get the intervals between many pairs of events in one call, eg. to draw every sync in a timeline without crossing into wasm for each one. Pairs are flattened as `[source0, target0, source1, target1, ...]` and the intervals are returned flattened the same way, as `[lower0, upper0, lower1, upper1, ...]`. Errs if there's an odd number of events or any pair has no interval
+    #[wasm_bindgen(js_name = intervals)]
+    pub fn intervals_js(&mut self, pairs: Vec<EventID>) -> Result<Vec<f64>, JsValue> {
+        if !pairs.len().is_multiple_of(2) {
+            return Err(JsValue::from_str(
+                "expected pairs of events but got an odd number of events",
+            ));
+        }
+
+        let pairs: Vec<(EventID, EventID)> = pairs.chunks(2).map(|p| (p[0], p[1])).collect();
+        let intervals = self.intervals(&pairs)?;
+        Ok(intervals
+            .into_iter()
+            .flat_map(|i| self.lower_upper(i))
+            .collect())
+    }
+
+    /// Low-level API to get the directional distance between two events. Advanced use only. If you can't explain why you should use this over `interval`, use `interval` instead
+    #[wasm_bindgen(js_name = eventDistance)]
+    pub fn event_distance(&mut self, source: EventID, target: EventID) -> Result<JsValue, JsValue> {
+        self.distance_number(source, target).map(JsValue::from_f64)
+    }
+
+    /// `eventDistance` as a plain number, eg. for dashboards that query many distances every frame
+    #[wasm_bindgen(js_name = distanceNumber)]
+    pub fn distance_number(&mut self, source: EventID, target: EventID) -> Result<f64, JsValue> {
+        // ensure source and target already exist
+        if !self.stn.contains_node(source) {
+            return Err(JsValue::from_str(&format!(
+                "Source {} is not already in the Schedule. Have you added it with `addEpisode`?",
+                source
+            )));
+        }
+        if !self.stn.contains_node(target) {
+            return Err(JsValue::from_str(&format!(
+                "Target {} is not already in the Schedule. Have you added it with `addEpisode`?",
+                target
+            )));
+        }
+
+        match self.compile() {
+            Ok(_) => (),
+            Err(e) => return Err(e),
+        }
+
+        match self.dispatchable.distance(source, target) {
+            Some(t) => Ok(t),
+            None => Err(JsValue::from_str(&"Cannot find path from start to target")),
+        }
+    }
+
+    /// Get the execution window of an event as a `Float64Array` of `[lower, upper]` instead of an `Interval` that has to be freed, eg. for dashboards that redraw every frame. Rounded like other outputs
+    #[wasm_bindgen(js_name = windowLowerUpper)]
+    pub fn window_lower_upper(&mut self, event: EventID) -> Result<Vec<f64>, JsValue> {
+        let window = self.window(event)?;
+        Ok(self.lower_upper(window))
+    }
+
+    /// Get the execution windows of many events as one `Float64Array`, flattened as `[lower0, upper0, lower1, upper1, ...]`. Rounded like other outputs. Errs if any event isn't in the Schedule
+    #[wasm_bindgen(js_name = windowsLowerUpper)]
+    pub fn windows_lower_upper(&mut self, events: Vec<EventID>) -> Result<Vec<f64>, JsValue> {
+        let mut bounds = Vec::with_capacity(events.len() * 2);
+        for event in events {
+            let window = self.window(event)?;
+            bounds.extend(self.lower_upper(window));
+        }
+        Ok(bounds)
+    }
+
+    /// Write the execution window of an event into an existing Interval instead of creating one, so a display can reuse the same Interval on every query. Not rounded. Errs and leaves `out` alone if the event isn't in the Schedule
+    #[wasm_bindgen(js_name = windowInto)]
+    pub fn window_into(&mut self, event: EventID, out: &mut Interval) -> Result<(), JsValue> {
+        *out = self.window(event)?;
+        Ok(())
+    }
+
+    /// Write the interval between two events into an existing Interval instead of creating one. Not rounded. Errs and leaves `out` alone if there's no interval between them
+    #[wasm_bindgen(js_name = intervalInto)]
+    pub fn interval_into(
+        &mut self,
+        source: EventID,
+        target: EventID,
+        out: &mut Interval,
+    ) -> Result<(), JsValue> {
+        *out = self.interval(source, target)?;
+        Ok(())
+    }
+
+    /// Get the interval between two events as a `Float64Array` of `[lower, upper]` instead of an `Interval` that has to be freed. Rounded like other outputs
+    #[wasm_bindgen(js_name = intervalLowerUpper)]
+    pub fn interval_lower_upper(
+        &mut self,
+        source: EventID,
+        target: EventID,
+    ) -> Result<Vec<f64>, JsValue> {
+        let interval = self.interval(source, target)?;
+        Ok(self.lower_upper(interval))
+    }
+
+    /// Replace the interval between two events. Errs if the interval isn't `[lower, upper]` or the Schedule isn't a draft
+    #[wasm_bindgen(js_name = update_interval)]
+    pub fn update_interval_js(
+        &mut self,
+        source: EventID,
+        target: EventID,
+        interval: Vec<f64>,
+    ) -> Result<(), JsValue> {
+        let interval = self.plan_time_interval("change a constraint", interval)?;
+        self.update_interval(source, target, interval);
+        Ok(())
+    }
+
+    /// Add a constraint between the start or end of two events. Errs if either source or target is not already in the Schedule or the interval is required. Defaults to a [0, 0] interval between events unless changed with `setDefaults`
+    #[wasm_bindgen(js_name = addConstraint)]
+    pub fn add_constraint(
+        &mut self,
+        source: EventID,
+        target: EventID,
+        interval: Option<Vec<f64>>,
+    ) -> Result<(), JsValue> {
+        // ensure source and target already exist
+        self.require_draft("add a constraint")
+            .map_err(|e| JsValue::from_str(&e))?;
+        if !self.stn.contains_node(source) {
+            return Err(JsValue::from_str(&format!(
+                "Source {} is not already in the Schedule. Have you added it with `addEpisode`?",
+                source
+            )));
+        }
+        if !self.stn.contains_node(target) {
+            return Err(JsValue::from_str(&format!(
+                "Target {} is not already in the Schedule. Have you added it with `addEpisode`?",
+                target
+            )));
+        }
+
+        let d = or_default(interval, self.defaults.constraint, "a constraint interval")
+            .map_err(|e| JsValue::from_str(&e))?;
+        let i = Interval::try_from_slice(&d).map_err(|e| JsValue::from_str(&e))?;
+
+        self.stn.add_edge(source, target, i.upper());
+        self.stn.add_edge(target, source, -i.lower());
+        self.journal.push(Mutation::AddConstraint {
+            source,
+            target,
+            interval: i,
+        });
+
+        self.mark_dirty();
+        Ok(())
+    }
+
+    /// Remove the constraint between two events. Only errs if an Event is missing
+    #[wasm_bindgen(catch, js_name = removeConstraint)]
+    pub fn remove_constraint(&mut self, source: EventID, target: EventID) -> Result<(), JsValue> {
+        self.require_draft("remove a constraint")
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.unlink(source, target)?;
+        self.journal
+            .push(Mutation::RemoveConstraint { source, target });
+        Ok(())
+    }
+
+    /// Remove the edge from source to target without recording it in the journal
+    fn unlink(&mut self, source: EventID, target: EventID) -> Result<(), JsValue> {
+        // ensure source and target exist
+        if !self.stn.contains_node(source) {
+            return Err(JsValue::from_str(&format!(
+                "Source event {} is not in the Schedule. No constraints to remove",
+                source
+            )));
+        }
+        if !self.stn.contains_node(target) {
+            return Err(JsValue::from_str(&format!(
+                "Target event {} is not in the Schedule. No constraints to remove",
+                target
+            )));
+        }
+
+        // TODO: check. and don't throw an err if there is no edge
+        self.stn.remove_edge(source, target);
+        self.mark_dirty();
+        Ok(())
+    }
+
+    /// Remove all constraints between two episodes
+    #[wasm_bindgen(catch, js_name = removeConstraints)]
+    pub fn remove_constraints(
+        &mut self,
+        source: &Episode,
+        target: &Episode,
+    ) -> Result<(), JsValue> {
+        self.require_draft("remove constraints")
+            .map_err(|e| JsValue::from_str(&e))?;
+
+        // let's not assume that source and target are in order. therefore, 2 episodes have 8 possible constraints between them:
+        //    2 episodes x 2 events each x 2 directions for each edge
+
+        self.unlink(source.start(), target.start())?;
+
+        self.unlink(source.start(), target.start())?;
+        self.unlink(source.start(), target.end())?;
+        self.unlink(source.start(), target.end())?;
+        self.unlink(target.end(), source.start())?;
+        self.unlink(target.end(), source.start())?;
+        self.unlink(target.end(), source.end())?;
+        self.unlink(target.end(), source.end())?;
+
+        self.journal.push(Mutation::RemoveConstraints {
+            source: (source.start(), source.end()),
+            target: (target.start(), target.end()),
+        });
+
+        Ok(())
+    }
+
+    /// Whether or not the Schedule can be executed, ie. it compiles, no constraints contradict each other, and every execution window is valid
+    #[wasm_bindgen(js_name = isConsistent)]
+    pub fn is_consistent(&mut self) -> bool {
+        if self.compile_with_cancel(&CancelToken::new()).is_err() {
+            return false;
+        }
+
+        !self.has_negative_cycle() && self.execution_windows.values().all(|w| w.is_valid())
+    }
+
+    /// Find out how tight the constraint between two events could be made without making the Schedule inconsistent. This is the interval implied by every other constraint. Any new [lower, upper] constraint between the events must overlap it, and the tightest possible constraint is a single time within it. Errs if the Schedule is already inconsistent
+    #[wasm_bindgen(js_name = maxTightening)]
+    pub fn max_tightening(
+        &mut self,
+        source: EventID,
+        target: EventID,
+    ) -> Result<Interval, JsValue> {
+        for event in [source, target].iter() {
+            if !self.stn.contains_node(*event) {
+                return Err(JsValue::from_str(&format!(
+                    "Event {} is not in the Schedule",
+                    event
+                )));
+            }
+        }
+
+        self.compile()?;
+        if self.has_negative_cycle() {
+            return Err(JsValue::from_str(
+                "the Schedule is already inconsistent. Loosen or remove conflicting constraints first",
+            ));
+        }
+
+        self.interval(source, target)
+    }
+
+    /// Get the [earliest, latest] time the Schedule could end. Times are relative to the root event, which is assumed to happen at t=0 if it hasn't been committed
+    pub fn makespan(&mut self) -> Result<Interval, JsValue> {
+        let mut s = self.clone();
+        s.compile()?;
+        let root = match s.root() {
+            Some(r) => r,
+            None => {
+                return Err(JsValue::from_str(
+                    "cannot find the first event in the Schedule",
+                ))
+            }
+        };
+        if !s.committments.contains_key(&root) {
+            s.commit(root, 0.)?;
+        }
+
+        let makespan = s
+            .execution_windows
+            .values()
+            .fold(Interval::new(0., 0.), |m, w| {
+                Interval::new(m.lower().max(w.lower()), m.upper().max(w.upper()))
+            });
+        Ok(makespan)
+    }
+
+    /// Get the events the Schedule could end with, ie. every event that no other event has to happen after. Sorted by event ID
+    #[wasm_bindgen(js_name = terminalEvents)]
+    pub fn terminal_events(&mut self) -> Result<Vec<EventID>, JsValue> {
+        self.compile_or_keep()?;
+
+        // an edge from t to e shorter than 0 means t has to happen after e
+        let mut terminal: Vec<EventID> = self
+            .dispatchable
+            .nodes()
+            .filter(|e| {
+                self.dispatchable
+                    .neighbors_directed(*e, petgraph::Incoming)
+                    .all(|t| {
+                        self.dispatchable
+                            .edge_weight(t, *e)
+                            .is_none_or(|w| *w >= 0.)
+                    })
+            })
+            .collect();
+        terminal.sort_unstable();
+        Ok(terminal)
+    }
+
+    /// Get the last event in the Schedule, ie. the event that can't happen before any other event. If multiple events could be last, the one with the lowest ID is returned. `undefined` if no event is guaranteed to be last, eg. parallel Episodes that don't finish together. See `terminalEvents` for every event the Schedule could end with
+    #[wasm_bindgen(js_name = horizonEvent)]
+    pub fn horizon_event(&mut self) -> Result<Option<EventID>, JsValue> {
+        self.compile_or_keep()?;
+
+        // all outgoing edges should be <= 0 for the last node
+        let mut candidates: Vec<EventID> = self
+            .dispatchable
+            .nodes()
+            .filter(|e| {
+                self.dispatchable
+                    .neighbors_directed(*e, petgraph::Outgoing)
+                    .all(|t| {
+                        self.dispatchable
+                            .edge_weight(*e, t)
+                            .is_some_and(|w| *w <= 0.)
+                    })
+            })
+            .collect();
+        candidates.sort_unstable();
+        Ok(candidates.first().copied())
+    }
+
+    /// Get the [earliest, latest] time the last of the terminal events happens, see `terminalEvents`. Times are relative to the root event, which is assumed to happen at t=0 if it hasn't been committed
+    #[wasm_bindgen(js_name = planEndWindow)]
+    pub fn plan_end_window(&mut self) -> Result<Interval, JsValue> {
+        let terminal = self.terminal_events()?;
+        let (_, _, windows) = self
+            .windows_from_root()
+            .map_err(|e| JsValue::from_str(&e))?;
+        terminal
+            .iter()
+            .map(|e| windows[e])
+            .reduce(|end, w| Interval::new(end.lower().max(w.lower()), end.upper().max(w.upper())))
+            .ok_or_else(|| JsValue::from_str("the Schedule has no events"))
+    }
+
+    /// Create a copy of this Schedule that shares its history up to `event` and can diverge afterward. If `event` has been committed, commitments made after it are dropped from the branch. Otherwise, every commitment is kept
+    #[wasm_bindgen(js_name = branchAt)]
+    pub fn branch_at(&self, event: EventID) -> Result<Schedule, JsValue> {
+        if !self.stn.contains_node(event) {
+            return Err(JsValue::from_str(&format!(
+                "Event {} is not in the Schedule. Cannot branch",
+                event
+            )));
+        }
+
+        let mut branch = self.clone();
+        branch.branch_point = Some(event);
+
+        if let Some(t) = self.committments.get(&event) {
+            branch.committments.retain(|_, time| *time <= *t);
+            branch.reset_windows();
+            // forget snapshots of the commitments that were dropped
+            let kept = &branch.committments;
+            branch
+                .window_history
+                .retain(|s| s.commits.iter().all(|(e, _)| kept.contains_key(e)));
+        }
+
+        Ok(branch)
+    }
+
+    /// Create a copy of this plan with every duration and constraint multiplied by `factor`, eg. 1.5 for a training run at one and a half times the pace. Unbounded sides stay unbounded and zero offsets, like syncs, stay zero. The copy starts fresh, without commitments, observations, or window history. Errs if the factor isn't a positive number
+    #[wasm_bindgen(js_name = scalePlan)]
+    pub fn scale_plan(&self, factor: f64) -> Result<Schedule, JsValue> {
+        if !factor.is_finite() || factor <= 0. {
+            return Err(JsValue::from_str(&format!(
+                "cannot scale a plan by {}. The factor must be a positive number",
+                factor
+            )));
+        }
+
+        let mut scaled = self.clone();
+        for (_, _, weight) in scaled.stn.all_edges_mut() {
+            *weight = scale_bound(*weight, factor);
+        }
+        scaled.journal = self
+            .journal
+            .iter()
+            .filter_map(|m| m.scaled(factor))
+            .collect();
+        for interval in scaled.parameters.values_mut() {
+            *interval = interval.scale(factor);
+        }
+        for distribution in scaled.distributions.values_mut() {
+            *distribution = distribution.scale(factor);
+        }
+        for preference in scaled.preferences.values_mut() {
+            if let Preference::Target(t) = preference {
+                *t *= factor;
+            }
+        }
+        if let Propagation::Horizon(h) = &mut scaled.propagation {
+            *h *= factor;
+        }
+
+        scaled.committments.clear();
+        scaled.observations.clear();
+        scaled.window_history.clear();
+        scaled.branch_point = None;
+        scaled.reset_windows();
+        Ok(scaled)
+    }
+
+    /// The event this Schedule was branched at, if it is a branch
+    #[wasm_bindgen(getter, js_name = branchPoint)]
+    pub fn branch_point(&self) -> Option<EventID> {
+        self.branch_point
+    }
+
+    /// Compare the feasibility and makespan of this Schedule against another, eg. a branch
+    pub fn compare(&mut self, other: &mut Schedule) -> BranchComparison {
+        let feasible = self.is_consistent();
+        let other_feasible = other.is_consistent();
+        let unknown = Interval(-f64::MAX, f64::MAX);
+
+        BranchComparison {
+            feasible,
+            other_feasible,
+            makespan: if feasible {
+                self.makespan().unwrap_or(unknown)
+            } else {
+                unknown
+            },
+            other_makespan: if other_feasible {
+                other.makespan().unwrap_or(unknown)
+            } else {
+                unknown
+            },
+        }
+    }
+
+    /// Get the execution window of every event as an array of `{ event, earliest, latest, nominal, converged }` objects. Designed for drawing window bars
+    #[wasm_bindgen(js_name = timeWindows)]
+    pub fn time_windows_js(&mut self) -> Result<JsValue, JsValue> {
+        let windows = self.time_windows()?;
+        self.output(&windows)
+    }
+
+    /// Set when an Episode would prefer to occur within its execution window. `preference` is one of `"earliest"`, `"latest"`, `"midpoint"`, or `"target"`, in which case `target` is the preferred start time
+    #[wasm_bindgen(js_name = setPreference)]
+    pub fn set_preference_js(
+        &mut self,
+        episode: &Episode,
+        preference: &str,
+        target: Option<f64>,
+    ) -> Result<(), JsValue> {
+        let p = match (preference, target) {
+            ("earliest", _) => Preference::Earliest,
+            ("latest", _) => Preference::Latest,
+            ("midpoint", _) => Preference::Midpoint,
+            ("target", Some(t)) => Preference::Target(t),
+            ("target", None) => {
+                return Err(JsValue::from_str("a target preference needs a target time"))
+            }
+            (p, _) => {
+                return Err(JsValue::from_str(&format!(
+                    "unknown preference \"{}\". Expected earliest, latest, midpoint, or target",
+                    p
+                )))
+            }
+        };
+
+        self.set_preference(episode, p);
+        Ok(())
+    }
+
+    /// Choose a concrete time for every event based on its preference. Returns an object mapping event IDs to times
+    #[wasm_bindgen(js_name = extractSchedule)]
+    pub fn extract_schedule_js(&mut self) -> Result<JsValue, JsValue> {
+        let times = self.extract_schedule()?;
+        self.output(&times)
+    }
+
+    /// Choose a few concrete schedules that trade a short makespan for more slack, from the earliest-start schedule to the most robust one. Returns an array of `{ makespan, slack, times }` sorted by makespan, without schedules that are worse on both counts. `points` defaults to 5
+    #[wasm_bindgen(js_name = paretoSchedules)]
+    pub fn pareto_schedules_js(&mut self, points: Option<usize>) -> Result<JsValue, JsValue> {
+        let schedules = self
+            .pareto_schedules(points.unwrap_or(5))
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.output(&schedules)
+    }
+
+    /// Get structural statistics about the Schedule: the depth of the longest chain of events, the most events that can happen in parallel, the number of constraints that fork or join chains, and the number of redundant constraints
+    #[wasm_bindgen(js_name = complexity)]
+    pub fn complexity_js(&self) -> Complexity {
+        self.complexity()
+    }
+
+    /// Get every constraint between two events that isn't authored but is implied by other constraints, as an array of `{ source, target, interval }` objects. Only constraints whose interval spans `threshold` or less are reported
+    #[wasm_bindgen(js_name = impliedConstraints)]
+    pub fn implied_constraints_js(&mut self, threshold: f64) -> Result<JsValue, JsValue> {
+        let implied = self.implied_constraints(threshold)?;
+        self.output(&implied)
+    }
+
+    /// Get the events that can't happen after an event, as an array of `{ source, target, interval }` objects from each of them to the event. Includes orderings implied by other constraints. Errs if the event isn't in the Schedule
+    #[wasm_bindgen(js_name = predecessors)]
+    pub fn predecessors_js(&mut self, event: EventID) -> Result<JsValue, JsValue> {
+        let predecessors = self.predecessors(event)?;
+        self.output(&predecessors)
+    }
+
+    /// Get the uncommitted events an event is waiting on, ie. what has to happen before it can be dispatched, as an array of `{ event, window, interval }` objects where `window` is what's left of the blocker's execution window and `interval` is the time from the blocker to the event. Events that must be simultaneous with it don't block it. Errs if the event isn't in the Schedule
+    #[wasm_bindgen(js_name = blocking)]
+    pub fn blocking_js(&mut self, event: EventID) -> Result<JsValue, JsValue> {
+        let blockers = self.blocking(event)?;
+        self.output(&blockers)
+    }
+
+    /// Whether or not the constraints allow `a` to happen strictly before `b`, eg. to enable dragging `a` ahead of `b`. Always the opposite of `mustPrecede(b, a)`. Errs if either event isn't in the Schedule
+    #[wasm_bindgen(js_name = canPrecede)]
+    pub fn can_precede(&mut self, a: EventID, b: EventID) -> Result<bool, JsValue> {
+        // the most b can come after a
+        Ok(self.precedence_distance(a, b)? > 0.)
+    }
+
+    /// Whether or not the constraints keep `b` from ever happening before `a`, eg. to disable dragging `b` ahead of `a`. Events that must happen at the same time must precede each other. Errs if either event isn't in the Schedule
+    #[wasm_bindgen(js_name = mustPrecede)]
+    pub fn must_precede(&mut self, a: EventID, b: EventID) -> Result<bool, JsValue> {
+        // the most a can come after b
+        Ok(self.precedence_distance(b, a)? <= 0.)
+    }
+
+    /// Get the events that can't happen before an event, as an array of `{ source, target, interval }` objects from the event to each of them. Includes orderings implied by other constraints. Errs if the event isn't in the Schedule
+    #[wasm_bindgen(js_name = successors)]
+    pub fn successors_js(&mut self, event: EventID) -> Result<JsValue, JsValue> {
+        let successors = self.successors(event)?;
+        self.output(&successors)
+    }
+
+    /// Get the authored constraints that could be removed without changing the compiled Schedule, as an array of `{ source, target, interval }` objects. Useful for simplifying a plan before review
+    #[wasm_bindgen(js_name = redundantConstraints)]
+    pub fn redundant_constraints_js(&self) -> Result<JsValue, JsValue> {
+        self.output(&self.redundant_constraints())
+    }
+
+    /// Get the fewest authored constraints that compile to the same Schedule, as an array of `{ source, target, interval }` objects, eg. to uplink a plan over a slow link. A bound that other constraints already imply is left unbounded, and a constraint with both bounds implied is left out. Errs if the constraints are inconsistent
+    #[wasm_bindgen(js_name = minimalEquivalent)]
+    pub fn minimal_equivalent_js(&self) -> Result<JsValue, JsValue> {
+        let constraints = self
+            .minimal_equivalent()
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.output(&constraints)
+    }
+
+    /// Get the chain of events that sets the earliest possible end of the Schedule, from the root to the event that can happen last. Every step along the path is a constraint with no slack at its lower bound
+    #[wasm_bindgen(js_name = criticalPath)]
+    pub fn critical_path_js(&mut self) -> Result<Vec<EventID>, JsValue> {
+        self.critical_path()
+    }
+
+    /// Explain the interval between two events with the authored constraints that produce it. Returns `{ source, target, interval, upper, lower }` where `upper` and `lower` are the constraints along the shortest paths that set each bound, as `{ source, target, interval, bound }` objects in path order
+    #[wasm_bindgen(js_name = explainInterval)]
+    pub fn explain_interval_js(
+        &self,
+        source: EventID,
+        target: EventID,
+    ) -> Result<JsValue, JsValue> {
+        let explanation = self.explain_interval(source, target)?;
+        self.output(&explanation)
+    }
+
+    /// Find loops in the authored constraints and suggest how to break them. Returns an array of `{ kind, events, constraints, excess, suggestions }` objects, where kind is `"contradictory"` or `"rigid"`
+    #[wasm_bindgen(js_name = diagnoseCycles)]
+    pub fn diagnose_cycles_js(&self) -> Result<JsValue, JsValue> {
+        let diagnostics = self.diagnose_cycles();
+        self.output(&diagnostics)
+    }
+
+    /// Check the Schedule for common problems. Returns an array of `{ severity, message, events }` objects, where severity is `"error"` or `"warning"`
+    #[wasm_bindgen(js_name = lint)]
+    pub fn lint_js(&mut self) -> Result<JsValue, JsValue> {
+        let findings = self.lint();
+        self.output(&findings)
+    }
+
+    /// Describe the authored constraints in Graphviz DOT, eg. for `dot -Tsvg`. Events are labeled with their first alias if they have one. Constraint and Episode metadata is written as JSON in each edge's `comment`
+    #[wasm_bindgen(js_name = toDot)]
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph schedule {\n");
+        for event in self.stn.nodes() {
+            let label = match self.aliases_of(event).first() {
+                Some(alias) => format!("{} ({})", alias, event),
+                None => event.to_string(),
+            };
+            dot.push_str(&format!("  {} [label={:?}];\n", event, label));
+        }
+        for c in self.constraints() {
+            let comment = match &c.metadata {
+                Some(metadata) => format!(", comment={:?}", metadata.to_string()),
+                None => String::new(),
+            };
+            dot.push_str(&format!(
+                "  {} -> {} [label=\"{}\"{}];\n",
+                c.source, c.target, c.interval, comment
+            ));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Attach an external ID, eg. a UUID from a procedure database, to an event. An event can have multiple aliases, but each alias refers to one event. Errs if the event doesn't exist or the alias is already used by a different event
+    #[wasm_bindgen(js_name = setEventAlias)]
+    pub fn set_event_alias(&mut self, event: EventID, alias: &str) -> Result<(), JsValue> {
+        if !self.stn.contains_node(event) {
+            return Err(JsValue::from_str(&format!(
+                "Event {} is not in the Schedule. Cannot alias it as \"{}\"",
+                event, alias
+            )));
+        }
+
+        match self.event_aliases.get(alias) {
+            Some(e) if *e != event => Err(JsValue::from_str(&format!(
+                "\"{}\" is already an alias for event {}",
+                alias, e
+            ))),
+            _ => {
+                self.event_aliases.insert(alias.to_string(), event);
+                self.journal.push(Mutation::SetEventAlias {
+                    event,
+                    alias: alias.to_string(),
+                });
+                Ok(())
+            }
+        }
+    }
+
+    /// Attach an external ID to an Episode. Errs if either of its events doesn't exist or the alias is already used by a different Episode
+    #[wasm_bindgen(js_name = setEpisodeAlias)]
+    pub fn set_episode_alias(&mut self, episode: &Episode, alias: &str) -> Result<(), JsValue> {
+        if !self.stn.contains_node(episode.start()) || !self.stn.contains_node(episode.end()) {
+            return Err(JsValue::from_str(&format!(
+                "Episode ({}, {}) is not in the Schedule. Cannot alias it as \"{}\"",
+                episode.start(),
+                episode.end(),
+                alias
+            )));
+        }
+
+        match self.episode_aliases.get(alias) {
+            Some(e) if e != episode => Err(JsValue::from_str(&format!(
+                "\"{}\" is already an alias for Episode ({}, {})",
+                alias,
+                e.start(),
+                e.end()
+            ))),
+            _ => {
+                self.episode_aliases.insert(alias.to_string(), *episode);
+                self.journal.push(Mutation::SetEpisodeAlias {
+                    start: episode.start(),
+                    end: episode.end(),
+                    alias: alias.to_string(),
+                });
+                Ok(())
+            }
+        }
+    }
+
+    /// Find an event by its external ID
+    #[wasm_bindgen(js_name = eventByAlias)]
+    pub fn event_by_alias(&self, alias: &str) -> Option<EventID> {
+        self.event_aliases.get(alias).copied()
+    }
+
+    /// Find an Episode by its external ID
+    #[wasm_bindgen(js_name = episodeByAlias)]
+    pub fn episode_by_alias(&self, alias: &str) -> Option<Episode> {
+        self.episode_aliases.get(alias).copied()
+    }
+
+    /// Get every external ID attached to an event
+    #[wasm_bindgen(js_name = eventAliases)]
+    pub fn event_aliases(&self, event: EventID) -> Vec<JsValue> {
+        self.aliases_of(event)
+            .iter()
+            .map(|a| JsValue::from_str(a))
+            .collect()
+    }
+
+    /// Remove an external ID from whichever event or Episode it refers to. Returns whether or not the alias existed
+    #[wasm_bindgen(js_name = removeAlias)]
+    pub fn remove_alias(&mut self, alias: &str) -> bool {
+        let event = self.event_aliases.remove(alias).is_some();
+        let episode = self.episode_aliases.remove(alias).is_some();
+        if event || episode {
+            self.journal.push(Mutation::RemoveAlias {
+                alias: alias.to_string(),
+            });
+        }
+        event || episode
+    }
+
+    /// Attach arbitrary JSON-compatible data to an Episode, eg. a procedure reference, color, or crew notes. Replaces any existing metadata
+    #[wasm_bindgen(js_name = setEpisodeMetadata)]
+    pub fn set_episode_metadata_js(
+        &mut self,
+        episode: &Episode,
+        metadata: JsValue,
+    ) -> Result<(), JsValue> {
+        let value = from_js(&metadata)?;
+        self.set_episode_metadata(episode, value)
+    }
+
+    /// Get the data attached to an Episode. `undefined` if there is none
+    #[wasm_bindgen(js_name = episodeMetadata)]
+    pub fn episode_metadata_js(&self, episode: &Episode) -> Result<JsValue, JsValue> {
+        match self.episode_metadata(episode) {
+            Some(v) => to_js(v),
+            None => Ok(JsValue::UNDEFINED),
+        }
+    }
+
+    /// Attach arbitrary JSON-compatible data to the constraint between two events. Replaces any existing metadata. Errs if there is no constraint between the events
+    #[wasm_bindgen(js_name = setConstraintMetadata)]
+    pub fn set_constraint_metadata_js(
+        &mut self,
+        source: EventID,
+        target: EventID,
+        metadata: JsValue,
+    ) -> Result<(), JsValue> {
+        let value = from_js(&metadata)?;
+        self.set_constraint_metadata(source, target, value)
+    }
+
+    /// Get the data attached to the constraint between two events, in either direction. `undefined` if there is none
+    #[wasm_bindgen(js_name = constraintMetadata)]
+    pub fn constraint_metadata_js(
+        &self,
+        source: EventID,
+        target: EventID,
+    ) -> Result<JsValue, JsValue> {
+        match self.constraint_metadata(source, target) {
+            Some(v) => to_js(v),
+            None => Ok(JsValue::UNDEFINED),
+        }
+    }
+
+    /// Record the source document of the constraint between two events, eg. `setConstraintProvenance(3, 4, "EVA-2B rev C", 42, "jsmith")`. It's cited by `lint` and `diagnoseCycles` and reported with the constraint by `constraints` and `explainInterval`. Replaces any existing provenance. Errs if there is no constraint between the events
+    #[wasm_bindgen(js_name = setConstraintProvenance)]
+    pub fn set_constraint_provenance_js(
+        &mut self,
+        source: EventID,
+        target: EventID,
+        document: &str,
+        line: Option<u32>,
+        author: Option<String>,
+    ) -> Result<(), JsValue> {
+        let provenance = Provenance {
+            document: document.to_string(),
+            line,
+            author,
+        };
+        self.set_constraint_provenance(source, target, provenance)
+    }
+
+    /// Get the provenance of the constraint between two events, in either direction, as `{ document, line, author }`. `undefined` if there is none
+    #[wasm_bindgen(js_name = constraintProvenance)]
+    pub fn constraint_provenance_js(
+        &self,
+        source: EventID,
+        target: EventID,
+    ) -> Result<JsValue, JsValue> {
+        match self.constraint_provenance(source, target) {
+            Some(p) => to_js(p),
+            None => Ok(JsValue::UNDEFINED),
+        }
+    }
+
+    /// Get the mutations made to this Schedule as an array of `{ op, ...parameters }` objects. Pass `since` to only get the mutations after the first `since`, eg. the number of mutations already persisted
+    #[wasm_bindgen(js_name = exportJournal)]
+    pub fn export_journal_js(&self, since: Option<usize>) -> Result<JsValue, JsValue> {
+        let since = since.unwrap_or(0).min(self.journal.len());
+        to_js(&self.journal[since..].to_vec())
+    }
+
+    /// Replay mutations exported with `exportJournal`, eg. to rebuild a Schedule after a crash or to sync changes from another client. Stops at the first mutation that can't be applied
+    #[wasm_bindgen(js_name = applyJournal)]
+    pub fn apply_journal_js(&mut self, entries: JsValue) -> Result<(), JsValue> {
+        let entries: Vec<Mutation> = from_js(&entries)?;
+        self.apply_journal(&entries)
+    }
+
+    /// Merge two journals recorded against this Schedule, eg. by two planners editing the same timeline. Returns `{ mutations, conflicts }`. Apply `mutations` with `applyJournal`. Each conflict is a `{ ours, theirs, reason }` object for manual resolution
+    #[wasm_bindgen(js_name = mergeJournals)]
+    pub fn merge_journals_js(&self, ours: JsValue, theirs: JsValue) -> Result<JsValue, JsValue> {
+        let ours: Vec<Mutation> = from_js(&ours)?;
+        let theirs: Vec<Mutation> = from_js(&theirs)?;
+        to_js(&self.merge_journals(&ours, &theirs))
+    }
+
+    /// The number of mutations in the journal
+    #[wasm_bindgen(getter, js_name = journalLength)]
+    pub fn journal_length(&self) -> usize {
+        self.journal.len()
+    }
+
+    /// Choose how far `commitEvent` propagates to other events' windows. `strategy` is one of `"full"` (every event, the default), `"horizon"`, which only updates events that can happen within `limit` time after the committed event, or `"hops"`, which only updates events within `limit` constraints of it. Call `reconcile` to catch every window up later
+    #[wasm_bindgen(js_name = setPropagation)]
+    pub fn set_propagation_js(
+        &mut self,
+        strategy: &str,
+        limit: Option<f64>,
+    ) -> Result<(), JsValue> {
+        let propagation = match (strategy, limit) {
+            ("full", _) => Propagation::Full,
+            ("horizon", Some(h)) => Propagation::Horizon(h),
+            ("hops", Some(h)) if h >= 0. => Propagation::Hops(h as usize),
+            ("horizon", _) | ("hops", _) => {
+                return Err(JsValue::from_str(&format!(
+                    "{} propagation needs a non-negative limit",
+                    strategy
+                )))
+            }
+            (s, _) => {
+                return Err(JsValue::from_str(&format!(
+                    "unknown propagation \"{}\". Expected full, horizon, or hops",
+                    s
+                )))
+            }
+        };
+
+        self.set_propagation(propagation);
+        Ok(())
+    }
+
+    /// Whether or not queries are being answered from an older compile because the Schedule has changed in a way that doesn't compile. See `compileError` for why
+    #[wasm_bindgen(getter)]
+    pub fn stale(&self) -> bool {
+        self.dirty && self.compile_error.is_some() && self.compiled_before()
+    }
+
+    /// Why the last compile failed, or `undefined` if it didn't or the Schedule has changed since
+    #[wasm_bindgen(getter, js_name = compileError)]
+    pub fn compile_error_js(&self) -> Option<String> {
+        self.compile_error.clone()
+    }
+
+    /// Where the Schedule is in its lifecycle: `"draft"`, `"frozen"`, `"executing"`, or `"complete"`
+    #[wasm_bindgen(getter, js_name = phase)]
+    pub fn phase_js(&self) -> String {
+        self.phase.to_string()
+    }
+
+    /// Lock the plan once it's been reviewed. Plan-time changes, like adding Episodes or constraints, throw from then on unless they're made with `amend`. Errs unless the Schedule is a draft
+    pub fn freeze(&mut self) -> Result<(), JsValue> {
+        self.advance(Phase::Frozen)
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Unlock a frozen plan for more authoring. Errs unless the Schedule is frozen. Once execution begins, the plan can only be changed with `amend`
+    pub fn thaw(&mut self) -> Result<(), JsValue> {
+        self.advance(Phase::Draft)
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Start executing a frozen plan. Committing an event does this implicitly. Errs unless the Schedule is frozen
+    #[wasm_bindgen(js_name = beginExecution)]
+    pub fn begin_execution(&mut self) -> Result<(), JsValue> {
+        self.advance(Phase::Executing)
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Finish executing. The Schedule accepts no more changes or commitments. Errs unless the Schedule is executing
+    pub fn complete(&mut self) -> Result<(), JsValue> {
+        self.advance(Phase::Complete)
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Make a plan-time change to a frozen or executing plan, eg. `amend("EV2 suit pressure issue", { op: "updateInterval", source: 3, target: 4, interval: [40, 60] })`. The change is written like a journal entry and is recorded in the journal with the reason. Errs if the reason is empty, the change isn't a plan-time change, the Schedule is complete, or the change can't be applied
+    #[wasm_bindgen(js_name = amend)]
+    pub fn amend_js(&mut self, reason: &str, mutation: JsValue) -> Result<(), JsValue> {
+        let mutation: Mutation = from_js(&mutation)?;
+        self.amend(reason, mutation)
+    }
+
+    /// Start a batch of changes that either all apply or are all undone, eg. rearranging several Episodes in an editor. Finish with `commitTransaction` or `rollbackTransaction`. Errs if a transaction is already open
+    #[wasm_bindgen(js_name = beginTransaction)]
+    pub fn begin_transaction_js(&mut self) -> Result<(), JsValue> {
+        self.begin_transaction().map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Keep the changes made since `beginTransaction` if the Schedule still compiles. If it doesn't, eg. because the changes made the constraints inconsistent, every change is undone and the reason is thrown. Errs if no transaction is open
+    #[wasm_bindgen(js_name = commitTransaction)]
+    pub fn commit_transaction_js(&mut self) -> Result<(), JsValue> {
+        self.commit_transaction().map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Undo every change made since `beginTransaction`, including commitments and journal entries. Errs if no transaction is open
+    #[wasm_bindgen(js_name = rollbackTransaction)]
+    pub fn rollback_transaction_js(&mut self) -> Result<(), JsValue> {
+        self.rollback_transaction()
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Whether or not a transaction is open
+    #[wasm_bindgen(getter = inTransaction)]
+    pub fn in_transaction(&self) -> bool {
+        self.transaction.is_some()
+    }
+
+    /// Choose the `[lower, upper]` intervals used when `addEpisode` or `addConstraint` are called without one, eg. `[0, Number.MAX_VALUE]` so a forgotten constraint only orders events instead of making them simultaneous. Pass nothing for either to require it explicitly. Both are [0, 0] by default. Errs if an interval isn't `[lower, upper]`
+    #[wasm_bindgen(js_name = setDefaults)]
+    pub fn set_defaults_js(
+        &mut self,
+        duration: Option<Vec<f64>>,
+        constraint: Option<Vec<f64>>,
+    ) -> Result<(), JsValue> {
+        let parse = |i: Option<Vec<f64>>| {
+            i.map(|i| Interval::try_from_slice(&i))
+                .transpose()
+                .map_err(|e| JsValue::from_str(&e))
+        };
+        self.set_defaults(Defaults {
+            duration: parse(duration)?,
+            constraint: parse(constraint)?,
+        });
+        Ok(())
+    }
+
+    /// Refuse to compile plans with more than `maxEvents` events, `maxEdges` edges, two per constraint, or a distance matrix bigger than `maxMatrixBytes`, 8 bytes per pair of events. Compiling a plan over any limit errs instead. `streamedBounds` works within `maxMatrixBytes`. Pass nothing to remove a limit
+    #[wasm_bindgen(js_name = setLimits)]
+    pub fn set_limits_js(
+        &mut self,
+        max_events: Option<usize>,
+        max_edges: Option<usize>,
+        max_matrix_bytes: Option<usize>,
+    ) {
+        self.set_limits(Limits {
+            max_events,
+            max_edges,
+            max_matrix_bytes,
+        });
+    }
+
+    /// Get execution windows and intervals without compiling, for plans too big to hold the distance between every pair of events. Only the rows of the distance matrix that are needed are computed, as many at a time as fit in `maxMatrixBytes`. Pairs are flattened as `[source0, target0, source1, target1, ...]`, like `intervals`. Returns `{ root, windows, intervals }`, where `windows` are like `timeWindows` and `intervals` are `[lower, upper]` in the order of the pairs. Errs if there's an odd number of events, an event isn't in the Schedule, the constraints are inconsistent, or there's no first event
+    #[wasm_bindgen(js_name = streamedBounds)]
+    pub fn streamed_bounds_js(&mut self, pairs: Vec<EventID>) -> Result<JsValue, JsValue> {
+        if !pairs.len().is_multiple_of(2) {
+            return Err(JsValue::from_str(
+                "expected pairs of events but got an odd number of events",
+            ));
+        }
+
+        let pairs: Vec<(EventID, EventID)> = pairs.chunks(2).map(|p| (p[0], p[1])).collect();
+        let bounds = self
+            .streamed_bounds(&pairs)
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.output(&bounds)
+    }
+
+    /// Aim to compile in `budget` milliseconds. The compile then picks the cheapest way to get every distance: building on the last compile when constraints were only tightened or added, or Johnson's algorithm instead of Floyd-Warshall on big sparse plans. Pass nothing to always use Floyd-Warshall, the default
+    #[wasm_bindgen(js_name = setCompileBudget)]
+    pub fn set_compile_budget_js(&mut self, budget: Option<f64>) {
+        self.set_compile_budget(budget);
+    }
+
+    /// Skip building the dispatchable graph after each compile, a big allocation on big plans. `interval`, `window`, and commitments read distances straight from the compiled matrix instead, and the graph is built the first time something else needs it, eg. `exportDispatchable`. Off by default
+    #[wasm_bindgen(js_name = setLazyDispatchable)]
+    pub fn set_lazy_dispatchable_js(&mut self, lazy: bool) {
+        self.set_lazy_dispatchable(lazy);
+    }
+
+    /// Record how Floyd-Warshall finds the implied constraints on every compile, for teaching. Each relaxation that shortens the distance from event `i` to event `j` by going through event `k` is kept, up to `limit` of them. Get them with `compileTrace`. Pass nothing to stop tracing
+    #[wasm_bindgen(js_name = setTrace)]
+    pub fn set_trace_js(&mut self, limit: Option<usize>) {
+        self.set_trace(limit);
+    }
+
+    /// Compile and get the relaxations recorded by `setTrace` as `{ limit, relaxations, dropped }`, where each relaxation is `{ k, i, j, old, new }` and `dropped` counts the relaxations past the limit. `undefined` if tracing is off. Errs if the Schedule doesn't compile
+    #[wasm_bindgen(js_name = compileTrace)]
+    pub fn compile_trace_js(&mut self) -> Result<JsValue, JsValue> {
+        self.compile_or_keep()?;
+        match &self.trace {
+            Some(trace) => self.output(trace),
+            None => Ok(JsValue::UNDEFINED),
+        }
+    }
+
+    /// Get every edge of the dispatchable graph as `[source, target, distance]`, building it if it's lazy. Errs if the Schedule doesn't compile
+    #[wasm_bindgen(js_name = exportDispatchable)]
+    pub fn export_dispatchable_js(&mut self) -> Result<JsValue, JsValue> {
+        let edges: Vec<(EventID, EventID, f64)> = self
+            .dispatchable_graph()?
+            .all_edges()
+            .map(|(s, t, w)| (s, t, *w))
+            .collect();
+        self.output(&edges)
+    }
+
+    /// Get every edge of the distance graph built from the Episodes and constraints, before compiling, as `{ source, target, weight, meaning, label }`. `meaning` is `"episodeUpper"`, `"episodeLower"`, `"constraintUpper"`, or `"constraintLower"`, and `label` says which Episode or constraint the edge came from, eg. `"negative lower bound of Episode (0, 1)"`
+    #[wasm_bindgen(js_name = labeledEdges)]
+    pub fn labeled_edges_js(&self) -> Result<JsValue, JsValue> {
+        self.output(&self.labeled_edges())
+    }
+
+    /// Get how the last successful compile went as `{ strategy, events, edges, changed, steps, withinBudget }`. `strategy` is `"floydWarshall"`, `"johnson"`, `"incremental"`, or `"bounded"`, `changed` counts the edges an incremental compile propagated, and `steps` is the estimated work
+    #[wasm_bindgen(js_name = stats)]
+    pub fn stats_js(&self) -> Result<JsValue, JsValue> {
+        to_js(&self.stats())
+    }
+
+    /// Roughly how much work compiling will take, as the number of steps of all-pairs shortest paths: the cube of the number of events. Useful for warning before compiling a huge plan. Grows quickly, eg. 1000 events is a billion steps
+    #[wasm_bindgen(js_name = estimatedCompileCost)]
+    pub fn estimated_compile_cost(&self) -> f64 {
+        (self.stn.node_count() as f64).powi(3)
+    }
+
+    /// Get the execution window of every event after each commit, oldest first, eg. to animate how uncertainty collapsed during execution. Returns an array of `{ sequence, commits, windows }` objects, where `commits` lists the `[event, time]` commitments that led to the snapshot and `windows` maps events to `[earliest, latest]`
+    #[wasm_bindgen(js_name = windowHistory)]
+    pub fn window_history_js(&self) -> Result<JsValue, JsValue> {
+        let history: Vec<&WindowSnapshot> = self.window_history.iter().collect();
+        self.output(&history)
+    }
+
+    /// Get the execution windows that changed since a snapshot in the window history as `{ since, version, changed, removed }`, so a `WindowMirror` doesn't need every window after every commit. Pass the mirror's `version`, or nothing for a full sync. Errs if the snapshot is no longer in the history
+    #[wasm_bindgen(js_name = deltaSince)]
+    pub fn delta_since_js(&mut self, version: Option<usize>) -> Result<JsValue, JsValue> {
+        let delta = self.delta_since(version)?;
+        self.output(&delta)
+    }
+
+    /// Keep only the latest `limit` snapshots in the window history, eg. to bound memory on a long mission. Older snapshots are dropped right away. Pass nothing to keep every snapshot, the default
+    #[wasm_bindgen(js_name = setHistoryLimit)]
+    pub fn set_history_limit(&mut self, limit: Option<usize>) {
+        self.history_limit = limit;
+        self.journal.push(Mutation::SetHistoryLimit { limit });
+        if let Some(limit) = limit {
+            while self.window_history.len() > limit {
+                self.window_history.pop_front();
+            }
+        }
+    }
+
+    /// Propagate every commitment to every event, regardless of the propagation strategy. Use after committing with limited propagation to bring far-off windows up to date
+    pub fn reconcile(&mut self) -> Result<(), JsValue> {
+        self.compile()?;
+        self.propagate_commitments();
+        Ok(())
+    }
+
+    /// Reject commitments that are earlier than an already committed event that must precede them, eg. because the wrong event was clicked. `commitEvent` throws and `commitEvents` lists them as rejected instead of narrowing windows into nonsense. Off by default
+    #[wasm_bindgen(js_name = setStrictCommits)]
+    pub fn set_strict_commits_js(&mut self, strict: bool) {
+        self.set_strict_commits(strict);
+    }
+
+    /// Round times in serialized outputs like `timeWindows`, `constraints`, and `intervals` to some decimal places, eg. 3 for milliseconds when times are in seconds. Pass nothing to stop rounding, the default. Only outputs are rounded, never the math behind them. Intervals returned directly, eg. by `window`, are not affected
+    #[wasm_bindgen(js_name = setOutputRounding)]
+    pub fn set_output_rounding_js(&mut self, decimals: Option<u32>) {
+        self.set_output_decimals(decimals);
+    }
+
+    /// Choose how infinite bounds are written in serialized outputs like `timeWindows` and `constraints`. `mode` is one of `"raw"` (±Number.MAX_VALUE, the default), `"unbounded"` (`"unbounded"` and `"-unbounded"` strings), or `"horizon"`, which writes ±horizon instead. Finite numbers, eg. times past the horizon or metadata, are written as-is. Intervals returned directly, eg. by `window`, are not affected
+    #[wasm_bindgen(js_name = setOutputBounds)]
+    pub fn set_output_bounds_js(
+        &mut self,
+        mode: &str,
+        horizon: Option<f64>,
+    ) -> Result<(), JsValue> {
+        let output_bounds = match (mode, horizon) {
+            ("raw", _) => OutputBounds::Raw,
+            ("unbounded", _) => OutputBounds::Unbounded,
+            ("horizon", Some(h)) => OutputBounds::Horizon(h),
+            ("horizon", None) => {
+                return Err(JsValue::from_str("horizon output bounds need a horizon"))
+            }
+            (m, _) => {
+                return Err(JsValue::from_str(&format!(
+                    "unknown output bounds \"{}\". Expected raw, unbounded, or horizon",
+                    m
+                )))
+            }
+        };
+
+        self.set_output_bounds(output_bounds);
+        Ok(())
+    }
+
+    /// Get every authored constraint as an array of `{ source, target, interval, metadata? }` objects
+    #[wasm_bindgen(js_name = constraints)]
+    pub fn constraints_js(&self) -> Result<JsValue, JsValue> {
+        self.output(&self.constraints())
+    }
+
+    /// Get the authored and implied timing between two events as `{ source, target, authored?, bounds, episode, implied? }`. `authored` is the constraint written between the events, oriented from source to target, and `implied` is the interval that follows from every constraint. `bounds` counts the authored bounds and `episode` is whether the authored constraint is an Episode's duration. Errs if either event isn't in the Schedule
+    #[wasm_bindgen(js_name = constraintBetween)]
+    pub fn constraint_between_js(
+        &mut self,
+        source: EventID,
+        target: EventID,
+    ) -> Result<JsValue, JsValue> {
+        let between = self.constraint_between(source, target)?;
+        self.output(&between)
+    }
+
+    /// Dump the compiled distances as a table of strings with headers, like the legacy STN's `dump_constraint_table`. Returns an array of rows where the first row and column are event headers and each cell is the distance from the row's event to the column's event, written in the format from `setIntervalFormat`, so `"∞"` if unbounded by default. Events with aliases are labeled by their first alias. Errs if the Schedule doesn't compile
+    #[wasm_bindgen(js_name = dumpConstraintTable)]
+    pub fn dump_constraint_table_js(&mut self) -> Result<JsValue, JsValue> {
+        let table = self
+            .dump_constraint_table()
+            .map_err(|e| JsValue::from_str(&e))?;
+        to_js(&table)
+    }
+
+    /// Get a stable hash of the plan as hex, eg. to check whether a cached compile is for the current plan or whether two plan files describe the same plan. Commitments, metadata, and formatting don't change it
+    #[wasm_bindgen(js_name = hash)]
+    pub fn hash_js(&self) -> String {
+        self.hash()
+    }
+
+    /// Compile and certify that the plan is consistent as `{ engine, version, inputs, distances, events, edges, stamp }`. `inputs` and `distances` hash the authored constraints and commitments and the compiled distance matrix, and `stamp` hashes the rest of the certificate. Errs if the Schedule doesn't compile
+    #[wasm_bindgen(js_name = feasibilityCertificate)]
+    pub fn feasibility_certificate_js(&mut self) -> Result<JsValue, JsValue> {
+        let certificate = self
+            .feasibility_certificate()
+            .map_err(|e| JsValue::from_str(&e))?;
+        to_js(&certificate)
+    }
+
+    /// Check a certificate from `feasibilityCertificate` against this Schedule. Errs with the reason if it was edited, made by another engine version, or doesn't match the Schedule's inputs or compiled distances
+    #[wasm_bindgen(js_name = verifyCertificate)]
+    pub fn verify_certificate_js(&mut self, certificate: JsValue) -> Result<(), JsValue> {
+        let certificate: FeasibilityCertificate = from_js(&certificate)?;
+        self.verify_certificate(&certificate)
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Remove any constraints around this Episode, except the constraints between the start and end of the Episode. This should be performed prior to moving an episode in the STN
+    #[wasm_bindgen(catch, js_name = freeEpisode)]
+    pub fn free_episode(&mut self, episode: &Episode) -> Result<(), JsValue> {
+        self.require_draft("free an Episode")
+            .map_err(|e| JsValue::from_str(&e))?;
+        let cloned_stn = self.stn.clone();
+        let incoming_edges = cloned_stn.neighbors_directed(episode.start(), Incoming);
+        let outgoing_edges = cloned_stn.neighbors_directed(episode.end(), Outgoing);
+
+        for e in incoming_edges {
+            self.stn.remove_edge(e, episode.start());
+        }
+
+        for e in outgoing_edges {
+            self.stn.remove_edge(episode.end(), e);
+        }
+
+        self.journal.push(Mutation::FreeEpisode {
+            start: episode.start(),
+            end: episode.end(),
+        });
+        self.mark_dirty();
+        Ok(())
+    }
+}
+
+impl Schedule {
+    /// Low-level API for creating nodes in the graph. Advanced use only. If you can't explain why you should use this over `addEpisode`, use `add_episode` instead. Panics if the Schedule isn't a draft. `createEvent` errs instead
+    pub fn create_event(&mut self) -> EventID {
+        let event_id = self.next_event_id();
+        self.insert_event(event_id)
+    }
+
+    /// Create a new Episode and add it to this Schedule. Defaults to a [0, 0] duration unless changed with `set_defaults`. Panics if the duration isn't `[lower, upper]` or is required, or the Schedule isn't a draft. `addEpisode` errs instead
+    pub fn add_episode(&mut self, duration: Option<Vec<f64>>) -> Episode {
+        if let Err(e) = self.require_draft("add an Episode") {
+            panic!("{}", e);
+        }
+        let d = or_default(duration, self.defaults.duration, "a duration")
+            .unwrap_or_else(|e| panic!("{}", e));
+        let i = Interval::try_from_slice(&d).unwrap_or_else(|e| panic!("{}", e));
+
+        let episode = self.new_episode();
+        self.link_episode(episode, i);
+        self.journal.push(Mutation::AddEpisode {
+            start: episode.start(),
+            end: episode.end(),
+            duration: i,
+        });
+        episode
+    }
+
+    /// Create a new Episode whose duration is out of the executor's control, eg. a hardware warm-up. Its end happens somewhere within `duration` after its start and can only be observed, not scheduled. Panics like `add_episode`
+    pub fn add_contingent_episode(&mut self, duration: Option<Vec<f64>>) -> Episode {
+        let episode = self.add_episode(duration);
+        self.contingent.insert(episode);
+        self.journal.push(Mutation::MarkContingent {
+            start: episode.start(),
+            end: episode.end(),
+        });
+        episode
+    }
+
+    /// Replace the interval between two events. Panics if the interval isn't `[lower, upper]` or the Schedule isn't a draft
+    pub fn update_interval(&mut self, source: EventID, target: EventID, interval: Vec<f64>) {
+        if let Err(e) = self.require_draft("change a constraint") {
+            panic!("{}", e);
+        }
+        let i = Interval::try_from_slice(&interval).unwrap_or_else(|e| panic!("{}", e));
+        self.journal.push(Mutation::UpdateInterval {
+            source,
+            target,
+            interval: i,
+        });
+
+        // update the edge in the STN
+        self.stn.add_edge(source, target, i.upper());
+        self.stn.add_edge(target, source, -i.lower());
+
+        // mark the STN dirty
+        self.mark_dirty();
+    }
+
+    /// Bind a parameter to a [lower, upper] interval. Rebinding replaces the interval. Takes effect the next time the Schedule compiles. Panics if the interval isn't `[lower, upper]` or the Schedule isn't a draft
+    pub fn bind_parameter(&mut self, name: &str, interval: Vec<f64>) {
+        if let Err(e) = self.require_draft("bind a parameter") {
+            panic!("{}", e);
+        }
+        let interval = Interval::try_from_slice(&interval).unwrap_or_else(|e| panic!("{}", e));
+        self.parameters.insert(name.to_string(), interval);
+        self.journal.push(Mutation::BindParameter {
+            name: name.to_string(),
+            interval,
+        });
+        self.mark_dirty();
+    }
+
+    /// Whether or not an event has been added to the Schedule
+    pub fn contains_event(&self, event: EventID) -> bool {
+        self.stn.contains_node(event)
+    }
+
+    /// Find authored constraints that contradict each other without compiling. Returns the events on a negative cycle in order, or `None` if the constraints are consistent. Unlike `is_consistent`, this never errs
+    pub fn negative_cycle(&self) -> Option<Vec<EventID>> {
+        negative_cycle(&self.stn)
+    }
+
+    /// Get the interval between each pair of events, in order. Errs on the first pair without an interval
+    pub fn intervals(&mut self, pairs: &[(EventID, EventID)]) -> Result<Vec<Interval>, JsValue> {
+        pairs
+            .iter()
+            .map(|(source, target)| self.interval(*source, *target))
+            .collect()
+    }
+
+    /// Set the largest plan `compile` will take on
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+        self.journal.push(Mutation::SetLimits { limits });
+    }
+
+    /// Aim to compile in `budget` milliseconds, or `None` to always compile with Floyd-Warshall. With a budget, the compile builds on the last one when constraints were only tightened or added, and uses Johnson's algorithm on sparse plans that are too big for Floyd-Warshall. See `stats` for what the last compile did
+    pub fn set_compile_budget(&mut self, budget: Option<f64>) {
+        self.compile_budget = budget;
+        self.journal.push(Mutation::SetCompileBudget { budget });
+    }
+
+    /// Keep the distance matrix from Floyd-Warshall compiles instead of building the dispatchable graph, an edge for every pair of events, after each one. Intervals, windows, and commitments read the matrix directly, and the graph is only built when something needs it, eg. `dispatchable_graph`. Compiles that build on the last one or use Johnson's algorithm still build the graph. Off by default
+    pub fn set_lazy_dispatchable(&mut self, lazy: bool) {
+        self.lazy_dispatchable = lazy;
+        self.journal.push(Mutation::SetLazyDispatchable { lazy });
+    }
+
+    /// Record the relaxations of Floyd-Warshall on every compile, up to `limit`, eg. to show how the implied constraints of a plan arise. Traced compiles always run dense Floyd-Warshall, whatever the compile budget, and are slower. `None` stops tracing. Marks the Schedule as changed so the next query compiles with the new setting
+    pub fn set_trace(&mut self, limit: Option<usize>) {
+        self.trace_limit = limit;
+        self.journal.push(Mutation::SetTrace { limit });
+        if limit.is_none() {
+            self.trace = None;
+        }
+        self.mark_dirty();
+    }
+
+    /// The relaxations recorded by the last compile, if it was traced
+    pub fn trace(&self) -> Option<&Trace> {
+        self.trace.as_ref()
+    }
+
+    /// Compile and get the dispatchable graph, building it if it's lazy
+    pub fn dispatchable_graph(&mut self) -> Result<&DiGraphMap<EventID, f64>, JsValue> {
+        self.compile_or_keep()?;
+        Ok(&self.dispatchable)
+    }
+
+    /// How the last successful compile went
+    pub fn stats(&self) -> CompileStats {
+        self.stats
+    }
+
+    /// Set the intervals used for Episodes and constraints added without one, eg. `Defaults::strict()` to require them
+    pub fn set_defaults(&mut self, defaults: Defaults) {
+        self.defaults = defaults;
+        self.journal.push(Mutation::SetDefaults { defaults });
+    }
+
+    /// Check the plan against the limits without compiling. Errs with how far over a limit the plan is
+    pub fn check_limits(&self) -> Result<(), String> {
+        let events = self.stn.node_count();
+        if let Some(max) = self.limits.max_events.filter(|max| events > *max) {
+            return Err(format!(
+                "the plan has {} events, more than the limit of {}",
+                events, max
+            ));
+        }
+        let edges = self.stn.edge_count();
+        if let Some(max) = self.limits.max_edges.filter(|max| edges > *max) {
+            return Err(format!(
+                "the plan has {} edges, more than the limit of {}",
+                edges, max
+            ));
+        }
+        let bytes = events * events * std::mem::size_of::<f64>();
+        if let Some(max) = self.limits.max_matrix_bytes.filter(|max| bytes > *max) {
+            return Err(format!(
+                "the distances between {} events need {} bytes, more than the limit of {}. Stream the bounds you need instead",
+                events, bytes, max
+            ));
+        }
+        Ok(())
+    }
+
+    /// Compile, checking a token between iterations of all-pairs shortest paths so another thread can abandon a stale compile. A cancelled compile changes nothing and reports how far it got. Errors are plain strings, so this is also the way to compile outside of wasm
+    pub fn compile_with_cancel(&mut self, token: &CancelToken) -> Result<(), Abort> {
+        if !self.dirty {
+            return Ok(());
+        }
+
+        let mut result = self.recompile(token);
+        if result.is_ok()
+            && self.auto_root
+            && self.roots.len() != 1
+            && self.phase == Phase::Draft
+            && self.stn.node_count() > 1
+        {
+            self.anchor_components();
+            result = self.recompile(token);
+        }
+        if let Err(Abort::Failed(e)) = &result {
+            self.compile_error = Some(e.clone());
+        }
+        result
+    }
+
+    /// Make sure the Schedule has exactly one root, eg. when Episodes are only constrained pairwise and nothing says which comes first. If there isn't one, a virtual origin event is created and constrained `[0, ∞]` before the first events of every group of connected events, and its ID is returned. Later calls reuse the same origin. Errs if the Schedule doesn't compile or a root needs to be added outside of a draft
+    pub fn ensure_root(&mut self) -> Result<EventID, String> {
+        self.compile_with_cancel(&CancelToken::new())
+            .map_err(|e| e.to_string())?;
+        if let [root] = self.roots[..] {
+            return Ok(root);
+        }
+
+        self.require_draft("add a root event")?;
+        let origin = self.anchor_components();
+        self.compile_with_cancel(&CancelToken::new())
+            .map_err(|e| e.to_string())?;
+        Ok(origin)
+    }
+
+    /// Call `ensure_root` every time a draft compiles without exactly one root. Off by default
+    pub fn set_auto_root(&mut self, auto_root: bool) {
+        self.auto_root = auto_root;
+        self.journal
+            .push(Mutation::SetAutoRoot { enabled: auto_root });
+    }
+
+    /// The origin event created by `ensure_root`, if there is one
+    pub fn virtual_root(&self) -> Option<EventID> {
+        self.virtual_root
+    }
+
+    /// Constrain the virtual origin, creating it if needed, `[0, ∞]` before every event that no other event in its group of connected events has to precede. Returns the origin. Assumes the Schedule has been compiled and is a draft
+    fn anchor_components(&mut self) -> EventID {
+        let origin = match self.virtual_root {
+            Some(origin) if self.stn.contains_node(origin) => origin,
+            _ => {
+                let origin = self.create_event();
+                self.journal
+                    .push(Mutation::SetVirtualRoot { event: origin });
+                origin
+            }
+        };
+        self.virtual_root = Some(origin);
+
+        let mut seen = BTreeSet::new();
+        seen.insert(origin);
+        let mut first = vec![];
+        for event in self.stn.nodes().collect::<Vec<EventID>>() {
+            if !seen.insert(event) {
+                continue;
+            }
+            let mut component = vec![event];
+            let mut i = 0;
+            while i < component.len() {
+                let e = component[i];
+                for n in self
+                    .stn
+                    .neighbors_directed(e, petgraph::Outgoing)
+                    .chain(self.stn.neighbors_directed(e, petgraph::Incoming))
+                {
+                    if n == origin {
+                        // already anchored
+                        component.clear();
+                        break;
+                    }
+                    if seen.insert(n) {
+                        component.push(n);
+                    }
+                }
+                i += 1;
+            }
+
+            // an edge to another event shorter than 0 means that event has to happen first
+            first.extend(component.iter().copied().filter(|e| {
+                component.iter().all(|t| {
+                    self.dispatchable
+                        .edge_weight(*e, *t)
+                        .is_none_or(|w| *w >= 0.)
+                })
+            }));
+        }
+
+        for event in first {
+            self.update_interval(origin, event, vec![0., f64::MAX]);
+        }
+        origin
+    }
+
+    /// Compile and compare the implied interval between every pair of events against the compile before, eg. to see the impact of a single edit. Each pair is reported once, with the lower event ID as the source. Events added since the compile before are left out. Empty if the Schedule has only been compiled once
+    pub fn compile_diff(&mut self) -> Result<Vec<BoundChange>, JsValue> {
+        self.compile()?;
+
+        let before = &self.previous_dispatchable;
+        let mut events: Vec<EventID> = self
+            .dispatchable
+            .nodes()
+            .filter(|e| before.contains_node(*e))
+            .collect();
+        events.sort_unstable();
+
+        let mut changes = vec![];
+        for (i, source) in events.iter().enumerate() {
+            for target in events[i + 1..].iter() {
+                let old = implied_interval(before, *source, *target);
+                let new = implied_interval(&self.dispatchable, *source, *target);
+                if old != new {
+                    changes.push(BoundChange {
+                        source: *source,
+                        target: *target,
+                        before: old,
+                        after: new,
+                        lower: (new.lower() - old.lower()).clamp(-f64::MAX, f64::MAX),
+                        upper: (old.upper() - new.upper()).clamp(-f64::MAX, f64::MAX),
+                    });
+                }
+            }
+        }
+        Ok(changes)
+    }
+
+    /// Why the last compile failed, if the Schedule hasn't changed since
+    pub fn compile_error(&self) -> Option<&str> {
+        self.compile_error.as_deref()
+    }
+
+    /// Where the Schedule is in its lifecycle
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// Move to another phase of the lifecycle and record it in the journal. A draft can be frozen, a frozen plan can be thawed back into a draft or start executing, and an executing plan can be completed. Errs on any other transition
+    pub fn advance(&mut self, phase: Phase) -> Result<(), String> {
+        let allowed = matches!(
+            (self.phase, phase),
+            (Phase::Draft, Phase::Frozen)
+                | (Phase::Frozen, Phase::Draft)
+                | (Phase::Frozen, Phase::Executing)
+                | (Phase::Executing, Phase::Complete)
+        );
+        if !allowed {
+            return Err(format!(
+                "a Schedule that is {} can't become {}",
+                self.phase, phase
+            ));
+        }
+
+        self.phase = phase;
+        self.journal.push(Mutation::SetPhase { phase });
+        Ok(())
+    }
+
+    /// Make a plan-time change to a frozen or executing plan and record it in the journal with the reason it was made. Also works on drafts. Errs if the reason is empty, the change is a commitment, an observation, or a phase change, the Schedule is complete, or the change can't be applied
+    pub fn amend(&mut self, reason: &str, mutation: Mutation) -> Result<(), JsValue> {
+        if reason.trim().is_empty() {
+            return Err(JsValue::from_str("an amendment needs a reason"));
+        }
+        if self.phase == Phase::Complete {
+            return Err(JsValue::from_str(
+                "the Schedule is complete and can't be amended",
+            ));
+        }
+        let plan_time = !matches!(
+            mutation,
+            Mutation::CommitEvent { .. }
+                | Mutation::ObserveContingent { .. }
+                | Mutation::SplitEpisode { .. }
+                | Mutation::SetPhase { .. }
+                | Mutation::Amend { .. }
+        );
+        if !plan_time {
+            return Err(JsValue::from_str(
+                "only plan-time changes can be amendments. Commit events and observe or split Episodes directly",
+            ));
+        }
+
+        // apply the change as if authoring, then replace its journal entry with the amendment
+        let phase = self.phase;
+        let recorded = self.journal.len();
+        self.phase = Phase::Draft;
+        let applied = self.apply_journal(std::slice::from_ref(&mutation));
+        self.phase = phase;
+        self.journal.truncate(recorded);
+        applied?;
+
+        self.journal.push(Mutation::Amend {
+            reason: reason.to_string(),
+            mutation: Box::new(mutation),
+        });
+        Ok(())
+    }
+
+    /// Make a batch of changes that either all apply or are all undone. If `changes` errs or the Schedule no longer compiles afterwards, eg. because the constraints became inconsistent, the Schedule is put back the way it was and the error is returned
+    ///
+    /// ```
+    /// use temporal_networks::schedule::Schedule;
+    ///
+    /// let mut schedule = Schedule::new();
+    /// let first = schedule.add_episode(Some(vec![10., 20.]));
+    /// let second = schedule.add_episode(Some(vec![10., 20.]));
+    ///
+    /// // the second can't end before the first starts
+    /// let result = schedule.transaction(|s| -> Result<(), String> {
+    ///     s.update_interval(first.end(), second.start(), vec![0., 0.]);
+    ///     s.update_interval(second.end(), first.start(), vec![0., 5.]);
+    ///     Ok(())
+    /// });
+    /// assert!(result.is_err());
+    /// assert_eq!(schedule.constraints().len(), 2);
+    /// ```
+    pub fn transaction<T, E: From<String>>(
+        &mut self,
+        changes: impl FnOnce(&mut Schedule) -> Result<T, E>,
+    ) -> Result<T, E> {
+        self.begin_transaction()?;
+        match changes(self) {
+            Ok(value) => {
+                self.commit_transaction()?;
+                Ok(value)
+            }
+            Err(e) => {
+                self.rollback_transaction()?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Start a transaction. Errs if one is already open
+    pub fn begin_transaction(&mut self) -> Result<(), String> {
+        if self.transaction.is_some() {
+            return Err("a transaction is already open".to_string());
+        }
+        self.transaction = Some(Box::new(self.clone()));
+        Ok(())
+    }
+
+    /// Keep the changes made in the open transaction if the Schedule compiles, and roll them back otherwise. Errs if no transaction is open or the changes were rolled back
+    pub fn commit_transaction(&mut self) -> Result<(), String> {
+        if self.transaction.is_none() {
+            return Err("no transaction is open".to_string());
+        }
+        if let Err(e) = self.compile_with_cancel(&CancelToken::new()) {
+            self.rollback_transaction()?;
+            return Err(format!("the transaction was rolled back: {}", e));
+        }
+        self.transaction = None;
+        Ok(())
+    }
+
+    /// Undo every change made in the open transaction. Errs if no transaction is open
+    pub fn rollback_transaction(&mut self) -> Result<(), String> {
+        match self.transaction.take() {
+            Some(before) => {
+                *self = *before;
+                Ok(())
+            }
+            None => Err("no transaction is open".to_string()),
+        }
+    }
+
+    /// Errs with an explanation unless plan-time changes are allowed, ie. the Schedule is a draft
+    fn require_draft(&self, change: &str) -> Result<(), String> {
+        match self.phase {
+            Phase::Draft => Ok(()),
+            Phase::Complete => Err(format!("cannot {}. The Schedule is complete", change)),
+            phase => Err(format!(
+                "cannot {} while the Schedule is {}. Use `amend` to change the plan",
+                change, phase
+            )),
+        }
+    }
+
+    /// Check an interval passed in from JS for a plan-time change, defaulting to [0, 0]. Errs if the interval isn't `[lower, upper]` or the Schedule isn't a draft
+    fn plan_time_interval(&self, change: &str, interval: Vec<f64>) -> Result<Vec<f64>, JsValue> {
+        self.require_draft(change)
+            .map_err(|e| JsValue::from_str(&e))?;
+        Interval::try_from_slice(&interval).map_err(|e| JsValue::from_str(&e))?;
+        Ok(interval)
+    }
+
+    /// Errs unless events can be committed. Committing to a frozen plan starts executing it
+    fn require_executable(&mut self) -> Result<(), String> {
+        match self.phase {
+            Phase::Complete => Err("cannot commit events. The Schedule is complete".to_string()),
+            Phase::Frozen => self.advance(Phase::Executing),
+            _ => Ok(()),
+        }
+    }
+
+    /// Mark the Schedule as changed since the last compile, which also clears the last compile error
+    fn mark_dirty(&mut self) {
+        self.dirty = true;
+        self.compile_error = None;
+    }
+
+    /// Compile for a query. If compiling fails, keep answering from the last good compile instead of erring, see `stale`. A failed compile isn't retried until the Schedule changes. Errs if the Schedule has never compiled
+    fn compile_or_keep(&mut self) -> Result<(), JsValue> {
+        if self.dirty && self.compile_error.is_some() && self.compiled_before() {
+            return Ok(());
+        }
+        match self.compile_with_cancel(&CancelToken::new()) {
+            Err(Abort::Failed(_)) if self.compiled_before() => Ok(()),
+            result => result.map_err(|e| JsValue::from_str(&e.to_string())),
+        }
+    }
+
+    /// Whether or not there is a dispatchable graph from an earlier compile
+    fn compiled_before(&self) -> bool {
+        self.dispatchable.node_count() > 0
+    }
+
+    /// Run all-pairs shortest paths and rebuild the dispatchable graph. Leaves the last good dispatchable graph in place on failure
+    fn recompile(&mut self, token: &CancelToken) -> Result<(), Abort> {
+        // TODO: is it a problem if there are any detached Events/Episodes?
+
+        tracing::debug!(
+            events = self.stn.node_count(),
+            edges = self.stn.edge_count(),
+            "compile started"
+        );
+        let result = self.rebuild_dispatchable(token);
+        match &result {
+            Ok(()) => tracing::debug!(
+                edges = self.dispatchable.edge_count(),
+                commitments = self.committments.len(),
+                "compile finished"
+            ),
+            Err(Abort::Failed(e)) => tracing::warn!(reason = e.as_str(), "compile failed"),
+            Err(e) => tracing::debug!(reason = %e, "compile cancelled"),
+        }
+        result
+    }
+
+    fn rebuild_dispatchable(&mut self, token: &CancelToken) -> Result<(), Abort> {
+        self.check_limits().map_err(Abort::Failed)?;
+        self.apply_parameters().map_err(Abort::Failed)?;
+
+        // settle the order of mutually exclusive Episodes first
+        let sequenced;
+        let graph = if self.mutexes.is_empty() {
+            &self.stn
+        } else {
+            sequenced = self.sequence_mutexes().map_err(Abort::Failed)?;
+            &sequenced
+        };
+
+        // run all-pairs shortest paths
+        let (mut stats, changes) = self.choose_strategy(graph);
+        let inconsistent = || {
+            Abort::Failed(format!(
+                "the constraints are inconsistent. No times satisfy the constraints between events {:?}",
+                negative_cycle(graph).unwrap_or_default()
+            ))
+        };
+        let lazy = self.lazy_dispatchable;
+        let mut trace = self.trace_limit.map(Trace::new);
+        let keep = |matrix: DenseMatrix| {
+            if !matrix.consistent() {
+                Err(inconsistent())
+            } else if lazy {
+                Ok(Dispatchable::lazy(matrix))
+            } else {
+                Ok(Dispatchable::from_graph(dispatchable_graph(
+                    &matrix.to_map(),
+                )))
+            }
+        };
+        let dispatchable = match stats.strategy {
+            Strategy::FloydWarshall if trace.is_some() => keep(floyd_warshall_traced(
+                graph,
+                trace.as_mut().unwrap(),
+                token,
+            )?)?,
+            Strategy::FloydWarshall if graph.node_count() >= BLOCKED_MIN_EVENTS => {
+                keep(floyd_warshall_blocked(graph, BLOCK_SIZE, token)?)?
+            }
+            Strategy::FloydWarshall if lazy || graph.node_count() >= DENSE_MIN_EVENTS => {
+                keep(floyd_warshall_dense(graph, token)?)?
+            }
+            Strategy::FloydWarshall => {
+                let mappings = floyd_warshall_cancellable(graph, token)?;
+                if !mappings.consistent() {
+                    return Err(inconsistent());
+                }
+                Dispatchable::from_graph(dispatchable_graph(mappings.distances()))
+            }
+            Strategy::Johnson => match johnson_cancellable(graph, token) {
+                Ok(mappings) => Dispatchable::from_graph(dispatchable_graph(mappings.distances())),
+                Err(Abort::Failed(_)) => return Err(inconsistent()),
+                Err(e) => return Err(e),
+            },
+            Strategy::Incremental | Strategy::Bounded => {
+                let mut distances = self.dispatchable.to_map();
+                let nodes: Vec<EventID> = graph.nodes().collect();
+                let bounded = stats.strategy == Strategy::Bounded;
+                for (i, edge) in changes.iter().enumerate() {
+                    if token.is_cancelled() {
+                        return Err(Abort::Cancelled(Progress {
+                            completed: i,
+                            total: changes.len(),
+                        }));
+                    }
+                    tighten_edge(&mut distances, &nodes, *edge, bounded)
+                        .map_err(|_| inconsistent())?;
+                }
+                Dispatchable::from_graph(dispatchable_graph(&distances))
+            }
+        };
+        stats.changed = changes.len();
+        self.stats = stats;
+        self.compiled_graph = graph.clone();
+        self.trace = trace;
+
+        // replace the dispatchable graph, keeping the old one to diff against
+        self.previous_dispatchable = std::mem::replace(&mut self.dispatchable, dispatchable);
+        self.intervals.clear();
+        // mark not-dirty as soon as possible so nothing below recurses back into compiling
+        self.dirty = false;
+        self.compile_error = None;
+        self.roots = self.root_candidates();
+
+        // rebuild execution windows from the known committments in one pass over the new distances. Same as adding each commitment as a rigid edge from a t=0 event before running APSP, but without growing the graph or replaying commits one at a time
+        self.propagate_commitments();
+
+        Ok(())
+    }
+
+    /// Pick how to compile a distance graph within the compile budget. Without a budget, always Floyd-Warshall. Otherwise, if every edge is the same or tighter than at the last compile, only the tightened edges are propagated. If not, Floyd-Warshall runs unless it would go over the budget and Johnson's algorithm is cheaper. Returns the strategy with its estimated cost, and the tightened edges for an incremental compile
+    fn choose_strategy(
+        &self,
+        graph: &DiGraphMap<EventID, f64>,
+    ) -> (CompileStats, Vec<(EventID, EventID, f64)>) {
+        let events = graph.node_count();
+        let edges = graph.edge_count();
+        let n = events as f64;
+        let full = n.powi(3);
+        let stats = |strategy, steps: f64, within_budget| CompileStats {
+            strategy,
+            events,
+            edges,
+            changed: 0,
+            steps,
+            within_budget,
+        };
+
+        let budget = match self.compile_budget {
+            Some(ms) if self.trace_limit.is_none() => ms * STEPS_PER_MS,
+            _ => return (stats(Strategy::FloydWarshall, full, true), vec![]),
+        };
+
+        if let Some(changes) = self.tightened_edges(graph) {
+            let steps = changes.len() as f64 * n * n;
+            let strategy = if steps <= budget {
+                Strategy::Incremental
+            } else {
+                Strategy::Bounded
+            };
+            return (stats(strategy, steps, steps <= budget), changes);
+        }
+
+        // Bellman-Ford for the potentials, then Dijkstra from every event
+        let e = edges as f64;
+        let johnson = n * e + n * (e + n) * (n + 1.).log2();
+        if full <= budget || full <= johnson {
+            (stats(Strategy::FloydWarshall, full, full <= budget), vec![])
+        } else {
+            (stats(Strategy::Johnson, johnson, johnson <= budget), vec![])
+        }
+    }
+
+    /// The edges that are new or shorter than at the last compile, if the graph has the same events and no edge got longer or was removed. `None` if there is no good compile to build on
+    fn tightened_edges(
+        &self,
+        graph: &DiGraphMap<EventID, f64>,
+    ) -> Option<Vec<(EventID, EventID, f64)>> {
+        let before = &self.compiled_graph;
+        if !self.compiled_before()
+            || before.node_count() != graph.node_count()
+            || before.nodes().any(|n| !graph.contains_node(n))
+        {
+            return None;
+        }
+
+        let weight =
+            |g: &DiGraphMap<EventID, f64>, s, t| g.edge_weight(s, t).copied().unwrap_or(f64::MAX);
+        if before.all_edges().any(|(s, t, w)| weight(graph, s, t) > *w) {
+            return None;
+        }
+        Some(
+            graph
+                .all_edges()
+                .filter(|(s, t, w)| **w < weight(before, *s, *t))
+                .map(|(s, t, w)| (s, t, *w))
+                .collect(),
+        )
+    }
+
+    /// Snapshots of the execution windows after each commit, oldest first. Windows aren't recompiled, so they show what the Schedule reported at the time, eg. with limited propagation
+    pub fn window_history(&self) -> impl Iterator<Item = &WindowSnapshot> {
+        self.window_history.iter()
+    }
+
+    /// Get the windows that changed since a snapshot in the window history, or every window if `version` is `None`. Windows that changed because of edits since the snapshot are included too. Errs if the snapshot was dropped from the history or hasn't happened yet
+    pub fn delta_since(&mut self, version: Option<usize>) -> Result<WindowDelta, JsValue> {
+        let before = match version {
+            Some(v) => match self.window_history.iter().find(|s| s.sequence == v) {
+                Some(snapshot) => Some(snapshot.windows.clone()),
+                None => {
+                    return Err(JsValue::from_str(&format!(
+                        "snapshot {} isn't in the window history. Ask for a full sync",
+                        v
+                    )))
+                }
+            },
+            None => None,
+        };
+
+        let windows = self.time_windows()?;
+        let changed = windows
+            .into_iter()
+            .filter(|w| {
+                before
+                    .as_ref()
+                    .is_none_or(|b| b.get(&w.event) != Some(&Interval::new(w.earliest, w.latest)))
+            })
+            .collect();
+        let removed = before
+            .iter()
+            .flat_map(|b| b.keys())
+            .filter(|e| !self.execution_windows.contains_key(e))
+            .copied()
+            .collect();
+
+        Ok(WindowDelta {
+            since: version,
+            version: self.window_history.back().map(|s| s.sequence),
+            changed,
+            removed,
+        })
+    }
+
+    /// Get the execution window of every event in order of event ID
+    pub fn time_windows(&mut self) -> Result<Vec<TimeWindow>, JsValue> {
+        self.compile_or_keep()?;
+
+        let windows = self
+            .execution_windows
+            .iter()
+            .map(|(event, window)| self.time_window(*event, *window))
+            .collect();
+
+        Ok(windows)
+    }
+
+    fn time_window(&self, event: EventID, window: Interval) -> TimeWindow {
+        TimeWindow {
+            event,
+            earliest: window.lower(),
+            latest: window.upper(),
+            nominal: self.preference(event).choose(window),
+            converged: window.converged(),
+        }
+    }
+
+    /// Get the execution window of every event and the intervals between `pairs` of events without compiling, for plans too big to hold the distance between every pair of events. Only the rows and columns of the distance matrix that are needed are computed, and intervals are computed as many rows at a time as fit in `Limits::max_matrix_bytes`. Commitments narrow the windows but not the intervals, like a compile. Nothing is cached, so every call pays for its shortest paths again. Errs if an event isn't in the Schedule, the constraints are inconsistent, or there's no first event
+    pub fn streamed_bounds(
+        &mut self,
+        pairs: &[(EventID, EventID)],
+    ) -> Result<StreamedBounds, String> {
+        if let Some(missing) = pairs
+            .iter()
+            .flat_map(|(s, t)| [*s, *t])
+            .find(|e| !self.contains_event(*e))
+        {
+            return Err(format!("could not find event {}", missing));
+        }
+
+        self.apply_parameters()?;
+        let mut graph = if self.mutexes.is_empty() {
+            self.stn.clone()
+        } else {
+            self.sequence_mutexes()?
+        };
+        let inconsistent = |graph: &DiGraphMap<EventID, f64>| {
+            format!(
+                "the constraints are inconsistent. No times satisfy the constraints between events {:?}",
+                negative_cycle(graph).unwrap_or_default()
+            )
+        };
+        let rows = DistanceRows::new(&graph).map_err(|_| inconsistent(&graph))?;
+        let root = streamed_root(&rows)?;
+
+        // windows include commitments, as rigid edges from the root. Intervals don't, like `interval`
+        let start = self.committments.get(&root).copied().unwrap_or(0.);
+        let committed = if self.committments.keys().any(|e| *e != root) {
+            for (event, time) in self.committments.iter() {
+                for (s, t, w) in [(root, *event, time - start), (*event, root, start - time)] {
+                    let w = graph.edge_weight(s, t).map_or(w, |old| old.min(w));
+                    graph.add_edge(s, t, w);
+                }
+            }
+            Some(DistanceRows::new(&graph).map_err(|_| inconsistent(&graph))?)
+        } else {
+            None
+        };
+
+        let nodes = rows.nodes().to_vec();
+        let committed = committed.as_ref().unwrap_or(&rows);
+        let from_root = committed.row(root).unwrap_or_default();
+        let to_root = committed.column(root).unwrap_or_default();
+        let windows = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, event)| {
+                let earliest = if to_root[i] >= f64::MAX {
+                    -f64::MAX
+                } else {
+                    start - to_root[i]
+                };
+                let latest = if from_root[i] >= f64::MAX {
+                    f64::MAX
+                } else {
+                    start + from_root[i]
+                };
+                self.time_window(*event, Interval::new(earliest, latest))
+            })
+            .collect();
+
+        // both directions of every pair, a tile of rows at a time
+        let mut sources: Vec<EventID> = pairs.iter().flat_map(|(s, t)| [*s, *t]).collect();
+        sources.sort_unstable();
+        sources.dedup();
+        let tile = match self.limits.max_matrix_bytes {
+            Some(bytes) => bytes / (nodes.len().max(1) * std::mem::size_of::<f64>()),
+            None => sources.len(),
+        };
+        let wanted: BTreeSet<(EventID, EventID)> = pairs
+            .iter()
+            .flat_map(|(s, t)| [(*s, *t), (*t, *s)])
+            .collect();
+        let mut distances = BTreeMap::new();
+        rows.for_each_tile(&sources, tile, &CancelToken::new(), |sources, tile| {
+            for (source, row) in sources.iter().zip(tile.chunks_exact(nodes.len())) {
+                for (_, target) in wanted.range((*source, EventID::MIN)..=(*source, EventID::MAX)) {
+                    if let Ok(j) = nodes.binary_search(target) {
+                        distances.insert((*source, *target), row[j]);
+                    }
+                }
+            }
+        })
+        .map_err(|e| e.to_string())?;
+
+        let intervals = pairs
+            .iter()
+            .map(|(s, t)| {
+                let lower = distances[&(*t, *s)];
+                // avoid returning -0
+                let lower = if lower == 0. { -0. } else { lower };
+                Interval::new(-lower, distances[&(*s, *t)])
+            })
+            .collect();
+
+        Ok(StreamedBounds {
+            root,
+            windows,
+            intervals,
+        })
+    }
+
+    /// Attach arbitrary data to an Episode. Errs if the Episode isn't in the Schedule
+    pub fn set_episode_metadata(
+        &mut self,
+        episode: &Episode,
+        metadata: Value,
+    ) -> Result<(), JsValue> {
+        if !self.stn.contains_node(episode.start()) || !self.stn.contains_node(episode.end()) {
+            return Err(JsValue::from_str(&format!(
+                "Episode ({}, {}) is not in the Schedule",
+                episode.start(),
+                episode.end()
+            )));
+        }
+
+        self.episode_metadata.insert(*episode, metadata.clone());
+        self.journal.push(Mutation::SetEpisodeMetadata {
+            start: episode.start(),
+            end: episode.end(),
+            metadata,
+        });
+        Ok(())
+    }
+
+    /// Get the data attached to an Episode
+    pub fn episode_metadata(&self, episode: &Episode) -> Option<&Value> {
+        self.episode_metadata.get(episode)
+    }
+
+    /// Attach arbitrary data to the constraint between two events. It is reported with the constraint no matter which direction it is queried from. Errs if there is no constraint between the events
+    pub fn set_constraint_metadata(
+        &mut self,
+        source: EventID,
+        target: EventID,
+        metadata: Value,
+    ) -> Result<(), JsValue> {
+        if !self.stn.contains_edge(source, target) && !self.stn.contains_edge(target, source) {
+            return Err(JsValue::from_str(&format!(
+                "there is no constraint between events {} and {}",
+                source, target
+            )));
+        }
+
+        self.constraint_metadata.remove(&(target, source));
+        self.constraint_metadata
+            .insert((source, target), metadata.clone());
+        self.journal.push(Mutation::SetConstraintMetadata {
+            source,
+            target,
+            metadata,
+        });
+        Ok(())
+    }
+
+    /// Get the data attached to the constraint between two events, in either direction
+    pub fn constraint_metadata(&self, source: EventID, target: EventID) -> Option<&Value> {
+        self.constraint_metadata
+            .get(&(source, target))
+            .or_else(|| self.constraint_metadata.get(&(target, source)))
+    }
+
+    /// Record where the constraint between two events came from. Errs if there is no constraint between the events
+    pub fn set_constraint_provenance(
+        &mut self,
+        source: EventID,
+        target: EventID,
+        provenance: Provenance,
+    ) -> Result<(), JsValue> {
+        if !self.stn.contains_edge(source, target) && !self.stn.contains_edge(target, source) {
+            return Err(JsValue::from_str(&format!(
+                "there is no constraint between events {} and {}",
+                source, target
+            )));
+        }
+
+        self.constraint_provenance.remove(&(target, source));
+        self.constraint_provenance
+            .insert((source, target), provenance.clone());
+        self.journal.push(Mutation::SetConstraintProvenance {
+            source,
+            target,
+            provenance,
+        });
+        Ok(())
+    }
+
+    /// Get where the constraint between two events came from, in either direction
+    pub fn constraint_provenance(&self, source: EventID, target: EventID) -> Option<&Provenance> {
+        self.constraint_provenance
+            .get(&(source, target))
+            .or_else(|| self.constraint_provenance.get(&(target, source)))
+    }
+
+    /// Cite the sources of the constraints between consecutive events, eg. `" (from EVA-2B line 4; EVA-2B line 9)"`. Empty if none of them have provenance
+    fn cite(&self, pairs: &[(EventID, EventID)]) -> String {
+        let mut sources: Vec<String> = vec![];
+        for (a, b) in pairs.iter() {
+            if let Some(p) = self.constraint_provenance(*a, *b) {
+                let p = p.to_string();
+                if !sources.contains(&p) {
+                    sources.push(p);
+                }
+            }
+        }
+        if sources.is_empty() {
+            String::new()
+        } else {
+            format!(" (from {})", sources.join("; "))
+        }
+    }
+
+    /// Look for the first event in the Schedule. Uses the roots cached by the last compile, so they may be stale if the Schedule has changed since. Call `compile` first to get fresh ones
+    pub fn find_root(&self) -> Root {
+        let mut roots = self.roots.clone();
+
+        match roots.len() {
+            0 => Root::Missing,
+            1 => Root::Unique(roots.remove(0)),
+            _ => Root::Ambiguous(roots),
+        }
+    }
+
+    /// Every event in the dispatchable graph that can't happen after any other event. Assumes the Schedule has been compiled
+    fn root_candidates(&self) -> Vec<EventID> {
+        // all incoming edges should be <= 0 for the first node
+        let events = self.dispatchable.events();
+        events
+            .iter()
+            .copied()
+            .filter(|s| {
+                events
+                    .iter()
+                    .all(|t| self.dispatchable.distance(*t, *s).is_none_or(|w| w <= 0.))
+            })
+            .collect()
+    }
+
+    /// Every mutation made to this Schedule through its public API, in order
+    pub fn journal(&self) -> &[Mutation] {
+        &self.journal
+    }
+
+    /// Handle a contingent Episode that finished outside its declared duration by extending the duration to include the observation, then observing it. Reports whether the Schedule is still consistent and controllable and how downstream windows moved. Also works for durations within bounds, in which case nothing is extended. Errs if the Episode isn't contingent or its start hasn't been committed
+    pub fn repair(
+        &mut self,
+        episode: &Episode,
+        duration: f64,
+        policy: RepairPolicy,
+    ) -> Result<RepairReport, JsValue> {
+        let declared = self.get_duration(episode);
+        let extended = Interval::new(
+            declared.lower().min(duration),
+            declared.upper().max(duration),
+        );
+
+        let before: BTreeMap<EventID, Interval> = self
+            .time_windows()?
+            .iter()
+            .map(|w| (w.event, Interval::new(w.earliest, w.latest)))
+            .collect();
+
+        let mut repaired = self.clone();
+        if extended != declared {
+            repaired.plan_change(
+                &format!(
+                    "contingent Episode ({}, {}) took {}, outside its declared duration {}",
+                    episode.start(),
+                    episode.end(),
+                    duration,
+                    declared
+                ),
+                Mutation::UpdateInterval {
+                    source: episode.start(),
+                    target: episode.end(),
+                    interval: extended,
+                },
+            )?;
+        }
+        // windows may widen, so rebuild them from the commitments
+        repaired.reset_windows();
+        repaired.observe_contingent(episode, duration)?;
+        repaired.compile()?;
+
+        let mut changes = vec![];
+        let mut infeasible = vec![];
+        for (event, after) in repaired.execution_windows.iter() {
+            if !after.is_valid() {
+                infeasible.push(*event);
+            }
+            if repaired.committments.contains_key(event) {
+                continue;
+            }
+            match before.get(event) {
+                Some(b) if b != after => changes.push(WindowChange {
+                    event: *event,
+                    before: *b,
+                    after: *after,
+                }),
+                _ => (),
+            }
+        }
+
+        let report = RepairReport {
+            declared,
+            extended,
+            consistent: repaired.is_consistent(),
+            strongly_controllable: repaired.is_strongly_controllable(),
+            changes,
+            infeasible,
+        };
+
+        if policy == RepairPolicy::Extend {
+            *self = repaired;
+        }
+        Ok(report)
+    }
+
+    /// Describe the uncertain duration of an Episode. Errs if the Episode isn't in the Schedule
+    pub fn set_distribution(
+        &mut self,
+        episode: &Episode,
+        distribution: Distribution,
+    ) -> Result<(), JsValue> {
+        if !self.stn.contains_edge(episode.start(), episode.end()) {
+            return Err(JsValue::from_str(&format!(
+                "Episode ({}, {}) is not in the Schedule",
+                episode.start(),
+                episode.end()
+            )));
+        }
+        distribution.validate().map_err(|e| JsValue::from_str(&e))?;
+
+        self.distributions.insert(*episode, distribution);
+        self.journal.push(Mutation::SetDistribution {
+            start: episode.start(),
+            end: episode.end(),
+            distribution,
+        });
+        Ok(())
+    }
+
+    /// The distribution over an Episode's duration, if it has one
+    pub fn distribution(&self, episode: &Episode) -> Option<Distribution> {
+        self.distributions.get(episode).copied()
+    }
+
+    /// Split a total acceptable risk across Episodes with distributions with Iterative Risk Allocation [1]. Each Episode's risk becomes deterministic duration bounds. Risk starts out evenly split. Then each Episode whose bounds could be wider without losing strong controllability gives up the risk it doesn't need, which is handed to the Episodes that are limiting the Schedule. By Boole's inequality, the probability that any duration falls outside of its bounds is at most the total risk
+    ///
+    /// [1] Ono, M., & Williams, B. C. (2008). Iterative risk allocation: A new approach to robust model predictive control with a joint chance constraint. 47th IEEE Conference on Decision and Control.
+    pub fn allocate_risk(&self, total_risk: f64) -> Result<RiskAllocation, JsValue> {
+        if !(total_risk > 0. && total_risk < 1.) {
+            return Err(JsValue::from_str(&format!(
+                "total risk must be between 0 and 1 exclusive, got {}",
+                total_risk
+            )));
+        }
+
+        // how aggressively inactive Episodes give up risk
+        const ALPHA: f64 = 0.5;
+        const ITERATIONS: usize = 20;
+
+        let uncertain: Vec<(Episode, Distribution)> = self
+            .distributions
+            .iter()
+            .filter(|(e, _)| self.stn.contains_edge(e.start(), e.end()))
+            .map(|(e, d)| (*e, *d))
+            .collect();
+        let mut risks = vec![total_risk / uncertain.len().max(1) as f64; uncertain.len()];
+
+        if self.risk_feasible(&uncertain, &risks) {
+            for _ in 0..ITERATIONS {
+                let needed: Vec<f64> = (0..uncertain.len())
+                    .map(|i| self.needed_risk(&uncertain, &risks, i))
+                    .collect();
+                let active: Vec<usize> = (0..uncertain.len())
+                    .filter(|i| needed[*i] >= risks[*i] * (1. - 1e-6))
+                    .collect();
+                if active.is_empty() || active.len() == uncertain.len() {
+                    break;
+                }
+
+                for i in 0..uncertain.len() {
+                    if !active.contains(&i) {
+                        risks[i] = ALPHA * risks[i] + (1. - ALPHA) * needed[i];
+                    }
+                }
+                let residual = total_risk - risks.iter().sum::<f64>();
+                for i in active.iter() {
+                    risks[*i] += residual / active.len() as f64;
+                }
+            }
+        }
+
+        let assignments = uncertain
+            .iter()
+            .zip(risks.iter())
+            .map(|((episode, distribution), risk)| RiskAssignment {
+                start: episode.start(),
+                end: episode.end(),
+                risk: *risk,
+                bounds: distribution.bounds(*risk),
+            })
+            .collect();
+
+        Ok(RiskAllocation {
+            total_risk,
+            assignments,
+            feasible: self.risk_feasible(&uncertain, &risks),
+        })
+    }
+
+    /// Estimate the expected time and variance of every event PERT-style. Durations come from each Episode's `Distribution`. Episodes without one are treated as uniform over their duration, or as their lower bound if the duration is unbounded. Other precedence constraints add their lower bound with no variance. An event's expected time is the latest expected arrival over its predecessors, and its variance is the variance of that path. Events with no predecessors start at t=0 and committed events are fixed at their commitments. Events caught in a cycle of precedences are left out
+    pub fn expected_times(&self) -> Vec<ExpectedTime> {
+        let incoming = self.forward_durations();
+
+        let mut expected: BTreeMap<EventID, (f64, f64)> = BTreeMap::new();
+        for event in self.precedence_layers().into_iter().flatten() {
+            let estimate = match self.committments.get(&event) {
+                Some(t) => (*t, 0.),
+                None => incoming
+                    .get(&event)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|(source, duration)| {
+                        expected
+                            .get(source)
+                            .map(|(m, v)| (m + duration.mean(), v + duration.variance()))
+                    })
+                    .fold((0., 0.), |best, e| if e.0 > best.0 { e } else { best }),
+            };
+            expected.insert(event, estimate);
+        }
+
+        expected
+            .into_iter()
+            .map(|(event, (mean, variance))| ExpectedTime {
+                event,
+                mean,
+                variance,
+            })
+            .collect()
+    }
+
+    /// For every event, the events that must come before it and the distribution of the time between them. Episodes use their `Distribution` if they have one, or are uniform over their duration if it's bounded. Every other precedence constraint is a fixed lag of its lower bound
+    fn forward_durations(&self) -> BTreeMap<EventID, Vec<(EventID, Distribution)>> {
+        let mut incoming: BTreeMap<EventID, Vec<(EventID, Distribution)>> = BTreeMap::new();
+        for c in self.constraints().iter() {
+            // orient every constraint forward in time
+            let (source, target, interval) = if c.interval.lower() >= 0. {
+                (c.source, c.target, c.interval)
+            } else if c.interval.upper() <= 0. {
+                (c.target, c.source, -c.interval)
+            } else {
+                continue;
+            };
+
+            let episode = Episode(source, target);
+            let duration = match self.distributions.get(&episode) {
+                Some(d) => *d,
+                None if self.episodes.contains(&episode) && interval.upper() < f64::MAX => {
+                    Distribution::Uniform {
+                        lower: interval.lower(),
+                        upper: interval.upper(),
+                    }
+                }
+                None => Distribution::Uniform {
+                    lower: interval.lower(),
+                    upper: interval.lower(),
+                },
+            };
+            incoming.entry(target).or_default().push((source, duration));
+        }
+        incoming
+    }
+
+    /// Run a Monte Carlo simulation of the Schedule. Each run samples every Episode duration, starts every event as early as its predecessors allow, and then checks every authored constraint. The same seed always gives the same result. Committed events are fixed at their commitments
+    pub fn simulate(&self, runs: usize, seed: u64) -> Simulation {
+        let incoming = self.forward_durations();
+        let order: Vec<EventID> = self.precedence_layers().into_iter().flatten().collect();
+        let constraints = self.constraints();
+        let mut rng = Rng::new(seed);
+
+        let mut makespans = Vec::with_capacity(runs);
+        let mut successes = 0;
+        for _ in 0..runs {
+            let mut times: BTreeMap<EventID, f64> = BTreeMap::new();
+            for event in order.iter() {
+                let time = match self.committments.get(event) {
+                    Some(t) => *t,
+                    None => incoming
+                        .get(event)
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|(source, duration)| {
+                            times
+                                .get(source)
+                                .map(|t| t + duration.sample(&mut rng).max(0.))
+                        })
+                        .fold(0., f64::max),
+                };
+                times.insert(*event, time);
+            }
+
+            let satisfied = constraints.iter().all(|c| {
+                match (times.get(&c.source), times.get(&c.target)) {
+                    // allow for floating point error
+                    (Some(s), Some(t)) => {
+                        let elapsed = t - s;
+                        elapsed >= c.interval.lower() - 1e-9 && elapsed <= c.interval.upper() + 1e-9
+                    }
+                    _ => true,
+                }
+            });
+            if satisfied {
+                successes += 1;
+            }
+            makespans.push(times.values().cloned().fold(0., f64::max));
+        }
+
+        makespans.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let percentile = |p: f64| -> f64 {
+            if makespans.is_empty() {
+                return 0.;
+            }
+            let i = ((makespans.len() - 1) as f64 * p).round() as usize;
+            makespans[i]
+        };
+
+        Simulation {
+            runs,
+            success_rate: if runs == 0 {
+                0.
+            } else {
+                successes as f64 / runs as f64
+            },
+            mean_makespan: if runs == 0 {
+                0.
+            } else {
+                makespans.iter().sum::<f64>() / runs as f64
+            },
+            p05_makespan: percentile(0.05),
+            p50_makespan: percentile(0.5),
+            p95_makespan: percentile(0.95),
+        }
+    }
+
+    /// Aggregate the extracted schedule into fixed-width buckets at each resolution. Buckets are aligned to multiples of the resolution and cover every grouped Episode. Overlapping Episodes in the same group, eg. a step and its substeps, are only counted once. Episodes that aren't in the extracted schedule are ignored
+    pub fn zoom(
+        &mut self,
+        resolutions: &[f64],
+        groups: &BTreeMap<String, Vec<Episode>>,
+    ) -> Result<Vec<Zoom>, JsValue> {
+        if let Some(r) = resolutions.iter().find(|r| !r.is_finite() || **r <= 0.) {
+            return Err(JsValue::from_str(&format!(
+                "resolutions must be positive, got {}",
+                r
+            )));
+        }
+
+        let times = self.extract_schedule()?;
+
+        // the union of the time each group spends busy, as sorted, disjoint spans
+        let busy: BTreeMap<&String, Vec<(f64, f64)>> = groups
+            .iter()
+            .map(|(group, episodes)| {
+                let mut spans: Vec<(f64, f64)> = episodes
+                    .iter()
+                    .filter_map(|e| match (times.get(&e.start()), times.get(&e.end())) {
+                        (Some(s), Some(t)) if t > s => Some((*s, *t)),
+                        _ => None,
+                    })
+                    .collect();
+                spans.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+                let mut merged: Vec<(f64, f64)> = Vec::with_capacity(spans.len());
+                for (s, t) in spans {
+                    match merged.last_mut() {
+                        Some(last) if s <= last.1 => last.1 = last.1.max(t),
+                        _ => merged.push((s, t)),
+                    }
+                }
+                (group, merged)
+            })
+            .collect();
+
+        let first = busy
+            .values()
+            .flatten()
+            .map(|s| s.0)
+            .fold(f64::MAX, f64::min);
+        let last = busy
+            .values()
+            .flatten()
+            .map(|s| s.1)
+            .fold(f64::MIN, f64::max);
+
+        let zooms = resolutions
+            .iter()
+            .map(|resolution| {
+                let mut buckets = vec![];
+                if first <= last {
+                    let origin = (first / resolution).floor() * resolution;
+                    let count = ((last - origin) / resolution).ceil() as usize;
+                    for i in 0..count {
+                        let start = origin + i as f64 * resolution;
+                        let end = start + resolution;
+                        let occupancy = busy
+                            .iter()
+                            .map(|(group, spans)| {
+                                let covered: f64 = spans
+                                    .iter()
+                                    .map(|(s, t)| (t.min(end) - s.max(start)).max(0.))
+                                    .sum();
+                                ((*group).clone(), covered / resolution)
+                            })
+                            .collect();
+                        buckets.push(Bucket {
+                            start,
+                            end,
+                            occupancy,
+                        });
+                    }
+                }
+                Zoom {
+                    resolution: *resolution,
+                    buckets,
+                }
+            })
+            .collect();
+
+        Ok(zooms)
+    }
+
+    /// Keep the Episodes in each group from overlapping, since one person can't be in two places at once. Every pair of Episodes in a group that isn't already in order is ordered if it can be: if only one order is feasible, that one, otherwise whichever is nominally first based on the interval between their starts, preferring the order they're listed. Each new constraint is propagated before the next pair is checked. Pairs that overlap whichever goes first are reported as conflicts and left alone. Errs if the Schedule is inconsistent or isn't a draft
+    pub fn level(&mut self, groups: &BTreeMap<String, Vec<Episode>>) -> Result<Leveling, JsValue> {
+        self.require_draft("level resources")
+            .map_err(|e| JsValue::from_str(&e))?;
+        let mut leveling = Leveling::default();
+
+        for (group, episodes) in groups.iter() {
+            for (i, a) in episodes.iter().enumerate() {
+                for b in episodes.iter().skip(i + 1) {
+                    self.compile()?;
+                    if self.has_negative_cycle() {
+                        return Err(JsValue::from_str(
+                            "the Schedule is inconsistent, so it can't be leveled",
+                        ));
+                    }
+
+                    let distance = |source: EventID, target: EventID| {
+                        self.dispatchable
+                            .edge_weight(source, target)
+                            .copied()
+                            .unwrap_or(f64::MAX)
+                    };
+                    // already in order if the gap between them can't be negative
+                    if -distance(b.start(), a.end()) >= 0. || -distance(a.start(), b.end()) >= 0. {
+                        continue;
+                    }
+                    // an order is feasible if the gap can be at least 0
+                    let a_first = distance(a.end(), b.start()) >= 0.;
+                    let b_first = distance(b.end(), a.start()) >= 0.;
+
+                    let (first, second) = match (a_first, b_first) {
+                        (true, false) => (a, b),
+                        (false, true) => (b, a),
+                        (true, true) => {
+                            // middle of the window for b's start relative to a's start
+                            let midpoint = (distance(a.start(), b.start())
+                                - distance(b.start(), a.start()))
+                                / 2.;
+                            if midpoint >= 0. {
+                                (a, b)
+                            } else {
+                                (b, a)
+                            }
+                        }
+                        (false, false) => {
+                            leveling.conflicts.push(Overlap {
+                                group: group.clone(),
+                                first: (a.start(), a.end()),
+                                second: (b.start(), b.end()),
+                            });
+                            continue;
+                        }
+                    };
+
+                    self.tighten(first.end(), second.start(), Interval::new(0., f64::MAX));
+                    leveling.ordered.push(Overlap {
+                        group: group.clone(),
+                        first: (first.start(), first.end()),
+                        second: (second.start(), second.end()),
+                    });
+                }
+            }
+        }
+
+        Ok(leveling)
+    }
+
+    /// Find gaps where a new Episode of a duration fits without pushing the horizon event. Without an actor, the new Episode could start after the root or after any Episode ends, and has to finish before the horizon. With one, it has to fit between two of the actor's Episodes, taken in order of their earliest starts, or before the first or after the last. A gap is as long as the most time the constraints and commitments allow between its ends, and it's a candidate if it fits the duration's lower bound. Candidates are ranked by slack, most first, then by anchor. Errs if an actor's event isn't in the Schedule or the Schedule is inconsistent
+    pub fn suggest_insertion(
+        &mut self,
+        duration: Interval,
+        actor: Option<&[Episode]>,
+    ) -> Result<Vec<Insertion>, JsValue> {
+        for event in actor
+            .unwrap_or_default()
+            .iter()
+            .flat_map(|e| [e.start(), e.end()])
+        {
+            if !self.contains_event(event) {
+                return Err(JsValue::from_str(&format!(
+                    "could not find event {}",
+                    event
+                )));
+            }
+        }
+        self.compile_or_keep()?;
+        if self.has_negative_cycle() {
+            return Err(JsValue::from_str(
+                "the Schedule is inconsistent, so nothing can be inserted",
+            ));
+        }
+
+        let root = self.root();
+        let horizon = self.horizon_event()?;
+        let gaps: Vec<(Option<EventID>, Option<EventID>)> = match actor {
+            None => root
+                .into_iter()
+                .chain(self.episodes.iter().map(|e| e.end()))
+                .map(|anchor| (Some(anchor), horizon))
+                .collect(),
+            Some(episodes) => {
+                let mut lane = episodes.to_vec();
+                let earliest = |e: &Episode| {
+                    self.execution_windows
+                        .get(&e.start())
+                        .map_or(f64::MIN, |w| w.lower())
+                };
+                lane.sort_by(|a, b| earliest(a).total_cmp(&earliest(b)).then(a.cmp(b)));
+                let starts = lane.iter().map(|e| Some(e.start())).chain(Some(horizon));
+                std::iter::once(root)
+                    .chain(lane.iter().map(|e| Some(e.end())))
+                    .zip(starts)
+                    .collect()
+            }
+        };
+
+        let mut insertions: Vec<Insertion> = gaps
+            .into_iter()
+            .filter_map(|(anchor, before)| {
+                let anchor = anchor?;
+                if before == Some(anchor) {
+                    return None;
+                }
+                let room = before.map_or(f64::MAX, |b| self.room(anchor, b));
+                if room < duration.lower() {
+                    return None;
+                }
+                let slack = if room >= f64::MAX {
+                    f64::MAX
+                } else {
+                    room - duration.lower()
+                };
+                Some(Insertion {
+                    anchor,
+                    before,
+                    offsets: Interval::new(0., slack),
+                    slack,
+                })
+            })
+            .collect();
+        insertions.sort_by(|a, b| b.slack.total_cmp(&a.slack).then(a.anchor.cmp(&b.anchor)));
+        insertions.dedup();
+        Ok(insertions)
+    }
+
+    /// The most time there can be from one event to a later one, given the constraints and the execution windows. Assumes the Schedule has been compiled
+    fn room(&self, from: EventID, to: EventID) -> f64 {
+        let distance = self.dispatchable.distance(from, to).unwrap_or(f64::MAX);
+        match (
+            self.execution_windows.get(&from),
+            self.execution_windows.get(&to),
+        ) {
+            (Some(f), Some(t)) if t.upper() < f64::MAX && f.lower() > f64::MIN => {
+                distance.min(t.upper() - f.lower())
+            }
+            _ => distance,
+        }
+    }
+
+    /// Bind every parameter in a set of assumptions at once
+    pub fn bind_parameters(&mut self, parameters: &BTreeMap<String, Interval>) {
+        for (name, interval) in parameters.iter() {
+            self.bind_parameter(name, vec![interval.lower(), interval.upper()]);
+        }
+    }
+
+    /// The parameter an Episode's duration comes from, if any
+    pub fn duration_parameter(&self, episode: &Episode) -> Option<&str> {
+        self.duration_parameters.get(episode).map(|p| p.as_str())
+    }
+
+    /// Write the bound parameters into the durations of the Episodes that use them. Errs on the first Episode whose parameter isn't bound
+    fn apply_parameters(&mut self) -> Result<(), String> {
+        for (episode, parameter) in self.duration_parameters.iter() {
+            let interval = self.parameters.get(parameter).ok_or_else(|| {
+                format!(
+                    "Episode ({}, {}) uses parameter \"{}\", which isn't bound",
+                    episode.start(),
+                    episode.end(),
+                    parameter
+                )
+            })?;
+            self.stn
+                .add_edge(episode.start(), episode.end(), interval.upper());
+            self.stn
+                .add_edge(episode.end(), episode.start(), -interval.lower());
+        }
+        Ok(())
+    }
+
+    /// The authored constraints plus an ordering for every mutex. Orders are chosen by backtracking search, trying the first Episode of each mutex first, so the result is the first consistent combination
+    fn sequence_mutexes(&self) -> Result<DiGraphMap<EventID, f64>, String> {
+        let mut graph = self.stn.clone();
+        if negative_cycle(&graph).is_some() {
+            return Err("the constraints are inconsistent".to_string());
+        }
+        if sequence(&mut graph, &self.mutexes) {
+            Ok(graph)
+        } else {
+            Err(
+                "no order of the mutually exclusive Episodes is consistent with the constraints"
+                    .to_string(),
+            )
+        }
+    }
+
+    /// Keep events out of absolute blackout windows by clipping their execution windows with constraints from the root. `events` filters which events are kept out, or every event if `None`. Times are relative to the root, which is assumed to happen at t=0 if it hasn't been committed. Each clip is propagated before the windows are checked again, since it can push other filtered events into a blackout. Events can still happen exactly at the start or end of a blackout. Events that would be left with no valid time aren't clipped and are reported as infeasible instead. Outside of a draft, the clips are recorded as amendments
+    pub fn apply_blackouts(
+        &mut self,
+        events: Option<&[EventID]>,
+        blackouts: &[Interval],
+    ) -> Result<BlackoutReport, JsValue> {
+        let events: Vec<EventID> = match events {
+            Some(events) => events.to_vec(),
+            None => self.stn.nodes().collect(),
+        };
+        for event in events.iter() {
+            if !self.stn.contains_node(*event) {
+                return Err(JsValue::from_str(&format!(
+                    "event {} is not in the Schedule",
+                    event
+                )));
+            }
+        }
+        if let Some(b) = blackouts.iter().find(|b| !b.is_valid()) {
+            return Err(JsValue::from_str(&format!(
+                "blackout {} ends before it starts",
+                b
+            )));
+        }
+
+        let (root, start, before) = self
+            .windows_from_root()
+            .map_err(|e| JsValue::from_str(&e))?;
+        let mut report = BlackoutReport::default();
+        let mut windows = before.clone();
+        // every clip moves a window edge past a blackout, so this terminates
+        loop {
+            let mut changed = false;
+            for event in events.iter() {
+                let window = windows[event];
+                let clipped = clip(window, blackouts);
+                if clipped == window || report.infeasible.contains(event) {
+                    continue;
+                }
+                if !clipped.is_valid() || *event == root {
+                    report.infeasible.push(*event);
+                    continue;
+                }
+
+                let authored = Interval::new(
+                    self.stn.edge_weight(*event, root).map_or(-f64::MAX, |l| -l),
+                    self.stn
+                        .edge_weight(root, *event)
+                        .copied()
+                        .unwrap_or(f64::MAX),
+                );
+                let interval =
+                    authored & Interval::new(clipped.lower() - start, clipped.upper() - start);
+                self.plan_change(
+                    &format!("keep event {} out of blackouts", event),
+                    Mutation::UpdateInterval {
+                        source: root,
+                        target: *event,
+                        interval,
+                    },
+                )?;
+                changed = true;
+            }
+            if !changed {
+                break;
+            }
+            windows = self
+                .windows_from_root()
+                .map_err(|e| JsValue::from_str(&e))?
+                .2;
+        }
+
+        for (event, after) in windows.iter() {
+            if !after.is_valid() && !report.infeasible.contains(event) {
+                report.infeasible.push(*event);
+            }
+            if before[event] != *after {
+                report.clipped.push(WindowChange {
+                    event: *event,
+                    before: before[event],
+                    after: *after,
+                });
+            }
+        }
+        report.split = events
+            .iter()
+            .filter(|e| {
+                let w = windows[e];
+                blackouts
+                    .iter()
+                    .any(|b| w.lower() < b.lower() && b.upper() < w.upper())
+            })
+            .copied()
+            .collect();
+        report.infeasible.sort_unstable();
+        Ok(report)
+    }
+
+    /// The root, the time it happens, and the execution window of every event, committing the root at t=0 in a copy if it hasn't been committed. Every commitment is propagated fully
+    pub(crate) fn windows_from_root(
+        &self,
+    ) -> Result<(EventID, f64, BTreeMap<EventID, Interval>), String> {
+        let mut s = self.clone();
+        s.compile_with_cancel(&CancelToken::new())
+            .map_err(|e| e.to_string())?;
+        let root = s
+            .root()
+            .ok_or_else(|| "cannot find the first event in the Schedule".to_string())?;
+        let start = s.committments.get(&root).copied().unwrap_or(0.);
+        Ok((root, start, s.windows_from(root)?))
+    }
+
+    /// The execution window of every event with `origin` at t=0 unless it's been committed, in a copy. Every commitment is propagated fully
+    pub(crate) fn windows_from(
+        &self,
+        origin: EventID,
+    ) -> Result<BTreeMap<EventID, Interval>, String> {
+        let mut s = self.clone();
+        s.compile_with_cancel(&CancelToken::new())
+            .map_err(|e| e.to_string())?;
+        s.committments.entry(origin).or_insert(0.);
+        s.propagate_commitments();
+        Ok(s.execution_windows)
+    }
+
+    /// Make a plan-time change. In a draft it's applied like any other change. Otherwise it's recorded as an amendment with the reason
+    fn plan_change(&mut self, reason: &str, mutation: Mutation) -> Result<(), JsValue> {
+        if self.phase == Phase::Draft {
+            self.apply_journal(std::slice::from_ref(&mutation))
+        } else {
+            self.amend(reason, mutation)
+        }
+    }
+
+    /// Intersect the authored constraint between two events with another interval, or add the interval if there isn't one
+    fn tighten(&mut self, source: EventID, target: EventID, interval: Interval) {
+        let upper = self.stn.edge_weight(source, target).copied();
+        let lower = self.stn.edge_weight(target, source).map(|l| -l);
+        let existing = Interval::new(lower.unwrap_or(-f64::MAX), upper.unwrap_or(f64::MAX));
+        let tightened = existing & interval;
+        self.update_interval(source, target, vec![tightened.lower(), tightened.upper()]);
+    }
+
+    /// Set the duration of every Episode in a risk allocation to its bounds
+    pub fn apply_risk_allocation(&mut self, allocation: &RiskAllocation) {
+        for a in allocation.assignments.iter() {
+            self.update_interval(a.start, a.end, vec![a.bounds.lower(), a.bounds.upper()]);
+        }
+    }
+
+    /// Whether or not the Schedule is strongly controllable when each uncertain Episode's duration comes from its risk
+    fn risk_feasible(&self, uncertain: &[(Episode, Distribution)], risks: &[f64]) -> bool {
+        let mut s = self.clone();
+        for ((episode, distribution), risk) in uncertain.iter().zip(risks.iter()) {
+            let bounds = distribution.bounds(*risk);
+            s.stn
+                .add_edge(episode.start(), episode.end(), bounds.upper());
+            s.stn
+                .add_edge(episode.end(), episode.start(), -bounds.lower());
+        }
+        s.mark_dirty();
+        s.is_strongly_controllable()
+    }
+
+    /// The smallest risk an uncertain Episode can take while the others keep theirs, found by bisection
+    fn needed_risk(&self, uncertain: &[(Episode, Distribution)], risks: &[f64], i: usize) -> f64 {
+        let mut trial = risks.to_vec();
+        let (mut low, mut high) = (0., risks[i]);
+        for _ in 0..20 {
+            trial[i] = (low + high) / 2.;
+            if self.risk_feasible(uncertain, &trial) {
+                high = trial[i];
+            } else {
+                low = trial[i];
+            }
+        }
+        high
+    }
+
+    /// Forget every execution window so they are rebuilt from the commitments on the next compile
+    fn reset_windows(&mut self) {
+        for window in self.execution_windows.values_mut() {
+            *window = Interval(-f64::MAX, f64::MAX);
+        }
+        self.mark_dirty();
+    }
+
+    /// The contingent Episode ending at an event, if any
+    fn contingent_episode_ending_at(&self, event: EventID) -> Option<Episode> {
+        self.contingent.iter().find(|e| e.end() == event).copied()
+    }
+
+    /// Build an STN over the controllable events that is consistent if and only if this Schedule is strongly controllable. Each contingent end is replaced by its start plus the worst-case duration for every constraint it takes part in [1]. Commitments to controllable events are kept. Errs if a contingent Episode starts at the end of another contingent Episode
+    ///
+    /// [1] Vidal, T., & Fargier, H. (1999). Handling contingency in temporal constraint networks: from consistency to controllabilities. Journal of Experimental & Theoretical Artificial Intelligence, 11(1), 23–45.
+    fn strong_reduction(&self) -> Result<Schedule, String> {
+        // contingent end -> (start, [lower, upper] duration)
+        // observed Episodes are no longer uncertain. Their ends are committed like any other event
+        let links: BTreeMap<EventID, (EventID, Interval)> = self
+            .contingent
+            .iter()
+            .filter(|e| !self.observations.contains_key(e))
+            .map(|e| (e.end(), (e.start(), self.get_duration(e))))
+            .collect();
+
+        for (start, _) in links.values() {
+            if links.contains_key(start) {
+                return Err(format!(
+                    "contingent Episodes can't be chained: event {} is both a contingent start and end",
+                    start
+                ));
+            }
+        }
+
+        // an event expressed as a controllable event plus a [lower, upper] offset
+        let substitute = |event: EventID| match links.get(&event) {
+            Some((start, duration)) => (*start, *duration),
+            None => (event, Interval::new(0., 0.)),
+        };
+
+        let mut reduced = Schedule::new();
+        for event in self.stn.nodes().filter(|n| !links.contains_key(n)) {
+            reduced.add_event(event);
+        }
+
+        for (source, target, weight) in self.stn.all_edges() {
+            if self.contingent.contains(&Episode(source, target))
+                || self.contingent.contains(&Episode(target, source))
+            {
+                // the contingent link itself
+                continue;
+            }
+
+            // target - source <= weight must hold for every outcome, so assume the source finishes early and the target late
+            let (s, source_offset) = substitute(source);
+            let (t, target_offset) = substitute(target);
+            let w = if *weight >= f64::MAX {
+                f64::MAX
+            } else {
+                weight + source_offset.lower() - target_offset.upper()
+            };
+
+            if s == t {
+                if w < 0. {
+                    // contingent Episodes sharing a start contradict each other
+                    reduced.stn.add_edge(s, s, w);
+                }
+                continue;
+            }
+
+            let tightest = match reduced.stn.edge_weight(s, t) {
+                Some(existing) => existing.min(w),
+                None => w,
+            };
+            reduced.stn.add_edge(s, t, tightest);
+        }
+
+        reduced.committments = self
+            .committments
+            .iter()
+            .filter(|(e, _)| !links.contains_key(e))
+            .map(|(e, t)| (*e, *t))
+            .collect();
+        reduced.mark_dirty();
+
+        Ok(reduced)
+    }
+
+    /// Choose a fixed time for every controllable event that works no matter how long contingent Episodes take. Times follow each event's `Preference`. Errs if the Schedule is not strongly controllable
+    pub fn conformant_schedule(&self) -> Result<BTreeMap<EventID, f64>, JsValue> {
+        let mut reduced = self.strong_reduction().map_err(|e| JsValue::from_str(&e))?;
+        if !reduced.is_consistent() {
+            return Err(JsValue::from_str(
+                "the Schedule is not strongly controllable. No fixed schedule works for every contingent duration",
+            ));
+        }
+
+        reduced.preferences = self.preferences.clone();
+        reduced.extract_schedule()
+    }
+
+    /// Merge two journals of mutations made to copies of this Schedule. See `journal::merge`
+    pub fn merge_journals(&self, ours: &[Mutation], theirs: &[Mutation]) -> Merge {
+        journal::merge(self.next_event_id(), ours, theirs)
+    }
+
+    /// Replay mutations in order. Each one is recorded in this Schedule's journal as it is applied, so replaying a journal onto a new Schedule reproduces both the Schedule and the journal. Stops at the first mutation that can't be applied, eg. a plan-time change to a Schedule that isn't a draft
+    pub fn apply_journal(&mut self, entries: &[Mutation]) -> Result<(), JsValue> {
+        for entry in entries.iter() {
+            match entry {
+                Mutation::CreateEvent { event } => {
+                    self.require_draft("create an event")
+                        .map_err(|e| JsValue::from_str(&e))?;
+                    self.insert_event(*event);
+                }
+                Mutation::AddEpisode {
+                    start,
+                    end,
+                    duration,
+                } => {
+                    self.require_draft("add an Episode")
+                        .map_err(|e| JsValue::from_str(&e))?;
+                    if self.stn.contains_node(*start) || self.stn.contains_node(*end) {
+                        return Err(JsValue::from_str(&format!(
+                            "cannot replay Episode ({}, {}). Its events already exist",
+                            start, end
+                        )));
+                    }
+                    let episode = Episode(self.add_event(*start), self.add_event(*end));
+                    self.link_episode(episode, *duration);
+                    self.journal.push(entry.clone());
+                }
+                Mutation::AddConstraint {
+                    source,
+                    target,
+                    interval,
+                } => self.add_constraint(
+                    *source,
+                    *target,
+                    Some(vec![interval.lower(), interval.upper()]),
+                )?,
+                Mutation::UpdateInterval {
+                    source,
+                    target,
+                    interval,
+                } => {
+                    let interval = self.plan_time_interval(
+                        "change a constraint",
+                        vec![interval.lower(), interval.upper()],
+                    )?;
+                    self.update_interval(*source, *target, interval)
+                }
+                Mutation::RemoveConstraint { source, target } => {
+                    self.remove_constraint(*source, *target)?
+                }
+                Mutation::RemoveConstraints { source, target } => self.remove_constraints(
+                    &Episode(source.0, source.1),
+                    &Episode(target.0, target.1),
+                )?,
+                Mutation::FreeEpisode { start, end } => {
+                    self.free_episode(&Episode(*start, *end))?
+                }
+                Mutation::CommitEvent { event, time } => self.record_commit(*event, *time)?,
+                Mutation::MarkContingent { start, end } => {
+                    self.set_contingent(&Episode(*start, *end))?
+                }
+                Mutation::ObserveContingent {
+                    start,
+                    end,
+                    duration,
+                } => self.observe_contingent(&Episode(*start, *end), *duration)?,
+                Mutation::SplitEpisode {
+                    start,
+                    end,
+                    elapsed,
+                    pause,
+                    resume,
+                } => {
+                    if self.stn.contains_node(*pause) || self.stn.contains_node(*resume) {
+                        return Err(JsValue::from_str(&format!(
+                            "cannot replay the split of Episode ({}, {}). Events {} and {} already exist",
+                            start, end, pause, resume
+                        )));
+                    }
+                    self.split(&Episode(*start, *end), *elapsed, *pause, *resume)?;
+                }
+                Mutation::AddMutex { first, second } => {
+                    self.add_mutex(&Episode(first.0, first.1), &Episode(second.0, second.1))?
+                }
+                Mutation::SetDurationParameter {
+                    start,
+                    end,
+                    parameter,
+                } => self.set_duration_parameter(&Episode(*start, *end), parameter)?,
+                Mutation::BindParameter { name, interval } => {
+                    let interval = self.plan_time_interval(
+                        "bind a parameter",
+                        vec![interval.lower(), interval.upper()],
+                    )?;
+                    self.bind_parameter(name, interval)
+                }
+                Mutation::SetEventAlias { event, alias } => self.set_event_alias(*event, alias)?,
+                Mutation::SetEpisodeAlias { start, end, alias } => {
+                    self.set_episode_alias(&Episode(*start, *end), alias)?
+                }
+                Mutation::RemoveAlias { alias } => {
+                    self.remove_alias(alias);
+                }
+                Mutation::SetEpisodeMetadata {
+                    start,
+                    end,
+                    metadata,
+                } => self.set_episode_metadata(&Episode(*start, *end), metadata.clone())?,
+                Mutation::SetConstraintMetadata {
+                    source,
+                    target,
+                    metadata,
+                } => self.set_constraint_metadata(*source, *target, metadata.clone())?,
+                Mutation::SetConstraintProvenance {
+                    source,
+                    target,
+                    provenance,
+                } => self.set_constraint_provenance(*source, *target, provenance.clone())?,
+                Mutation::SetPreference {
+                    start,
+                    end,
+                    preference,
+                } => self.set_preference(&Episode(*start, *end), *preference),
+                Mutation::SetDistribution {
+                    start,
+                    end,
+                    distribution,
+                } => self.set_distribution(&Episode(*start, *end), *distribution)?,
+                Mutation::SetVirtualRoot { event } => {
+                    self.virtual_root = Some(*event);
+                    self.journal.push(entry.clone());
+                }
+                Mutation::SetDefaults { defaults } => self.set_defaults(*defaults),
+                Mutation::SetLimits { limits } => self.set_limits(*limits),
+                Mutation::SetCompileBudget { budget } => self.set_compile_budget(*budget),
+                Mutation::SetLazyDispatchable { lazy } => self.set_lazy_dispatchable(*lazy),
+                Mutation::SetTrace { limit } => self.set_trace(*limit),
+                Mutation::SetHistoryLimit { limit } => self.set_history_limit(*limit),
+                Mutation::SetStrictCommits { strict } => self.set_strict_commits(*strict),
+                Mutation::SetAutoRoot { enabled } => self.set_auto_root(*enabled),
+                Mutation::SetPropagation { propagation } => self.set_propagation(*propagation),
+                Mutation::SetOutputBounds { bounds } => self.set_output_bounds(*bounds),
+                Mutation::SetOutputDecimals { decimals } => self.set_output_decimals(*decimals),
+                Mutation::SetPhase { phase } => {
+                    self.advance(*phase).map_err(|e| JsValue::from_str(&e))?
+                }
+                Mutation::Amend { reason, mutation } => {
+                    self.amend(reason, mutation.as_ref().clone())?
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Add an event with a specific ID, eg. one registered from an external payload. Does nothing but mark the Schedule dirty if the event already exists
+    pub(crate) fn insert_event(&mut self, event: EventID) -> EventID {
+        if let Err(e) = self.require_draft("create an event") {
+            panic!("{}", e);
+        }
+        self.journal.push(Mutation::CreateEvent { event });
+        self.add_event(event)
+    }
+
+    /// The ID the next new event will get
+    fn next_event_id(&self) -> EventID {
+        self.stn.nodes().max().map_or(0, |n| n + 1)
+    }
+
+    /// Split an Episode at `elapsed` with the given IDs for the new events, see `split_episode`
+    fn split(
+        &mut self,
+        episode: &Episode,
+        elapsed: f64,
+        pause: EventID,
+        resume: EventID,
+    ) -> Result<Episode, JsValue> {
+        self.require_executable()
+            .map_err(|e| JsValue::from_str(&e))?;
+        let (start, end) = (episode.start(), episode.end());
+        if !self.episodes.contains(episode) {
+            return Err(JsValue::from_str(&format!(
+                "Episode ({}, {}) is not in the Schedule",
+                start, end
+            )));
+        }
+        let started = match self.committments.get(&start) {
+            Some(t) => *t,
+            None => {
+                return Err(JsValue::from_str(&format!(
+                    "Episode ({}, {}) can't be split because it hasn't started",
+                    start, end
+                )))
+            }
+        };
+        if self.committments.contains_key(&end) {
+            return Err(JsValue::from_str(&format!(
+                "Episode ({}, {}) can't be split because it has already ended",
+                start, end
+            )));
+        }
+        let duration = self.get_duration(episode);
+        if !(0. ..=duration.upper()).contains(&elapsed) {
+            return Err(JsValue::from_str(&format!(
+                "Episode ({}, {}) can't be split after {}. It lasts {}",
+                start, end, elapsed, duration
+            )));
+        }
+
+        self.stn.remove_edge(start, end);
+        self.stn.remove_edge(end, start);
+        self.episodes.remove(episode);
+        self.add_event(pause);
+        self.add_event(resume);
+        self.link_episode(Episode(start, pause), Interval::new(elapsed, elapsed));
+        let rest = Episode(resume, end);
+        let remaining = (duration.lower() - elapsed).max(0.);
+        let upper = if duration.upper() >= f64::MAX {
+            f64::MAX
+        } else {
+            duration.upper() - elapsed
+        };
+        self.link_episode(rest, Interval::new(remaining, upper));
+        self.stn.add_edge(pause, resume, f64::MAX);
+        self.stn.add_edge(resume, pause, 0.);
+
+        // the rest carries on as the original Episode
+        if self.contingent.remove(episode) {
+            self.contingent.insert(rest);
+        }
+        if let Some(metadata) = self.episode_metadata.remove(episode) {
+            self.episode_metadata.insert(rest, metadata);
+        }
+        for aliased in self.episode_aliases.values_mut() {
+            if aliased == episode {
+                *aliased = rest;
+            }
+        }
+        for (first, second) in self.mutexes.iter_mut() {
+            for e in [first, second] {
+                if e == episode {
+                    *e = rest;
+                }
+            }
+        }
+        // neither describes the rest of the Episode
+        self.distributions.remove(episode);
+        self.duration_parameters.remove(episode);
+
+        self.journal.push(Mutation::SplitEpisode {
+            start,
+            end,
+            elapsed,
+            pause,
+            resume,
+        });
+        self.commit(pause, started + elapsed)?;
+        Ok(rest)
+    }
+
+    /// Add an event with a specific ID without recording it in the journal
+    fn add_event(&mut self, event: EventID) -> EventID {
+        if !self.stn.contains_node(event) {
+            self.execution_windows
+                .insert(event, Interval(-std::f64::MAX, std::f64::MAX));
+            self.stn.add_node(event);
+        }
+
+        self.mark_dirty();
+        event
+    }
+
+    /// Apply many commitments in order with a single propagation pass at the end. Each entry is checked against the commitments accepted before it, so an entry is rejected if the event doesn't exist, the time isn't finite, or the time conflicts with an earlier commitment. Committing an event again replaces its earlier time. Only errs if the Schedule doesn't compile
+    pub fn commit_events(&mut self, log: Vec<(EventID, f64)>) -> Result<CommitReport, JsValue> {
+        self.require_executable()
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.compile()?;
+
+        let mut report = CommitReport::default();
+        for (event, time) in log.into_iter() {
+            match self.check_commitment(event, time) {
+                Ok(()) => {
+                    self.committments.insert(event, time);
+                    self.journal.push(Mutation::CommitEvent { event, time });
+                    report.applied.push(event);
+                }
+                Err(reason) => {
+                    tracing::warn!(event, time, reason = reason.as_str(), "commitment rejected");
+                    report.rejected.push(RejectedCommit {
+                        event,
+                        time,
+                        reason,
+                    })
+                }
+            }
+        }
+
+        self.propagate_commitments();
+        report.infeasible = self.infeasible_events();
+        if !report.infeasible.is_empty() {
+            tracing::warn!(infeasible = ?report.infeasible, "commitment left events infeasible");
+        }
+        if !report.applied.is_empty() {
+            let commits = report
+                .applied
+                .iter()
+                .map(|e| (*e, self.committments[e]))
+                .collect();
+            self.record_windows(commits);
+        }
+        Ok(report)
+    }
+
+    /// Whether or not an event can be committed at a time given the existing commitments. Assumes the Schedule has been compiled
+    fn check_commitment(&self, event: EventID, time: f64) -> Result<(), String> {
+        if !self.stn.contains_node(event) {
+            return Err(format!("event {} is not in the Schedule", event));
+        }
+        if self.contingent_episode_ending_at(event).is_some() {
+            return Err(format!(
+                "event {} ends a contingent Episode. Observe its duration instead",
+                event
+            ));
+        }
+        if !time.is_finite() {
+            return Err(format!("{} is not a valid time", time));
+        }
+        if self.strict_commits {
+            self.check_order(event, time)?;
+        }
+
+        for (other, other_time) in self.committments.iter() {
+            if *other == event {
+                continue;
+            }
+
+            let upper = self.dispatchable.distance(*other, event);
+            let lower = self.dispatchable.distance(event, *other);
+            if let (Some(upper), Some(lower)) = (upper, lower) {
+                let allowed = Interval::new(other_time - lower, other_time + upper);
+                if !allowed.contains(time) {
+                    return Err(format!(
+                        "committing at {} conflicts with event {} at {}, which requires {}",
+                        time, other, other_time, allowed
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The shortest distance from one event to another, unbounded if there's no edge. Errs if either event isn't in the Schedule
+    fn precedence_distance(&mut self, source: EventID, target: EventID) -> Result<f64, JsValue> {
+        for event in [source, target] {
+            if !self.contains_event(event) {
+                return Err(JsValue::from_str(&format!(
+                    "could not find event {}",
+                    event
+                )));
+            }
+        }
+        self.compile_or_keep()?;
+        Ok(self
+            .dispatchable
+            .distance(source, target)
+            .unwrap_or(f64::MAX))
+    }
+
+    /// Whether or not a set of events can all be committed at the same time, given the existing commitments and the constraints between them. Assumes the Schedule has been compiled
+    fn check_simultaneous(&self, events: &[EventID], time: f64) -> Result<(), String> {
+        for (i, event) in events.iter().enumerate() {
+            match self.committments.get(event) {
+                Some(committed) if *committed != time => {
+                    return Err(format!(
+                        "cannot commit event {} with {:?}: it's already committed at {}",
+                        event, events, committed
+                    ));
+                }
+                _ => {}
+            }
+            self.check_commitment(*event, time)
+                .map_err(|e| format!("cannot commit event {} with {:?}: {}", event, events, e))?;
+
+            for other in events[i + 1..].iter() {
+                let upper = self.dispatchable.distance(*event, *other);
+                let lower = self.dispatchable.distance(*other, *event).map(|l| -l);
+                let interval = Interval::new(lower.unwrap_or(-f64::MAX), upper.unwrap_or(f64::MAX));
+                if !interval.contains(0.) {
+                    return Err(format!(
+                        "events {} and {} can't happen at the same time. They must be {} apart",
+                        event, other, interval
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether or not a commitment comes no earlier than every committed event that must precede the event, ie. every event it can't happen before. Assumes the Schedule has been compiled
+    fn check_order(&self, event: EventID, time: f64) -> Result<(), String> {
+        for (other, other_time) in self.committments.iter() {
+            if *other == event || time >= *other_time {
+                continue;
+            }
+
+            // the other event must precede if the shortest path back to it is never positive
+            if self
+                .dispatchable
+                .distance(event, *other)
+                .is_some_and(|w| w <= 0.)
+            {
+                return Err(format!(
+                    "committing event {} at {} is earlier than event {} at {}, which must precede it",
+                    event, time, other, other_time
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Reject commitments that are earlier than a committed event that must precede them, eg. because the wrong event was clicked, instead of narrowing windows around them. Applies to `commit_event` and `commit_events`. Off by default
+    pub fn set_strict_commits(&mut self, strict: bool) {
+        self.strict_commits = strict;
+        self.journal.push(Mutation::SetStrictCommits { strict });
+    }
+
+    /// Rebuild every execution window from the commitments in one pass. Assumes the Schedule has been compiled
+    fn propagate_commitments(&mut self) {
+        for (event, window) in self.execution_windows.iter_mut() {
+            *window = match self.committments.get(event) {
+                Some(t) => Interval::new(*t, *t),
+                None => Interval(-f64::MAX, f64::MAX),
+            };
+        }
+
+        for (committed, time) in self.committments.iter() {
+            for (event, window) in self.execution_windows.iter_mut() {
+                if self.committments.contains_key(event) {
+                    continue;
+                }
+
+                let upper = self.dispatchable.distance(*committed, *event);
+                let lower = self.dispatchable.distance(*event, *committed);
+                if let (Some(upper), Some(lower)) = (upper, lower) {
+                    // bounds_i = bounds_i ^ (v + time_event_to_neighbor)
+                    *window &= Interval::new(*time, *time) + Interval::new(-lower, upper);
+                }
+            }
+        }
+        tracing::debug!(
+            commitments = self.committments.len(),
+            windows = self.execution_windows.len(),
+            "propagated commitments"
+        );
+    }
+
+    /// Choose how far `commit_event` propagates commitments. Compiling, including the compile a commit triggers after an edit, and `commit_events` always propagate fully
+    pub fn set_propagation(&mut self, propagation: Propagation) {
+        self.propagation = propagation;
+        self.journal.push(Mutation::SetPropagation { propagation });
+    }
+
+    /// The events whose windows a commitment to `event` updates under the propagation strategy. Assumes the Schedule has been compiled
+    fn propagation_targets(&self, event: EventID) -> Vec<EventID> {
+        match self.propagation {
+            Propagation::Full => self
+                .dispatchable
+                .events()
+                .into_iter()
+                .filter(|n| self.dispatchable.distance(event, *n).is_some())
+                .collect(),
+            Propagation::Horizon(horizon) => self
+                .dispatchable
+                .events()
+                .into_iter()
+                .filter(|n| self.dispatchable.distance(event, *n).is_some())
+                // the earliest the neighbor can happen after the event
+                .filter(|n| {
+                    self.dispatchable
+                        .distance(*n, event)
+                        .is_some_and(|d| -d <= horizon)
+                })
+                .collect(),
+            Propagation::Hops(hops) => {
+                let mut reached: BTreeSet<EventID> = BTreeSet::new();
+                let mut frontier = vec![event];
+                for _ in 0..hops {
+                    frontier = frontier
+                        .iter()
+                        .flat_map(|e| self.stn.neighbors(*e))
+                        .filter(|n| *n != event && reached.insert(*n))
+                        .collect();
+                }
+                reached.into_iter().collect()
+            }
+        }
+    }
+
+    /// Choose how infinite bounds are written in serialized outputs
+    pub fn set_output_bounds(&mut self, output_bounds: OutputBounds) {
+        self.output_bounds = output_bounds;
+        self.journal.push(Mutation::SetOutputBounds {
+            bounds: output_bounds,
+        });
+    }
+
+    /// Round serialized outputs to some decimal places, or stop rounding with `None`. Only outputs are rounded, never the math behind them
+    pub fn set_output_decimals(&mut self, decimals: Option<u32>) {
+        self.output_decimals = decimals;
+        self.journal.push(Mutation::SetOutputDecimals { decimals });
+    }
+
+    /// Serialize a value for JS, rounding it and writing infinite bounds according to the output settings
+    fn output<T: Serialize>(&self, value: &T) -> Result<JsValue, JsValue> {
+        let mut value =
+            serde_json::to_value(value).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        if let Some(decimals) = self.output_decimals {
+            round_output(&mut value, decimals);
+        }
+        self.output_bounds.apply(&mut value);
+        to_js(&value)
+    }
+
+    /// An interval as `[lower, upper]` numbers, rounded like serialized outputs
+    fn lower_upper(&self, interval: Interval) -> Vec<f64> {
+        [interval.lower(), interval.upper()]
+            .iter()
+            .map(|v| self.output_decimals.map_or(*v, |d| round_to(*v, d)))
+            .collect()
+    }
+
+    /// Get every external ID attached to an event, in alphabetical order
+    pub fn aliases_of(&self, event: EventID) -> Vec<String> {
+        self.event_aliases
+            .iter()
+            .filter(|(_, e)| **e == event)
+            .map(|(a, _)| a.clone())
+            .collect()
+    }
+
+    /// Get every constraint authored between two events, including Episode durations. Each pair of events is reported once, in the direction it was first constrained
+    pub fn constraints(&self) -> Vec<Constraint> {
+        let mut seen = BTreeSet::new();
+        let mut constraints = vec![];
+
+        for (source, target, upper) in self.stn.all_edges() {
+            if !seen.insert((source.min(target), source.max(target))) {
+                continue;
+            }
+
+            let lower = match self.stn.edge_weight(target, source) {
+                Some(l) if *l == 0. => 0.,
+                Some(l) => -*l,
+                None => -f64::MAX,
+            };
+            constraints.push(Constraint {
+                source,
+                target,
+                interval: Interval::new(lower, *upper),
+                metadata: self
+                    .constraint_metadata(source, target)
+                    .or_else(|| self.episode_metadata(&Episode(source, target)))
+                    .or_else(|| self.episode_metadata(&Episode(target, source)))
+                    .cloned(),
+                provenance: self.constraint_provenance(source, target).cloned(),
+            });
+        }
+
+        constraints
+    }
+
+    /// Get every edge of the distance graph built from the Episodes and constraints, labeled with the bound it encodes, eg. to check the STN encoding of a plan by hand. An interval [lower, upper] from a to b becomes an edge a → b weighing `upper` and an edge b → a weighing `-lower`. Constraints are in the direction they were first constrained, like `constraints`, and a bound that was never set has no edge
+    pub fn labeled_edges(&self) -> Vec<LabeledEdge> {
+        let mut edges = vec![];
+        for c in self.constraints() {
+            let (source, target) = (c.source, c.target);
+            let episode = [Episode(source, target), Episode(target, source)]
+                .iter()
+                .copied()
+                .find(|e| self.episodes.contains(e));
+
+            let (upper, lower) = match episode {
+                Some(e) => {
+                    let kind = if self.contingent.contains(&e) {
+                        "contingent Episode"
+                    } else {
+                        "Episode"
+                    };
+                    let name = format!("{} ({}, {})", kind, e.start(), e.end());
+                    (
+                        (
+                            e.start(),
+                            e.end(),
+                            EdgeMeaning::EpisodeUpper,
+                            format!("upper bound of {}", name),
+                        ),
+                        (
+                            e.end(),
+                            e.start(),
+                            EdgeMeaning::EpisodeLower,
+                            format!("negative lower bound of {}", name),
+                        ),
+                    )
+                }
+                None => {
+                    let name = format!("the constraint from {} to {}", source, target);
+                    (
+                        (
+                            source,
+                            target,
+                            EdgeMeaning::ConstraintUpper,
+                            format!("upper bound of {}", name),
+                        ),
+                        (
+                            target,
+                            source,
+                            EdgeMeaning::ConstraintLower,
+                            format!("negative lower bound of {}", name),
+                        ),
+                    )
+                }
+            };
+            for (source, target, meaning, label) in [upper, lower] {
+                if let Some(weight) = self.stn.edge_weight(source, target) {
+                    edges.push(LabeledEdge {
+                        source,
+                        target,
+                        weight: *weight,
+                        meaning,
+                        label,
+                    });
+                }
+            }
+        }
+        edges
+    }
+
+    /// Compile and certify that the plan is consistent. Errs if the Schedule doesn't compile
+    pub fn feasibility_certificate(&mut self) -> Result<FeasibilityCertificate, String> {
+        self.compile_with_cancel(&CancelToken::new())
+            .map_err(|e| e.to_string())?;
+
+        let mut certificate = FeasibilityCertificate {
+            engine: env!("CARGO_PKG_NAME").to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            inputs: self.inputs_hash(),
+            distances: hash_graph(Sha256::new(), &self.dispatchable),
+            events: self.stn.node_count(),
+            edges: self.stn.edge_count(),
+            stamp: String::new(),
+        };
+        certificate.stamp = certificate.digest();
+        Ok(certificate)
+    }
+
+    /// Check that a certificate is unedited, came from this engine version, and matches this Schedule's inputs and compiled distances
+    pub fn verify_certificate(
+        &mut self,
+        certificate: &FeasibilityCertificate,
+    ) -> Result<(), String> {
+        if !certificate.stamp_matches() {
+            return Err("the certificate's stamp doesn't match its contents".to_string());
+        }
+        let current = self.feasibility_certificate()?;
+        if (&certificate.engine, &certificate.version) != (&current.engine, &current.version) {
+            return Err(format!(
+                "the certificate is from {} {}, not {} {}",
+                certificate.engine, certificate.version, current.engine, current.version
+            ));
+        }
+        if certificate.inputs != current.inputs {
+            return Err("the certificate is for different inputs".to_string());
+        }
+        if certificate.distances != current.distances {
+            return Err("the certificate is for a different distance matrix".to_string());
+        }
+        Ok(())
+    }
+
+    /// Create an N+1 x N+1 matrix of strings representing the compiled distances, where N is the number of events. The first row and column are headers: the event's first alias if it has one, otherwise its ID. The cell at row i and column j is the distance from event i to event j, written in the `Display` format of intervals, so unbounded distances are `"∞"` by default.
+    ///
+    /// Two events where the second happens [4, 5] after the first would look like so:
+    ///
+    /// `[ [ "",   "0",  "1" ],
+    ///    [ "0",  "0",  "5" ],
+    ///    [ "1", "-4",  "0" ] ]`
+    pub fn dump_constraint_table(&mut self) -> Result<Vec<Vec<String>>, String> {
+        self.compile_with_cancel(&CancelToken::new())
+            .map_err(|e| e.to_string())?;
+
+        let format = display_format();
+        let mut events: Vec<EventID> = self.stn.nodes().collect();
+        events.sort_unstable();
+        let header = |event: EventID| {
+            self.aliases_of(event)
+                .into_iter()
+                .next()
+                .unwrap_or_else(|| event.to_string())
+        };
+
+        let mut table = vec![std::iter::once(String::new())
+            .chain(events.iter().map(|e| header(*e)))
+            .collect::<Vec<String>>()];
+        for i in events.iter() {
+            let mut row = vec![header(*i)];
+            for j in events.iter() {
+                let distance = match self.dispatchable.edge_weight(*i, *j) {
+                    Some(d) => *d,
+                    None if i == j => 0.,
+                    None => f64::MAX,
+                };
+                row.push(format.time(distance));
+            }
+            table.push(row);
+        }
+        Ok(table)
+    }
+
+    /// Hash the plan and the commitments made against it
+    fn inputs_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.hash().as_bytes());
+        for (event, time) in self.committments.iter() {
+            hasher.update(event.to_le_bytes());
+            hasher.update(time.to_bits().to_le_bytes());
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// A stable hex SHA-256 of the plan: the events, the authored constraints with bound parameters applied, parameterized durations, contingent Episodes, and mutually exclusive Episodes. Two Schedules with the same plan hash the same however the constraints were added, and compiling, committing, or attaching metadata doesn't change it. Event IDs are part of the plan
+    pub fn hash(&self) -> String {
+        let mut graph = self.stn.clone();
+        let mut hasher = Sha256::new();
+
+        let mut events: Vec<EventID> = graph.nodes().collect();
+        events.sort_unstable();
+        for event in events {
+            hasher.update(event.to_le_bytes());
+        }
+        hasher.update([0]);
+        for (episode, parameter) in self.duration_parameters.iter() {
+            hasher.update(episode.0.to_le_bytes());
+            hasher.update(episode.1.to_le_bytes());
+            hasher.update(parameter.as_bytes());
+            hasher.update([0]);
+            // the same as compiling would
+            if let Some(interval) = self.parameters.get(parameter) {
+                graph.add_edge(episode.start(), episode.end(), interval.upper());
+                graph.add_edge(episode.end(), episode.start(), -interval.lower());
+            }
+        }
+        hasher.update([0]);
+        for episode in self.contingent.iter() {
+            hasher.update(episode.0.to_le_bytes());
+            hasher.update(episode.1.to_le_bytes());
+        }
+        hasher.update([0]);
+        for (a, b) in self.mutexes.iter() {
+            for event in [a.0, a.1, b.0, b.1].iter() {
+                hasher.update(event.to_le_bytes());
+            }
+        }
+        hasher.update([0]);
+        hash_graph(hasher, &graph)
+    }
+
+    /// Get the authored constraint between two events, oriented from source to target, alongside the interval implied by every constraint. Errs if either event isn't in the Schedule
+    pub fn constraint_between(
+        &mut self,
+        source: EventID,
+        target: EventID,
+    ) -> Result<ConstraintBetween, JsValue> {
+        for event in [source, target].iter() {
+            if !self.stn.contains_node(*event) {
+                return Err(JsValue::from_str(&format!(
+                    "event {} is not in the Schedule",
+                    event
+                )));
+            }
+        }
+        self.compile_or_keep()?;
+
+        let upper = self.stn.edge_weight(source, target).copied();
+        // avoid returning -0
+        let lower = self
+            .stn
+            .edge_weight(target, source)
+            .map(|l| if *l == 0. { 0. } else { -l });
+        // an unbounded side isn't a bound, even if there's an edge for it
+        let bounds = [upper, lower]
+            .iter()
+            .filter(|b| b.is_some_and(|b| b.abs() < f64::MAX))
+            .count();
+        let authored = if upper.is_some() || lower.is_some() {
+            Some(Constraint {
+                source,
+                target,
+                interval: Interval::new(lower.unwrap_or(-f64::MAX), upper.unwrap_or(f64::MAX)),
+                metadata: self.constraint_metadata(source, target).cloned(),
+                provenance: self.constraint_provenance(source, target).cloned(),
+            })
+        } else {
+            None
+        };
+
+        let implied_upper = self.dispatchable.edge_weight(source, target).copied();
+        let implied_lower =
+            self.dispatchable
+                .edge_weight(target, source)
+                .map(|l| if *l == 0. { 0. } else { -l });
+        let implied = if implied_upper.is_some() || implied_lower.is_some() {
+            Some(Interval::new(
+                implied_lower.unwrap_or(-f64::MAX),
+                implied_upper.unwrap_or(f64::MAX),
+            ))
+        } else {
+            None
+        };
+
+        Ok(ConstraintBetween {
+            source,
+            target,
+            authored,
+            bounds,
+            episode: self.episodes.contains(&Episode(source, target))
+                || self.episodes.contains(&Episode(target, source)),
+            implied,
+        })
+    }
+
+    /// Get the constraints implied between pairs of events that don't have an authored constraint between them. Only bounded intervals with an `upper - lower` span of `threshold` or less are reported, so loose couplings can be filtered out. Each pair of events is reported once, from the lower event ID to the higher
+    pub fn implied_constraints(&mut self, threshold: f64) -> Result<Vec<Constraint>, JsValue> {
+        self.compile_or_keep()?;
+
+        let events: Vec<EventID> = self.stn.nodes().collect();
+        let mut implied = vec![];
+
+        for (i, source) in events.iter().enumerate() {
+            for target in events.iter().skip(i + 1) {
+                if self.stn.contains_edge(*source, *target)
+                    || self.stn.contains_edge(*target, *source)
+                {
+                    continue;
+                }
+
+                let interval = match self.interval(*source, *target) {
+                    Ok(i) => i,
+                    // no path between the events
+                    Err(_) => continue,
+                };
+                if interval.lower() <= -f64::MAX || interval.upper() >= f64::MAX {
+                    continue;
+                }
+
+                if interval.upper() - interval.lower() <= threshold {
+                    implied.push(Constraint {
+                        source: *source,
+                        target: *target,
+                        interval,
+                        metadata: None,
+                        provenance: None,
+                    });
+                }
+            }
+        }
+
+        Ok(implied)
+    }
+
+    /// Events that can't happen after `event`, with the interval from each of them to it. Includes orderings implied by other constraints. Events simultaneous with `event` are both predecessors and successors. Sorted by event ID
+    pub fn predecessors(&mut self, event: EventID) -> Result<Vec<Constraint>, JsValue> {
+        self.ordered_relative_to(event, true)
+    }
+
+    /// Uncommitted events that must happen before `event`, so it can't be dispatched yet, with their remaining execution windows. Events that must be simultaneous with `event` are dispatched with it and don't block it. Sorted by event ID. Errs if the event isn't in the Schedule
+    pub fn blocking(&mut self, event: EventID) -> Result<Vec<Blocker>, JsValue> {
+        let predecessors = self.predecessors(event)?;
+        Ok(predecessors
+            .into_iter()
+            .filter(|c| !self.committments.contains_key(&c.source))
+            .filter(|c| c.interval != Interval::new(0., 0.))
+            .map(|c| Blocker {
+                event: c.source,
+                window: self.execution_windows[&c.source],
+                interval: c.interval,
+            })
+            .collect())
+    }
+
+    /// Events that can't happen before `event`, with the interval from it to each of them. Includes orderings implied by other constraints. Sorted by event ID
+    pub fn successors(&mut self, event: EventID) -> Result<Vec<Constraint>, JsValue> {
+        self.ordered_relative_to(event, false)
+    }
+
+    /// Events whose interval from (or to, if `before`) an event has a lower bound of at least 0
+    fn ordered_relative_to(
+        &mut self,
+        event: EventID,
+        before: bool,
+    ) -> Result<Vec<Constraint>, JsValue> {
+        if !self.stn.contains_node(event) {
+            return Err(JsValue::from_str(&format!(
+                "event {} is not in the Schedule",
+                event
+            )));
+        }
+        self.compile_or_keep()?;
+
+        let mut related = vec![];
+        for other in self.dispatchable.nodes() {
+            if other == event {
+                continue;
+            }
+            let (source, target) = if before {
+                (other, event)
+            } else {
+                (event, other)
+            };
+            // the lower bound from source to target is the negated distance back
+            let lower = match self.dispatchable.edge_weight(target, source) {
+                // avoid returning -0
+                Some(l) if *l == 0. => 0.,
+                Some(l) if *l < 0. => -*l,
+                _ => continue,
+            };
+            let upper = self
+                .dispatchable
+                .edge_weight(source, target)
+                .copied()
+                .unwrap_or(f64::MAX);
+            related.push(Constraint {
+                source,
+                target,
+                interval: Interval::new(lower, upper),
+                metadata: None,
+                provenance: None,
+            });
+        }
+
+        related.sort_by_key(|c| if before { c.source } else { c.target });
+        Ok(related)
+    }
+
+    /// Whether or not a pair of events has a negative round trip distance in the dispatchable graph, ie. the constraints contradict each other. Assumes the Schedule has been compiled
+    fn has_negative_cycle(&self) -> bool {
+        self.dispatchable
+            .all_edges()
+            .any(
+                |(source, target, upper)| match self.dispatchable.edge_weight(target, source) {
+                    Some(lower) => *upper + *lower < 0.,
+                    None => false,
+                },
+            )
+    }
+
+    /// Constraints where the target can never happen before the source, as (source, target) pairs
+    fn precedences(&self) -> Vec<(EventID, EventID)> {
+        self.constraints()
+            .iter()
+            .filter(|c| c.interval.lower() >= 0.)
+            .map(|c| (c.source, c.target))
+            .collect()
+    }
+
+    /// Group events into layers of authored precedences, where every event comes after at least one event in the previous layer. Unlike `layers`, events joined by a zero-lag precedence are in different layers, so flattening the layers gives an order in which to propagate times forwards. Events caught in a cycle of precedences are left out
+    fn precedence_layers(&self) -> Vec<Vec<EventID>> {
+        let precedences = self.precedences();
+
+        let mut successors: BTreeMap<EventID, Vec<EventID>> = BTreeMap::new();
+        let mut indegree: BTreeMap<EventID, usize> = self.stn.nodes().map(|n| (n, 0)).collect();
+        for (source, target) in precedences.iter() {
+            successors.entry(*source).or_default().push(*target);
+            *indegree.entry(*target).or_default() += 1;
+        }
+
+        // longest-path layering with Kahn's algorithm
+        let mut layer_of: BTreeMap<EventID, usize> = BTreeMap::new();
+        let mut queue: Vec<EventID> = indegree
+            .iter()
+            .filter(|(_, d)| **d == 0)
+            .map(|(n, _)| *n)
+            .collect();
+        let mut layers: Vec<Vec<EventID>> = vec![];
+
+        while let Some(event) = queue.pop() {
+            let layer = *layer_of.entry(event).or_insert(0);
+            if layers.len() <= layer {
+                layers.resize(layer + 1, vec![]);
+            }
+            layers[layer].push(event);
+
+            for next in successors.get(&event).into_iter().flatten() {
+                let l = layer_of.entry(*next).or_insert(0);
+                *l = (*l).max(layer + 1);
+
+                let d = indegree.entry(*next).or_default();
+                *d -= 1;
+                if *d == 0 {
+                    queue.push(*next);
+                }
+            }
+        }
+
+        for layer in layers.iter_mut() {
+            layer.sort_unstable();
+        }
+        layers
+    }
+
+    /// Whether or not another path is at least as tight as the edge from source to target
+    fn dominated(&self, source: EventID, target: EventID) -> bool {
+        let weight = match self.stn.edge_weight(source, target) {
+            Some(w) if *w < f64::MAX => *w,
+            // no upper bound in this direction
+            _ => return true,
+        };
+
+        match bellman_ford(&self.stn, source, Some((source, target))) {
+            Ok(distances) => distances.get(&target).is_some_and(|d| *d <= weight),
+            Err(_) => false,
+        }
+    }
+
+    /// Get the authored constraints that are dominated by other paths through the Schedule in both directions. Removing any one of them leaves the dispatchable graph unchanged, though removing several at once may not
+    pub fn redundant_constraints(&self) -> Vec<Constraint> {
+        self.constraints()
+            .into_iter()
+            .filter(|c| self.dominated(c.source, c.target) && self.dominated(c.target, c.source))
+            .collect()
+    }
+
+    /// Get the fewest authored constraints that compile to the same dispatchable graph. Bounds implied by other paths are removed one at a time, so no bound that's left can be removed, unlike `redundant_constraints` where removing several at once may change the Schedule. Durations of contingent Episodes are always kept. Metadata and provenance are kept with their constraints. Errs if the constraints are inconsistent
+    pub fn minimal_equivalent(&self) -> Result<Vec<Constraint>, String> {
+        if let Some(cycle) = self.negative_cycle() {
+            return Err(format!(
+                "the constraints are inconsistent. No times satisfy the constraints between events {:?}",
+                cycle
+            ));
+        }
+
+        let mut reduced = self.clone();
+        let mut edges: Vec<_> = self.stn.all_edges().map(|(s, t, _)| (s, t)).collect();
+        edges.sort_unstable();
+        for (source, target) in edges {
+            let contingent = self.contingent.contains(&Episode(source, target))
+                || self.contingent.contains(&Episode(target, source));
+            if contingent {
+                continue;
+            }
+            // checked against what's left, so two edges can't each be removed for implying the other
+            if reduced.dominated(source, target) {
+                reduced.stn.remove_edge(source, target);
+            }
+        }
+
+        // keep each constraint in the direction it was authored
+        let minimal = self
+            .constraints()
+            .into_iter()
+            .filter_map(|c| {
+                let upper = reduced.stn.edge_weight(c.source, c.target).copied();
+                // avoid returning -0
+                let lower =
+                    reduced
+                        .stn
+                        .edge_weight(c.target, c.source)
+                        .map(|l| if *l == 0. { 0. } else { -l });
+                if upper.is_none() && lower.is_none() {
+                    return None;
+                }
+                Some(Constraint {
+                    interval: Interval::new(lower.unwrap_or(-f64::MAX), upper.unwrap_or(f64::MAX)),
+                    ..c
+                })
+            })
+            .collect();
+        Ok(minimal)
+    }
+
+    /// Find the authored constraints that produce the interval between two events. Each bound comes from a shortest path through the constraints: the upper bound from the path from the source to the target and the lower bound from the path back. A bound without a path is unbounded and has no constraints. Commitments are not considered. Errs if either event isn't in the Schedule or the constraints are inconsistent
+    pub fn explain_interval(
+        &self,
+        source: EventID,
+        target: EventID,
+    ) -> Result<Explanation, JsValue> {
+        for event in [source, target].iter() {
+            if !self.stn.contains_node(*event) {
+                return Err(JsValue::from_str(&format!(
+                    "event {} is not in the Schedule",
+                    event
+                )));
+            }
+        }
+
+        let oracle = floyd_warshall(&self.stn).map_err(|e| JsValue::from_str(&e))?;
+        if !oracle.consistent() {
+            return Err(JsValue::from_str(
+                "the constraints are inconsistent, so there are no shortest paths",
+            ));
+        }
+
+        let constraints = self.constraints();
+        let bindings = |from: EventID, to: EventID| -> Vec<Binding> {
+            let path = match oracle.path(from, to) {
+                Some(p) => p,
+                None => return vec![],
+            };
+            path.windows(2)
+                .filter_map(|hop| {
+                    constraints
+                        .iter()
+                        .find(|c| {
+                            (c.source, c.target) == (hop[0], hop[1])
+                                || (c.source, c.target) == (hop[1], hop[0])
+                        })
+                        .map(|c| Binding {
+                            constraint: c.clone(),
+                            bound: if c.source == hop[0] { "upper" } else { "lower" },
+                        })
+                })
+                .collect()
+        };
+
+        let upper = oracle.distance(source, target).unwrap_or(f64::MAX);
+        // avoid returning -0
+        let lower =
+            oracle
+                .distance(target, source)
+                .map_or(-f64::MAX, |l| if l == 0. { 0. } else { -l });
+
+        Ok(Explanation {
+            source,
+            target,
+            interval: Interval::new(lower, upper),
+            upper: if upper < f64::MAX {
+                bindings(source, target)
+            } else {
+                vec![]
+            },
+            lower: if lower > -f64::MAX {
+                bindings(target, source)
+            } else {
+                vec![]
+            },
+        })
+    }
+
+    /// Group events into precedence layers from the compiled Schedule. An event must happen strictly after another if the distance graph requires a positive gap between them, which includes gaps implied by chains of constraints. Each event is placed one layer after the latest layer of the events it must follow, so no two events in a layer are required to happen in a particular order. Errs if the Schedule is inconsistent
+    pub fn layers(&mut self) -> Result<Vec<Vec<EventID>>, JsValue> {
+        self.compile_or_keep()?;
+        if self.has_negative_cycle() {
+            return Err(JsValue::from_str(
+                "the Schedule is inconsistent, so events have no precedence order",
+            ));
+        }
+
+        let events: Vec<EventID> = self.stn.nodes().collect();
+        // the dispatchable graph is transitively closed, so an event always has more predecessors than anything it follows
+        let mut predecessors: Vec<(EventID, Vec<EventID>)> = events
+            .iter()
+            .map(|b| {
+                let before = events
+                    .iter()
+                    .filter(|a| match self.dispatchable.edge_weight(*b, **a) {
+                        Some(d) => *d < 0.,
+                        None => false,
+                    })
+                    .copied()
+                    .collect();
+                (*b, before)
+            })
+            .collect();
+        predecessors.sort_by_key(|(event, before)| (before.len(), *event));
+
+        let mut layer_of: BTreeMap<EventID, usize> = BTreeMap::new();
+        let mut layers: Vec<Vec<EventID>> = vec![];
+        for (event, before) in predecessors.iter() {
+            let layer = before
+                .iter()
+                .filter_map(|a| layer_of.get(a))
+                .map(|l| l + 1)
+                .max()
+                .unwrap_or(0);
+            layer_of.insert(*event, layer);
+            if layers.len() <= layer {
+                layers.resize(layer + 1, vec![]);
+            }
+            layers[layer].push(*event);
+        }
+
+        for layer in layers.iter_mut() {
+            layer.sort_unstable();
+        }
+        Ok(layers)
+    }
+
+    /// The earliest time of every event connected to the root, relative to the root. Assumes the Schedule has been compiled
+    fn earliest_times(&self, root: EventID) -> BTreeMap<EventID, f64> {
+        self.stn
+            .nodes()
+            .filter_map(|e| match self.dispatchable.edge_weight(e, root) {
+                Some(d) if *d < f64::MAX => Some((e, -d)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The chain of events that sets the earliest possible end of the Schedule, from the root to the event that can happen last. Empty if the Schedule has no root
+    pub fn critical_path(&mut self) -> Result<Vec<EventID>, JsValue> {
+        self.compile_or_keep()?;
+        let root = match self.root() {
+            Some(r) => r,
+            None => return Ok(vec![]),
+        };
+        let earliest = self.earliest_times(root);
+        let constraints = self.constraints();
+
+        // the last event, preferring the lowest ID on ties
+        let mut event = match earliest
+            .iter()
+            .fold(None, |last: Option<(EventID, f64)>, (e, t)| match last {
+                Some((_, l)) if l >= *t => last,
+                _ => Some((*e, *t)),
+            }) {
+            Some((e, _)) => e,
+            None => return Ok(vec![]),
+        };
+
+        // walk back along constraints that leave no slack
+        let mut path = vec![event];
+        while event != root {
+            let tight = constraints.iter().find(|c| {
+                c.target == event
+                    && c.interval.lower() >= 0.
+                    && !path.contains(&c.source)
+                    && match (earliest.get(&c.source), earliest.get(&event)) {
+                        (Some(s), Some(t)) => (s + c.interval.lower() - t).abs() < 1e-9,
+                        _ => false,
+                    }
+            });
+            match tight {
+                Some(c) => {
+                    event = c.source;
+                    path.push(event);
+                }
+                None => break,
+            }
+        }
+
+        path.reverse();
+        Ok(path)
+    }
+
+    /// Check the Schedule for common problems: contradictory constraints, a missing or ambiguous root, events that aren't connected to the root, rigid loops, and redundant constraints. Never errs, so it's safe to run on a broken Schedule
+    pub fn lint(&mut self) -> Vec<Finding> {
+        if let Some(cycle) = self.negative_cycle() {
+            let hops: Vec<(EventID, EventID)> = cycle
+                .iter()
+                .zip(cycle.iter().cycle().skip(1))
+                .map(|(a, b)| (*a, *b))
+                .collect();
+            return vec![Finding {
+                severity: "error",
+                message: format!("constraints contradict each other{}", self.cite(&hops)),
+                events: cycle,
+            }];
+        }
+        if self.compile_with_cancel(&CancelToken::new()).is_err() {
+            return vec![Finding {
+                severity: "error",
+                message: "the Schedule could not be compiled".to_string(),
+                events: vec![],
+            }];
+        }
+
+        let mut findings = vec![];
+        match self.find_root() {
+            Root::Missing => findings.push(Finding {
+                severity: "error",
+                message: "no event happens before every other event".to_string(),
+                events: vec![],
+            }),
+            Root::Ambiguous(candidates) => findings.push(Finding {
+                severity: "warning",
+                message: "more than one event could be first".to_string(),
+                events: candidates,
+            }),
+            Root::Unique(root) => {
+                let earliest = self.earliest_times(root);
+                let detached: Vec<EventID> = self
+                    .stn
+                    .nodes()
+                    .filter(|e| !earliest.contains_key(e))
+                    .collect();
+                if !detached.is_empty() {
+                    findings.push(Finding {
+                        severity: "warning",
+                        message: "events are not constrained to happen after the root".to_string(),
+                        events: detached,
+                    });
+                }
+            }
+        }
+
+        for diagnostic in self.diagnose_cycles() {
+            let pairs: Vec<(EventID, EventID)> = diagnostic
+                .constraints
+                .iter()
+                .map(|b| (b.constraint.source, b.constraint.target))
+                .collect();
+            findings.push(Finding {
+                severity: "warning",
+                message: format!(
+                    "a loop of constraints forces events to happen at the same time{}",
+                    self.cite(&pairs)
+                ),
+                events: diagnostic.events,
+            });
+        }
+
+        for c in self.redundant_constraints() {
+            findings.push(Finding {
+                severity: "warning",
+                message: format!(
+                    "the {} constraint from {} to {} is implied by other constraints{}",
+                    c.interval,
+                    c.source,
+                    c.target,
+                    self.cite(&[(c.source, c.target)])
+                ),
+                events: vec![c.source, c.target],
+            });
+        }
+
+        findings
+    }
+
+    /// Find loops in the authored constraints before compiling. Contradictory loops are negative cycles in the distance graph, where the constraints around the loop need more time than they allow. Rigid loops are cycles of precedences with no lag, eg. `a -> b`, `b -> c`, and `c -> a`, all `[0, 5]`, which are satisfiable but force every event in the loop to happen at the same time. Each loop is reported with the constraints that form it and suggested fixes, including flipping constraints that look like they were written backwards. Never errs, so it's safe to run on a broken Schedule
+    pub fn diagnose_cycles(&self) -> Vec<CycleDiagnostic> {
+        let constraints = self.constraints();
+        let binding = |from: EventID, to: EventID| -> Option<Binding> {
+            constraints
+                .iter()
+                .find(|c| (c.source, c.target) == (from, to) || (c.source, c.target) == (to, from))
+                .map(|c| Binding {
+                    constraint: c.clone(),
+                    bound: if c.source == from { "upper" } else { "lower" },
+                })
+        };
+
+        let mut diagnostics = vec![];
+
+        // remove each contradictory loop once it's found so the next search finds a different one
+        let mut remaining = self.stn.clone();
+        while let Some(events) = negative_cycle(&remaining) {
+            let hops: Vec<(EventID, EventID)> = events
+                .iter()
+                .zip(events.iter().cycle().skip(1))
+                .map(|(a, b)| (*a, *b))
+                .collect();
+            let excess = -hops
+                .iter()
+                .filter_map(|(a, b)| self.stn.edge_weight(*a, *b))
+                .sum::<f64>();
+            let bindings: Vec<Binding> = hops.iter().filter_map(|(a, b)| binding(*a, *b)).collect();
+
+            let mut suggestions = vec![];
+            for b in bindings.iter() {
+                let c = &b.constraint;
+                let cite = self.cite(&[(c.source, c.target)]);
+                suggestions.push(if b.bound == "upper" {
+                    format!(
+                        "raise the upper bound of {} -> {} from {} to {}{}",
+                        c.source,
+                        c.target,
+                        c.interval.upper(),
+                        c.interval.upper() + excess,
+                        cite
+                    )
+                } else {
+                    format!(
+                        "lower the lower bound of {} -> {} from {} to {}{}",
+                        c.source,
+                        c.target,
+                        c.interval.lower(),
+                        c.interval.lower() - excess,
+                        cite
+                    )
+                });
+            }
+            for b in bindings.iter() {
+                let c = &b.constraint;
+                if self.flip_is_consistent(c) {
+                    suggestions.push(format!(
+                        "{} -> {} {} may be backwards. Either swap its events or negate it to [{}, {}]{}",
+                        c.source,
+                        c.target,
+                        c.interval,
+                        -c.interval.upper(),
+                        -c.interval.lower(),
+                        self.cite(&[(c.source, c.target)])
+                    ));
+                }
+            }
+
+            for (a, b) in hops.iter() {
+                remaining.remove_edge(*a, *b);
+                remaining.remove_edge(*b, *a);
+            }
+            diagnostics.push(CycleDiagnostic {
+                kind: "contradictory",
+                events,
+                constraints: bindings,
+                excess,
+                suggestions,
+            });
+        }
+
+        // cycles of precedences that aren't contradictory must have no lag at all
+        let mut precedences: DiGraphMap<EventID, ()> = DiGraphMap::new();
+        for (source, target) in self.precedences() {
+            precedences.add_edge(source, target, ());
+        }
+        for mut component in tarjan_scc(&precedences) {
+            if component.len() < 2 {
+                continue;
+            }
+            component.sort_unstable();
+            let contradictory = diagnostics
+                .iter()
+                .any(|d| d.events.iter().any(|e| component.contains(e)));
+            if contradictory {
+                continue;
+            }
+
+            let events = match precedence_loop(&precedences, &component) {
+                Some(e) => e,
+                None => continue,
+            };
+            // in time order, so each constraint is followed forwards by its lower bound
+            let bindings: Vec<Binding> = events
+                .iter()
+                .zip(events.iter().cycle().skip(1))
+                .filter_map(|(a, b)| binding(*b, *a))
+                .collect();
+            let suggestions = vec![
+                format!(
+                    "events {:?} must happen at the same time. If that's intended, constrain them with [0, 0] from event {} instead of a loop",
+                    component, component[0]
+                ),
+                "otherwise, swap the events of the constraint that should go the other way"
+                    .to_string(),
+            ];
+
+            diagnostics.push(CycleDiagnostic {
+                kind: "rigid",
+                events,
+                constraints: bindings,
+                excess: 0.,
+                suggestions,
+            });
+        }
+
+        diagnostics
+    }
+
+    /// Whether or not the authored constraints would be consistent if a constraint's source and target were swapped
+    fn flip_is_consistent(&self, constraint: &Constraint) -> bool {
+        let (s, t) = (constraint.source, constraint.target);
+        let mut flipped = self.stn.clone();
+        if let (Some(upper), Some(lower)) = (
+            self.stn.edge_weight(s, t).copied(),
+            self.stn.edge_weight(t, s).copied(),
+        ) {
+            flipped.add_edge(s, t, lower);
+            flipped.add_edge(t, s, upper);
+        }
+        negative_cycle(&flipped).is_none()
+    }
+
+    /// Structural statistics about the Schedule
+    pub fn complexity(&self) -> Complexity {
+        let layers = self.precedence_layers();
+        let precedences = self.precedences();
+
+        let mut outdegree: BTreeMap<EventID, usize> = BTreeMap::new();
+        let mut indegree: BTreeMap<EventID, usize> = BTreeMap::new();
+        for (source, target) in precedences.iter() {
+            *outdegree.entry(*source).or_default() += 1;
+            *indegree.entry(*target).or_default() += 1;
+        }
+
+        Complexity {
+            depth: layers.len(),
+            width: layers.iter().map(|l| l.len()).max().unwrap_or(0),
+            syncs: precedences
+                .iter()
+                .filter(|(source, target)| outdegree[source] > 1 || indegree[target] > 1)
+                .count(),
+            redundant: self.redundant_constraints().len(),
+        }
+    }
+
+    /// Set when an Episode would prefer to occur. `Earliest`, `Latest`, and `Midpoint` apply to both the start and end of the Episode, while a `Target` time applies to the start
+    pub fn set_preference(&mut self, episode: &Episode, preference: Preference) {
+        self.preferences.insert(episode.start(), preference);
+        match preference {
+            Preference::Target(_) => self.preferences.remove(&episode.end()),
+            _ => self.preferences.insert(episode.end(), preference),
+        };
+        self.journal.push(Mutation::SetPreference {
+            start: episode.start(),
+            end: episode.end(),
+            preference,
+        });
+    }
+
+    /// Get the preference for an event
+    pub fn preference(&self, event: EventID) -> Preference {
+        self.preferences.get(&event).copied().unwrap_or_default()
+    }
+
+    /// Choose up to `points` concrete schedules that trade makespan against slack. Each one is the earliest-start schedule after every controllable constraint is tightened by an increasing amount of slack, up to the most the constraints allow. Narrow constraints are tightened by at most half their width. Schedules with a longer makespan and no more slack than another are left out. The root is assumed to happen at t=0 if it hasn't been committed. Errs if the Schedule doesn't compile or has no root
+    pub fn pareto_schedules(&mut self, points: usize) -> Result<Vec<ParetoPoint>, String> {
+        self.compile_with_cancel(&CancelToken::new())
+            .map_err(|e| e.to_string())?;
+        let root = self
+            .root()
+            .ok_or_else(|| "cannot find the first event in the Schedule".to_string())?;
+
+        let constraints: Vec<Constraint> = self
+            .constraints()
+            .into_iter()
+            .filter(|c| {
+                !self.contingent.contains(&Episode(c.source, c.target))
+                    && c.interval.lower() != c.interval.upper()
+            })
+            .collect();
+        // no more slack than the widest two-sided constraint can give
+        let most = constraints
+            .iter()
+            .filter(|c| c.interval.lower() > -f64::MAX && c.interval.upper() < f64::MAX)
+            .map(|c| (c.interval.upper() - c.interval.lower()) / 2.)
+            .fold(0., f64::max);
+
+        // binary search for the most slack that's still consistent
+        let (mut feasible, mut infeasible) = (0., most);
+        if self.with_slack(&constraints, most, root).is_some() {
+            feasible = most;
+        } else {
+            for _ in 0..40 {
+                let middle = (feasible + infeasible) / 2.;
+                if self.with_slack(&constraints, middle, root).is_some() {
+                    feasible = middle;
+                } else {
+                    infeasible = middle;
+                }
+            }
+        }
+
+        let steps = points.max(1);
+        let mut schedules: Vec<ParetoPoint> = vec![];
+        for step in 0..steps {
+            let slack = if steps == 1 {
+                0.
+            } else {
+                feasible * step as f64 / (steps - 1) as f64
+            };
+            let times = match self.with_slack(&constraints, slack, root) {
+                Some(t) => t,
+                None => continue,
+            };
+            let start = times[&root];
+            let makespan = times.values().fold(0., |m: f64, t| m.max(t - start));
+            let slack = constraints
+                .iter()
+                .filter_map(|c| {
+                    let d = times.get(&c.target)? - times.get(&c.source)?;
+                    Some(
+                        [d - c.interval.lower(), c.interval.upper() - d]
+                            .iter()
+                            .filter(|s| s.abs() < f64::MAX / 2.)
+                            .fold(f64::MAX, |m, s| m.min(*s)),
+                    )
+                })
+                .fold(f64::MAX, f64::min);
+            schedules.push(ParetoPoint {
+                makespan,
+                slack: if slack == f64::MAX { 0. } else { slack },
+                times,
+            });
+        }
+
+        schedules.sort_by(|a, b| {
+            a.makespan
+                .partial_cmp(&b.makespan)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        // keep a schedule only if it has more slack than every shorter one
+        let mut front: Vec<ParetoPoint> = vec![];
+        for point in schedules.into_iter() {
+            if front.last().is_none_or(|last| point.slack > last.slack) {
+                front.push(point);
+            }
+        }
+        Ok(front)
+    }
+
+    /// The earliest-start schedule after tightening each constraint by `slack`, or at most half its width. `None` if the tightened constraints are inconsistent
+    fn with_slack(
+        &self,
+        constraints: &[Constraint],
+        slack: f64,
+        root: EventID,
+    ) -> Option<BTreeMap<EventID, f64>> {
+        let mut s = self.clone();
+        for c in constraints.iter() {
+            let (lower, upper) = (c.interval.lower(), c.interval.upper());
+            let tighten = if lower > -f64::MAX && upper < f64::MAX {
+                slack.min((upper - lower) / 2.)
+            } else {
+                slack
+            };
+            if upper < f64::MAX {
+                s.stn.add_edge(c.source, c.target, upper - tighten);
+            }
+            if lower > -f64::MAX {
+                s.stn.add_edge(c.target, c.source, -(lower + tighten));
+            }
+        }
+        s.mark_dirty();
+        s.compile_with_cancel(&CancelToken::new()).ok()?;
+
+        s.committments.entry(root).or_insert(0.);
+        s.propagate_commitments();
+        if !s.infeasible_events().is_empty() {
+            return None;
+        }
+        let times = s
+            .execution_windows
+            .iter()
+            .filter(|(_, w)| w.lower() > -f64::MAX)
+            .map(|(e, w)| (*e, w.lower()))
+            .collect();
+        Some(times)
+    }
+
+    /// Choose a concrete time for every event based on its `Preference`. Events are dispatched in order of their earliest times and each choice is propagated before the next, so the result satisfies every constraint. The root is assumed to happen at t=0 if it hasn't been committed. Events that aren't connected to the root are left out
+    pub fn extract_schedule(&mut self) -> Result<BTreeMap<EventID, f64>, JsValue> {
+        let mut s = self.clone();
+        s.compile()?;
+        let root = match s.root() {
+            Some(r) => r,
+            None => {
+                return Err(JsValue::from_str(
+                    "cannot find the first event in the Schedule",
+                ))
+            }
+        };
+        if !s.committments.contains_key(&root) {
+            s.commit(root, 0.)?;
+        }
+
+        loop {
+            // the next event to dispatch is the earliest one that hasn't been committed
+            let next = s
+                .execution_windows
+                .iter()
+                .filter(|(event, _)| !s.committments.contains_key(event))
+                .filter_map(|(event, window)| {
+                    s.preference(*event)
+                        .choose(*window)
+                        .map(|time| (*event, window.lower(), time))
+                })
+                .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+
+            match next {
+                Some((event, _, time)) => s.commit(event, time)?,
+                None => break,
+            }
+        }
+
+        Ok(s.committments)
+    }
+}
+
+/// Order the remaining mutexes one at a time, backtracking when an order leaves the graph inconsistent. On success the graph holds an ordering constraint for every mutex. On failure it's left as it was
+fn sequence(graph: &mut DiGraphMap<EventID, f64>, mutexes: &[(Episode, Episode)]) -> bool {
+    let ((a, b), rest) = match mutexes.split_first() {
+        Some(m) => m,
+        None => return true,
+    };
+
+    for (first, second) in [(a, b), (b, a)].iter() {
+        // the first has to end no later than the second starts: end - start <= 0
+        let previous = graph.edge_weight(second.start(), first.end()).copied();
+        graph.add_edge(
+            second.start(),
+            first.end(),
+            previous.map_or(0., |w| w.min(0.)),
+        );
+
+        if negative_cycle(graph).is_none() && sequence(graph, rest) {
+            return true;
+        }
+
+        match previous {
+            Some(w) => graph.add_edge(second.start(), first.end(), w),
+            None => graph.remove_edge(second.start(), first.end()),
+        };
+    }
+
+    false
+}
+
+/// Find a loop of precedences through the lowest event of a strongly connected component, in time order
+fn precedence_loop(
+    precedences: &DiGraphMap<EventID, ()>,
+    component: &[EventID],
+) -> Option<Vec<EventID>> {
+    let start = *component.first()?;
+    let mut parents: BTreeMap<EventID, EventID> = BTreeMap::new();
+    let mut queue = std::collections::VecDeque::from(vec![start]);
+
+    while let Some(event) = queue.pop_front() {
+        for next in precedences.neighbors_directed(event, Outgoing) {
+            if next == start {
+                let mut events = vec![event];
+                while let Some(parent) = parents.get(events.last()?) {
+                    events.push(*parent);
+                }
+                events.reverse();
+                return Some(events);
+            }
+            if component.contains(&next) && !parents.contains_key(&next) {
+                parents.insert(next, event);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+/// Move the edges of a window out of any blackouts they fall strictly inside of. The result is invalid if the window is entirely blacked out
+fn clip(window: Interval, blackouts: &[Interval]) -> Interval {
+    let (mut lower, mut upper) = (window.lower(), window.upper());
+    // clipping one edge can land it in another blackout
+    let mut moved = true;
+    while moved && lower <= upper {
+        moved = false;
+        for b in blackouts.iter() {
+            if b.lower() < lower && lower < b.upper() {
+                lower = b.upper();
+                moved = true;
+            }
+            if b.lower() < upper && upper < b.upper() {
+                upper = b.lower();
+                moved = true;
+            }
+        }
+    }
+    Interval::new(lower, upper)
+}
+
+/// The interval from one event to another in a distance graph. A missing edge is unbounded
+fn implied_interval(
+    graph: &DiGraphMap<EventID, f64>,
+    source: EventID,
+    target: EventID,
+) -> Interval {
+    let lower = graph.edge_weight(target, source).map_or(-f64::MAX, |l| -*l);
+    let upper = graph
+        .edge_weight(source, target)
+        .copied()
+        .unwrap_or(f64::MAX);
+    Interval::new(lower, upper)
+}
+
+/// A dispatchable graph with an edge for every distance
+fn dispatchable_graph(distances: &BTreeMap<(EventID, EventID), f64>) -> DiGraphMap<EventID, f64> {
+    let mut graph = DiGraphMap::new();
+    for ((source, target), weight) in distances.iter() {
+        graph.add_edge(*source, *target, *weight);
+    }
+    graph
+}
+
+/// Find the first event from one row and a few columns of the distance matrix. Nothing can be later relative to another event than the first event, so it's among the events closest to any event. Of those, it's the lowest ID that no event can happen before
+fn streamed_root(rows: &DistanceRows) -> Result<EventID, String> {
+    let missing = || "cannot find the first event in the Schedule".to_string();
+    let nodes = rows.nodes();
+    let row = rows
+        .row(*nodes.first().ok_or_else(missing)?)
+        .unwrap_or_default();
+    let closest = row.iter().copied().fold(f64::MAX, f64::min);
+    nodes
+        .iter()
+        .zip(row.iter())
+        .filter(|(_, d)| **d == closest)
+        .map(|(event, _)| *event)
+        .find(|event| {
+            rows.column(*event)
+                .is_some_and(|column| column.iter().all(|d| *d <= 0.))
+        })
+        .ok_or_else(missing)
+}
+
+/// Finish a hash with every edge of a graph in a fixed order, so equal graphs hash the same however they were built
+fn hash_graph(mut hasher: Sha256, graph: &DiGraphMap<EventID, f64>) -> String {
+    let mut edges: Vec<_> = graph.all_edges().collect();
+    edges.sort_unstable_by_key(|(s, t, _)| (*s, *t));
+    for (source, target, weight) in edges {
+        // -0 and 0 are the same distance
+        let weight = if *weight == 0. { 0. } else { *weight };
+        hasher.update(source.to_le_bytes());
+        hasher.update(target.to_le_bytes());
+        hasher.update(weight.to_bits().to_le_bytes());
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::Progress;
+    use crate::probability::Distribution;
+    use serde_json::json;
+
+    /// Two Episodes in series, [1, 5] then [2, 9]
+    fn series() -> (Schedule, Episode, Episode) {
+        let mut schedule = Schedule::new();
+        let episode1 = schedule.add_episode(Some(vec![1., 5.]));
+        let episode2 = schedule.add_episode(Some(vec![2., 9.]));
+        schedule
+            .add_constraint(episode1.end(), episode2.start(), None)
+            .unwrap();
+        (schedule, episode1, episode2)
+    }
+
+    #[test]
+    fn test_branch_at_drops_later_commitments() {
+        let (mut schedule, episode1, episode2) = series();
+        schedule.commit_event(episode1.start(), 0.).unwrap();
+        schedule.commit_event(episode1.end(), 3.).unwrap();
+        schedule.commit_event(episode2.start(), 3.).unwrap();
+        schedule.commit_event(episode2.end(), 10.).unwrap();
+
+        let mut branch = schedule.branch_at(episode1.end()).unwrap();
+
+        assert_eq!(branch.branch_point(), Some(episode1.end()));
+        assert_eq!(
+            branch.window(episode2.end()).unwrap(),
+            Interval::new(5., 12.),
+            "the branch forgets when episode 2 ended"
+        );
+        assert_eq!(
+            schedule.window(episode2.end()).unwrap(),
+            Interval::new(10., 10.),
+            "the original Schedule is untouched"
+        );
+    }
+
+    #[test]
+    fn test_branch_at_future_event_keeps_history() {
+        let (mut schedule, episode1, episode2) = series();
+        schedule.commit_event(episode1.start(), 0.).unwrap();
+        schedule.commit_event(episode1.end(), 3.).unwrap();
+
+        let mut branch = schedule.branch_at(episode2.end()).unwrap();
+
+        assert_eq!(
+            branch.window(episode2.end()).unwrap(),
+            Interval::new(5., 12.)
+        );
+    }
+
+    #[test]
+    fn test_time_windows() {
+        let (mut schedule, episode1, episode2) = series();
+        schedule.commit_event(episode1.start(), 0.).unwrap();
+
+        let windows = schedule.time_windows().unwrap();
+        assert_eq!(windows.len(), 4);
+        assert_eq!(
+            windows[0],
+            TimeWindow {
+                event: episode1.start(),
+                earliest: 0.,
+                latest: 0.,
+                nominal: Some(0.),
+                converged: true,
+            }
+        );
+        assert_eq!(
+            windows[episode2.end() as usize],
+            TimeWindow {
+                event: episode2.end(),
+                earliest: 3.,
+                latest: 14.,
+                nominal: Some(8.5),
+                converged: false,
+            }
+        );
+
+        // nothing to anchor the windows to yet
+        let (mut schedule, _, _) = series();
+        assert!(schedule
+            .time_windows()
+            .unwrap()
+            .iter()
+            .all(|w| w.nominal.is_none()));
+    }
+
+    #[test]
+    fn test_preference_choose() {
+        let window = Interval::new(2., 6.);
+        assert_eq!(Preference::Earliest.choose(window), Some(2.));
+        assert_eq!(Preference::Latest.choose(window), Some(6.));
+        assert_eq!(Preference::Midpoint.choose(window), Some(4.));
+        assert_eq!(Preference::Target(5.).choose(window), Some(5.));
+        assert_eq!(Preference::Target(9.).choose(window), Some(6.));
+
+        let half_bounded = Interval::new(2., f64::MAX);
+        assert_eq!(Preference::Latest.choose(half_bounded), Some(2.));
+        assert_eq!(Preference::Target(9.).choose(half_bounded), Some(9.));
+        assert_eq!(
+            Preference::Earliest.choose(Interval::new(-f64::MAX, f64::MAX)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_extract_schedule_honors_preferences() {
+        let (mut schedule, episode1, episode2) = series();
+
+        let times = schedule.extract_schedule().unwrap();
+        assert_eq!(times[&episode1.start()], 0.);
+        assert_eq!(times[&episode1.end()], 3.);
+        assert_eq!(times[&episode2.start()], 3.);
+        assert_eq!(times[&episode2.end()], 8.5);
+
+        schedule.set_preference(&episode1, Preference::Earliest);
+        schedule.set_preference(&episode2, Preference::Latest);
+        let times = schedule.extract_schedule().unwrap();
+        assert_eq!(times[&episode1.end()], 1.);
+        assert_eq!(times[&episode2.end()], 10.);
+
+        // extraction doesn't commit anything
+        assert_eq!(
+            schedule.window(episode2.end()).unwrap(),
+            Interval::new(-f64::MAX, f64::MAX)
+        );
+
+        let windows = schedule.time_windows().unwrap();
+        assert!(windows.iter().all(|w| w.nominal.is_none()));
+    }
+
+    #[test]
+    fn test_complexity() {
+        // two episodes in parallel between the start and end of a parent
+        let mut schedule = Schedule::new();
+        let parent = schedule.add_episode(Some(vec![0., 100.]));
+        let child1 = schedule.add_episode(Some(vec![1., 2.]));
+        let child2 = schedule.add_episode(Some(vec![1., 4.]));
+        for child in [child1, child2].iter() {
+            schedule
+                .add_constraint(parent.start(), child.start(), None)
+                .unwrap();
+            schedule
+                .add_constraint(child.end(), parent.end(), Some(vec![0., f64::MAX]))
+                .unwrap();
+        }
+
+        let complexity = schedule.complexity();
+        assert_eq!(
+            complexity.depth, 4,
+            "parent start, child start, child end, parent end"
+        );
+        assert_eq!(complexity.width, 2);
+        // the parent duration, the fork, and the join
+        assert_eq!(complexity.syncs, 5);
+        assert_eq!(complexity.redundant, 0);
+
+        // a second copy of the path through child1
+        schedule
+            .add_constraint(parent.start(), child1.end(), Some(vec![0., 10.]))
+            .unwrap();
+        assert_eq!(schedule.complexity().redundant, 1);
+    }
+
+    #[test]
+    fn test_max_tightening() {
+        // from STNs for EVAs
+        let mut schedule = Schedule::new();
+        let x0 = schedule.create_event();
+        let l = schedule.add_episode(Some(vec![30., 40.]));
+        let s = schedule.add_episode(Some(vec![40., 50.]));
+        schedule
+            .add_constraint(x0, l.start(), Some(vec![10., 20.]))
+            .unwrap();
+        schedule
+            .add_constraint(x0, s.end(), Some(vec![60., 70.]))
+            .unwrap();
+        schedule
+            .add_constraint(s.start(), l.end(), Some(vec![10., 20.]))
+            .unwrap();
+
+        assert_eq!(
+            schedule.max_tightening(x0, l.end()).unwrap(),
+            Interval::new(40., 50.)
+        );
+
+        // tightening within the implied interval stays consistent
+        schedule
+            .add_constraint(x0, l.end(), Some(vec![45., 45.]))
+            .unwrap();
+        assert!(schedule.is_consistent());
+        assert_eq!(
+            schedule.max_tightening(x0, s.start()).unwrap(),
+            Interval::new(25., 30.)
+        );
+    }
+
+    #[test]
+    fn test_implied_constraints() {
+        let (mut schedule, episode1, episode2) = series();
+
+        let implied = schedule.implied_constraints(10.).unwrap();
+        assert_eq!(
+            implied,
+            vec![
+                Constraint {
+                    source: episode1.start(),
+                    target: episode2.start(),
+                    interval: Interval::new(1., 5.),
+                    metadata: None,
+                    provenance: None,
+                },
+                Constraint {
+                    source: episode1.end(),
+                    target: episode2.end(),
+                    interval: Interval::new(2., 9.),
+                    metadata: None,
+                    provenance: None,
+                },
+            ]
+        );
+
+        // the start to end of the whole Schedule spans [3, 14]
+        let loose = schedule.implied_constraints(11.).unwrap();
+        assert_eq!(loose.len(), 3);
+        assert!(loose.contains(&Constraint {
+            source: episode1.start(),
+            target: episode2.end(),
+            interval: Interval::new(3., 14.),
+            metadata: None,
+            provenance: None,
+        }));
+    }
+
+    #[test]
+    fn test_redundant_constraints() {
+        let (mut schedule, episode1, episode2) = series();
+        assert!(schedule.redundant_constraints().is_empty());
+
+        // implied by the Episode durations and the constraint between them
+        schedule
+            .add_constraint(episode1.start(), episode2.end(), Some(vec![2., 20.]))
+            .unwrap();
+        let redundant = schedule.redundant_constraints();
+        assert_eq!(
+            redundant,
+            vec![Constraint {
+                source: episode1.start(),
+                target: episode2.end(),
+                interval: Interval::new(2., 20.),
+                metadata: None,
+                provenance: None,
+            }]
+        );
+
+        // removing it doesn't change the compiled Schedule
+        let before = schedule.interval(episode1.start(), episode2.end()).unwrap();
+        schedule
+            .remove_constraint(episode1.start(), episode2.end())
+            .unwrap();
+        schedule
+            .remove_constraint(episode2.end(), episode1.start())
+            .unwrap();
+        assert_eq!(
+            schedule.interval(episode1.start(), episode2.end()).unwrap(),
+            before
+        );
+
+        // queries after removing one that isn't redundant see the change. Only the upper bound is removed
+        schedule
+            .remove_constraint(episode1.end(), episode2.start())
+            .unwrap();
+        assert_eq!(
+            schedule.interval(episode1.end(), episode2.start()).unwrap(),
+            Interval::new(0., f64::MAX)
+        );
+    }
+
+    #[test]
+    fn test_aliases() {
+        let (mut schedule, episode1, episode2) = series();
+
+        schedule
+            .set_event_alias(episode1.end(), "proc-42/step-3")
+            .unwrap();
+        schedule.set_event_alias(episode1.end(), "egress").unwrap();
+        schedule
+            .set_episode_alias(&episode2, "2b0c6a1e-8f4e-4bfb-9d61-7f3f0c1f7e55")
+            .unwrap();
+
+        assert_eq!(
+            schedule.event_by_alias("proc-42/step-3"),
+            Some(episode1.end())
+        );
+        assert_eq!(
+            schedule.episode_by_alias("2b0c6a1e-8f4e-4bfb-9d61-7f3f0c1f7e55"),
+            Some(episode2)
+        );
+        assert_eq!(schedule.event_by_alias("missing"), None);
+        assert_eq!(
+            schedule.aliases_of(episode1.end()),
+            vec!["egress".to_string(), "proc-42/step-3".to_string()]
+        );
+
+        // aliases travel with branches
+        let branch = schedule.branch_at(episode1.start()).unwrap();
+        assert_eq!(branch.event_by_alias("egress"), Some(episode1.end()));
+
+        assert!(schedule.remove_alias("egress"));
+        assert!(!schedule.remove_alias("egress"));
+        assert_eq!(schedule.event_by_alias("egress"), None);
+    }
+
+    #[test]
+    fn test_metadata() {
+        let (mut schedule, episode1, episode2) = series();
+
+        schedule
+            .set_episode_metadata(
+                &episode1,
+                json!({ "procedure": "2.301", "color": "#c0392b" }),
+            )
+            .unwrap();
+        schedule
+            .set_constraint_metadata(episode2.start(), episode1.end(), json!("wait for EV2"))
+            .unwrap();
+
+        assert_eq!(
+            schedule.episode_metadata(&episode1),
+            Some(&json!({ "procedure": "2.301", "color": "#c0392b" }))
+        );
+        assert_eq!(schedule.episode_metadata(&episode2), None);
+
+        // metadata is exported with the constraint in the direction it was authored
+        let sync = schedule
+            .constraints()
+            .into_iter()
+            .find(|c| c.source == episode1.end() && c.target == episode2.start())
+            .unwrap();
+        assert_eq!(sync.metadata, Some(json!("wait for EV2")));
+        assert_eq!(
+            serde_json::to_value(&sync).unwrap(),
+            json!({
+                "source": episode1.end(),
+                "target": episode2.start(),
+                "interval": [0., 0.],
+                "metadata": "wait for EV2",
+            })
+        );
+
+        // and an Episode's with its duration
+        let duration = schedule
+            .constraints()
+            .into_iter()
+            .find(|c| c.source == episode1.start() && c.target == episode1.end())
+            .unwrap();
+        assert_eq!(
+            duration.metadata,
+            Some(json!({ "procedure": "2.301", "color": "#c0392b" }))
+        );
+        assert!(schedule.to_dot().contains(&format!(
+            "  {} -> {} [label=\"[1, 5]\", comment=\"{{\\\"color\\\":\\\"#c0392b\\\",\\\"procedure\\\":\\\"2.301\\\"}}\"];",
+            episode1.start(),
+            episode1.end()
+        )));
+    }
+
+    #[test]
+    fn test_find_root() {
+        let (mut schedule, episode1, _) = series();
+        // roots are found when the Schedule compiles
+        assert_eq!(schedule.find_root(), Root::Missing);
+        schedule.compile().unwrap();
+        assert_eq!(schedule.find_root(), Root::Unique(episode1.start()));
+        assert_eq!(schedule.root(), Some(episode1.start()));
+
+        // a parallel Episode that starts at the same time is also a candidate
+        let episode3 = schedule.add_episode(Some(vec![1., 2.]));
+        schedule
+            .add_constraint(episode1.start(), episode3.start(), None)
+            .unwrap();
+        // looking doesn't compile, so the cached roots are stale until the next compile
+        assert_eq!(schedule.find_root(), Root::Unique(episode1.start()));
+        assert!(schedule.dirty);
+        schedule.compile().unwrap();
+        assert_eq!(
+            schedule.find_root(),
+            Root::Ambiguous(vec![episode1.start(), episode3.start()])
+        );
+        assert_eq!(schedule.root(), Some(episode1.start()));
+    }
+
+    #[test]
+    fn test_round_output() {
+        let mut value = json!({ "event": 3, "window": [0.1 + 0.2, f64::MAX], "nominal": 12.3456 });
+        round_output(&mut value, 3);
+        assert_eq!(
+            value,
+            json!({ "event": 3, "window": [0.3, f64::MAX], "nominal": 12.346 })
+        );
+
+        // outputs are rounded but the math isn't
+        let mut schedule = Schedule::new();
+        let episode = schedule.add_episode(Some(vec![0.1, 0.2]));
+        let next = schedule.add_episode(Some(vec![0.2, 0.2]));
+        schedule.update_interval(episode.end(), next.start(), vec![0., 0.]);
+        schedule.set_output_decimals(Some(3));
+        assert_eq!(
+            schedule.interval(episode.start(), next.end()).unwrap(),
+            Interval::new(0.1 + 0.2, 0.4)
+        );
+    }
+
+    #[test]
+    fn test_output_bounds() {
+        let value = json!({ "event": 3, "window": [-f64::MAX, 12.5], "latest": f64::MAX });
+
+        let mut raw = value.clone();
+        OutputBounds::Raw.apply(&mut raw);
+        assert_eq!(raw, value);
+
+        let mut unbounded = value.clone();
+        OutputBounds::Unbounded.apply(&mut unbounded);
+        assert_eq!(
+            unbounded,
+            json!({ "event": 3, "window": ["-unbounded", 12.5], "latest": "unbounded" })
+        );
+
+        let mut horizon = value;
+        OutputBounds::Horizon(480.).apply(&mut horizon);
+        assert_eq!(
+            horizon,
+            json!({ "event": 3, "window": [-480., 12.5], "latest": 480. })
+        );
+
+        // only unbounded numbers are rewritten, so finite times and metadata pass through
+        let mut far =
+            json!({ "event": 500, "window": [-1e12, f64::MAX], "metadata": { "cost": 5000.5 } });
+        OutputBounds::Horizon(1000.).apply(&mut far);
+        assert_eq!(
+            far,
+            json!({ "event": 500, "window": [-1e12, 1000.], "metadata": { "cost": 5000.5 } })
+        );
+    }
+
+    #[test]
+    fn test_commit_events() {
+        let (mut schedule, episode1, episode2) = series();
+        schedule.compile().unwrap();
+        let root = schedule.root().unwrap();
+
+        let mut one_by_one = schedule.clone();
+        one_by_one.commit_event(root, 0.).unwrap();
+        one_by_one.commit_event(episode1.end(), 3.).unwrap();
+
+        let report = schedule
+            .commit_events(vec![
+                (root, 0.),
+                (episode1.end(), 3.),
+                // the second Episode can't start before the first ends
+                (episode2.start(), 2.),
+                (42, 1.),
+            ])
+            .unwrap();
+
+        assert_eq!(report.applied, vec![root, episode1.end()]);
+        assert_eq!(
+            report
+                .rejected
+                .iter()
+                .map(|r| r.event)
+                .collect::<Vec<EventID>>(),
+            vec![episode2.start(), 42]
+        );
+
+        // same windows as committing one at a time
+        for event in [episode2.start(), episode2.end()].iter() {
+            assert_eq!(
+                schedule.window(*event).unwrap(),
+                one_by_one.window(*event).unwrap()
+            );
+        }
+        assert_eq!(
+            schedule.window(episode2.end()).unwrap(),
+            Interval::new(5., 12.)
+        );
+    }
+
+    #[test]
+    fn test_compile_budget() {
+        let distances = |s: &Schedule| -> BTreeMap<(EventID, EventID), f64> {
+            s.dispatchable
+                .all_edges()
+                .map(|(a, b, w)| ((a, b), *w))
+                .collect()
+        };
+        let mut plan = crate::examples::sts134(0.);
+        let mut reference = plan.schedule.clone();
+        plan.schedule.set_compile_budget(Some(0.));
+        plan.schedule.compile().unwrap();
+        assert!(!plan.schedule.stats().within_budget);
+        assert_eq!(plan.schedule.stats().events, 16);
+
+        // tightening builds on the last compile
+        for s in [&mut plan.schedule, &mut reference] {
+            s.update_interval(plan.egress.start(), plan.egress.end(), vec![42., 48.]);
+            s.compile().unwrap();
+        }
+        assert_eq!(plan.schedule.stats().strategy, Strategy::Bounded);
+        assert_eq!(plan.schedule.stats().changed, 2);
+        assert_eq!(distances(&plan.schedule), distances(&reference));
+
+        plan.schedule.set_compile_budget(Some(1000.));
+        for s in [&mut plan.schedule, &mut reference] {
+            s.update_interval(plan.misse7.start(), plan.misse7.end(), vec![56., 60.]);
+            s.compile().unwrap();
+        }
+        assert_eq!(plan.schedule.stats().strategy, Strategy::Incremental);
+        assert_eq!(distances(&plan.schedule), distances(&reference));
+
+        // loosening starts over
+        plan.schedule
+            .update_interval(plan.egress.start(), plan.egress.end(), vec![30., 60.]);
+        plan.schedule.compile().unwrap();
+        assert_eq!(plan.schedule.stats().strategy, Strategy::FloydWarshall);
+        assert!(plan.schedule.stats().within_budget);
+
+        // a long chain is sparse enough for Johnson
+        let mut chain = Schedule::new();
+        let mut last = chain.add_episode(Some(vec![1., 2.]));
+        for _ in 0..40 {
+            let next = chain.add_episode(Some(vec![1., 2.]));
+            chain.update_interval(last.end(), next.start(), vec![0., 5.]);
+            last = next;
+        }
+        let mut reference = chain.clone();
+        chain.set_compile_budget(Some(0.));
+        chain.compile().unwrap();
+        reference.compile().unwrap();
+        assert_eq!(chain.stats().strategy, Strategy::Johnson);
+        assert_eq!(distances(&chain), distances(&reference));
+    }
+
+    #[test]
+    fn test_precedence() {
+        let plan = crate::examples::sts134(0.);
+        let mut schedule = plan.schedule;
+
+        // EV2 may start egress any time after the activity starts
+        assert!(schedule
+            .must_precede(plan.egress.start(), plan.ev2_egress.start())
+            .unwrap());
+        assert!(schedule
+            .can_precede(plan.egress.start(), plan.ev2_egress.start())
+            .unwrap());
+        assert!(!schedule
+            .can_precede(plan.ev2_egress.start(), plan.egress.start())
+            .unwrap());
+
+        // both EVs start MISSE7 together
+        let (ev1, ev2) = (plan.ev1_misse7.start(), plan.ev2_misse7.start());
+        assert!(schedule.must_precede(ev1, ev2).unwrap());
+        assert!(schedule.must_precede(ev2, ev1).unwrap());
+        assert!(!schedule.can_precede(ev1, ev2).unwrap());
+
+        // EV2 starts egress no later than 10 minutes in, when EV1 finishes egress at the earliest
+        let (ev2_start, ev1_end) = (plan.ev2_egress.start(), plan.ev1_egress.end());
+        assert!(schedule.must_precede(ev2_start, ev1_end).unwrap());
+        assert!(schedule.can_precede(ev2_start, ev1_end).unwrap());
+        assert!(!schedule.must_precede(ev1_end, ev2_start).unwrap());
+    }
+
+    #[test]
+    fn test_suggest_insertion() {
+        let mut schedule = Schedule::new();
+        let a = schedule.add_episode(Some(vec![10., 20.]));
+        let b = schedule.add_episode(Some(vec![30., 40.]));
+        schedule
+            .add_constraint(a.end(), b.start(), Some(vec![0., f64::MAX]))
+            .unwrap();
+        schedule.update_interval(a.start(), b.end(), vec![0., 100.]);
+
+        // anywhere before the horizon, most room first
+        assert_eq!(
+            schedule
+                .suggest_insertion(Interval::new(15., 15.), None)
+                .unwrap(),
+            vec![
+                Insertion {
+                    anchor: a.start(),
+                    before: Some(b.end()),
+                    offsets: Interval::new(0., 85.),
+                    slack: 85.,
+                },
+                Insertion {
+                    anchor: a.end(),
+                    before: Some(b.end()),
+                    offsets: Interval::new(0., 75.),
+                    slack: 75.,
+                },
+            ]
+        );
+
+        // one actor doing both, listed out of order
+        let actor = [b, a];
+        let between = schedule
+            .suggest_insertion(Interval::new(15., 20.), Some(&actor))
+            .unwrap();
+        assert_eq!(
+            between,
+            vec![Insertion {
+                anchor: a.end(),
+                before: Some(b.start()),
+                offsets: Interval::new(0., 45.),
+                slack: 45.,
+            }]
+        );
+
+        // a slow first Episode eats into the gap
+        schedule.commit_event(a.start(), 0.).unwrap();
+        schedule.commit_event(a.end(), 20.).unwrap();
+        let between = schedule
+            .suggest_insertion(Interval::new(15., 20.), Some(&actor))
+            .unwrap();
+        assert_eq!(between[0].slack, 35.);
+        assert!(schedule
+            .suggest_insertion(Interval::new(60., 60.), Some(&actor))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_split_episode() {
+        let (mut schedule, episode1, episode2) = series();
+        schedule.commit_event(episode1.start(), 0.).unwrap();
+        schedule.commit_event(episode1.end(), 3.).unwrap();
+        schedule.commit_event(episode2.start(), 3.).unwrap();
+
+        // interrupted 1 in, before the shortest it could take
+        let rest = schedule.split_episode(&episode2, 1.).unwrap();
+        let pause = rest.start() - 1;
+        assert_eq!(rest.end(), episode2.end());
+        assert_eq!(
+            schedule.get_duration(&Episode(episode2.start(), pause)),
+            Interval::new(1., 1.)
+        );
+        assert_eq!(schedule.get_duration(&rest), Interval::new(1., 8.));
+        assert_eq!(schedule.window(pause).unwrap(), Interval::new(4., 4.));
+        assert_eq!(
+            schedule.window(rest.start()).unwrap(),
+            Interval::new(4., f64::MAX)
+        );
+
+        // resumed later and split again
+        schedule.commit_event(rest.start(), 10.).unwrap();
+        let last = schedule.split_episode(&rest, 2.).unwrap();
+        assert_eq!(schedule.get_duration(&last), Interval::new(0., 6.));
+        assert_eq!(
+            schedule.window(last.start() - 1).unwrap(),
+            Interval::new(12., 12.)
+        );
+
+        let mut replayed = Schedule::new();
+        replayed.apply_journal(schedule.journal()).unwrap();
+        assert_eq!(
+            replayed.time_windows().unwrap(),
+            schedule.time_windows().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_labeled_edges() {
+        let (mut schedule, episode1, episode2) = series();
+        schedule
+            .add_constraint(episode1.start(), episode2.end(), Some(vec![5., f64::MAX]))
+            .unwrap();
+
+        let edges = schedule.labeled_edges();
+        assert_eq!(edges.len(), 8);
+        assert!(edges.contains(&LabeledEdge {
+            source: episode2.end(),
+            target: episode2.start(),
+            weight: -2.,
+            meaning: EdgeMeaning::EpisodeLower,
+            label: "negative lower bound of Episode (2, 3)".to_string(),
+        }));
+        assert!(edges.contains(&LabeledEdge {
+            source: episode1.end(),
+            target: episode2.start(),
+            weight: 0.,
+            meaning: EdgeMeaning::ConstraintUpper,
+            label: "upper bound of the constraint from 1 to 2".to_string(),
+        }));
+        // an unbounded upper bound is still an edge
+        let upper = edges
+            .iter()
+            .find(|e| e.source == episode1.start() && e.target == episode2.end())
+            .unwrap();
+        assert_eq!(upper.weight, f64::MAX);
+        assert_eq!(upper.meaning, EdgeMeaning::ConstraintUpper);
+    }
+
+    #[test]
+    fn test_trace() {
+        let (mut schedule, episode1, episode2) = series();
+        schedule.set_compile_budget(Some(1.));
+        schedule.set_trace(Some(100));
+        let interval = schedule.interval(episode1.start(), episode2.end()).unwrap();
+        assert_eq!(interval, Interval::new(3., 14.));
+
+        let trace = schedule.trace().unwrap();
+        assert_eq!(schedule.stats().strategy, Strategy::FloydWarshall);
+        assert!(trace.relaxations.iter().all(|r| r.new < r.old));
+        // the longest it can take from start to finish is implied through the events between them
+        assert!(trace
+            .relaxations
+            .iter()
+            .any(|r| r.i == episode1.start() && r.j == episode2.end() && r.new == 14.));
+
+        schedule.set_trace(None);
+        schedule.compile().unwrap();
+        assert!(schedule.trace().is_none());
+    }
+
+    #[test]
+    fn test_transaction() {
+        let (mut schedule, episode1, episode2) = series();
+        let journal = schedule.journal().len();
+
+        // a failing step undoes the steps before it
+        let result = schedule.transaction(|s| {
+            s.update_interval(episode1.start(), episode1.end(), vec![1., 1.]);
+            s.free_episode(&episode2)
+                .map_err(|_| "free failed".to_string())?;
+            Err::<(), String>("changed my mind".to_string())
+        });
+        assert_eq!(result, Err("changed my mind".to_string()));
+        assert_eq!(schedule.journal().len(), journal);
+        assert!(schedule.episodes.contains(&episode2));
+
+        schedule.begin_transaction().unwrap();
+        assert!(schedule.begin_transaction().is_err());
+        schedule.update_interval(episode2.end(), episode1.start(), vec![0., 1.]);
+        assert!(schedule
+            .commit_transaction()
+            .unwrap_err()
+            .starts_with("the transaction was rolled back: the constraints are inconsistent"));
+        assert!(!schedule.in_transaction());
+        assert!(schedule.is_consistent());
+
+        schedule.begin_transaction().unwrap();
+        schedule.update_interval(episode1.start(), episode1.end(), vec![2., 3.]);
+        schedule.commit_transaction().unwrap();
+        assert_eq!(schedule.journal().len(), journal + 1);
+        assert_eq!(
+            schedule.rollback_transaction(),
+            Err("no transaction is open".to_string())
+        );
+    }
+
+    #[test]
+    fn test_commit_simultaneous() {
+        let mut plan = crate::examples::sts134(0.);
+        let schedule = &mut plan.schedule;
+        schedule.commit_event(plan.egress.start(), 0.).unwrap();
+
+        let together = vec![
+            plan.egress.end(),
+            plan.ev1_setup.end(),
+            plan.ev2_egress.end(),
+        ];
+        assert_eq!(
+            schedule.check_simultaneous(&[plan.ev1_egress.end(), plan.egress.end()], 15.),
+            Err(format!(
+                "events {} and {} can't happen at the same time. They must be [20, 40] apart",
+                plan.ev1_egress.end(),
+                plan.egress.end()
+            ))
+        );
+        assert!(schedule
+            .check_simultaneous(&together, 55.)
+            .unwrap_err()
+            .starts_with(&format!("cannot commit event {}", plan.egress.end())));
+
+        let journal = schedule.journal().len();
+        schedule.commit_simultaneous(together.clone(), 45.).unwrap();
+        assert_eq!(schedule.journal().len(), journal + 3);
+        assert_eq!(
+            schedule.window(plan.misse7.start()).unwrap(),
+            Interval::new(45., 45.)
+        );
+        assert_eq!(schedule.window_history().last().unwrap().commits.len(), 3);
+
+        // events already committed elsewhere are rejected, so every change is journaled and a replay ends up in the same place
+        let (mut schedule, episode1, episode2) = series();
+        schedule.commit_event(episode1.end(), 5.).unwrap();
+        assert_eq!(
+            schedule.check_simultaneous(&[episode1.end(), episode2.start()], 7.),
+            Err(format!(
+                "cannot commit event {} with {:?}: it's already committed at 5",
+                episode1.end(),
+                [episode1.end(), episode2.start()]
+            ))
+        );
+        schedule
+            .commit_simultaneous(vec![episode1.end(), episode2.start()], 5.)
+            .unwrap();
+        assert_eq!(schedule.window_history().last().unwrap().commits.len(), 1);
+        let mut replayed = Schedule::new();
+        replayed.apply_journal(schedule.journal()).unwrap();
+        assert_eq!(
+            replayed.window(episode1.end()),
+            schedule.window(episode1.end())
+        );
+        assert_eq!(
+            replayed.window(episode2.end()),
+            schedule.window(episode2.end())
+        );
+        assert_eq!(
+            replayed.window(episode2.end()).unwrap(),
+            Interval::new(7., 14.)
+        );
+    }
+
+    #[test]
+    fn test_numeric_queries() {
+        let mut plan = crate::examples::sts134(0.);
+        let schedule = &mut plan.schedule;
+        schedule.commit_event(plan.egress.start(), 0.).unwrap();
+        schedule.set_output_decimals(Some(0));
+        schedule.update_interval(plan.egress.start(), plan.egress.end(), vec![40.4, 50.]);
+
+        assert_eq!(
+            schedule
+                .distance_number(plan.egress.end(), plan.egress.start())
+                .unwrap(),
+            -40.4
+        );
+        assert_eq!(
+            schedule
+                .interval_lower_upper(plan.egress.start(), plan.egress.end())
+                .unwrap(),
+            vec![40., 50.]
+        );
+        assert_eq!(
+            schedule.window_lower_upper(plan.misse7.start()).unwrap(),
+            vec![40., 50.]
+        );
+        assert_eq!(
+            schedule
+                .windows_lower_upper(vec![plan.egress.start(), plan.misse7.start()])
+                .unwrap(),
+            vec![0., 0., 40., 50.]
+        );
+
+        // reused intervals aren't rounded
+        let mut reused = Interval::default();
+        schedule
+            .interval_into(plan.egress.start(), plan.egress.end(), &mut reused)
+            .unwrap();
+        assert_eq!(reused, Interval::new(40.4, 50.));
+        schedule
+            .window_into(plan.misse7.start(), &mut reused)
+            .unwrap();
+        assert_eq!(reused, Interval::new(40.4, 50.));
+    }
+
+    #[test]
+    fn test_lazy_dispatchable() {
+        let mut plan = crate::examples::sts134(0.);
+        let mut reference = plan.schedule.clone();
+        plan.schedule.set_lazy_dispatchable(true);
+        for s in [&mut plan.schedule, &mut reference] {
+            s.commit_event(plan.egress.start(), 0.).unwrap();
+            s.commit_event(plan.ev1_egress.end(), 15.).unwrap();
+        }
+
+        let pairs = [
+            (plan.egress.start(), plan.misse7.end()),
+            (plan.ev2_egress.end(), plan.ev1_setup.start()),
+        ];
+        assert_eq!(
+            plan.schedule.intervals(&pairs).unwrap(),
+            reference.intervals(&pairs).unwrap()
+        );
+        assert_eq!(
+            plan.schedule.time_windows().unwrap(),
+            reference.time_windows().unwrap()
+        );
+        assert_eq!(plan.schedule.root(), reference.root());
+        assert!(plan.schedule.dispatchable.graph.get().is_none());
+
+        let edges = |g: &DiGraphMap<EventID, f64>| -> BTreeMap<(EventID, EventID), f64> {
+            g.all_edges().map(|(s, t, w)| ((s, t), *w)).collect()
+        };
+        let expected = edges(reference.dispatchable_graph().unwrap());
+        assert_eq!(edges(plan.schedule.dispatchable_graph().unwrap()), expected);
+    }
+
+    #[test]
+    fn test_streamed_bounds() {
+        let mut plan = crate::examples::sts134(0.);
+        let pairs = [
+            (plan.egress.start(), plan.misse7.end()),
+            (plan.ev2_egress.end(), plan.ev1_setup.start()),
+            (plan.misse7.end(), plan.lim_cons.end()),
+        ];
+        plan.schedule.commit_event(plan.egress.start(), 0.).unwrap();
+        plan.schedule.commit_event(plan.egress.end(), 45.).unwrap();
+        let mut reference = plan.schedule.clone();
+
+        // too big to compile, but not to stream one row at a time
+        plan.schedule.set_limits(Limits {
+            max_matrix_bytes: Some(16 * 8),
+            ..Default::default()
+        });
+        assert_eq!(
+            plan.schedule.check_limits(),
+            Err("the distances between 16 events need 2048 bytes, more than the limit of 128. Stream the bounds you need instead".to_string())
+        );
+
+        let bounds = plan.schedule.streamed_bounds(&pairs).unwrap();
+        assert_eq!(Some(bounds.root), reference.root());
+        assert_eq!(bounds.windows, reference.time_windows().unwrap());
+        assert_eq!(bounds.intervals, reference.intervals(&pairs).unwrap());
+        assert_eq!(bounds.intervals[0], Interval::new(95., 115.));
+
+        assert_eq!(
+            plan.schedule.streamed_bounds(&[(0, 99)]),
+            Err("could not find event 99".to_string())
+        );
+        plan.schedule
+            .update_interval(plan.misse7.end(), plan.egress.start(), vec![0., 10.]);
+        assert!(plan
+            .schedule
+            .streamed_bounds(&pairs)
+            .unwrap_err()
+            .contains("inconsistent"));
+    }
+
+    #[test]
+    fn test_compile_diff() {
+        let (mut schedule, episode1, episode2) = series();
+        assert!(schedule.compile_diff().unwrap().is_empty());
+
+        schedule.update_interval(episode1.start(), episode1.end(), vec![2., 3.]);
+        let changes = schedule.compile_diff().unwrap();
+        assert_eq!(
+            changes[0],
+            BoundChange {
+                source: episode1.start(),
+                target: episode1.end(),
+                before: Interval::new(1., 5.),
+                after: Interval::new(2., 3.),
+                lower: 1.,
+                upper: 2.,
+            }
+        );
+        // the second Episode moved with the end of the first
+        assert!(changes
+            .iter()
+            .any(|c| c.source == episode1.start() && c.target == episode2.end()));
+        assert!(!changes
+            .iter()
+            .any(|c| c.source == episode2.start() && c.target == episode2.end()));
+    }
+
+    #[test]
+    fn test_defaults() {
+        let mut schedule = Schedule::new();
+        schedule.set_defaults(Defaults {
+            duration: Some(Interval::new(1., 2.)),
+            constraint: Some(Interval::new(0., f64::MAX)),
+        });
+        let episode1 = schedule.add_episode(None);
+        let episode2 = schedule.add_episode(Some(vec![3., 4.]));
+        schedule
+            .add_constraint(episode1.end(), episode2.start(), None)
+            .unwrap();
+        assert_eq!(
+            schedule.interval(episode1.start(), episode2.end()).unwrap(),
+            Interval::new(4., f64::MAX)
+        );
+
+        assert_eq!(
+            or_default(None, Defaults::strict().constraint, "a constraint interval"),
+            Err("a constraint interval is required. Pass a [lower, upper] interval".to_string())
+        );
+    }
+
+    #[test]
+    fn test_ensure_root() {
+        let mut schedule = Schedule::new();
+        let episode1 = schedule.add_episode(Some(vec![1., 5.]));
+        let episode2 = schedule.add_episode(Some(vec![2., 9.]));
+        assert_eq!(schedule.find_root(), Root::Missing);
+
+        let origin = schedule.ensure_root().unwrap();
+        assert_eq!(origin, 4);
+        assert_eq!(schedule.find_root(), Root::Unique(origin));
+        assert_eq!(schedule.virtual_root(), Some(origin));
+        assert_eq!(
+            schedule.interval(origin, episode1.start()).unwrap(),
+            Interval::new(0., f64::MAX)
+        );
+        assert_eq!(
+            schedule.interval(origin, episode2.start()).unwrap(),
+            Interval::new(0., f64::MAX)
+        );
+        // only the first event of each Episode is anchored
+        assert_eq!(schedule.constraints().len(), 4);
+        assert_eq!(schedule.ensure_root(), Ok(origin));
+
+        // joined at the end, so neither start is first
+        let mut auto = Schedule::new();
+        auto.set_auto_root(true);
+        let episode3 = auto.add_episode(Some(vec![1., 5.]));
+        let episode4 = auto.add_episode(Some(vec![2., 9.]));
+        auto.add_constraint(episode3.end(), episode4.end(), None)
+            .unwrap();
+        auto.compile().unwrap();
+        assert_eq!(auto.root(), auto.virtual_root());
+        // both starts are anchored
+        assert_eq!(auto.constraints().len(), 5);
+    }
+
+    #[test]
+    fn test_terminal_events() {
+        let (mut schedule, episode1, episode2) = series();
+        let parallel = schedule.add_episode(Some(vec![4., 20.]));
+        schedule
+            .add_constraint(episode1.start(), parallel.start(), None)
+            .unwrap();
+
+        assert_eq!(
+            schedule.terminal_events().unwrap(),
+            vec![episode2.end(), parallel.end()]
+        );
+        // neither Episode is guaranteed to finish last
+        assert_eq!(schedule.horizon_event().unwrap(), None);
+        assert_eq!(schedule.plan_end_window().unwrap(), Interval::new(4., 20.));
+
+        schedule
+            .add_constraint(parallel.end(), episode2.end(), Some(vec![0., f64::MAX]))
+            .unwrap();
+        assert_eq!(schedule.horizon_event().unwrap(), Some(episode2.end()));
+        assert_eq!(schedule.plan_end_window().unwrap(), Interval::new(4., 14.));
+    }
+
+    #[test]
+    fn test_blocking() {
+        let (mut schedule, episode1, episode2) = series();
+        schedule.compile().unwrap();
+        let root = schedule.root().unwrap();
+        schedule.commit_event(root, 0.).unwrap();
+
+        // the first Episode's end is simultaneous with the second's start
+        assert!(schedule.blocking(episode2.start()).unwrap().is_empty());
+        let blockers = schedule.blocking(episode2.end()).unwrap();
+        assert_eq!(
+            blockers.iter().map(|b| b.event).collect::<Vec<EventID>>(),
+            vec![episode1.end(), episode2.start()]
+        );
+        assert_eq!(blockers[0].window, Interval::new(1., 5.));
+
+        schedule.commit_event(episode1.end(), 3.).unwrap();
+        schedule.commit_event(episode2.start(), 3.).unwrap();
+        assert!(schedule.blocking(episode2.end()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_strict_commits() {
+        let (mut schedule, episode1, episode2) = series();
+        schedule.compile().unwrap();
+        let root = schedule.root().unwrap();
+        schedule.set_strict_commits(true);
+        schedule.commit_event(root, 0.).unwrap();
+        schedule.commit_event(episode1.end(), 3.).unwrap();
+
+        // eg. the wrong event was clicked
+        assert_eq!(
+            schedule.check_order(episode2.end(), 2.),
+            Err(
+                "committing event 3 at 2 is earlier than event 1 at 3, which must precede it"
+                    .to_string()
+            )
+        );
+        let report = schedule
+            .commit_events(vec![(episode2.end(), 2.), (episode2.start(), 3.)])
+            .unwrap();
+        assert_eq!(report.applied, vec![episode2.start()]);
+        assert_eq!(report.rejected[0].event, episode2.end());
+    }
+
+    #[test]
+    fn test_journal_replay() {
+        let (mut schedule, episode1, episode2) = series();
+        let x = schedule.create_event();
+        schedule
+            .add_constraint(episode2.end(), x, Some(vec![0., 30.]))
+            .unwrap();
+        schedule.update_interval(episode1.start(), episode1.end(), vec![2., 4.]);
+        schedule.commit_event(episode1.start(), 0.).unwrap();
+        schedule.complete_episode(&episode1, 3.).unwrap();
+
+        // internal calls, like compiling and replaying commitments, aren't journaled
+        schedule.compile().unwrap();
+        assert_eq!(schedule.journal().len(), 8);
+
+        // the journal survives a round trip through JSON
+        let json = serde_json::to_string(schedule.journal()).unwrap();
+        let entries: Vec<Mutation> = serde_json::from_str(&json).unwrap();
+
+        let mut replayed = Schedule::new();
+        replayed.apply_journal(&entries).unwrap();
+
+        assert_eq!(replayed.journal(), schedule.journal());
+        for event in [episode2.start(), episode2.end(), x].iter() {
+            assert_eq!(
+                replayed.window(*event).unwrap(),
+                schedule.window(*event).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_journal_replays_state() {
+        let (mut schedule, episode1, episode2) = series();
+        let loose = schedule.add_episode(Some(vec![0., 10.]));
+        schedule.set_auto_root(true);
+        schedule.compile().unwrap();
+        schedule
+            .set_event_alias(episode1.start(), "egress")
+            .unwrap();
+        schedule.set_episode_alias(&episode2, "setup").unwrap();
+        schedule.set_event_alias(episode2.end(), "gone").unwrap();
+        schedule.remove_alias("gone");
+        schedule
+            .set_episode_metadata(&episode1, json!({ "procedure": "2.301" }))
+            .unwrap();
+        schedule
+            .set_constraint_metadata(episode1.end(), episode2.start(), json!("wait"))
+            .unwrap();
+        schedule
+            .set_constraint_provenance(
+                episode1.end(),
+                episode2.start(),
+                Provenance {
+                    document: "EVA-2B".to_string(),
+                    line: Some(4),
+                    author: None,
+                },
+            )
+            .unwrap();
+        schedule.set_preference(&episode2, Preference::Target(6.));
+        schedule
+            .set_distribution(
+                &loose,
+                Distribution::Uniform {
+                    lower: 0.,
+                    upper: 10.,
+                },
+            )
+            .unwrap();
+        schedule.set_defaults(Defaults {
+            duration: None,
+            constraint: Some(Interval::new(0., f64::MAX)),
+        });
+        schedule.set_limits(Limits {
+            max_events: Some(100),
+            ..Default::default()
+        });
+        schedule.set_compile_budget(Some(5.));
+        schedule.set_lazy_dispatchable(true);
+        schedule.set_trace(Some(10));
+        schedule.set_history_limit(Some(3));
+        schedule.set_strict_commits(true);
+        schedule.set_propagation(Propagation::Hops(2));
+        schedule.set_output_bounds(OutputBounds::Horizon(100.));
+        schedule.set_output_decimals(Some(3));
+
+        // everything but caches from compiling
+        let state = |s: &Schedule| {
+            let mut edges: Vec<(EventID, EventID, f64)> =
+                s.stn.all_edges().map(|(a, b, w)| (a, b, *w)).collect();
+            edges.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            format!(
+                "{:?}",
+                (
+                    (
+                        edges,
+                        &s.episodes,
+                        &s.event_aliases,
+                        &s.episode_aliases,
+                        &s.episode_metadata,
+                        &s.constraint_metadata,
+                        &s.constraint_provenance,
+                        &s.preferences,
+                        &s.distributions,
+                        s.virtual_root,
+                    ),
+                    (
+                        s.defaults,
+                        s.limits,
+                        s.compile_budget,
+                        s.lazy_dispatchable,
+                        s.trace_limit,
+                        s.history_limit,
+                        s.strict_commits,
+                        s.auto_root,
+                        s.propagation,
+                        s.output_bounds,
+                        s.output_decimals,
+                        &s.journal,
+                    ),
+                )
+            )
+        };
+
+        let json = serde_json::to_string(schedule.journal()).unwrap();
+        let mut replayed = Schedule::new();
+        replayed
+            .apply_journal(&serde_json::from_str::<Vec<Mutation>>(&json).unwrap())
+            .unwrap();
+        assert!(schedule.virtual_root().is_some());
+        assert_eq!(state(&replayed), state(&schedule));
+    }
+
+    #[test]
+    fn test_merge_journals() {
+        let (base, episode1, episode2) = series();
+
+        let mut ours = base.clone();
+        let a = ours.add_episode(Some(vec![1., 2.]));
+        ours.add_constraint(episode2.end(), a.start(), None)
+            .unwrap();
+
+        let mut theirs = base.clone();
+        let b = theirs.add_episode(Some(vec![3., 4.]));
+        theirs
+            .add_constraint(episode1.start(), b.start(), None)
+            .unwrap();
+
+        let base_length = base.journal().len();
+        let merge = base.merge_journals(
+            &ours.journal()[base_length..],
+            &theirs.journal()[base_length..],
+        );
+        assert!(merge.conflicts.is_empty());
+
+        let mut merged = base.clone();
+        merged.apply_journal(&merge.mutations).unwrap();
+        assert_eq!(merged.stn.node_count(), 8);
+        assert_eq!(
+            merged.interval(episode1.start(), a.start()).unwrap(),
+            Interval::new(3., 14.)
+        );
+    }
+
+    #[test]
+    fn test_strong_controllability() {
+        let mut schedule = Schedule::new();
+        // eg. waiting for a hardware warm-up that takes 5 to 10 minutes
+        let warmup = schedule.add_contingent_episode(Some(vec![5., 10.]));
+        let task = schedule.add_episode(Some(vec![2., 3.]));
+        schedule
+            .add_constraint(warmup.end(), task.start(), Some(vec![0., 5.]))
+            .unwrap();
+        schedule.set_preference(&task, Preference::Earliest);
+
+        assert!(schedule.is_contingent(&warmup));
+        assert!(schedule.is_strongly_controllable());
+
+        // starting the task 10 minutes after the warm-up starts works for every warm-up duration
+        let times = schedule.conformant_schedule().unwrap();
+        assert_eq!(times.get(&warmup.start()), Some(&0.));
+        assert_eq!(times.get(&task.start()), Some(&10.));
+        assert_eq!(times.get(&warmup.end()), None);
+
+        // a 4 minute gap can't cover the 5 minutes of warm-up uncertainty without observing it
+        let mut tight = schedule.clone();
+        tight.update_interval(warmup.end(), task.start(), vec![0., 4.]);
+        assert!(tight.is_consistent());
+        assert!(!tight.is_strongly_controllable());
+    }
+
+    #[test]
+    fn test_observe_contingent() {
+        let mut schedule = Schedule::new();
+        let warmup = schedule.add_contingent_episode(Some(vec![5., 10.]));
+        let task = schedule.add_episode(Some(vec![2., 3.]));
+        schedule
+            .add_constraint(warmup.end(), task.start(), Some(vec![0., 5.]))
+            .unwrap();
+
+        schedule.commit_event(warmup.start(), 0.).unwrap();
+        schedule.observe_contingent(&warmup, 6.).unwrap();
+
+        assert_eq!(schedule.observed_duration(&warmup), Some(6.));
+        assert_eq!(
+            schedule.window(warmup.end()).unwrap(),
+            Interval::new(6., 6.)
+        );
+        assert_eq!(
+            schedule.window(task.start()).unwrap(),
+            Interval::new(6., 11.)
+        );
+
+        // once observed, a gap that was too tight to fix ahead of time is fine
+        schedule.update_interval(warmup.end(), task.start(), vec![0., 4.]);
+        assert!(schedule.is_strongly_controllable());
+
+        // observations are replayed from the journal
+        let mut replayed = Schedule::new();
+        replayed.apply_journal(schedule.journal()).unwrap();
+        assert_eq!(
+            replayed.window(task.start()).unwrap(),
+            schedule.window(task.start()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_repair_overrun() {
+        let mut schedule = Schedule::new();
+        let warmup = schedule.add_contingent_episode(Some(vec![5., 10.]));
+        let task = schedule.add_episode(Some(vec![2., 3.]));
+        schedule
+            .add_constraint(warmup.end(), task.start(), Some(vec![0., 5.]))
+            .unwrap();
+        schedule
+            .add_constraint(warmup.start(), task.end(), Some(vec![0., 20.]))
+            .unwrap();
+        schedule.commit_event(warmup.start(), 0.).unwrap();
+
+        // the warm-up took 12 minutes instead of at most 10
+        let dry_run = schedule.repair(&warmup, 12., RepairPolicy::DryRun).unwrap();
+        assert_eq!(schedule.observed_duration(&warmup), None);
+
+        let report = schedule.repair(&warmup, 12., RepairPolicy::Extend).unwrap();
+        assert_eq!(report, dry_run);
+        assert_eq!(report.declared, Interval::new(5., 10.));
+        assert_eq!(report.extended, Interval::new(5., 12.));
+        assert!(report.consistent);
+        assert!(report.infeasible.is_empty());
+        assert!(report.changes.contains(&WindowChange {
+            event: task.start(),
+            before: Interval::new(5., 15.),
+            after: Interval::new(12., 17.),
+        }));
+
+        assert_eq!(schedule.get_duration(&warmup), Interval::new(5., 12.));
+        assert_eq!(schedule.observed_duration(&warmup), Some(12.));
+
+        // an overrun past the deadline leaves the task without a valid time
+        let mut late = Schedule::new();
+        let warmup = late.add_contingent_episode(Some(vec![5., 10.]));
+        let task = late.add_episode(Some(vec![2., 3.]));
+        late.add_constraint(warmup.end(), task.start(), None)
+            .unwrap();
+        late.add_constraint(warmup.start(), task.end(), Some(vec![0., 14.]))
+            .unwrap();
+        late.commit_event(warmup.start(), 0.).unwrap();
+
+        let report = late.repair(&warmup, 13., RepairPolicy::Extend).unwrap();
+        assert!(!report.consistent);
+        assert_eq!(report.infeasible, vec![task.start(), task.end()]);
+    }
+
+    #[test]
+    fn test_allocate_risk() {
+        let mut schedule = Schedule::new();
+        let drive = schedule.add_contingent_episode(Some(vec![0., 100.]));
+        let survey = schedule.add_contingent_episode(Some(vec![0., 100.]));
+        // the survey has to start within 5 minutes of arriving, but arrival can't be observed
+        schedule
+            .add_constraint(drive.end(), survey.start(), Some(vec![0., 5.]))
+            .unwrap();
+        schedule
+            .add_constraint(drive.start(), survey.end(), Some(vec![0., 40.]))
+            .unwrap();
+        schedule
+            .set_distribution(
+                &drive,
+                Distribution::Normal {
+                    mean: 10.,
+                    std_dev: 1.,
+                },
+            )
+            .unwrap();
+        schedule
+            .set_distribution(
+                &survey,
+                Distribution::Normal {
+                    mean: 10.,
+                    std_dev: 2.,
+                },
+            )
+            .unwrap();
+
+        let allocation = schedule.allocate_risk(0.05).unwrap();
+        assert!(allocation.feasible);
+        assert_eq!(allocation.assignments.len(), 2);
+
+        let total: f64 = allocation.assignments.iter().map(|a| a.risk).sum();
+        assert!(total <= 0.05 + 1e-9);
+
+        // the drive is the tight link. it needs bounds less than 5 minutes wide
+        let drive_bounds = allocation.assignments[0].bounds;
+        assert!(drive_bounds.upper() - drive_bounds.lower() <= 5.);
+
+        schedule.apply_risk_allocation(&allocation);
+        assert!(schedule.is_strongly_controllable());
+    }
+
+    #[test]
+    fn test_expected_times() {
+        let mut schedule = Schedule::new();
+        let egress = schedule.add_episode(Some(vec![10., 20.]));
+        let ev1 = schedule.add_episode(Some(vec![0., 100.]));
+        let ev2 = schedule.add_episode(Some(vec![20., 40.]));
+        let ingress = schedule.add_episode(Some(vec![5., 5.]));
+        for task in [ev1, ev2].iter() {
+            schedule
+                .add_constraint(egress.end(), task.start(), None)
+                .unwrap();
+            schedule
+                .add_constraint(task.end(), ingress.start(), Some(vec![0., f64::MAX]))
+                .unwrap();
+        }
+        schedule
+            .set_distribution(
+                &ev1,
+                Distribution::Normal {
+                    mean: 35.,
+                    std_dev: 3.,
+                },
+            )
+            .unwrap();
+
+        let expected: BTreeMap<EventID, ExpectedTime> = schedule
+            .expected_times()
+            .into_iter()
+            .map(|e| (e.event, e))
+            .collect();
+
+        // uniform [10, 20] egress
+        assert_eq!(expected[&egress.end()].mean, 15.);
+        assert!((expected[&egress.end()].variance - 100. / 12.).abs() < 1e-9);
+        // EV1's 35 minute mean beats EV2's 30, so ingress waits on EV1
+        assert_eq!(expected[&ev2.end()].mean, 45.);
+        assert_eq!(expected[&ingress.start()].mean, 50.);
+        assert!((expected[&ingress.start()].variance - (100. / 12. + 9.)).abs() < 1e-9);
+        assert_eq!(expected[&ingress.end()].mean, 55.);
+
+        // commitments pin events
+        schedule.commit_event(egress.start(), 0.).unwrap();
+        schedule.commit_event(egress.end(), 12.).unwrap();
+        let ingress_start = schedule
+            .expected_times()
+            .into_iter()
+            .find(|e| e.event == ingress.start())
+            .unwrap();
+        assert_eq!(ingress_start.mean, 47.);
+        assert_eq!(ingress_start.variance, 9.);
+    }
+
+    #[test]
+    fn test_critical_path() {
+        let (mut schedule, episode1, episode2) = series();
+        let side = schedule.add_episode(Some(vec![1., 1.]));
+        schedule
+            .add_constraint(episode1.start(), side.start(), None)
+            .unwrap();
+
+        assert_eq!(
+            schedule.critical_path().unwrap(),
+            vec![
+                episode1.start(),
+                episode1.end(),
+                episode2.start(),
+                episode2.end()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compile_rebuilds_windows_from_commitments() {
+        let (mut schedule, episode1, _) = series();
+        schedule.commit_event(episode1.start(), 0.).unwrap();
+        assert_eq!(
+            schedule.window(episode1.end()).unwrap(),
+            Interval::new(1., 5.)
+        );
+
+        // loosening a constraint widens the windows of committed plans on the next compile
+        schedule.update_interval(episode1.start(), episode1.end(), vec![1., 10.]);
+        assert_eq!(
+            schedule.window(episode1.end()).unwrap(),
+            Interval::new(1., 10.)
+        );
+        assert_eq!(
+            schedule.window(episode1.start()).unwrap(),
+            Interval::new(0., 0.)
+        );
+    }
+
+    #[test]
+    fn test_limited_propagation() {
+        let (mut schedule, episode1, episode2) = series();
+        schedule.set_propagation(Propagation::Hops(1));
+        // compiling propagates fully, so compile before committing
+        schedule.compile().unwrap();
+        schedule.commit_event(episode1.start(), 0.).unwrap();
+        assert_eq!(
+            schedule.window(episode1.end()).unwrap(),
+            Interval::new(1., 5.)
+        );
+        assert_eq!(schedule.window(episode2.end()).unwrap().upper(), f64::MAX);
+
+        schedule.reconcile().unwrap();
+        assert_eq!(
+            schedule.window(episode2.end()).unwrap(),
+            Interval::new(3., 14.)
+        );
+
+        // episode2 can't start until at least 1 after episode1 starts
+        let (mut schedule, episode1, episode2) = series();
+        schedule.set_propagation(Propagation::Horizon(1.));
+        schedule.compile().unwrap();
+        schedule.commit_event(episode1.start(), 0.).unwrap();
+        assert_eq!(
+            schedule.window(episode2.start()).unwrap(),
+            Interval::new(1., 5.)
+        );
+        assert_eq!(schedule.window(episode2.end()).unwrap().lower(), -f64::MAX);
+    }
+
+    #[test]
+    fn test_infeasible_events() {
+        let mut plan = crate::examples::walkthrough();
+        let report = plan.schedule.commit_events(vec![(plan.x0, 0.)]).unwrap();
+        assert!(report.infeasible.is_empty());
+        assert!(plan.schedule.infeasible_events().is_empty());
+
+        // l can't start until 10, so ending it at 35 leaves it no time
+        plan.schedule.record_commit(plan.l.end(), 35.).unwrap();
+        assert_eq!(plan.schedule.infeasible_events(), vec![plan.l.start()]);
+    }
+
+    #[test]
+    fn test_constraint_provenance() {
+        let mut plan = crate::examples::walkthrough();
+        let x0 = plan.x0;
+        let l_end = plan.l.end();
+        plan.schedule
+            .set_constraint_provenance(
+                x0,
+                plan.l.start(),
+                Provenance {
+                    document: "EVA-2B".to_string(),
+                    line: Some(12),
+                    author: Some("jsmith".to_string()),
+                },
+            )
+            .unwrap();
+        plan.schedule.update_interval(x0, l_end, vec![0., 35.]);
+        plan.schedule
+            .set_constraint_provenance(
+                l_end,
+                x0,
+                Provenance {
+                    document: "EVA-2B".to_string(),
+                    line: Some(40),
+                    author: None,
+                },
+            )
+            .unwrap();
+
+        let constraint = plan
+            .schedule
+            .constraints()
+            .into_iter()
+            .find(|c| (c.source, c.target) == (x0, l_end))
+            .unwrap();
+        assert_eq!(constraint.provenance.unwrap().line, Some(40));
+
+        let findings = plan.schedule.lint();
+        assert!(findings[0]
+            .message
+            .ends_with("(from EVA-2B line 40; EVA-2B line 12 by jsmith)"));
+        let diagnostic = &plan.schedule.diagnose_cycles()[0];
+        assert!(diagnostic
+            .suggestions
+            .iter()
+            .any(|s| s.ends_with("(from EVA-2B line 40)")));
+    }
+
+    #[test]
+    fn test_duration_parameters() {
+        let mut schedule = Schedule::new();
+        let root = schedule.create_event();
+        let translate = schedule.add_episode(Some(vec![5., 5.]));
+        let work = schedule.add_episode(Some(vec![20., 30.]));
+        schedule.update_interval(root, translate.start(), vec![0., 0.]);
+        schedule.update_interval(translate.end(), work.start(), vec![0., 0.]);
+        schedule
+            .set_duration_parameter(&translate, "translation_time")
+            .unwrap();
+        assert_eq!(
+            schedule.compile_with_cancel(&CancelToken::new()),
+            Err(Abort::Failed(format!(
+                "Episode ({}, {}) uses parameter \"translation_time\", which isn't bound",
+                translate.start(),
+                translate.end()
+            )))
+        );
+
+        let mut optimistic = BTreeMap::new();
+        optimistic.insert("translation_time".to_string(), Interval::new(10., 15.));
+        schedule.bind_parameters(&optimistic);
+        assert_eq!(
+            schedule.interval(root, work.end()).unwrap(),
+            Interval::new(30., 45.)
+        );
+
+        // recompile against a different assumption
+        schedule.bind_parameter("translation_time", vec![25., 30.]);
+        assert_eq!(
+            schedule.interval(root, work.end()).unwrap(),
+            Interval::new(45., 60.)
+        );
+
+        let mut replayed = Schedule::new();
+        replayed.apply_journal(schedule.journal()).unwrap();
+        assert_eq!(
+            replayed.interval(root, work.end()).unwrap(),
+            Interval::new(45., 60.)
+        );
+        assert_eq!(
+            replayed.duration_parameter(&translate),
+            Some("translation_time")
+        );
+    }
+
+    #[test]
+    fn test_scale_plan() {
+        let mut plan = crate::examples::walkthrough();
+        plan.schedule.commit_event(plan.x0, 0.).unwrap();
 
-        // all incoming edges should be <= 0 for the first node
-        self.dispatchable.nodes().find(|s| {
-            self.dispatchable
-                .neighbors_directed(*s, petgraph::Incoming)
-                .all(|t| match self.dispatchable.edge_weight(t, *s) {
-                    Some(w) => *w <= 0.,
-                    None => false,
-                })
-        })
+        let mut scaled = plan.schedule.scale_plan(1.5).unwrap();
+        assert_eq!(
+            scaled.interval(plan.x0, plan.l.end()).unwrap(),
+            Interval::new(60., 75.)
+        );
+        assert_eq!(scaled.get_duration(&plan.l), Interval::new(45., 60.));
+        assert_eq!(scaled.window(plan.x0).unwrap().lower(), -f64::MAX);
+
+        let mut replayed = Schedule::new();
+        replayed.apply_journal(scaled.journal()).unwrap();
+        assert_eq!(replayed.constraints(), scaled.constraints());
+
+        // the original is untouched
+        assert_eq!(plan.schedule.get_duration(&plan.l), Interval::new(30., 40.));
     }
 
-    /// List event IDs in chronological order
-    pub fn order(&self) -> Vec<EventID> {
-        // TODO
-        vec![0]
+    #[test]
+    fn test_compile_with_cancel() {
+        let mut plan = crate::examples::walkthrough();
+        let token = CancelToken::new();
+        token.clone().cancel();
+
+        assert_eq!(
+            plan.schedule.compile_with_cancel(&token),
+            Err(Abort::Cancelled(Progress {
+                completed: 0,
+                total: 5
+            }))
+        );
+        assert!(plan.schedule.dispatchable.node_count() == 0);
+
+        assert_eq!(
+            plan.schedule.compile_with_cancel(&CancelToken::new()),
+            Ok(())
+        );
+        assert_eq!(
+            plan.schedule.interval(plan.x0, plan.l.end()).unwrap(),
+            Interval::new(40., 50.)
+        );
     }
 
-    /// Low-level API for creating nodes in the graph. Advanced use only. If you can't explain why you should use this over `addEpisode`, use `addEpisode` instead
-    #[wasm_bindgen(js_name = createEvent)]
-    pub fn create_event(&mut self) -> EventID {
-        let event_id = self.stn.node_count() as i32;
-        self.execution_windows
-            .insert(event_id, Interval(-std::f64::MAX, std::f64::MAX));
-        let n = self.stn.add_node(event_id);
+    #[test]
+    fn test_limits() {
+        let mut schedule = Schedule::new();
+        let a = schedule.add_episode(Some(vec![1., 2.]));
+        let b = schedule.add_episode(Some(vec![1., 2.]));
+        schedule.add_constraint(a.end(), b.start(), None).unwrap();
+        assert_eq!(schedule.estimated_compile_cost(), 64.);
 
-        self.dirty = true;
-        n
+        schedule.set_limits(Limits {
+            max_events: Some(4),
+            max_edges: Some(6),
+            max_matrix_bytes: None,
+        });
+        assert!(schedule.check_limits().is_ok());
+
+        schedule.create_event();
+        assert_eq!(
+            schedule.check_limits(),
+            Err("the plan has 5 events, more than the limit of 4".to_string())
+        );
+
+        schedule.set_limits(Limits {
+            max_events: None,
+            max_edges: Some(4),
+            max_matrix_bytes: None,
+        });
+        assert_eq!(
+            schedule.check_limits(),
+            Err("the plan has 6 edges, more than the limit of 4".to_string())
+        );
     }
 
-    /// Build an Episode but don't add it to the graph
-    fn new_episode(&mut self) -> Episode {
-        let start_id = self.create_event();
-        let end_id = self.create_event();
-        Episode(start_id, end_id)
+    #[test]
+    fn test_window_history() {
+        let mut schedule = Schedule::new();
+        let a = schedule.add_episode(Some(vec![10., 20.]));
+        let b = schedule.add_episode(Some(vec![5., 5.]));
+        schedule.add_constraint(a.end(), b.start(), None).unwrap();
+        schedule.commit_event(a.start(), 0.).unwrap();
+        schedule.commit_event(a.end(), 12.).unwrap();
+
+        let history: Vec<&WindowSnapshot> = schedule.window_history().collect();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].commits, vec![(a.start(), 0.)]);
+        assert_eq!(history[0].windows[&b.end()], Interval::new(15., 25.));
+        assert_eq!(history[1].windows[&b.end()], Interval::new(17., 17.));
+
+        schedule.set_history_limit(Some(1));
+        schedule
+            .commit_events(vec![(b.start(), 12.), (b.end(), 17.)])
+            .unwrap();
+        let history: Vec<&WindowSnapshot> = schedule.window_history().collect();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].sequence, 2);
+        assert_eq!(history[0].commits, vec![(b.start(), 12.), (b.end(), 17.)]);
+
+        let branch = schedule.branch_at(a.end()).unwrap();
+        assert_eq!(branch.window_history().count(), 0);
     }
 
-    /// Create a new Episode and add it to this Schedule
-    #[wasm_bindgen(catch, js_name = addEpisode)]
-    pub fn add_episode(&mut self, duration: Option<Vec<f64>>) -> Episode {
-        let d = duration.unwrap_or(vec![0., 0.]);
-        let i = Interval::from_vec(d);
+    #[test]
+    fn test_mutex() {
+        // two 5 minute Episodes that both want to start within 10 minutes of the root
+        let mut schedule = Schedule::new();
+        let root = schedule.create_event();
+        let a = schedule.add_episode(Some(vec![5., 5.]));
+        let b = schedule.add_episode(Some(vec![5., 5.]));
+        schedule.update_interval(root, a.start(), vec![0., 10.]);
+        schedule.update_interval(root, b.start(), vec![0., 10.]);
+        schedule.add_mutex(&a, &b).unwrap();
 
-        // create the Episode and add edges for its interval
-        // make it a distance graph so the lower bound is negative
-        let episode = self.new_episode();
-        self.stn.add_edge(episode.0, episode.1, i.upper());
-        self.stn.add_edge(episode.1, episode.0, -i.lower());
+        // a goes first since it was listed first
+        assert!(schedule.interval(a.end(), b.start()).unwrap().lower() >= 0.);
 
-        self.dirty = true;
-        episode
+        // a can't go first if b has to start right away
+        schedule.update_interval(root, b.start(), vec![0., 0.]);
+        assert!(schedule.interval(b.end(), a.start()).unwrap().lower() >= 0.);
+        assert_eq!(schedule.window(a.start()).unwrap().lower(), -f64::MAX);
+        schedule.commit_event(root, 0.).unwrap();
+        assert_eq!(schedule.window(a.start()).unwrap(), Interval::new(5., 10.));
+
+        // mutexes aren't authored constraints
+        assert_eq!(schedule.constraints().len(), 4);
+
+        let mut replayed = Schedule::new();
+        replayed.apply_journal(schedule.journal()).unwrap();
+        assert_eq!(replayed.window(a.start()).unwrap(), Interval::new(5., 10.));
     }
 
-    /// Get the controllable duration of an Episode
-    #[wasm_bindgen(js_name = getDuration)]
-    pub fn get_duration(&self, s: &Episode) -> Interval {
-        let lower = self.stn.edge_weight(s.1, s.0).unwrap_or(&0.);
-        let upper = self.stn.edge_weight(s.0, s.1).unwrap_or(&0.);
-        Interval::new(-*lower, *upper)
+    #[test]
+    fn test_level() {
+        // two 5 minute Episodes by one actor that can each start within 10 minutes of the root
+        let mut schedule = Schedule::new();
+        let root = schedule.create_event();
+        let a = schedule.add_episode(Some(vec![5., 5.]));
+        let b = schedule.add_episode(Some(vec![5., 5.]));
+        schedule.update_interval(root, a.start(), vec![0., 10.]);
+        schedule.update_interval(root, b.start(), vec![2., 10.]);
+
+        let mut groups = BTreeMap::new();
+        groups.insert("EV1".to_string(), vec![a, b]);
+        let leveling = schedule.level(&groups).unwrap();
+        assert_eq!(leveling.ordered.len(), 1);
+        assert_eq!(leveling.ordered[0].first, (a.start(), a.end()));
+        assert!(leveling.conflicts.is_empty());
+        assert!(schedule.interval(a.end(), b.start()).unwrap().lower() >= 0.);
+
+        // leveling again changes nothing
+        assert_eq!(schedule.level(&groups).unwrap(), Leveling::default());
+
+        // both have to start right away
+        schedule.update_interval(root, b.start(), vec![0., 0.]);
+        schedule.update_interval(a.end(), b.start(), vec![-f64::MAX, f64::MAX]);
+        schedule.update_interval(root, a.start(), vec![0., 0.]);
+        let leveling = schedule.level(&groups).unwrap();
+        assert_eq!(leveling.conflicts.len(), 1);
+        assert!(leveling.ordered.is_empty());
     }
 
-    /// Compile the Schedule into a dispatchable form. A dispatchable form is required to query the Schedule for almost any scheduling information. This method is called implicitly when you attempt to query the Schedule when the dispatchable graph is not up-to-date. However, you can proactively call `compile` at a time that is computationally convenient for your application to avoid paying the performance penalty when querying the Schedule
-    #[wasm_bindgen(catch)]
-    pub fn compile(&mut self) -> Result<(), JsValue> {
-        if !self.dirty {
-            return Ok(());
-        }
+    #[test]
+    fn test_intervals() {
+        let (mut schedule, episode1, episode2) = series();
+        let pairs = [
+            (episode1.start(), episode1.end()),
+            (episode1.start(), episode2.end()),
+        ];
+        let intervals = schedule.intervals(&pairs).unwrap();
+        assert_eq!(
+            intervals[0],
+            schedule.interval(pairs[0].0, pairs[0].1).unwrap()
+        );
 
-        // TODO: is it a problem if there are any detached Events/Episodes?
+        // memoized intervals are dropped when the Schedule recompiles
+        schedule.update_interval(episode1.start(), episode1.end(), vec![2., 3.]);
+        assert_eq!(
+            schedule.interval(episode1.start(), episode1.end()).unwrap(),
+            Interval::new(2., 3.)
+        );
+        assert_ne!(schedule.intervals(&pairs).unwrap(), intervals);
+    }
 
-        // run all-pairs shortest paths
-        let mappings = match floyd_warshall(&self.stn) {
-            Ok(d) => d,
-            Err(e) => return Err(JsValue::from_str(&e)),
-        };
+    #[test]
+    fn test_explain_interval() {
+        let (mut schedule, episode1, episode2) = series();
+        // a deadline that tightens the upper bound of the second Episode's end
+        schedule
+            .add_constraint(episode1.start(), episode2.end(), Some(vec![0., 10.]))
+            .unwrap();
 
-        // reset the dispatchable graph
-        self.dispatchable = DiGraphMap::new();
+        let explanation = schedule
+            .explain_interval(episode1.end(), episode2.end())
+            .unwrap();
+        assert_eq!(
+            explanation.interval,
+            schedule.interval(episode1.end(), episode2.end()).unwrap()
+        );
 
-        // add all the edges
-        for ((source, target), weight) in mappings.iter() {
-            self.dispatchable.add_edge(*source, *target, *weight);
-        }
-        // mark not-dirty as soon as possible so we can use commit_event below, which calls this function, without recursing to this point
-        self.dirty = false;
+        // the upper bound runs back along the first Episode's lower bound, then forwards along the deadline
+        let upper: Vec<(EventID, EventID, &str)> = explanation
+            .upper
+            .iter()
+            .map(|b| (b.constraint.source, b.constraint.target, b.bound))
+            .collect();
+        assert_eq!(
+            upper,
+            vec![
+                (episode1.start(), episode1.end(), "lower"),
+                (episode1.start(), episode2.end(), "upper")
+            ]
+        );
 
-        // update execution windows with known committments
-        let c = self.committments.clone();
-        for (executed_event, time) in c.iter() {
-            self.commit_event(*executed_event, *time)?;
-        }
+        // the lower bound comes from the gap and the second Episode
+        let lower: Vec<&str> = explanation.lower.iter().map(|b| b.bound).collect();
+        assert_eq!(lower, vec!["lower", "lower"]);
+    }
 
-        Ok(())
+    #[test]
+    fn test_layers() {
+        let (mut schedule, episode1, episode2) = series();
+        // a checklist item that starts with the first Episode
+        let checklist = schedule.add_episode(Some(vec![2., 3.]));
+        schedule
+            .add_constraint(episode1.start(), checklist.start(), Some(vec![0., 0.]))
+            .unwrap();
+
+        assert_eq!(
+            schedule.layers().unwrap(),
+            vec![
+                vec![episode1.start(), checklist.start()],
+                // the gap between Episodes can be 0, so episode 2 can start with the end of episode 1
+                vec![episode1.end(), episode2.start(), checklist.end()],
+                vec![episode2.end()]
+            ]
+        );
+        assert_eq!(
+            schedule.order().unwrap(),
+            vec![
+                episode1.start(),
+                checklist.start(),
+                episode1.end(),
+                episode2.start(),
+                checklist.end(),
+                episode2.end()
+            ]
+        );
     }
 
-    /// Greedily update execution windows
-    fn update_schedule(&mut self, event: EventID) -> Result<(), JsValue> {
-        self.compile()?;
+    #[test]
+    fn test_lint() {
+        let (mut schedule, episode1, episode2) = series();
+        assert_eq!(schedule.lint(), vec![]);
 
-        let d = self.dispatchable.clone();
-        for neighbor in d.neighbors(event) {
-            if self.committments.contains_key(&neighbor) {
-                // neighbor has already been scheduled
-                continue;
-            }
+        schedule
+            .add_constraint(episode1.start(), episode2.end(), Some(vec![3., 14.]))
+            .unwrap();
+        let findings = schedule.lint();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].severity, "warning");
+        assert_eq!(findings[0].events, vec![episode1.start(), episode2.end()]);
 
-            let time_to_neighbor = self.interval(event, neighbor)?;
-            let neighbor_window = match self.execution_windows.get(&neighbor) {
-                Some(i) => i,
-                None => return Err(JsValue::from_str(&format!("no such event {}", neighbor))),
-            };
-            let event_window = match self.execution_windows.get(&event) {
-                Some(i) => i,
-                None => return Err(JsValue::from_str(&format!("no such event {}", event))),
-            };
+        schedule
+            .add_constraint(episode1.start(), episode2.end(), Some(vec![0., 2.]))
+            .unwrap();
+        let findings = schedule.lint();
+        assert_eq!(findings[0].severity, "error");
+        assert_eq!(findings[0].events.len(), 4);
 
-            // update neighbor execution windows
-            // bounds_i = bounds_i ^ (v + time_event_to_neighbor)
-            let new_neighbor_window = *neighbor_window & (*event_window + time_to_neighbor);
-            self.execution_windows.insert(neighbor, new_neighbor_window);
-        }
+        let mut detached = Schedule::new();
+        detached.add_episode(Some(vec![1., 2.]));
+        detached.add_episode(Some(vec![1., 2.]));
+        assert_eq!(
+            detached.lint()[0].message,
+            "no event happens before every other event"
+        );
+    }
 
-        Ok(())
+    #[test]
+    fn test_diagnose_cycles() {
+        // two rigid Episodes that start together, but the shorter one is synced to end after the longer one
+        let mut schedule = Schedule::new();
+        let long = schedule.add_episode(Some(vec![35., 35.]));
+        let short = schedule.add_episode(Some(vec![25., 25.]));
+        schedule
+            .add_constraint(long.start(), short.start(), Some(vec![0., 0.]))
+            .unwrap();
+        schedule
+            .add_constraint(long.end(), short.end(), Some(vec![10., 10.]))
+            .unwrap();
+
+        let diagnostics = schedule.diagnose_cycles();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].kind, "contradictory");
+        assert_eq!(diagnostics[0].excess, 20.);
+        assert_eq!(
+            diagnostics[0].constraints.len(),
+            diagnostics[0].events.len()
+        );
+        let flip = diagnostics[0]
+            .suggestions
+            .iter()
+            .filter(|s| s.contains("backwards"))
+            .collect::<Vec<_>>();
+        assert_eq!(flip.len(), 1);
+        assert!(flip[0].starts_with(&format!("{} -> {} [10, 10]", long.end(), short.end())));
+
+        let mut rigid = Schedule::new();
+        let a = rigid.create_event();
+        let b = rigid.create_event();
+        let c = rigid.create_event();
+        rigid.add_constraint(a, b, Some(vec![0., 5.])).unwrap();
+        rigid.add_constraint(b, c, Some(vec![0., 5.])).unwrap();
+        rigid.add_constraint(c, a, Some(vec![0., 5.])).unwrap();
+
+        let diagnostics = rigid.diagnose_cycles();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].kind, "rigid");
+        assert_eq!(diagnostics[0].events, vec![a, b, c]);
+        assert!(diagnostics[0]
+            .constraints
+            .iter()
+            .all(|b| b.bound == "lower"));
+        assert!(rigid.lint().iter().any(|f| f.events == vec![a, b, c]));
     }
 
-    /// Low-level API for marking an event complete. Advanced use only. If you can't explain why you should use this over `completeEpisode`, use `completeEpisode` instead. Commits an event to a time within its interval and greedily updates the schedule for remaining events. Time is in elapsed time since the Schedule started
-    #[wasm_bindgen(catch, js_name = commitEvent)]
-    pub fn commit_event(&mut self, event: EventID, time: f64) -> Result<(), JsValue> {
-        self.committments.insert(event, time);
-        self.execution_windows
-            .insert(event, Interval::new(time, time));
-        self.update_schedule(event)?;
+    #[test]
+    fn test_to_dot() {
+        let (mut schedule, episode1, _) = series();
+        schedule
+            .set_event_alias(episode1.start(), "egress")
+            .unwrap();
 
-        Ok(())
+        let dot = schedule.to_dot();
+        assert!(dot.starts_with("digraph schedule {"));
+        assert!(dot.contains("0 [label=\"egress (0)\"];"));
+        assert!(dot.contains("0 -> 1 [label=\"[1, 5]\"];"));
     }
 
-    /// Mark an Episode complete to update the schedule to following Episodes. The time should be the elapsed time since the Schedule started (in the same units as well)
-    #[wasm_bindgen(catch, js_name = completeEpisode)]
-    pub fn complete_episode(&mut self, episode: &Episode, time: f64) -> Result<(), JsValue> {
-        self.commit_event(episode.end(), time)?;
-        Ok(())
+    #[test]
+    fn test_zoom_occupancy() {
+        let mut schedule = Schedule::new();
+        let walk = schedule.add_episode(Some(vec![10., 10.]));
+        let setup = schedule.add_episode(Some(vec![5., 5.]));
+        let photo = schedule.add_episode(Some(vec![12., 12.]));
+        schedule
+            .add_constraint(walk.end(), setup.start(), Some(vec![5., 5.]))
+            .unwrap();
+        schedule
+            .add_constraint(walk.start(), photo.start(), None)
+            .unwrap();
+
+        let mut groups = BTreeMap::new();
+        groups.insert("EV1".to_string(), vec![walk, setup]);
+        groups.insert("EV2".to_string(), vec![photo]);
+
+        let zooms = schedule.zoom(&[5., 15.], &groups).unwrap();
+        let occupancy = |zoom: &Zoom, group: &str| -> Vec<f64> {
+            zoom.buckets.iter().map(|b| b.occupancy[group]).collect()
+        };
+
+        assert_eq!(zooms[0].buckets.len(), 4);
+        assert_eq!(occupancy(&zooms[0], "EV1"), vec![1., 1., 0., 1.]);
+        assert_eq!(occupancy(&zooms[0], "EV2"), vec![1., 1., 0.4, 0.]);
+
+        assert_eq!(zooms[1].buckets.len(), 2);
+        assert_eq!(zooms[1].buckets[1].start, 15.);
+        assert_eq!(occupancy(&zooms[1], "EV1"), vec![10. / 15., 5. / 15.]);
+        assert_eq!(occupancy(&zooms[1], "EV2"), vec![12. / 15., 0.]);
     }
 
-    /// Get the execution window of an Event
-    #[wasm_bindgen(catch)]
-    pub fn window(&mut self, event: EventID) -> Result<Interval, JsValue> {
-        self.compile()?;
+    #[test]
+    fn test_simulate_skewed_durations() {
+        let mut schedule = Schedule::new();
+        let prep = schedule.add_episode(Some(vec![5., 5.]));
+        let task = schedule.add_episode(Some(vec![0., f64::MAX]));
+        schedule
+            .add_constraint(prep.end(), task.start(), None)
+            .unwrap();
+        schedule
+            .add_constraint(prep.start(), task.end(), Some(vec![0., 30.]))
+            .unwrap();
+        schedule
+            .set_distribution(
+                &task,
+                Distribution::Erlang {
+                    shift: 10.,
+                    shape: 2,
+                    rate: 0.5,
+                },
+            )
+            .unwrap();
 
-        match self.execution_windows.get(&event) {
-            Some(i) => Ok(*i),
-            None => Err(JsValue::from(&format!("could not find event {}", event))),
-        }
+        let simulation = schedule.simulate(5000, 42);
+        assert_eq!(simulation, schedule.simulate(5000, 42));
+        assert_eq!(simulation.runs, 5000);
+
+        // makespan is 5 + 10 + Gamma(2, 0.5), which is right-skewed with a mean of 19
+        assert!((simulation.mean_makespan - 19.).abs() < 0.2);
+        assert!(simulation.p50_makespan < simulation.mean_makespan);
+        // P(Gamma(2, 0.5) > 15) = e^-7.5 * 8.5
+        let expected_success = 1. - (-7.5f64).exp() * 8.5;
+        assert!((simulation.success_rate - expected_success).abs() < 0.01);
     }
 
-    /// Get the interval between two events
-    #[wasm_bindgen(catch)]
-    pub fn interval(&mut self, source: EventID, target: EventID) -> Result<Interval, JsValue> {
-        self.compile()?;
+    #[test]
+    fn test_compare_branches() {
+        let (mut schedule, episode1, episode2) = series();
 
-        let l = match self.dispatchable.edge_weight(target, source) {
-            Some(l) => l,
-            None => {
-                return Err(JsValue::from_str(&format!(
-                    "missing lower edge: {} to {}",
-                    target, source
-                )))
-            }
-        };
+        let mut branch = schedule.branch_at(episode1.end()).unwrap();
+        let late = branch.add_episode(Some(vec![4., 4.]));
+        branch
+            .add_constraint(episode2.end(), late.start(), None)
+            .unwrap();
 
-        let upper = match self.dispatchable.edge_weight(source, target) {
-            Some(u) => u,
-            None => {
-                return Err(JsValue::from_str(&format!(
-                    "missing upper edge: {} to {}",
-                    source, target
-                )))
+        let comparison = schedule.compare(&mut branch);
+        assert!(comparison.feasible && comparison.other_feasible);
+        assert_eq!(comparison.makespan, Interval::new(3., 14.));
+        assert_eq!(comparison.other_makespan, Interval::new(7., 18.));
+
+        // a branch that can't be executed
+        branch
+            .add_constraint(episode1.start(), late.end(), Some(vec![0., 1.]))
+            .unwrap();
+        let comparison = schedule.compare(&mut branch);
+        assert!(comparison.feasible);
+        assert!(!comparison.other_feasible);
+    }
+
+    #[test]
+    fn test_stale_queries() {
+        let mut plan = crate::examples::walkthrough();
+        let (x0, l) = (plan.x0, plan.l);
+        let schedule = &mut plan.schedule;
+        assert_eq!(
+            schedule.interval(x0, l.end()).unwrap(),
+            Interval::new(40., 50.)
+        );
+        assert!(!schedule.stale());
+
+        // too tight for the Episodes before it
+        schedule.update_interval(x0, l.end(), vec![0., 35.]);
+        assert_eq!(
+            schedule.interval(x0, l.end()).unwrap(),
+            Interval::new(40., 50.)
+        );
+        assert!(schedule.stale());
+        assert!(schedule.compile_error().unwrap().contains("inconsistent"));
+
+        schedule.update_interval(x0, l.end(), vec![0., 100.]);
+        assert_eq!(
+            schedule.interval(x0, l.end()).unwrap(),
+            Interval::new(40., 50.)
+        );
+        assert!(!schedule.stale());
+        assert_eq!(schedule.compile_error(), None);
+    }
+
+    #[test]
+    fn test_lifecycle() {
+        let mut plan = crate::examples::walkthrough();
+        let (x0, l) = (plan.x0, plan.l);
+        let schedule = &mut plan.schedule;
+        assert_eq!(schedule.phase(), Phase::Draft);
+        assert_eq!(
+            schedule.advance(Phase::Executing),
+            Err("a Schedule that is draft can't become executing".to_string())
+        );
+
+        schedule.advance(Phase::Frozen).unwrap();
+        assert_eq!(
+            schedule.require_draft("add a constraint"),
+            Err("cannot add a constraint while the Schedule is frozen. Use `amend` to change the plan".to_string())
+        );
+        schedule
+            .amend(
+                "EV1 is running late",
+                Mutation::UpdateInterval {
+                    source: x0,
+                    target: l.end(),
+                    interval: Interval::new(40., 45.),
+                },
+            )
+            .unwrap();
+        assert_eq!(schedule.phase(), Phase::Frozen);
+        assert_eq!(
+            schedule.interval(x0, l.end()).unwrap(),
+            Interval::new(40., 45.)
+        );
+
+        // committing starts execution
+        schedule.commit_event(x0, 0.).unwrap();
+        assert_eq!(schedule.phase(), Phase::Executing);
+        let n = schedule.journal().len();
+        assert_eq!(
+            schedule.journal()[n - 2],
+            Mutation::SetPhase {
+                phase: Phase::Executing
             }
-        };
+        );
+        assert!(
+            matches!(&schedule.journal()[n - 3], Mutation::Amend { reason, .. } if reason == "EV1 is running late")
+        );
 
-        // avoid returning -0
-        let lower = if *l == 0. { -0. } else { *l };
+        let mut replayed = Schedule::new();
+        replayed.apply_journal(schedule.journal()).unwrap();
+        assert_eq!(replayed.phase(), Phase::Executing);
+        assert_eq!(
+            replayed.interval(x0, l.end()).unwrap(),
+            Interval::new(40., 45.)
+        );
 
-        Ok(Interval::new(-lower, *upper))
+        schedule.advance(Phase::Complete).unwrap();
+        assert_eq!(
+            schedule.require_executable(),
+            Err("cannot commit events. The Schedule is complete".to_string())
+        );
     }
 
-    /// Low-level API to get the directional distance between two events. Advanced use only. If you can't explain why you should use this over `interval`, use `interval` instead
-    #[wasm_bindgen(js_name = eventDistance)]
-    pub fn event_distance(&mut self, source: EventID, target: EventID) -> Result<JsValue, JsValue> {
-        // ensure source and target already exist
-        if !self.stn.contains_node(source) {
-            return Err(JsValue::from_str(&format!(
-                "Source {} is not already in the Schedule. Have you added it with `addEpisode`?",
-                source
-            )));
-        }
-        if !self.stn.contains_node(target) {
-            return Err(JsValue::from_str(&format!(
-                "Target {} is not already in the Schedule. Have you added it with `addEpisode`?",
-                target
-            )));
-        }
+    #[test]
+    #[should_panic(expected = "cannot change a constraint while the Schedule is frozen")]
+    fn test_frozen_edit() {
+        let mut plan = crate::examples::walkthrough();
+        plan.schedule.advance(Phase::Frozen).unwrap();
+        plan.schedule
+            .update_interval(plan.x0, plan.l.end(), vec![0., 100.]);
+    }
 
-        match self.compile() {
-            Ok(_) => (),
-            Err(e) => return Err(e),
-        }
+    #[test]
+    fn test_predecessors_and_successors() {
+        let mut plan = crate::examples::walkthrough();
+        let (x0, l, s) = (plan.x0, plan.l, plan.s);
+        let schedule = &mut plan.schedule;
 
-        let t = match self.dispatchable.edge_weight(source, target) {
-            Some(t) => t,
-            None => return Err(JsValue::from_str(&"Cannot find path from start to target")),
-        };
+        // s.start is only ordered before l.end through constraints on the other Episode
+        let predecessors: Vec<(EventID, Interval)> = schedule
+            .predecessors(l.end())
+            .unwrap()
+            .iter()
+            .map(|c| (c.source, c.interval))
+            .collect();
+        assert_eq!(
+            predecessors,
+            vec![
+                (x0, Interval::new(40., 50.)),
+                (l.start(), Interval::new(30., 40.)),
+                (s.start(), Interval::new(10., 20.)),
+            ]
+        );
 
-        Ok(JsValue::from_f64(*t))
+        let successors: Vec<EventID> = schedule
+            .successors(l.start())
+            .unwrap()
+            .iter()
+            .map(|c| c.target)
+            .collect();
+        assert_eq!(successors, vec![l.end(), s.start(), s.end()]);
+        assert_eq!(schedule.successors(x0).unwrap().len(), 4);
+        assert!(schedule.successors(s.end()).unwrap().is_empty());
     }
 
-    pub fn update_interval(&mut self, source: EventID, target: EventID, interval: Vec<f64>) {
-        let i = Interval::from_vec(interval);
+    #[test]
+    fn test_blackouts() {
+        let mut plan = crate::examples::walkthrough();
+        let (l, s) = (plan.l, plan.s);
+        let schedule = &mut plan.schedule;
 
-        // update the edge in the STN
-        self.stn.add_edge(source, target, i.upper());
-        self.stn.add_edge(target, source, -i.lower());
+        // a blackout in the middle of a window can't be clipped
+        let report = schedule
+            .apply_blackouts(Some(&[s.end()]), &[Interval::new(62., 64.)])
+            .unwrap();
+        assert_eq!(report.split, vec![s.end()]);
+        assert!(report.clipped.is_empty());
 
-        // mark the STN dirty
-        self.dirty = true;
+        // l.end is pushed to 45, which pulls l.start back out of its blackout to 15
+        let report = schedule
+            .apply_blackouts(
+                Some(&[l.start(), l.end()]),
+                &[Interval::new(0., 15.), Interval::new(45., 55.)],
+            )
+            .unwrap();
+        let after: Vec<(EventID, Interval)> =
+            report.clipped.iter().map(|c| (c.event, c.after)).collect();
+        assert_eq!(
+            after,
+            vec![
+                (l.start(), Interval::new(15., 15.)),
+                (l.end(), Interval::new(45., 45.)),
+                (s.start(), Interval::new(25., 30.)),
+                (s.end(), Interval::new(65., 70.)),
+            ]
+        );
+        assert!(report.infeasible.is_empty());
+
+        let report = schedule
+            .apply_blackouts(None, &[Interval::new(10., 30.)])
+            .unwrap();
+        assert_eq!(report.infeasible, vec![l.start()]);
+        let windows = schedule.windows_from_root().unwrap().2;
+        assert_eq!(windows[&s.start()], Interval::new(30., 30.));
     }
 
-    /// Add a constraint between the start or end of two events. Errs if either source or target is not already in the Schedule. Defaults to a [0, 0] interval between events
-    #[wasm_bindgen(js_name = addConstraint)]
-    pub fn add_constraint(
-        &mut self,
-        source: EventID,
-        target: EventID,
-        interval: Option<Vec<f64>>,
-    ) -> Result<(), JsValue> {
-        // ensure source and target already exist
-        if !self.stn.contains_node(source) {
-            return Err(JsValue::from_str(&format!(
-                "Source {} is not already in the Schedule. Have you added it with `addEpisode`?",
-                source
-            )));
-        }
-        if !self.stn.contains_node(target) {
-            return Err(JsValue::from_str(&format!(
-                "Target {} is not already in the Schedule. Have you added it with `addEpisode`?",
-                target
-            )));
-        }
+    #[test]
+    fn test_constraint_between() {
+        let mut plan = crate::examples::walkthrough();
+        let (x0, l, s) = (plan.x0, plan.l, plan.s);
+        let schedule = &mut plan.schedule;
 
-        let d = interval.unwrap_or(vec![0., 0.]);
-        let i = Interval::from_vec(d);
+        // authored as s.start -> l.end, asked the other way around
+        let between = schedule.constraint_between(l.end(), s.start()).unwrap();
+        assert_eq!(
+            between.authored.map(|c| c.interval),
+            Some(Interval::new(-20., -10.))
+        );
+        assert_eq!(between.bounds, 2);
+        assert!(!between.episode);
+        assert_eq!(between.implied, Some(Interval::new(-20., -10.)));
 
-        self.stn.add_edge(source, target, i.upper());
-        self.stn.add_edge(target, source, -i.lower());
+        let duration = schedule.constraint_between(l.start(), l.end()).unwrap();
+        assert!(duration.episode);
 
-        self.dirty = true;
-        Ok(())
+        // only implied
+        let between = schedule.constraint_between(x0, l.end()).unwrap();
+        assert_eq!(between.authored, None);
+        assert_eq!(between.bounds, 0);
+        assert_eq!(between.implied, Some(Interval::new(40., 50.)));
+
+        schedule.update_interval(s.end(), l.start(), vec![-f64::MAX, 0.]);
+        assert_eq!(
+            schedule
+                .constraint_between(s.end(), l.start())
+                .unwrap()
+                .bounds,
+            1
+        );
     }
 
-    /// Remove the constraint between two events. Only errs if an Event is missing
-    #[wasm_bindgen(catch, js_name = removeConstraint)]
-    pub fn remove_constraint(&mut self, source: EventID, target: EventID) -> Result<(), JsValue> {
-        // ensure source and target exist
-        if !self.stn.contains_node(source) {
-            return Err(JsValue::from_str(&format!(
-                "Source event {} is not in the Schedule. No constraints to remove",
-                source
-            )));
+    #[test]
+    fn test_feasibility_certificate() {
+        let mut plan = crate::examples::walkthrough();
+        let certificate = plan.schedule.feasibility_certificate().unwrap();
+        assert!(certificate.stamp_matches());
+        assert_eq!(certificate.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(certificate.distances.len(), 64);
+        assert_eq!(plan.schedule.verify_certificate(&certificate), Ok(()));
+
+        // the same plan built again certifies the same
+        let mut again = crate::examples::walkthrough();
+        assert_eq!(
+            again.schedule.feasibility_certificate(),
+            Ok(certificate.clone())
+        );
+
+        let mut edited = certificate.clone();
+        edited.events += 1;
+        assert!(plan.schedule.verify_certificate(&edited).is_err());
+
+        plan.schedule
+            .update_interval(plan.l.start(), plan.l.end(), vec![30., 35.]);
+        assert_eq!(
+            plan.schedule.verify_certificate(&certificate),
+            Err("the certificate is for different inputs".to_string())
+        );
+    }
+
+    #[test]
+    fn test_pareto_schedules() {
+        let mut plan = crate::examples::walkthrough();
+        let front = plan.schedule.pareto_schedules(5).unwrap();
+        assert!(front.len() > 1);
+
+        // the earliest-start schedule comes first
+        assert_eq!(front[0].makespan, 60.);
+        assert_eq!(front[0].slack, 0.);
+        assert_eq!(front[0].times[&plan.l.start()], 10.);
+
+        for pair in front.windows(2) {
+            assert!(pair[0].makespan < pair[1].makespan);
+            assert!(pair[0].slack < pair[1].slack);
         }
-        if !self.stn.contains_node(target) {
-            return Err(JsValue::from_str(&format!(
-                "Target event {} is not in the Schedule. No constraints to remove",
-                target
-            )));
+        // every schedule keeps the constraints
+        for point in front.iter() {
+            let mut s = plan.schedule.clone();
+            let log = point.times.iter().map(|(e, t)| (*e, *t)).collect();
+            let report = s.commit_events(log).unwrap();
+            assert!(report.rejected.is_empty() && report.infeasible.is_empty());
         }
+    }
 
-        // TODO: check. and don't throw an err if there is no edge
-        self.stn.remove_edge(source, target);
-        Ok(())
+    #[test]
+    fn test_minimal_equivalent() {
+        let (mut schedule, episode1, episode2) = series();
+        schedule
+            .add_constraint(episode1.start(), episode2.end(), Some(vec![2., 20.]))
+            .unwrap();
+        // only the upper bound is implied
+        schedule
+            .add_constraint(episode1.start(), episode2.start(), Some(vec![1.5, 100.]))
+            .unwrap();
+
+        let minimal = schedule.minimal_equivalent().unwrap();
+        assert_eq!(minimal.len(), schedule.constraints().len() - 1);
+        assert!(minimal
+            .iter()
+            .any(|c| c.interval == Interval::new(1.5, f64::MAX)));
+
+        let mut rebuilt = Schedule::new();
+        for c in minimal.iter() {
+            rebuilt.update_interval(
+                c.source,
+                c.target,
+                vec![c.interval.lower(), c.interval.upper()],
+            );
+        }
+        let events: Vec<EventID> = schedule.stn.nodes().collect();
+        for source in events.iter() {
+            for target in events.iter() {
+                assert_eq!(
+                    rebuilt.interval(*source, *target).unwrap(),
+                    schedule.interval(*source, *target).unwrap()
+                );
+            }
+        }
     }
 
-    /// Remove all constraints between two episodes
-    #[wasm_bindgen(catch, js_name = removeConstraints)]
-    pub fn remove_constraints(
-        &mut self,
-        source: &Episode,
-        target: &Episode,
-    ) -> Result<(), JsValue> {
-        // let's not assume that source and target are in order. therefore, 2 episodes have 8 possible constraints between them:
-        //    2 episodes x 2 events each x 2 directions for each edge
+    #[test]
+    fn test_delta_since() {
+        let mut schedule = Schedule::new();
+        let a = schedule.add_episode(Some(vec![10., 20.]));
+        let b = schedule.add_episode(Some(vec![5., 5.]));
+        let c = schedule.add_episode(Some(vec![1., 2.]));
+        schedule.add_constraint(a.end(), b.start(), None).unwrap();
 
-        self.remove_constraint(source.start(), target.start())?;
-        // mark dirty as soon as one constraint is possibly removed
-        self.dirty = true;
+        let mut mirror = WindowMirror::new();
+        mirror.apply(&schedule.delta_since(None).unwrap()).unwrap();
+        assert_eq!(mirror.time_windows().len(), 6);
+        assert_eq!(mirror.version, None);
 
-        self.remove_constraint(source.start(), target.start())?;
-        self.remove_constraint(source.start(), target.end())?;
-        self.remove_constraint(source.start(), target.end())?;
-        self.remove_constraint(target.end(), source.start())?;
-        self.remove_constraint(target.end(), source.start())?;
-        self.remove_constraint(target.end(), source.end())?;
-        self.remove_constraint(target.end(), source.end())?;
+        schedule.commit_event(a.start(), 0.).unwrap();
+        mirror.apply(&schedule.delta_since(None).unwrap()).unwrap();
+        schedule.commit_event(a.end(), 12.).unwrap();
 
-        Ok(())
+        // only a's end and b changed, c isn't connected to a
+        let delta = schedule.delta_since(mirror.version).unwrap();
+        assert_eq!(delta.since, Some(0));
+        assert_eq!(delta.version, Some(1));
+        let changed: Vec<EventID> = delta.changed.iter().map(|w| w.event).collect();
+        assert_eq!(changed, vec![a.end(), b.start(), b.end()]);
+        assert!(!changed.contains(&c.start()));
+
+        mirror.apply(&delta).unwrap();
+        assert_eq!(mirror.time_windows(), schedule.time_windows().unwrap());
+        // the mirror has moved past it
+        assert!(mirror.apply(&delta).is_err());
     }
 
-    /// Remove any constraints around this Episode, except the constraints between the start and end of the Episode. This should be performed prior to moving an episode in the STN
-    #[wasm_bindgen(catch, js_name = freeEpisode)]
-    pub fn free_episode(&mut self, episode: &Episode) -> Result<(), JsValue> {
-        let cloned_stn = self.stn.clone();
-        let incoming_edges = cloned_stn.neighbors_directed(episode.start(), Incoming);
-        let outgoing_edges = cloned_stn.neighbors_directed(episode.end(), Outgoing);
+    #[test]
+    fn test_hash() {
+        let mut a = Schedule::new();
+        let x0 = a.create_event();
+        let l = a.add_episode(Some(vec![30., 40.]));
+        let mut b = a.clone();
+        a.update_interval(x0, l.start(), vec![10., 20.]);
+        a.update_interval(x0, l.end(), vec![0., 60.]);
+        b.update_interval(x0, l.end(), vec![0., 60.]);
+        b.update_interval(x0, l.start(), vec![10., 20.]);
+        assert_eq!(a.hash(), b.hash());
+        assert_eq!(a.hash().len(), 64);
 
-        for e in incoming_edges {
-            self.stn.remove_edge(e, episode.start());
-        }
+        // compiling and committing don't change the plan
+        let before = a.hash();
+        a.commit_event(x0, 0.).unwrap();
+        assert_eq!(a.hash(), before);
 
-        for e in outgoing_edges {
-            self.stn.remove_edge(episode.end(), e);
-        }
+        b.update_interval(x0, l.start(), vec![10., 21.]);
+        assert_ne!(b.hash(), before);
+    }
 
-        self.dirty = true;
-        Ok(())
+    #[test]
+    fn test_dump_constraint_table() {
+        let mut plan = crate::examples::walkthrough();
+        plan.schedule.set_event_alias(plan.x0, "X0").unwrap();
+        let table = plan.schedule.dump_constraint_table().unwrap();
+
+        assert_eq!(table[0], vec!["", "X0", "1", "2", "3", "4"]);
+        assert_eq!(table[1], vec!["X0", "0", "20", "50", "30", "70"]);
+        assert_eq!(table[2], vec!["1", "-10", "0", "40", "20", "60"]);
+
+        let mut open = Schedule::new();
+        let a = open.create_event();
+        let b = open.create_event();
+        open.update_interval(a, b, vec![0., f64::MAX]);
+        assert_eq!(
+            open.dump_constraint_table().unwrap()[1],
+            vec!["0", "0", "∞"]
+        );
     }
 }
@@ -0,0 +1,268 @@
+//! # Service
+//! Request and response types for running the solver behind an HTTP endpoint, eg. a backend service or a Worker. Every handler is a pure function from a request to a response, so a host server only needs to deserialize the body, call the handler, and serialize the result. `handle` does all three for hosts that just want to forward the route and body.
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsValue;
+
+use super::registration::{check, register, Consistency, Edge, RegistrationPayload, StnOptions};
+use super::schedule::{EventID, RejectedCommit, Schedule, TimeWindow};
+use super::to_js;
+
+/// An as-performed time for an event
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Commitment {
+    pub event: EventID,
+    pub time: f64,
+}
+
+/// Find the execution window of every event in a plan
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ComputeBoundsRequest {
+    pub edges: Vec<Edge>,
+    #[serde(default)]
+    pub options: StnOptions,
+    /// Events that have already happened
+    #[serde(default)]
+    pub commitments: Vec<Commitment>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ComputeBoundsResponse {
+    pub windows: Vec<TimeWindow>,
+}
+
+/// Commit events in a plan and find the execution windows that are left
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct CommitRequest {
+    pub edges: Vec<Edge>,
+    #[serde(default)]
+    pub options: StnOptions,
+    /// Events that have already happened. Every one must be valid
+    #[serde(default)]
+    pub commitments: Vec<Commitment>,
+    /// New events to commit, in order. Invalid entries are rejected without failing the request
+    pub commit: Vec<Commitment>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct CommitResponse {
+    pub applied: Vec<EventID>,
+    pub rejected: Vec<RejectedCommit>,
+    pub windows: Vec<TimeWindow>,
+}
+
+/// Check a plan for consistency. Responds with a `Consistency`
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ConsistencyRequest {
+    pub edges: Vec<Edge>,
+    #[serde(default)]
+    pub options: StnOptions,
+}
+
+/// Why a request failed, along with the HTTP status code that fits
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ServiceError {
+    pub status: u16,
+    pub error: String,
+}
+
+impl ServiceError {
+    /// The request couldn't be understood
+    pub fn bad_request(error: String) -> ServiceError {
+        ServiceError { status: 400, error }
+    }
+
+    /// The request was understood but the plan can't be executed
+    pub fn unprocessable(error: String) -> ServiceError {
+        ServiceError { status: 422, error }
+    }
+}
+
+/// A serialized response for the host server to send as-is
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+/// Register the edges and commitments as a compiled Schedule. Errs if the plan is inconsistent or any commitment is rejected
+fn schedule(
+    edges: &[Edge],
+    options: &StnOptions,
+    commitments: &[Commitment],
+) -> Result<Schedule, ServiceError> {
+    let payload = RegistrationPayload {
+        edges: edges.to_vec(),
+    };
+
+    // check first so that an inconsistent plan is never compiled
+    if let Consistency::Inconsistent { cycle } =
+        check(&payload, options).map_err(ServiceError::bad_request)?
+    {
+        return Err(ServiceError::unprocessable(format!(
+            "the plan is inconsistent. No times satisfy the constraints between events {:?}",
+            cycle
+        )));
+    }
+
+    let mut schedule = register(&payload, options).map_err(ServiceError::bad_request)?;
+    let log = commitments.iter().map(|c| (c.event, c.time)).collect();
+    let report = schedule
+        .commit_events(log)
+        .map_err(|_| ServiceError::unprocessable("could not compile the plan".to_string()))?;
+    if let Some(rejected) = report.rejected.first() {
+        return Err(ServiceError::unprocessable(format!(
+            "could not commit event {} at {}: {}",
+            rejected.event, rejected.time, rejected.reason
+        )));
+    }
+
+    Ok(schedule)
+}
+
+/// Get the execution windows of every event in a plan
+pub fn compute_bounds(
+    request: &ComputeBoundsRequest,
+) -> Result<ComputeBoundsResponse, ServiceError> {
+    let mut schedule = schedule(&request.edges, &request.options, &request.commitments)?;
+    let windows = schedule
+        .time_windows()
+        .map_err(|_| ServiceError::unprocessable("could not compile the plan".to_string()))?;
+    Ok(ComputeBoundsResponse { windows })
+}
+
+/// Commit new events on top of a plan's existing commitments
+pub fn commit(request: &CommitRequest) -> Result<CommitResponse, ServiceError> {
+    let mut schedule = schedule(&request.edges, &request.options, &request.commitments)?;
+    let log = request.commit.iter().map(|c| (c.event, c.time)).collect();
+    let report = schedule
+        .commit_events(log)
+        .map_err(|_| ServiceError::unprocessable("could not compile the plan".to_string()))?;
+    let windows = schedule
+        .time_windows()
+        .map_err(|_| ServiceError::unprocessable("could not compile the plan".to_string()))?;
+
+    Ok(CommitResponse {
+        applied: report.applied,
+        rejected: report.rejected,
+        windows,
+    })
+}
+
+/// Check a plan for consistency without compiling it
+pub fn consistency(request: &ConsistencyRequest) -> Result<Consistency, ServiceError> {
+    let payload = RegistrationPayload {
+        edges: request.edges.clone(),
+    };
+    check(&payload, &request.options).map_err(ServiceError::bad_request)
+}
+
+/// Deserialize a JSON body, run a handler, and serialize its response or error
+fn respond<Req, Res>(
+    body: &str,
+    handler: fn(&Req) -> Result<Res, ServiceError>,
+) -> Result<String, ServiceError>
+where
+    Req: serde::de::DeserializeOwned,
+    Res: serde::Serialize,
+{
+    let request: Req =
+        serde_json::from_str(body).map_err(|e| ServiceError::bad_request(e.to_string()))?;
+    let response = handler(&request)?;
+    serde_json::to_string(&response).map_err(|e| ServiceError::bad_request(e.to_string()))
+}
+
+/// Route a JSON request body to its handler. Routes are `"bounds"`, `"commit"`, and `"consistency"`, with or without a leading slash. Errors are returned as a JSON `ServiceError` with a matching status
+pub fn handle(route: &str, body: &str) -> HttpResponse {
+    let result = match route.trim_start_matches('/') {
+        "bounds" => respond(body, compute_bounds),
+        "commit" => respond(body, commit),
+        "consistency" => respond(body, consistency),
+        r => Err(ServiceError {
+            status: 404,
+            error: format!("unknown route \"{}\"", r),
+        }),
+    };
+
+    match result {
+        Ok(body) => HttpResponse { status: 200, body },
+        Err(e) => HttpResponse {
+            status: e.status,
+            body: serde_json::to_string(&e).unwrap_or_default(),
+        },
+    }
+}
+
+/// Route a JSON request body to its handler, eg. from a Worker's `fetch` handler. Returns `{ status, body }` where the body is a JSON string ready to send
+#[wasm_bindgen(js_name = handleRequest)]
+pub fn handle_js(route: &str, body: &str) -> Result<JsValue, JsValue> {
+    to_js(&handle(route, body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interval::Interval;
+
+    fn walkthrough() -> Vec<Edge> {
+        vec![
+            Edge::interval(0, 1, Interval::new(10., 20.)),
+            Edge::interval(1, 2, Interval::new(30., 40.)),
+        ]
+    }
+
+    #[test]
+    fn test_compute_bounds() {
+        let request = ComputeBoundsRequest {
+            edges: walkthrough(),
+            commitments: vec![Commitment {
+                event: 1,
+                time: 15.,
+            }],
+            ..Default::default()
+        };
+
+        let response = compute_bounds(&request).unwrap();
+        let end = response.windows.iter().find(|w| w.event == 2).unwrap();
+        assert_eq!((end.earliest, end.latest), (45., 55.));
+    }
+
+    #[test]
+    fn test_commit_rejects_bad_entries() {
+        let request = CommitRequest {
+            edges: walkthrough(),
+            commit: vec![
+                Commitment {
+                    event: 1,
+                    time: 12.,
+                },
+                Commitment {
+                    event: 2,
+                    time: 80.,
+                },
+            ],
+            ..Default::default()
+        };
+
+        let response = commit(&request).unwrap();
+        assert_eq!(response.applied, vec![1]);
+        assert_eq!(response.rejected[0].event, 2);
+    }
+
+    #[test]
+    fn test_handle() {
+        let body = r#"{ "edges": [
+            { "source": 0, "target": 1, "interval": [10, 20] },
+            { "source": 1, "target": 2, "interval": [30, 40] },
+            { "source": 0, "target": 2, "interval": [0, 35] }
+        ] }"#;
+
+        let response = handle("/consistency", body);
+        assert_eq!(response.status, 200);
+        assert!(response.body.contains("inconsistent"));
+
+        assert_eq!(handle("/bounds", body).status, 422);
+        assert_eq!(handle("/bounds", "not json").status, 400);
+        assert_eq!(handle("/nope", body).status, 404);
+    }
+}
@@ -0,0 +1,293 @@
+//! # Template
+//! Reusable plan fragments, eg. a standard "egress/setup" block, that are defined once and stamped into many Schedules. A template has its own event IDs starting at 0. Every time it's instantiated, the events get fresh IDs in the target Schedule and the named anchors are mapped to them so the fragment can be wired into the rest of the plan.
+//!
+//! ```
+//! use temporal_networks::schedule::Schedule;
+//! use temporal_networks::template::PlanTemplate;
+//!
+//! let mut egress = PlanTemplate::new("egress/setup");
+//! let depress = egress.add_episode(Some(vec![10., 20.]));
+//! let setup = egress.add_episode(Some(vec![30., 40.]));
+//! egress.add_constraint(depress.end(), setup.start(), None).unwrap();
+//! egress.expose("start", depress.start()).unwrap();
+//! egress.expose("end", setup.end()).unwrap();
+//!
+//! let mut schedule = Schedule::new();
+//! let first = egress.instantiate(&mut schedule);
+//! let second = egress.instantiate(&mut schedule);
+//! assert_ne!(first.anchors["start"], second.anchors["start"]);
+//! ```
+
+use std::collections::BTreeMap;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsValue;
+
+use super::interval::Interval;
+use super::schedule::{Episode, EventID, Phase, Schedule};
+use super::{from_js, to_js};
+
+/// An Episode in a template, in the template's own event IDs
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct TemplateEpisode {
+    pub start: EventID,
+    pub end: EventID,
+    pub duration: Interval,
+}
+
+/// A constraint between two events of a template, in the template's own event IDs
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct TemplateConstraint {
+    pub source: EventID,
+    pub target: EventID,
+    pub interval: Interval,
+}
+
+/// A plan fragment that can be instantiated into Schedules any number of times. Serializes to JSON so a library of templates can be stored and shared
+#[wasm_bindgen]
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct PlanTemplate {
+    name: String,
+    /// How many events the template has. Its event IDs are 0 through events - 1
+    events: EventID,
+    episodes: Vec<TemplateEpisode>,
+    constraints: Vec<TemplateConstraint>,
+    /// Events that can be referred to from outside the template
+    anchors: BTreeMap<String, EventID>,
+}
+
+/// The events an instantiated template got in a Schedule
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct TemplateInstance {
+    /// Template event IDs mapped to their IDs in the Schedule
+    pub events: BTreeMap<EventID, EventID>,
+    /// Anchor names mapped to their event IDs in the Schedule
+    pub anchors: BTreeMap<String, EventID>,
+}
+
+#[wasm_bindgen]
+impl PlanTemplate {
+    #[wasm_bindgen(constructor)]
+    pub fn new(name: &str) -> PlanTemplate {
+        PlanTemplate {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// Create a standalone event in the template, eg. a milestone. Returns its template event ID
+    #[wasm_bindgen(js_name = createEvent)]
+    pub fn create_event(&mut self) -> EventID {
+        self.events += 1;
+        self.events - 1
+    }
+
+    /// Create an Episode in the template. Defaults to a [0, 0] duration, like `Schedule.addEpisode`. The Episode refers to template event IDs. Errs if the duration isn't `[lower, upper]`
+    #[wasm_bindgen(js_name = addEpisode)]
+    pub fn add_episode_js(&mut self, duration: Option<Vec<f64>>) -> Result<Episode, JsValue> {
+        let duration = Interval::try_from_slice(&duration.unwrap_or(vec![0., 0.]))
+            .map_err(|e| JsValue::from_str(&e))?;
+        Ok(self.push_episode(duration))
+    }
+
+    /// Constrain two template events. Defaults to a [0, 0] interval. Errs if either event isn't in the template
+    #[wasm_bindgen(js_name = addConstraint)]
+    pub fn add_constraint(
+        &mut self,
+        source: EventID,
+        target: EventID,
+        interval: Option<Vec<f64>>,
+    ) -> Result<(), JsValue> {
+        for event in [source, target].iter() {
+            if !self.contains_event(*event) {
+                return Err(JsValue::from_str(&format!(
+                    "event {} is not in template \"{}\"",
+                    event, self.name
+                )));
+            }
+        }
+
+        let interval = Interval::try_from_slice(&interval.unwrap_or(vec![0., 0.]))
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.constraints.push(TemplateConstraint {
+            source,
+            target,
+            interval,
+        });
+        Ok(())
+    }
+
+    /// Name a template event so it can be wired up after instantiating, eg. `"start"`. Naming another event with the same name replaces it. Errs if the event isn't in the template
+    pub fn expose(&mut self, name: &str, event: EventID) -> Result<(), JsValue> {
+        if !self.contains_event(event) {
+            return Err(JsValue::from_str(&format!(
+                "event {} is not in template \"{}\"",
+                event, self.name
+            )));
+        }
+        self.anchors.insert(name.to_string(), event);
+        Ok(())
+    }
+
+    /// Stamp the template into a Schedule with fresh event IDs. Returns `{ events, anchors }`, mapping template event IDs and anchor names to events in the Schedule. Errs if the Schedule isn't a draft
+    #[wasm_bindgen(js_name = instantiate)]
+    pub fn instantiate_js(&self, schedule: &mut Schedule) -> Result<JsValue, JsValue> {
+        if schedule.phase() != Phase::Draft {
+            return Err(JsValue::from_str(&format!(
+                "cannot instantiate template \"{}\" while the Schedule is {}",
+                self.name,
+                schedule.phase()
+            )));
+        }
+        to_js(&self.instantiate(schedule))
+    }
+
+    /// Serialize the template to a plain object, eg. to store it in a library
+    #[wasm_bindgen(js_name = toJSON)]
+    pub fn to_json(&self) -> Result<JsValue, JsValue> {
+        to_js(self)
+    }
+
+    /// Load a template serialized with `toJSON`. Errs if it refers to events outside the template
+    #[wasm_bindgen(js_name = fromJSON)]
+    pub fn from_json(value: JsValue) -> Result<PlanTemplate, JsValue> {
+        let template: PlanTemplate = from_js(&value)?;
+        template.validate().map_err(|e| JsValue::from_str(&e))?;
+        Ok(template)
+    }
+}
+
+impl PlanTemplate {
+    /// Create an Episode in the template. Defaults to a [0, 0] duration. Panics if the duration isn't `[lower, upper]`. `addEpisode` errs instead
+    pub fn add_episode(&mut self, duration: Option<Vec<f64>>) -> Episode {
+        let duration = Interval::try_from_slice(&duration.unwrap_or(vec![0., 0.]))
+            .unwrap_or_else(|e| panic!("{}", e));
+        self.push_episode(duration)
+    }
+
+    fn push_episode(&mut self, duration: Interval) -> Episode {
+        let start = self.create_event();
+        let end = self.create_event();
+        self.episodes.push(TemplateEpisode {
+            start,
+            end,
+            duration,
+        });
+        Episode(start, end)
+    }
+
+    /// Check that every Episode, constraint, and anchor refers to events in the template, eg. after loading one from JSON
+    pub fn validate(&self) -> Result<(), String> {
+        let events = self
+            .episodes
+            .iter()
+            .flat_map(|e| vec![e.start, e.end])
+            .chain(
+                self.constraints
+                    .iter()
+                    .flat_map(|c| vec![c.source, c.target]),
+            )
+            .chain(self.anchors.values().copied());
+        for event in events {
+            if !self.contains_event(event) {
+                return Err(format!(
+                    "event {} is not in template \"{}\"",
+                    event, self.name
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether or not an event ID belongs to the template
+    pub fn contains_event(&self, event: EventID) -> bool {
+        event >= 0 && event < self.events
+    }
+
+    /// Template event IDs by anchor name
+    pub fn anchors(&self) -> &BTreeMap<String, EventID> {
+        &self.anchors
+    }
+
+    /// Stamp the template into a Schedule with fresh event IDs
+    pub fn instantiate(&self, schedule: &mut Schedule) -> TemplateInstance {
+        let mut events = BTreeMap::new();
+        for episode in self.episodes.iter() {
+            let e = schedule.add_episode(Some(vec![
+                episode.duration.lower(),
+                episode.duration.upper(),
+            ]));
+            events.insert(episode.start, e.start());
+            events.insert(episode.end, e.end());
+        }
+        for event in 0..self.events {
+            events
+                .entry(event)
+                .or_insert_with(|| schedule.create_event());
+        }
+
+        for c in self.constraints.iter() {
+            schedule.update_interval(
+                events[&c.source],
+                events[&c.target],
+                vec![c.interval.lower(), c.interval.upper()],
+            );
+        }
+
+        let anchors = self
+            .anchors
+            .iter()
+            .map(|(name, event)| (name.clone(), events[event]))
+            .collect();
+        TemplateInstance { events, anchors }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instantiate() {
+        let mut template = PlanTemplate::new("egress/setup");
+        let depress = template.add_episode(Some(vec![10., 20.]));
+        let setup = template.add_episode(Some(vec![30., 40.]));
+        let go = template.create_event();
+        template
+            .add_constraint(depress.end(), setup.start(), None)
+            .unwrap();
+        template.add_constraint(go, depress.start(), None).unwrap();
+        template.expose("start", go).unwrap();
+        template.expose("end", setup.end()).unwrap();
+
+        let mut schedule = Schedule::new();
+        let first = template.instantiate(&mut schedule);
+        let second = template.instantiate(&mut schedule);
+        assert_eq!(schedule.constraints().len(), 8);
+
+        // chain the second after the first
+        schedule.update_interval(first.anchors["end"], second.anchors["start"], vec![0., 0.]);
+        assert_eq!(
+            schedule
+                .interval(first.anchors["start"], second.anchors["end"])
+                .unwrap(),
+            Interval::new(80., 120.)
+        );
+
+        let json = serde_json::to_string(&template).unwrap();
+        let loaded: PlanTemplate = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, template);
+
+        let broken = json.replace("\"end\":3", "\"end\":30");
+        let broken: PlanTemplate = serde_json::from_str(&broken).unwrap();
+        assert_eq!(
+            broken.validate(),
+            Err("event 30 is not in template \"egress/setup\"".to_string())
+        );
+    }
+}
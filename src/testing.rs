@@ -0,0 +1,141 @@
+//! # Testing
+//! Helpers for writing compact regression tests against known-good numbers, eg. in downstream crates. Enable with the `test-utils` feature. The example plans from `examples` are re-exported as fixtures.
+//!
+//! ```
+//! use std::collections::BTreeMap;
+//! use temporal_networks::interval::Interval;
+//! use temporal_networks::testing::{assert_windows, walkthrough};
+//!
+//! let plan = walkthrough();
+//! let mut expected = BTreeMap::new();
+//! expected.insert(plan.l.end(), Interval::new(40., 50.));
+//! assert_windows(&plan.schedule, &expected, 1e-9);
+//! ```
+//!
+//! The helpers panic with a readable message instead of returning errors, like `assert_eq!`. They never create JS values, so they're safe to use in native tests.
+
+use std::collections::BTreeMap;
+
+use super::interval::Interval;
+use super::schedule::{EventID, Schedule};
+
+pub use super::examples::{sts134, walkthrough, Sts134, Walkthrough};
+
+/// Assert that the authored constraints are consistent. On failure, the message lists the loops of constraints that contradict each other and how to fix them
+pub fn assert_consistent(schedule: &Schedule) {
+    if schedule.negative_cycle().is_none() {
+        return;
+    }
+
+    let mut message = String::from("the Schedule is inconsistent");
+    for diagnostic in schedule.diagnose_cycles() {
+        if diagnostic.kind != "contradictory" {
+            continue;
+        }
+        message.push_str(&format!(
+            "\n  events {:?} are short by {}",
+            diagnostic.events, diagnostic.excess
+        ));
+        for suggestion in diagnostic.suggestions.iter() {
+            message.push_str(&format!("\n    - {}", suggestion));
+        }
+    }
+    panic!("{}", message);
+}
+
+/// Assert that the execution window of each event matches the expected interval to within `tolerance`. Windows are relative to the root at t=0 unless the root has already been committed. The Schedule itself isn't changed. Events that aren't in `expected` aren't checked
+pub fn assert_windows(schedule: &Schedule, expected: &BTreeMap<EventID, Interval>, tolerance: f64) {
+    assert_consistent(schedule);
+
+    let mut s = schedule.clone();
+    for event in expected.keys() {
+        assert!(
+            s.contains_event(*event),
+            "event {} is not in the Schedule",
+            event
+        );
+    }
+    s.compile().expect("the Schedule could not be compiled");
+    if let Some(root) = s.root() {
+        let committed = s.window(root).is_ok_and(|w| w.converged());
+        if !committed {
+            s.commit_events(vec![(root, 0.)])
+                .expect("the Schedule could not be compiled");
+        }
+    }
+
+    let windows = s
+        .time_windows()
+        .expect("the Schedule could not be compiled");
+    let mismatches: Vec<String> = windows
+        .iter()
+        .filter_map(|w| {
+            let e = expected.get(&w.event)?;
+            let close = (w.earliest - e.lower()).abs() <= tolerance
+                && (w.latest - e.upper()).abs() <= tolerance;
+            if close {
+                None
+            } else {
+                Some(format!(
+                    "  event {}: expected {} but got {}",
+                    w.event,
+                    e,
+                    Interval::new(w.earliest, w.latest)
+                ))
+            }
+        })
+        .collect();
+
+    assert!(
+        mismatches.is_empty(),
+        "execution windows don't match\n{}",
+        mismatches.join("\n")
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_walkthrough_windows() {
+        let plan = walkthrough();
+        let mut expected = BTreeMap::new();
+        expected.insert(plan.x0, Interval::new(0., 0.));
+        expected.insert(plan.l.start(), Interval::new(10., 20.));
+        expected.insert(plan.l.end(), Interval::new(40., 50.));
+        expected.insert(plan.s.start(), Interval::new(20., 30.));
+        expected.insert(plan.s.end(), Interval::new(60., 70.));
+        assert_windows(&plan.schedule, &expected, 1e-9);
+    }
+
+    #[test]
+    fn test_sts134_windows() {
+        let mut plan = sts134(0.);
+        let mut expected = BTreeMap::new();
+        expected.insert(plan.egress.start(), Interval::new(0., 0.));
+        expected.insert(plan.ev2_egress.start(), Interval::new(0., 10.));
+        expected.insert(plan.misse7.start(), Interval::new(40., 50.));
+        assert_windows(&plan.schedule, &expected, 1e-9);
+        plan.schedule.compile().unwrap();
+        assert_eq!(plan.schedule.root(), Some(plan.lim_cons.start()));
+    }
+
+    #[test]
+    #[should_panic(expected = "event 2: expected [0, 1] but got [40, 50]")]
+    fn test_assert_windows_mismatch() {
+        let plan = walkthrough();
+        let mut expected = BTreeMap::new();
+        expected.insert(plan.l.end(), Interval::new(0., 1.));
+        assert_windows(&plan.schedule, &expected, 1e-9);
+    }
+
+    #[test]
+    #[should_panic(expected = "short by 5")]
+    fn test_assert_consistent() {
+        let mut plan = walkthrough();
+        plan.schedule
+            .update_interval(plan.x0, plan.l.end(), vec![0., 35.]);
+        assert_consistent(&plan.schedule);
+    }
+}
@@ -0,0 +1,20 @@
+const { expect } = require("chai");
+const { install } = require("../pkg");
+const { sts134, walkthrough } = require("../pkg/examples");
+
+describe("Examples", () => {
+  before(install);
+
+  it("should build the walkthrough", () => {
+    const { schedule, X0, L, S } = walkthrough();
+    expect(schedule.interval(X0, L.end).toJSON()).to.deep.equal([40, 50]);
+    expect(schedule.interval(X0, S.start).toJSON()).to.deep.equal([20, 30]);
+  });
+
+  it("should build STS-134 as a Mission", () => {
+    const { mission, steps } = sts134();
+    expect(Object.keys(steps)).to.have.lengthOf(7);
+    expect(steps.ev1Egress.plannedStartWindow()).to.deep.equal([0, 0]);
+    expect(mission.toSpec().deadlines).to.deep.equal([{ step: "misse7", deadline: 480 }]);
+  });
+});
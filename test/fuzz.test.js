@@ -0,0 +1,104 @@
+const { expect } = require("chai");
+const { install, Interval, Schedule, PlanTemplate, readPlan } = require("../pkg");
+
+// small seedable generator so failures can be reproduced
+function random(seed) {
+  return () => {
+    seed = (seed + 0x6d2b79f5) | 0;
+    let t = Math.imul(seed ^ (seed >>> 15), 1 | seed);
+    t = (t + Math.imul(t ^ (t >>> 7), 61 | t)) ^ t;
+    return ((t ^ (t >>> 14)) >>> 0) / 4294967296;
+  };
+}
+
+const ODD_NUMBERS = [0, -1, 1.5, NaN, Infinity, -Infinity, Number.MAX_VALUE, -Number.MAX_VALUE, 2 ** 31, -(2 ** 31)];
+
+function pick(rand, values) {
+  return values[Math.floor(rand() * values.length)];
+}
+
+function oddArray(rand) {
+  const length = Math.floor(rand() * 4);
+  return Array.from({ length }, () => pick(rand, ODD_NUMBERS));
+}
+
+function oddEvent(rand) {
+  return rand() < 0.5 ? Math.floor(rand() * 6) : pick(rand, [-1, 2 ** 31 - 1, 1000]);
+}
+
+function oddMutation(rand) {
+  return pick(rand, [
+    () => ({ op: "createEvent", event: oddEvent(rand) }),
+    () => ({ op: "addEpisode", start: oddEvent(rand), end: oddEvent(rand), duration: oddArray(rand) }),
+    () => ({ op: "updateInterval", source: oddEvent(rand), target: oddEvent(rand), interval: oddArray(rand) }),
+    () => ({ op: "bindParameter", name: "p", interval: oddArray(rand) }),
+    () => ({ op: "commitEvent", event: oddEvent(rand), time: pick(rand, ODD_NUMBERS) }),
+    () => ({ op: "setDistribution", start: oddEvent(rand), end: oddEvent(rand), distribution: { type: "erlang", shift: 0, shape: 0, rate: pick(rand, ODD_NUMBERS) } }),
+    () => ({ op: "setPhase", phase: pick(rand, ["draft", "frozen", "executing", "complete", "odd"]) }),
+    () => ({ op: pick(rand, ["removeConstraint", "unknown"]), source: oddEvent(rand), target: oddEvent(rand) }),
+  ])();
+}
+
+// an entry point may throw on bad input, but the module must not trap
+function survives(call) {
+  try {
+    call();
+  } catch (e) {
+    expect(e).not.to.be.instanceOf(WebAssembly.RuntimeError, `trapped: ${e}`);
+  }
+}
+
+describe("JS entry points with malformed input", () => {
+  before(install);
+
+  it("never trap the module", () => {
+    const rand = random(4449);
+    const schedule = new Schedule();
+    schedule.addEpisode([10, 20]);
+    schedule.addEpisode([30, 40]);
+
+    const calls = [
+      () => schedule.addEpisode(oddArray(rand)),
+      () => schedule.addContingentEpisode(oddArray(rand)),
+      () => schedule.addConstraint(oddEvent(rand), oddEvent(rand), oddArray(rand)),
+      () => schedule.update_interval(oddEvent(rand), oddEvent(rand), oddArray(rand)),
+      () => schedule.bindParameter("p", oddArray(rand)),
+      () => schedule.commitEvent(oddEvent(rand), pick(rand, ODD_NUMBERS)),
+      () => schedule.interval(oddEvent(rand), oddEvent(rand)),
+      () => schedule.window(oddEvent(rand)),
+      () => schedule.intervals(oddArray(rand).map(Math.floor)),
+      () => schedule.applyBlackouts(undefined, [oddArray(rand)]),
+      () => schedule.predecessors(oddEvent(rand)),
+      () => schedule.constraintBetween(oddEvent(rand), oddEvent(rand)),
+      () => schedule.timeWindows(),
+      () => schedule.freeze(),
+      () => schedule.thaw(),
+      () => schedule.beginExecution(),
+      () => schedule.applyJournal([oddMutation(rand), oddMutation(rand)]),
+      () => schedule.amend(pick(rand, ["", "odd input"]), oddMutation(rand)),
+      () => schedule.applyRiskAllocation({ totalRisk: 0, feasible: true, assignments: [{ start: oddEvent(rand), end: oddEvent(rand), risk: 0, bounds: oddArray(rand) }] }),
+      () => schedule.level({ a: [[oddEvent(rand), oddEvent(rand)], [oddEvent(rand), oddEvent(rand)]] }),
+      () => Interval.from_vec(oddArray(rand)),
+      () => new Interval(pick(rand, ODD_NUMBERS), pick(rand, ODD_NUMBERS)).toJSON(),
+      () => PlanTemplate.fromJSON({ name: "x", events: 1, episodes: [], constraints: [], anchors: { a: oddEvent(rand) } }),
+      () => readPlan(Uint8Array.from(oddArray(rand).map(n => Math.abs(n) % 256))),
+      () => readPlan(Uint8Array.from([0x53, 0x54, 0x4e, 0x01, 0xff, 0xff, 0xff, 0xff])),
+    ];
+    for (let i = 0; i < 500; i++) {
+      survives(pick(rand, calls));
+    }
+
+    // still usable afterwards
+    const fresh = new Schedule();
+    const episode = fresh.addEpisode([1, 2]);
+    expect(fresh.interval(episode.start, episode.end).toJSON()).to.deep.equal([1, 2]);
+    survives(() => schedule.addEpisode([1, 2]));
+  });
+
+  it("rejects intervals that aren't [lower, upper]", () => {
+    const schedule = new Schedule();
+    expect(() => schedule.addEpisode([1])).to.throw();
+    expect(() => Interval.from_vec([1, 2, 3])).to.throw();
+    expect(schedule.addEpisode([1, 2])).to.be.ok;
+  });
+});
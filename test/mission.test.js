@@ -254,6 +254,437 @@ describe("Mission high level API", () => {
     });
   });
 
+  describe("zoom", () => {
+    it("should report actor occupancy per bucket", () => {
+      const mission = new Mission();
+      const ev1 = mission.createActor("EV1");
+      const ev2 = mission.createActor("EV2");
+
+      mission.createStep("walk", [10, 10], ev1);
+      mission.createStep("setup", [5, 5], ev1);
+      mission.createStep("photos", [20, 20], ev2);
+
+      const [fine, coarse] = mission.zoom([5, 15]);
+
+      expect(fine.resolution).to.equal(5);
+      expect(fine.buckets).to.have.lengthOf(4);
+      expect(fine.buckets.map(b => b.occupancy.EV1)).to.deep.equal([1, 1, 1, 0]);
+
+      expect(coarse.buckets.map(b => [b.start, b.end])).to.deep.equal([[0, 15], [15, 30]]);
+      expect(coarse.buckets[0].occupancy).to.deep.equal({ EV1: 1, EV2: 1 });
+      expect(coarse.buckets[1].occupancy.EV2).to.be.closeTo(1 / 3, 1e-9);
+    });
+
+    it("should only count steps without substeps", () => {
+      const mission = new Mission();
+      const ev1 = mission.createActor("EV1");
+
+      const parent = mission.createStep("traverse", [10, 10], ev1);
+      parent.createStep("knob", [5, 5], ev1);
+
+      const [zoom] = mission.zoom([10]);
+      expect(zoom.buckets[0].occupancy).to.deep.equal({ EV1: 0.5 });
+    });
+  });
+
+  describe("syncs", () => {
+    const buildSync = () => {
+      const mission = new Mission();
+      const ev1 = mission.createActor("EV1");
+      const ev2 = mission.createActor("EV2");
+      const walk = mission.createStep("walk", [10, 10], ev1);
+      const photos = mission.createStep("photos", [20, 20], ev2);
+      const meet = mission.createSync("meet", [5, 5]);
+      const ingress = mission.createStep("ingress", [3, 3], ev1);
+      walk.name = "walk";
+      photos.name = "photos";
+      return { mission, ev1, ev2, walk, photos, meet, ingress };
+    };
+
+    it("should wait for every actor before starting", () => {
+      const { meet, ingress } = buildSync();
+      expect(meet.plannedStartWindow()[0]).to.equal(20);
+      expect(ingress.plannedStartWindow()[0]).to.equal(25);
+    });
+
+    it("should join after a chosen step", () => {
+      const mission = new Mission();
+      const ev1 = mission.createActor("EV1");
+      const ev2 = mission.createActor("EV2");
+      const walk = mission.createStep("walk", [10, 10], ev1);
+      mission.createStep("ingress", [3, 3], ev1);
+      mission.createStep("photos", [20, 20], ev2);
+
+      const meet = mission.createSync("meet", [5, 5], [ev1, ev2], new Map([[ev1, walk], [ev2, null]]));
+      expect(mission._branches.get(ev1).map(s => s.description)).to.deep.equal(["walk", "meet", "ingress"]);
+      expect(meet.plannedStartWindow()[0]).to.equal(10);
+    });
+
+    it("should round trip through a spec", () => {
+      const { mission } = buildSync();
+      const spec = mission.toSpec();
+      const meet = spec.steps.find(s => s.description === "meet");
+      expect(meet.actors).to.deep.equal(["EV1", "EV2"]);
+      expect(meet.after).to.deep.equal({ EV1: "walk", EV2: "photos" });
+
+      const { mission: reimported, steps } = Mission.fromSpec(spec);
+      expect(reimported.toSpec()).to.deep.equal(spec);
+      expect(steps[meet.name].plannedStartWindow()[0]).to.equal(20);
+    });
+  });
+
+  describe("level", () => {
+    it("should leave steps in series alone", () => {
+      const mission = new Mission();
+      const ev1 = mission.createActor("EV1");
+      mission.createStep("walk", [10, 10], ev1);
+      mission.createStep("setup", [5, 5], ev1);
+
+      expect(mission.level()).to.deep.equal({ ordered: [], conflicts: [] });
+    });
+
+    it("should report an actor in two places at once", () => {
+      const mission = new Mission();
+      const ev1 = mission.createActor("EV1");
+      const ev2 = mission.createActor("EV2");
+      const task = mission.createStep("task", [10, 10], ev1);
+      const helper = task.createStep("hold", [5, 5], ev2);
+      const photos = mission.createStep("photos", [20, 20], ev2);
+
+      const { conflicts } = mission.level();
+      expect(conflicts).to.have.lengthOf(1);
+      expect(conflicts[0].actor).to.equal("EV2");
+      expect([conflicts[0].first, conflicts[0].second]).to.have.members([helper, photos]);
+    });
+  });
+
+  describe("availability", () => {
+    it("should hold an actor's steps until they're available", () => {
+      const mission = new Mission();
+      const ev1 = mission.createActor("EV1");
+      const ev2 = mission.createActor("EV2");
+      mission.createStep("walk", [10, 10], ev1);
+      const egress = mission.createStep("egress", [5, 5], ev2);
+      const photos = mission.createStep("photos", [20, 20], ev2);
+      mission.addAvailability(ev2, [30, Number.MAX_VALUE]);
+
+      expect(egress.plannedStartWindow()[0]).to.equal(30);
+      expect(photos.plannedStartWindow()[0]).to.equal(35);
+      expect(mission.validate().errors).to.be.empty;
+    });
+
+    it("should report steps that can't fit", () => {
+      const mission = new Mission();
+      const ev1 = mission.createActor("EV1");
+      mission.createStep("walk", [10, 10], ev1);
+      mission.createStep("setup", [10, 10], ev1);
+      mission.addAvailability(ev1, [0, 15]);
+
+      const { errors } = mission.validate();
+      expect(errors).to.have.lengthOf(1);
+      expect(errors[0]).to.include("EV1");
+      expect(errors[0]).to.include("setup");
+    });
+
+    it("should round trip through a spec", () => {
+      const spec = {
+        actors: ["EV1"],
+        steps: [{ name: "walk", description: "walk", duration: [10, 10], actor: "EV1" }],
+        syncs: [],
+        deadlines: [],
+        availability: [{ actor: "EV1", window: [5, 60] }]
+      };
+      const { mission, steps } = Mission.fromSpec(spec);
+      expect(mission.toSpec()).to.deep.equal(spec);
+      expect(steps.walk.plannedStartWindow()).to.deep.equal([5, 50]);
+    });
+  });
+
+  describe("travel times", () => {
+    it("should keep steps at different worksites apart", () => {
+      const mission = new Mission();
+      const ev1 = mission.createActor("EV1");
+      const egress = mission.createStep("egress", [10, 10], ev1);
+      const bolts = mission.createStep("bolts", [20, 20], ev1);
+      const photos = mission.createStep("photos", [5, 5], ev1);
+      egress.location = "airlock";
+      bolts.location = "P1 truss";
+      photos.location = "P1 truss";
+      mission.setTravelTimes({ "P1 truss": { airlock: 15 } });
+
+      expect(bolts.plannedStartWindow()).to.deep.equal([25, 25]);
+      expect(photos.plannedStartWindow()).to.deep.equal([45, 45]);
+    });
+
+    it("should round trip through a spec", () => {
+      const spec = {
+        actors: ["EV1"],
+        steps: [
+          { name: "egress", description: "egress", duration: [10, 10], actor: "EV1", location: "airlock" },
+          { name: "bolts", description: "bolts", duration: [20, 20], actor: "EV1", location: "P1 truss" }
+        ],
+        syncs: [],
+        deadlines: [],
+        travelTimes: { airlock: { "P1 truss": 15 } }
+      };
+      const { mission, steps } = Mission.fromSpec(spec);
+      expect(mission.toSpec()).to.deep.equal(spec);
+      expect(steps.bolts.plannedStartWindow()).to.deep.equal([25, 25]);
+    });
+  });
+
+  describe("auto-chaining", () => {
+    it("should chain an actor's steps unless they're parallel", () => {
+      const mission = new Mission();
+      const ev1 = mission.createActor("EV1");
+      const egress = mission.createStep("egress", [10, 10], ev1);
+      const monitor = mission.createStep("monitor suit", [30, 30], ev1, undefined, { parallel: true });
+      const traverse = mission.createStep("traverse", [20, 20], ev1);
+
+      expect(egress.plannedStartWindow()).to.deep.equal([0, 0]);
+      expect(traverse.plannedStartWindow()).to.deep.equal([10, 10]);
+      expect(monitor.plannedStartWindow()).to.deep.equal([0, Number.MAX_VALUE]);
+    });
+
+    it("should leave steps unchained when turned off", () => {
+      const mission = new Mission();
+      mission.setAutoChain(false);
+      const ev1 = mission.createActor("EV1");
+      const egress = mission.createStep("egress", [10, 10], ev1);
+      const traverse = mission.createStep("traverse", [20, 20], ev1);
+      mission.addSync(egress, "end", traverse, "start", [5, 5]);
+
+      expect(traverse.plannedStartWindow()).to.deep.equal([15, Number.MAX_VALUE]);
+    });
+
+    it("should round trip through a spec", () => {
+      const spec = {
+        actors: ["EV1"],
+        steps: [
+          { name: "egress", description: "egress", duration: [10, 10], actor: "EV1" },
+          { name: "monitor", description: "monitor", duration: [30, 30], actor: "EV1", parallel: true }
+        ],
+        syncs: [],
+        deadlines: [],
+        autoChain: false
+      };
+      const { mission, steps } = Mission.fromSpec(spec);
+      expect(mission.toSpec()).to.deep.equal(spec);
+      expect(steps.monitor.parallel).to.be.true;
+    });
+  });
+
+  describe("reassign", () => {
+    function twoActors() {
+      const mission = new Mission();
+      const ev1 = mission.createActor("EV1");
+      const ev2 = mission.createActor("EV2");
+      const egress = mission.createStep("egress", [10, 10], ev1);
+      const bolts = mission.createStep("bolts", [20, 20], ev1);
+      const photos = mission.createStep("photos", [5, 5], ev1);
+      const walk = mission.createStep("walk", [15, 15], ev2);
+      return { mission, ev1, ev2, egress, bolts, photos, walk };
+    }
+
+    it("should move a step into another actor's chain", () => {
+      const { mission, ev2, bolts, photos, walk } = twoActors();
+      expect(photos.plannedStartWindow()).to.deep.equal([30, 30]);
+
+      mission.reassign(bolts, ev2);
+      expect(bolts.actor).to.equal(ev2);
+      expect(photos.plannedStartWindow()).to.deep.equal([10, 10]);
+      expect(bolts.plannedStartWindow()).to.deep.equal([15, 15]);
+
+      mission.reassign(photos, ev2, 0);
+      expect(photos.plannedStartWindow()).to.deep.equal([0, 0]);
+      expect(walk.plannedStartWindow()).to.deep.equal([5, 5]);
+      expect(bolts.plannedStartWindow()).to.deep.equal([20, 20]);
+    });
+
+    it("should amend the plan to reassign a step during execution", () => {
+      const { mission, ev2, bolts, photos } = twoActors();
+      expect(photos.plannedStartWindow()).to.deep.equal([30, 30]);
+      mission.schedule.freeze();
+      mission.schedule.beginExecution();
+
+      mission.reassign(bolts, ev2);
+      expect(mission.schedule.phase).to.equal("executing");
+      expect(photos.plannedStartWindow()).to.deep.equal([10, 10]);
+      expect(bolts.plannedStartWindow()).to.deep.equal([15, 15]);
+    });
+
+    it("should keep reassigned steps in order through a spec", () => {
+      const { mission, ev2, bolts, photos } = twoActors();
+      mission.reassign(bolts, ev2);
+      mission.reassign(photos, ev2, 0);
+
+      const { mission: rebuilt } = Mission.fromSpec(mission.toSpec());
+      const branch = rebuilt._branches.get(rebuilt._actors.find(a => a.name === "EV2"));
+      expect(branch.map(step => step.description)).to.deep.equal(["photos", "walk", "bolts"]);
+      expect(branch[2].plannedStartWindow()).to.deep.equal([20, 20]);
+      expect(rebuilt.toSpec()).to.deep.equal(mission.toSpec());
+    });
+
+    it("should undo a reassignment that isn't feasible", () => {
+      const { mission, ev1, ev2, bolts, photos } = twoActors();
+      mission.addDeadline(bolts, 30);
+
+      expect(() => mission.reassign(bolts, ev2)).to.throw("Cannot reassign");
+      expect(bolts.actor).to.equal(ev1);
+      expect(bolts.plannedStartWindow()).to.deep.equal([10, 10]);
+      expect(photos.plannedStartWindow()).to.deep.equal([30, 30]);
+    });
+  });
+
+  describe("consumables", () => {
+    it("should profile the worst-case margin over an actor's steps", () => {
+      const mission = new Mission();
+      const ev1 = mission.createActor("EV1");
+      const egress = mission.createStep("egress", [10, 20], ev1);
+      const bolts = mission.createStep("bolts", [20, 30], ev1);
+      bolts.usage.O2 = 2;
+      mission.addConsumable(ev1, "O2", 100);
+
+      const { consumable, points } = mission.marginProfile(ev1);
+      expect(consumable).to.equal("O2");
+      expect(points.map(({ time, margin }) => [time, margin])).to.deep.equal([[0, 100], [0, 100], [20, 80], [20, 80], [50, 20]]);
+      expect(points[4].step).to.equal(bolts);
+      expect(points[4].event).to.equal("end");
+
+      // a fast egress leaves more margin
+      egress.completedAt(10);
+      expect(mission.marginProfile(ev1, "O2").points[4].margin).to.equal(30);
+
+      expect(() => mission.marginProfile(ev1, "H2O")).to.throw("no consumable");
+    });
+
+    it("should alert on steps where the margin crosses a threshold", () => {
+      const mission = new Mission();
+      const ev1 = mission.createActor("EV1");
+      mission.createStep("egress", [10, 20], ev1);
+      const bolts = mission.createStep("bolts", [20, 30], ev1);
+      bolts.usage.O2 = 2;
+      mission.addConsumable(ev1, "O2", 100);
+      mission.alertIfMarginBelow("O2", 50, "caution");
+      mission.alertIfMarginBelow("O2", 10, "warning");
+      mission.alertIfMarginBelow("H2O", 10);
+
+      // 80 left when bolts starts at 20 at the latest, going down 2 a minute
+      expect(mission.marginAlerts()).to.deep.equal([
+        { actor: "EV1", consumable: "O2", threshold: 50, label: "caution", step: bolts, time: 35 }
+      ]);
+    });
+
+    it("should round trip through a spec", () => {
+      const spec = {
+        actors: ["EV1"],
+        steps: [{ name: "bolts", description: "bolts", duration: [20, 30], actor: "EV1", usage: { O2: 2 } }],
+        syncs: [],
+        deadlines: [],
+        consumables: [{ actor: "EV1", name: "O2", capacity: 100, rate: 1 }],
+        marginAlerts: [{ consumable: "O2", value: 50, label: "caution" }]
+      };
+      const { mission } = Mission.fromSpec(spec);
+      expect(mission.toSpec()).to.deep.equal(spec);
+    });
+  });
+
+  describe("actor hints", () => {
+    it("should sort actors by order", () => {
+      const mission = new Mission();
+      mission.createActor("EV1", { color: "red", order: 2 });
+      mission.createActor("EV2", { color: "blue", order: 1 });
+      mission.createActor("IV");
+
+      expect(mission.actorHints()).to.deep.equal([
+        { name: "EV2", color: "blue", order: 1 },
+        { name: "EV1", color: "red", order: 2 },
+        { name: "IV", color: null, order: null }
+      ]);
+    });
+
+    it("should carry hints through exports", () => {
+      const mission = new Mission();
+      const ev1 = mission.createActor("EV1", { color: "red" });
+      mission.createStep("walk", [10, 10], ev1);
+
+      const [zoom] = mission.zoom([10]);
+      expect(zoom.actors).to.deep.equal([{ name: "EV1", color: "red", order: null }]);
+
+      const spec = mission.toSpec();
+      expect(spec.actors).to.deep.equal([{ name: "EV1", color: "red" }]);
+      const { mission: reimported } = Mission.fromSpec(spec);
+      expect(reimported.actorHints()).to.deep.equal(mission.actorHints());
+    });
+  });
+
+  describe("spec", () => {
+    const spec = {
+      actors: ["EV1", "EV2"],
+      steps: [
+        { name: "egress", description: "EGRESS", duration: [1, 3], actor: "EV1" },
+        { name: "traverse", description: "TRAVERSE", duration: [5, 7], actor: "EV1" },
+        { name: "ev2-egress", description: "EGRESS", duration: [2, 4], actor: "EV2" },
+        { name: "knob", description: "turn knob", duration: [1, 1], parent: "traverse" }
+      ],
+      // EV1 can't start the traverse until EV2 is out of the airlock
+      syncs: [{ source: "ev2-egress.end", target: "traverse.start", interval: [0, Number.MAX_VALUE] }],
+      deadlines: [{ step: "traverse", deadline: 7.5 }]
+    };
+
+    it("should build a mission with steps keyed by name", () => {
+      const { mission, steps } = Mission.fromSpec(spec);
+
+      expect(mission instanceof Step).to.be.true;
+      expect(Object.keys(steps)).to.have.lengthOf(4);
+      expect(steps.egress.description).to.equal("EGRESS");
+      expect(steps.egress.actor.name).to.equal("EV1");
+      expect(steps.knob._parent).to.equal(steps.traverse);
+      expect(steps.knob.actor).to.equal(steps.traverse.actor);
+    });
+
+    it("should accept JSON", () => {
+      const { steps } = Mission.fromSpec(JSON.stringify(spec));
+      expect(steps.knob.plannedDuration()).to.deep.equal([1, 1]);
+    });
+
+    it("should apply syncs and deadlines", () => {
+      const { steps } = Mission.fromSpec(spec);
+
+      // the traverse waits on EV2 and can't start any later than 7.5 - 5
+      expect(steps.traverse.plannedStartWindow()).to.deep.equal([2, 2.5]);
+      expect(steps.knob.plannedStartWindow()).to.deep.equal([2, 2.5]);
+    });
+
+    it("should round trip through a spec", () => {
+      const { mission } = Mission.fromSpec(spec);
+      expect(mission.toSpec()).to.deep.equal(spec);
+    });
+
+    it("should capture runtime edits in the spec", () => {
+      const { mission, steps } = Mission.fromSpec(spec);
+      const ev3 = mission.createActor("EV3");
+      const ingress = mission.createStep("INGRESS", [3, 4], ev3);
+      steps.egress.completedAt(2);
+
+      const regenerated = mission.toSpec();
+      expect(regenerated.actors).to.deep.equal(["EV1", "EV2", "EV3"]);
+      expect(regenerated.steps.find(s => s.name === "egress").completed).to.equal(2);
+
+      const { steps: reimported } = Mission.fromSpec(regenerated);
+      const ingressName = `step-${ingress.start}`;
+      expect(reimported[ingressName].actor.name).to.equal("EV3");
+      expect(reimported.traverse.plannedStartWindow()).to.deep.equal([2, 2]);
+    });
+
+    it("should throw on unknown references", () => {
+      expect(() => Mission.fromSpec({ steps: [{ name: "a", duration: [1, 2], actor: "EV9" }] })).to.throw();
+      expect(() => Mission.fromSpec({ steps: [{ name: "a", duration: [1, 2], parent: "b" }] })).to.throw();
+      expect(() => Mission.fromSpec({ steps: [{ name: "a", duration: [1, 2] }], deadlines: [{ step: "b", deadline: 1 }] })).to.throw();
+      expect(() => Mission.fromSpec({ steps: [{ name: "a", duration: [1, 2] }, { name: "a", duration: [1, 2] }] })).to.throw();
+    });
+  });
+
   // it("should create 1 step for EV1 and 2 steps (nested) for EV2", () => {
   //   const mission = new Mission();
   //   const actor1 = mission.createActor("EV1");
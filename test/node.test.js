@@ -0,0 +1,32 @@
+const { expect } = require("chai");
+const { install } = require("../pkg");
+const { loadSchedule, readPlan, saveSchedule } = require("../pkg/node");
+
+describe("Node helpers", () => {
+  before(install);
+
+  const plan = {
+    edges: [
+      { source: 1, target: 2, interval: [10, 20] },
+      { source: 2, target: 3, interval: [30, 40] },
+    ],
+  };
+
+  it("should load a Schedule from a JSON Buffer", () => {
+    const schedule = loadSchedule(Buffer.from(JSON.stringify(plan)));
+    expect(schedule.interval(1, 3).toJSON()).to.deep.equal([40, 60]);
+  });
+
+  it("should round trip through the binary plan format", () => {
+    const schedule = loadSchedule(Buffer.from(JSON.stringify(plan)));
+    const bytes = saveSchedule(schedule);
+
+    expect(Buffer.isBuffer(bytes)).to.be.true;
+    expect(readPlan(bytes).edges).to.have.lengthOf(2);
+    expect(loadSchedule(bytes).interval(1, 3).toJSON()).to.deep.equal([40, 60]);
+  });
+
+  it("should reject anything other than bytes or a path", () => {
+    expect(() => loadSchedule(42)).to.throw();
+  });
+});
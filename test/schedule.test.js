@@ -134,6 +134,7 @@ describe("Schedule", () => {
     schedule.addConstraint(episode.end, episode2.start);
     schedule.addConstraint(episode2.end, episode3.start);
 
+    schedule.compile();
     expect(schedule.root).to.equal(
       episode.start,
       "the start of episode is the first event in the Schedule"
@@ -179,6 +180,24 @@ describe("Schedule", () => {
     const expected1 = [8, 14];
     expect(schedule.window(episode2.end).toJSON()).to.deep.equal(expected1);
   });
+
+  it("should export time windows for drawing", () => {
+    const schedule = new Schedule();
+    const episode1 = schedule.addEpisode((duration = [1, 5]));
+    const episode2 = schedule.addEpisode((duration = [2, 9]));
+    schedule.addConstraint(episode1.end, episode2.start);
+    schedule.commitEvent(episode1.start, 0);
+
+    const windows = schedule.timeWindows();
+    expect(windows).to.have.lengthOf(4);
+    expect(windows[episode2.end]).to.deep.equal({
+      event: episode2.end,
+      earliest: 3,
+      latest: 14,
+      nominal: 8.5,
+      converged: false
+    });
+  });
 });
 
 describe("examples", () => {
@@ -264,6 +283,7 @@ describe("examples", () => {
 
     it("can find the first episode", () => {
       const { schedule, A } = buildExample();
+      schedule.compile();
       expect(schedule.root).to.equal(A);
     });
   });
@@ -607,6 +627,7 @@ describe("examples", () => {
 
     it("should know the start of LIM CONS is the Schedule root", () => {
       const { schedule, limCons } = buildExample();
+      schedule.compile();
       expect(schedule.root).to.equal(limCons.start);
       expect(
         schedule.interval(schedule.root, limCons.start).toJSON()